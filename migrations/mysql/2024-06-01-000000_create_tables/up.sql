@@ -0,0 +1,75 @@
+CREATE TABLE hosts (
+    id VARCHAR(64) NOT NULL PRIMARY KEY,
+    hostname VARCHAR(255) NOT NULL,
+    os_type VARCHAR(64) NOT NULL,
+    os_name VARCHAR(255) NOT NULL,
+    os_version VARCHAR(255) NOT NULL,
+    os_arch VARCHAR(64) NOT NULL,
+    first_seen DATETIME(6) NOT NULL,
+    last_seen DATETIME(6) NOT NULL,
+    UNIQUE INDEX idx_hosts_hostname (hostname)
+);
+
+CREATE TABLE peers (
+    address VARCHAR(255) NOT NULL PRIMARY KEY,
+    success BOOLEAN NOT NULL,
+    last_seen DATETIME(6) NOT NULL
+);
+
+CREATE TABLE tasks (
+    id VARCHAR(64) NOT NULL PRIMARY KEY,
+    name VARCHAR(255) NOT NULL,
+    description TEXT NOT NULL,
+    status VARCHAR(32) NOT NULL,
+    result VARCHAR(32),
+    created_at DATETIME(6) NOT NULL,
+    started_at DATETIME(6),
+    finished_at DATETIME(6),
+    cost_ms BIGINT,
+    log_file TEXT NOT NULL,
+    INDEX idx_tasks_name (name),
+    INDEX idx_tasks_created_at (created_at)
+);
+
+CREATE TABLE jobs (
+    id VARCHAR(128) NOT NULL PRIMARY KEY,
+    task_id VARCHAR(64) NOT NULL,
+    seq INTEGER NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    status VARCHAR(32) NOT NULL,
+    result VARCHAR(32),
+    message TEXT NOT NULL,
+    cost_ms BIGINT,
+    INDEX idx_jobs_task_id (task_id),
+    FOREIGN KEY (task_id) REFERENCES tasks (id) ON DELETE CASCADE
+);
+
+CREATE TABLE job_attempts (
+    id VARCHAR(160) NOT NULL PRIMARY KEY,
+    job_id VARCHAR(128) NOT NULL,
+    task_id VARCHAR(64) NOT NULL,
+    seq INTEGER NOT NULL,
+    cmd TEXT NOT NULL,
+    args TEXT NOT NULL,
+    check_str TEXT,
+    status INTEGER,
+    stdout LONGTEXT,
+    stderr LONGTEXT,
+    result VARCHAR(32),
+    cost_ms BIGINT,
+    INDEX idx_job_attempts_task_id (task_id),
+    FOREIGN KEY (job_id) REFERENCES jobs (id) ON DELETE CASCADE
+);
+
+CREATE TABLE events (
+    id VARCHAR(64) NOT NULL PRIMARY KEY,
+    name VARCHAR(255) NOT NULL,
+    timestamp DATETIME(6) NOT NULL,
+    description TEXT NOT NULL,
+    priority VARCHAR(32) NOT NULL,
+    severity VARCHAR(32) NOT NULL,
+    category VARCHAR(255) NOT NULL,
+    status VARCHAR(32) NOT NULL,
+    progress TEXT NOT NULL,
+    INDEX idx_events_timestamp (timestamp)
+);
@@ -0,0 +1,8 @@
+CREATE TABLE host_snapshots (
+    id VARCHAR(64) NOT NULL PRIMARY KEY,
+    host_id VARCHAR(64) NOT NULL,
+    taken_at DATETIME(6) NOT NULL,
+    data LONGTEXT NOT NULL,
+    INDEX idx_host_snapshots_host_taken (host_id, taken_at),
+    FOREIGN KEY (host_id) REFERENCES hosts (id) ON DELETE CASCADE
+);
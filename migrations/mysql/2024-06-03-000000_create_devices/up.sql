@@ -0,0 +1,10 @@
+CREATE TABLE devices (
+    ip VARCHAR(64) NOT NULL PRIMARY KEY,
+    mac VARCHAR(32),
+    methods TEXT NOT NULL,
+    ports LONGTEXT NOT NULL,
+    status VARCHAR(16) NOT NULL,
+    first_seen DATETIME(6) NOT NULL,
+    last_seen DATETIME(6) NOT NULL,
+    INDEX idx_devices_status (status)
+);
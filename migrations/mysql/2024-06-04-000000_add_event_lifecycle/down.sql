@@ -0,0 +1,10 @@
+ALTER TABLE events
+    DROP INDEX idx_events_fingerprint,
+    DROP COLUMN silenced_until,
+    DROP COLUMN acknowledged_until,
+    DROP COLUMN resolved_at,
+    DROP COLUMN last_seen,
+    DROP COLUMN first_seen,
+    DROP COLUMN count,
+    DROP COLUMN fingerprint,
+    DROP COLUMN subject;
@@ -0,0 +1,10 @@
+ALTER TABLE events
+    ADD COLUMN subject VARCHAR(255) NOT NULL DEFAULT '',
+    ADD COLUMN fingerprint VARCHAR(255) NOT NULL DEFAULT '',
+    ADD COLUMN count BIGINT NOT NULL DEFAULT 1,
+    ADD COLUMN first_seen DATETIME(6),
+    ADD COLUMN last_seen DATETIME(6),
+    ADD COLUMN resolved_at DATETIME(6),
+    ADD COLUMN acknowledged_until DATETIME(6),
+    ADD COLUMN silenced_until DATETIME(6),
+    ADD INDEX idx_events_fingerprint (fingerprint, status);
@@ -0,0 +1,6 @@
+DROP TABLE events;
+DROP TABLE job_attempts;
+DROP TABLE jobs;
+DROP TABLE tasks;
+DROP TABLE peers;
+DROP TABLE hosts;
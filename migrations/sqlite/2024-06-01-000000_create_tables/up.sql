@@ -0,0 +1,73 @@
+CREATE TABLE hosts (
+    id VARCHAR(64) NOT NULL PRIMARY KEY,
+    hostname VARCHAR(255) NOT NULL,
+    os_type VARCHAR(64) NOT NULL,
+    os_name VARCHAR(255) NOT NULL,
+    os_version VARCHAR(255) NOT NULL,
+    os_arch VARCHAR(64) NOT NULL,
+    first_seen TIMESTAMP NOT NULL,
+    last_seen TIMESTAMP NOT NULL
+);
+CREATE UNIQUE INDEX idx_hosts_hostname ON hosts (hostname);
+
+CREATE TABLE peers (
+    address VARCHAR(255) NOT NULL PRIMARY KEY,
+    success BOOLEAN NOT NULL,
+    last_seen TIMESTAMP NOT NULL
+);
+
+CREATE TABLE tasks (
+    id VARCHAR(64) NOT NULL PRIMARY KEY,
+    name VARCHAR(255) NOT NULL,
+    description TEXT NOT NULL,
+    status VARCHAR(32) NOT NULL,
+    result VARCHAR(32),
+    created_at TIMESTAMP NOT NULL,
+    started_at TIMESTAMP,
+    finished_at TIMESTAMP,
+    cost_ms BIGINT,
+    log_file TEXT NOT NULL
+);
+CREATE INDEX idx_tasks_name ON tasks (name);
+CREATE INDEX idx_tasks_created_at ON tasks (created_at);
+
+CREATE TABLE jobs (
+    id VARCHAR(128) NOT NULL PRIMARY KEY,
+    task_id VARCHAR(64) NOT NULL REFERENCES tasks (id) ON DELETE CASCADE,
+    seq INTEGER NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    status VARCHAR(32) NOT NULL,
+    result VARCHAR(32),
+    message TEXT NOT NULL,
+    cost_ms BIGINT
+);
+CREATE INDEX idx_jobs_task_id ON jobs (task_id);
+
+CREATE TABLE job_attempts (
+    id VARCHAR(160) NOT NULL PRIMARY KEY,
+    job_id VARCHAR(128) NOT NULL REFERENCES jobs (id) ON DELETE CASCADE,
+    task_id VARCHAR(64) NOT NULL,
+    seq INTEGER NOT NULL,
+    cmd TEXT NOT NULL,
+    args TEXT NOT NULL,
+    check_str TEXT,
+    status INTEGER,
+    stdout TEXT,
+    stderr TEXT,
+    result VARCHAR(32),
+    cost_ms BIGINT
+);
+CREATE INDEX idx_job_attempts_task_id ON job_attempts (task_id);
+
+CREATE TABLE events (
+    id VARCHAR(64) NOT NULL PRIMARY KEY,
+    name VARCHAR(255) NOT NULL,
+    timestamp TIMESTAMP NOT NULL,
+    description TEXT NOT NULL,
+    priority VARCHAR(32) NOT NULL,
+    severity VARCHAR(32) NOT NULL,
+    category VARCHAR(255) NOT NULL,
+    status VARCHAR(32) NOT NULL,
+    progress TEXT NOT NULL
+);
+CREATE INDEX idx_events_timestamp ON events (timestamp);
@@ -0,0 +1,7 @@
+CREATE TABLE host_snapshots (
+    id VARCHAR(64) NOT NULL PRIMARY KEY,
+    host_id VARCHAR(64) NOT NULL REFERENCES hosts (id) ON DELETE CASCADE,
+    taken_at TIMESTAMP NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE INDEX idx_host_snapshots_host_taken ON host_snapshots (host_id, taken_at);
@@ -0,0 +1,10 @@
+CREATE TABLE devices (
+    ip VARCHAR(64) NOT NULL PRIMARY KEY,
+    mac VARCHAR(32),
+    methods TEXT NOT NULL,
+    ports TEXT NOT NULL,
+    status VARCHAR(16) NOT NULL,
+    first_seen TIMESTAMP NOT NULL,
+    last_seen TIMESTAMP NOT NULL
+);
+CREATE INDEX idx_devices_status ON devices (status);
@@ -0,0 +1,9 @@
+DROP INDEX idx_events_fingerprint;
+ALTER TABLE events DROP COLUMN silenced_until;
+ALTER TABLE events DROP COLUMN acknowledged_until;
+ALTER TABLE events DROP COLUMN resolved_at;
+ALTER TABLE events DROP COLUMN last_seen;
+ALTER TABLE events DROP COLUMN first_seen;
+ALTER TABLE events DROP COLUMN count;
+ALTER TABLE events DROP COLUMN fingerprint;
+ALTER TABLE events DROP COLUMN subject;
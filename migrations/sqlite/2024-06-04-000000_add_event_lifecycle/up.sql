@@ -0,0 +1,9 @@
+ALTER TABLE events ADD COLUMN subject VARCHAR(255) NOT NULL DEFAULT '';
+ALTER TABLE events ADD COLUMN fingerprint VARCHAR(255) NOT NULL DEFAULT '';
+ALTER TABLE events ADD COLUMN count BIGINT NOT NULL DEFAULT 1;
+ALTER TABLE events ADD COLUMN first_seen TIMESTAMP;
+ALTER TABLE events ADD COLUMN last_seen TIMESTAMP;
+ALTER TABLE events ADD COLUMN resolved_at TIMESTAMP;
+ALTER TABLE events ADD COLUMN acknowledged_until TIMESTAMP;
+ALTER TABLE events ADD COLUMN silenced_until TIMESTAMP;
+CREATE INDEX idx_events_fingerprint ON events (fingerprint, status);
@@ -1,5 +1,5 @@
-use std::io;
-use std::process::Command;
+use std::io::{self, Read};
+use std::process::{Child, Command, Stdio};
 use std::sync::mpsc;
 use std::time::{Instant, Duration};
 use std::thread;
@@ -101,11 +101,8 @@ impl Shell {
                 self.stderr = result.stderr.clone();
                 self.status = result.status;
                 
-                self.result = if result.status == Some(-1) && result.stderr == Some("Command timed out".to_string()) {
-                    Some(Result::Failed)
-                } else if result.status == Some(-2) && result.stderr == Some("Receiver disconnected before getting a result".to_string()) {
-                    Some(Result::Error)
-                } else if result.status != Some(0) {
+                // 超时的状态码为 -1，按失败处理
+                self.result = if result.status != Some(0) {
                     Some(Result::Failed)
                 } else if let Some(ref check_str) = self.check_str {
                     if let Some(ref stdout) = self.stdout {
@@ -143,36 +140,74 @@ struct CmdResult {
     stderr: Option<String>,
 }
 
-fn execute_with_timeout(cmd: &str, cmd_args: &[String], timeout: &Duration) -> io::Result<Option<CmdResult>> {
-    let command = cmd.to_string();
-    let args: Vec<String> = cmd_args.iter().map(|arg| arg.to_string()).collect();
+// 等待命令结束时检查的间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+// 在独立线程中读取管道，避免输出较多时命令阻塞在写管道上
+fn read_pipe<R: Read + Send + 'static>(pipe: Option<R>) -> mpsc::Receiver<String> {
     let (tx, rx) = mpsc::channel();
-    
     thread::spawn(move || {
-        let result = Command::new(command)
-            .args(&args)
-            .output()
-            .map(|output| CmdResult {
-                status: output.status.code(),
-                stdout: Some(String::from_utf8_lossy(&output.stdout).to_string()),
-                stderr: Some(String::from_utf8_lossy(&output.stderr).to_string()),
-            })
-            .map_err(|e| io::Error::new(io::ErrorKind::Other, e));
-        tx.send(result).ok();
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        tx.send(String::from_utf8_lossy(&buf).to_string()).ok();
     });
+    rx
+}
 
-    match rx.recv_timeout(*timeout) {
-        Ok(result) => result.map(Some),
-        Err(mpsc::RecvTimeoutError::Timeout) => Ok(Some(CmdResult {
-            stdout: None,
-            stderr: Some("Command timed out".to_string()),
-            status: Some(-1),
-        })),
-        Err(mpsc::RecvTimeoutError::Disconnected) => Ok(Some(CmdResult {
-            stdout: None,
-            stderr: Some("Receiver disconnected before getting a result".to_string()),
-            status: Some(-2),
-        })),
+// 终止命令，Unix 下命令在独立的进程组中，连同其子进程一起终止
+fn kill(child: &mut Child) {
+    #[cfg(unix)]
+    unsafe {
+        libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+fn timed_out() -> CmdResult {
+    CmdResult {
+        stdout: None,
+        stderr: Some("Command timed out".to_string()),
+        status: Some(-1),
+    }
+}
+
+fn execute_with_timeout(cmd: &str, cmd_args: &[String], timeout: &Duration) -> io::Result<Option<CmdResult>> {
+    let mut command = Command::new(cmd);
+    command.args(cmd_args).stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped());
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    let mut child = command.spawn()?;
+    let stdout = read_pipe(child.stdout.take());
+    let stderr = read_pipe(child.stderr.take());
+
+    let deadline = Instant::now() + *timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            // 后台运行的子进程可能仍持有管道，读取输出同样受超时限制
+            let output = stdout.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                .and_then(|stdout| Ok((stdout, stderr.recv_timeout(deadline.saturating_duration_since(Instant::now()))?)));
+            let Ok((stdout, stderr)) = output else {
+                kill(&mut child);
+                return Ok(Some(timed_out()));
+            };
+            return Ok(Some(CmdResult {
+                status: status.code(),
+                stdout: Some(stdout),
+                stderr: Some(stderr),
+            }));
+        }
+        if Instant::now() >= deadline {
+            // 超时后终止命令并回收，不留下仍在运行的进程
+            kill(&mut child);
+            return Ok(Some(timed_out()));
+        }
+        thread::sleep(POLL_INTERVAL);
     }
 }
 
@@ -200,4 +235,37 @@ mod unit_test_shell {
         assert!(cmd.stdout.is_some_and(|stdout| stdout.contains("% /")));
     }
 
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_cmd_03() {
+        // 超时后命令及其子进程被终止，以唯一的 sleep 参数识别子进程
+        let seconds = format!("30.{}", std::process::id());
+        let mut cmd = Shell::new(&0u32, "sh", Some(&["-c", &format!("sleep {}; echo done", seconds)]), &1u64, None);
+        let start = Instant::now();
+        cmd.execute();
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert_eq!(cmd.status, Some(-1));
+        assert!(cmd.result.is_some_and(|result| result == Result::Failed));
+
+        thread::sleep(Duration::from_millis(100));
+        let sleeping = format!("sleep\0{}\0", seconds);
+        let alive = std::fs::read_dir("/proc").unwrap()
+            .filter_map(|entry| std::fs::read(entry.ok()?.path().join("cmdline")).ok())
+            .any(|cmdline| String::from_utf8_lossy(&cmdline) == sleeping);
+        assert!(!alive);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_cmd_04() {
+        // 命令已退出但后台子进程仍持有输出管道，按超时处理
+        let mut cmd = Shell::new(&0u32, "sh", Some(&["-c", "sleep 30 & echo hi"]), &1u64, None);
+        let start = Instant::now();
+        cmd.execute();
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert_eq!(cmd.status, Some(-1));
+        assert_eq!(cmd.stderr, Some("Command timed out".to_string()));
+        assert!(cmd.result.is_some_and(|result| result == Result::Failed));
+    }
+
 }
\ No newline at end of file
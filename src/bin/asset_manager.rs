@@ -1,10 +1,206 @@
 extern crate clap;
-use clap::{Arg, Command};
+use std::error::Error;
+use std::thread;
+use std::time::Duration;
 
-use minirobot::info::hostinfo::HostInfo;
+use chrono::Local;
+use clap::{Arg, ArgMatches, Command};
+use colored::Colorize;
+
+use minirobot::database::connection::default_database_url;
+use minirobot::database::repository::Repository;
+use minirobot::info::hostname::hostname;
+use minirobot::info::package::PackageQuery;
+use minirobot::monitor::asset::account::check_account_changes;
+use minirobot::monitor::asset::snapshot::{self, HostSnapshot};
+use minirobot::task::history::parse_time;
 
 include!(concat!(env!("OUT_DIR"), "/version.rs"));
-include!(concat!(env!("OUT_DIR"), "/configfile.rs"));
+
+fn host_arg() -> Arg {
+    Arg::new("host")
+        .short('H')
+        .long("host")
+        .value_name("HOSTNAME")
+        .help("Hostname of recorded snapshots, default local hostname")
+        .value_parser(clap::value_parser!(String))
+}
+
+fn time_arg(name: &'static str, help: &'static str) -> Arg {
+    Arg::new(name)
+        .long(name)
+        .value_name("TIME")
+        .help(help)
+        .value_parser(clap::value_parser!(String))
+}
+
+fn display_snapshots(snapshots: &[HostSnapshot]) {
+    println!("{:<36}  {:<20} {:<20} {:<24} {:>6} {:>6} {:>6}", "ID", "HOST", "TAKEN", "OS", "NICS", "PORTS", "PKGS");
+    for s in snapshots {
+        println!("{:<36}  {:<20} {:<20} {:<24} {:>6} {:>6} {:>6}", s.id, s.hostname,
+                 s.taken_at.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S"),
+                 format!("{} {}", s.os_name, s.os_version), s.nics.len(), s.listening.len(), s.packages.len());
+    }
+}
+
+fn take_snapshot(repository: &Repository, json: bool) -> Result<(), Box<dyn Error>> {
+    let snapshot = HostSnapshot::capture();
+    let mut previous = repository.latest_snapshot(&snapshot.hostname, None)?;
+    // 主机名首次出现时按硬件指纹查找重装系统或改名前的记录
+    let mut renamed_from = None;
+    if let (None, Some(fingerprint)) = (&previous, &snapshot.fingerprint) {
+        previous = repository.find_by_fingerprint(fingerprint, &snapshot.hostname)?;
+        renamed_from = previous.as_ref().map(|p| p.hostname.clone());
+    }
+    repository.save_snapshot(&snapshot)?;
+    // 新增账号、SSH 公钥或 sudo 规则时记录事件
+    let diff = previous.as_ref().map(|previous| snapshot::diff(previous, &snapshot));
+    let event = diff.as_ref().and_then(|diff| check_account_changes(diff, &true));
+    if let Some(event) = &event {
+        repository.save_event(event)?;
+    }
+    if json {
+        println!("{}", snapshot.to_json());
+        return Ok(());
+    }
+    println!("Saved snapshot {} of {}", snapshot.id, snapshot.hostname);
+    if let Some(hostname) = renamed_from {
+        println!("Same hardware as previously recorded host {}", hostname);
+    }
+    if let Some(diff) = diff.filter(|diff| !diff.is_empty()) {
+        diff.display();
+    }
+    if let Some(event) = event {
+        println!("{}", event.description().red());
+    }
+    Ok(())
+}
+
+fn run(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let database_url = matches.get_one::<String>("database-url").cloned().unwrap_or_else(default_database_url);
+    let repository = Repository::new(&database_url)?;
+    let json = matches.get_flag("json");
+    let local_hostname = hostname();
+
+    match matches.subcommand() {
+        Some(("snapshot", sub)) => {
+            let count = sub.get_one::<u64>("count").copied();
+            match sub.get_one::<u64>("interval") {
+                Some(interval) => {
+                    let mut taken = 0;
+                    while count.is_none_or(|count| taken < count) {
+                        if taken > 0 {
+                            thread::sleep(Duration::from_secs(*interval));
+                        }
+                        if let Err(e) = take_snapshot(&repository, json) {
+                            eprintln!("{} asset manager: {}", NAME, e);
+                        }
+                        taken += 1;
+                    }
+                }
+                None => take_snapshot(&repository, json)?,
+            }
+        }
+        Some(("list", sub)) => {
+            let since = sub.get_one::<String>("since").map(|s| parse_time(s)).transpose()?;
+            let until = sub.get_one::<String>("until").map(|s| parse_time(s)).transpose()?;
+            let snapshots = repository.list_snapshots(sub.get_one::<String>("host").map(|h| h.as_str()), since, until)?;
+            if json {
+                println!("{}", serde_json::to_string(&snapshots)?);
+            } else {
+                display_snapshots(&snapshots);
+            }
+        }
+        Some(("show", sub)) => {
+            let snapshot = repository.load_snapshot(sub.get_one::<String>("id").unwrap())?;
+            if json {
+                println!("{}", snapshot.to_json());
+            } else {
+                snapshot.display();
+            }
+        }
+        Some(("diff", sub)) => {
+            let from = repository.load_snapshot(sub.get_one::<String>("from").unwrap())?;
+            let to = match sub.get_one::<String>("to") {
+                Some(id) => repository.load_snapshot(id)?,
+                None => repository.latest_snapshot(&from.hostname, None)?
+                    .ok_or_else(|| format!("no snapshot of {}", from.hostname))?,
+            };
+            let diff = snapshot::diff(&from, &to);
+            if json {
+                println!("{}", serde_json::to_string(&diff)?);
+            } else {
+                diff.display();
+            }
+        }
+        Some(("changes", sub)) => {
+            let host = sub.get_one::<String>("host").unwrap_or(&local_hostname);
+            let since = parse_time(sub.get_one::<String>("since").unwrap())?;
+            let until = sub.get_one::<String>("until").map(|s| parse_time(s)).transpose()?;
+            let diffs = repository.host_changes(host, since, until)?;
+            if json {
+                println!("{}", serde_json::to_string(&diffs)?);
+            } else if diffs.is_empty() {
+                println!("No changes on {} since {}", host, since.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S"));
+            } else {
+                for diff in &diffs {
+                    diff.display();
+                }
+            }
+        }
+        Some(("packages", sub)) => {
+            let host = sub.get_one::<String>("host").map(|h| h.as_str());
+            let found = match sub.get_one::<PackageQuery>("query") {
+                Some(query) => repository.find_packages(query, host)?,
+                // 未指定查询时列出主机最新快照中的全部软件包
+                None => {
+                    let host = host.unwrap_or(&local_hostname);
+                    let snapshot = repository.latest_snapshot(host, None)?.ok_or_else(|| format!("no snapshot of {}", host))?;
+                    snapshot.packages.iter().cloned().map(|package| snapshot::HostPackage {
+                        hostname: snapshot.hostname.clone(),
+                        snapshot_id: snapshot.id.clone(),
+                        taken_at: snapshot.taken_at,
+                        package,
+                    }).collect()
+                }
+            };
+            if json {
+                println!("{}", serde_json::to_string(&found)?);
+            } else {
+                println!("{:<20} {:<32} {:<32} {:<10} {:<24} {:<8}", "HOST", "PACKAGE", "VERSION", "ARCH", "SOURCE", "MANAGER");
+                for p in &found {
+                    println!("{:<20} {:<32} {:<32} {:<10} {:<24} {:<8}", p.hostname, p.package.name, p.package.version,
+                             p.package.arch, p.package.source, p.package.manager);
+                }
+            }
+        }
+        Some(("hosts", _)) => {
+            let hosts = repository.list_hosts()?;
+            if json {
+                let hosts: Vec<serde_json::Value> = hosts.iter().map(|h| serde_json::json!({
+                    "id": h.id,
+                    "hostname": h.hostname,
+                    "os_type": h.os_type,
+                    "os_name": h.os_name,
+                    "os_version": h.os_version,
+                    "os_arch": h.os_arch,
+                    "first_seen": h.first_seen.and_utc(),
+                    "last_seen": h.last_seen.and_utc(),
+                })).collect();
+                println!("{}", serde_json::to_string(&hosts)?);
+            } else {
+                println!("{:<20} {:<32} {:<20} {:<20}", "HOST", "OS", "FIRST SEEN", "LAST SEEN");
+                for h in &hosts {
+                    println!("{:<20} {:<32} {:<20} {:<20}", h.hostname, format!("{} {} {}", h.os_name, h.os_version, h.os_arch),
+                             h.first_seen.and_utc().with_timezone(&Local).format("%Y-%m-%d %H:%M:%S"),
+                             h.last_seen.and_utc().with_timezone(&Local).format("%Y-%m-%d %H:%M:%S"));
+                }
+            }
+        }
+        _ => unreachable!("subcommand required"),
+    }
+    Ok(())
+}
 
 fn main() {
     // 创建命令行参数解析器
@@ -13,21 +209,85 @@ fn main() {
         .author(AUTHORS)
         .about(ABOUT)
         .after_help(COPYRIGHT)
-        // .arg(
-        //     Arg::new("ctype")
-        //         .short('c')
-        //         .long("connect-type")
-        //         .value_name("CONNECTION_TYPE")
-        //         .help("Specify the type to connect target device")
-        //         .value_parser(clap::value_parser!(String)),
-        // )
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .arg(
+            Arg::new("database-url")
+                .long("database-url")
+                .value_name("URL")
+                .help("Specify the database storing host snapshots")
+                .global(true)
+                .value_parser(clap::value_parser!(String)),
+        )
+        .arg(
+            Arg::new("json")
+                .short('j')
+                .long("json")
+                .help("Output in JSON format")
+                .global(true)
+                .action(clap::ArgAction::SetTrue),
+        )
+        .subcommand(
+            Command::new("snapshot")
+                .about("Take a snapshot of the local host and show changes since the previous one")
+                .arg(
+                    Arg::new("interval")
+                        .short('i')
+                        .long("interval")
+                        .value_name("SECONDS")
+                        .help("Keep taking snapshots every SECONDS")
+                        .value_parser(clap::value_parser!(u64).range(1..)),
+                )
+                .arg(
+                    Arg::new("count")
+                        .short('c')
+                        .long("count")
+                        .value_name("N")
+                        .help("Stop after N snapshots when --interval is set")
+                        .requires("interval")
+                        .value_parser(clap::value_parser!(u64)),
+                ),
+        )
+        .subcommand(
+            Command::new("list")
+                .about("List recorded snapshots")
+                .arg(host_arg().help("Only snapshots of HOSTNAME"))
+                .arg(time_arg("since", "Only snapshots taken at or after TIME (YYYY-MM-DD[ HH:MM:SS] or RFC3339)"))
+                .arg(time_arg("until", "Only snapshots taken before TIME (YYYY-MM-DD[ HH:MM:SS] or RFC3339)")),
+        )
+        .subcommand(
+            Command::new("show")
+                .about("Show one snapshot")
+                .arg(Arg::new("id").value_name("SNAPSHOT_ID").required(true).help("Snapshot ID or unique ID prefix")),
+        )
+        .subcommand(
+            Command::new("diff")
+                .about("Compare two snapshots")
+                .arg(Arg::new("from").value_name("FROM_ID").required(true).help("Base snapshot ID or unique ID prefix"))
+                .arg(Arg::new("to").value_name("TO_ID").help("Target snapshot, default latest snapshot of the same host")),
+        )
+        .subcommand(
+            Command::new("changes")
+                .about("Show what changed on a host since TIME")
+                .arg(host_arg())
+                .arg(time_arg("since", "Changes after TIME (YYYY-MM-DD[ HH:MM:SS] or RFC3339)").required(true))
+                .arg(time_arg("until", "Changes before TIME (YYYY-MM-DD[ HH:MM:SS] or RFC3339)")),
+        )
+        .subcommand(
+            Command::new("packages")
+                .about("Find hosts whose latest snapshot has packages matching QUERY")
+                .arg(Arg::new("query")
+                    .value_name("QUERY")
+                    .help("Package name or source package with optional version constraint, e.g. 'openssl<3.0.12'; \
+                           without QUERY list all packages of the host")
+                    .value_parser(|s: &str| s.parse::<PackageQuery>()))
+                .arg(host_arg().help("Only the host HOSTNAME, default all hosts (local host without QUERY)")),
+        )
+        .subcommand(Command::new("hosts").about("List recorded hosts"))
         .get_matches();
 
-    println!("{} asset manager running", NAME);
-    println!("config file is {}", GLOBAL_CONFIG_FILE);
-
-    // 创建 HostInfo 对象
-    println!("运行主机当前信息:");
-    let host_info = HostInfo::new();
-    host_info.display();
+    if let Err(e) = run(&matches) {
+        eprintln!("{} asset manager: {}", NAME, e);
+        std::process::exit(1);
+    }
 }
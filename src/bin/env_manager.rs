@@ -1,11 +1,92 @@
 extern crate clap;
-use clap::{Arg, Command};
+use std::error::Error;
+use std::time::Duration;
 
+use clap::{Arg, ArgMatches, Command};
+
+use minirobot::database::connection::default_database_url;
+use minirobot::database::repository::Repository;
 use minirobot::info::hostinfo::HostInfo;
+use minirobot::monitor::env::device::{display_devices, DeviceStatus};
+use minirobot::monitor::env::discovery::{self, AllowList, DiscoveryOptions};
 
 include!(concat!(env!("OUT_DIR"), "/version.rs"));
 include!(concat!(env!("OUT_DIR"), "/configfile.rs"));
 
+fn discovery_options(matches: &ArgMatches) -> Result<DiscoveryOptions, Box<dyn Error>> {
+    let allow: Vec<String> = matches.get_many::<String>("allow").map(|v| v.cloned().collect()).unwrap_or_default();
+    let mut options = DiscoveryOptions::new(AllowList::parse(&allow)?);
+    if let Some(targets) = matches.get_many::<String>("target") {
+        options.targets = targets.map(|target| discovery::parse_network(target)).collect::<Result<_, _>>()?;
+    }
+    options.interface = matches.get_one::<String>("interface").cloned();
+    if matches.get_flag("no-ports") {
+        options.ports.clear();
+    } else if let Some(ports) = matches.get_one::<String>("ports") {
+        options.ports = discovery::parse_ports(ports)?;
+    }
+    options.rate = *matches.get_one::<u32>("rate").unwrap();
+    options.timeout = Duration::from_millis(*matches.get_one::<u64>("timeout").unwrap());
+    options.max_hosts = *matches.get_one::<usize>("max-hosts").unwrap();
+    options.arp = !matches.get_flag("no-arp");
+    options.icmp = !matches.get_flag("no-icmp");
+    options.syn = matches.get_flag("syn");
+    Ok(options)
+}
+
+fn run(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let Some((name, sub)) = matches.subcommand() else {
+        println!("{} environment manager running", NAME);
+        println!("config file is {}", GLOBAL_CONFIG_FILE);
+
+        // 创建 HostInfo 对象
+        println!("运行主机当前信息:");
+        let host_info = HostInfo::new();
+        host_info.display();
+        return Ok(());
+    };
+
+    let database_url = matches.get_one::<String>("database-url").cloned().unwrap_or_else(default_database_url);
+    let repository = Repository::new(&database_url)?;
+    let json = matches.get_flag("json");
+
+    match name {
+        "discover" => {
+            let options = discovery_options(sub)?;
+            let devices = discovery::discover(&options)?
+                .iter()
+                .map(|device| repository.upsert_device(device))
+                .collect::<Result<Vec<_>, _>>()?;
+            if json {
+                println!("{}", serde_json::to_string(&devices)?);
+            } else {
+                display_devices(&devices);
+                let candidates = devices.iter().filter(|device| device.status == DeviceStatus::Candidate).count();
+                println!("Discovered {} devices, {} candidates", devices.len(), candidates);
+            }
+        }
+        "devices" => {
+            let devices = repository.list_devices(sub.get_one::<DeviceStatus>("status").copied())?;
+            if json {
+                println!("{}", serde_json::to_string(&devices)?);
+            } else {
+                display_devices(&devices);
+            }
+        }
+        "accept" | "ignore" => {
+            let status = if name == "accept" { DeviceStatus::Accepted } else { DeviceStatus::Ignored };
+            for ip in sub.get_many::<String>("ip").unwrap() {
+                if !repository.set_device_status(ip, status)? {
+                    return Err(format!("device {} not found", ip).into());
+                }
+                println!("Device {} {}", ip, status);
+            }
+        }
+        _ => unreachable!("unknown subcommand"),
+    }
+    Ok(())
+}
+
 fn main() {
     // 创建命令行参数解析器
     let matches = Command::new(NAME)
@@ -13,21 +94,137 @@ fn main() {
         .author(AUTHORS)
         .about(ABOUT)
         .after_help(COPYRIGHT)
-        // .arg(
-        //     Arg::new("ctype")
-        //         .short('c')
-        //         .long("connect-type")
-        //         .value_name("CONNECTION_TYPE")
-        //         .help("Specify the type to connect target device")
-        //         .value_parser(clap::value_parser!(String)),
-        // )
+        .arg(
+            Arg::new("database-url")
+                .long("database-url")
+                .value_name("URL")
+                .help("Specify the database storing discovered devices")
+                .global(true)
+                .value_parser(clap::value_parser!(String)),
+        )
+        .arg(
+            Arg::new("json")
+                .short('j')
+                .long("json")
+                .help("Output in JSON format")
+                .global(true)
+                .action(clap::ArgAction::SetTrue),
+        )
+        .subcommand(
+            Command::new("discover")
+                .about("Discover devices by ARP/ICMP sweep and TCP port scan, and store them as candidate assets")
+                .arg(
+                    Arg::new("allow")
+                        .short('a')
+                        .long("allow")
+                        .value_name("CIDR")
+                        .help("CIDR allowed to scan, can be given multiple times; nothing outside is probed")
+                        .required(true)
+                        .action(clap::ArgAction::Append)
+                        .value_parser(clap::value_parser!(String)),
+                )
+                .arg(
+                    Arg::new("target")
+                        .short('t')
+                        .long("target")
+                        .value_name("CIDR")
+                        .help("Scan CIDR instead of the attached subnets, must be inside the allow-list")
+                        .action(clap::ArgAction::Append)
+                        .value_parser(clap::value_parser!(String)),
+                )
+                .arg(
+                    Arg::new("interface")
+                        .short('i')
+                        .long("interface")
+                        .value_name("NAME")
+                        .help("Only use the interface NAME")
+                        .value_parser(clap::value_parser!(String)),
+                )
+                .arg(
+                    Arg::new("ports")
+                        .short('p')
+                        .long("ports")
+                        .value_name("PORTS")
+                        .help("TCP ports to scan, e.g. 22,80,8000-8010 [default: common service ports]")
+                        .value_parser(clap::value_parser!(String)),
+                )
+                .arg(
+                    Arg::new("no-ports")
+                        .long("no-ports")
+                        .help("Skip port scan")
+                        .conflicts_with_all(["ports", "syn"])
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("syn")
+                        .long("syn")
+                        .help("Use SYN scan instead of TCP connect scan")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("no-arp")
+                        .long("no-arp")
+                        .help("Skip ARP sweep")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("no-icmp")
+                        .long("no-icmp")
+                        .help("Skip ICMP ping sweep")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("rate")
+                        .short('r')
+                        .long("rate")
+                        .value_name("PACKETS")
+                        .help("Maximum probes sent per second")
+                        .default_value("100")
+                        .value_parser(clap::value_parser!(u32).range(1..=10000)),
+                )
+                .arg(
+                    Arg::new("timeout")
+                        .long("timeout")
+                        .value_name("MILLISECONDS")
+                        .help("Time to wait for replies and connections")
+                        .default_value("1000")
+                        .value_parser(clap::value_parser!(u64).range(1..)),
+                )
+                .arg(
+                    Arg::new("max-hosts")
+                        .long("max-hosts")
+                        .value_name("N")
+                        .help("Refuse to scan more than N addresses")
+                        .default_value("4096")
+                        .value_parser(clap::value_parser!(usize)),
+                ),
+        )
+        .subcommand(
+            Command::new("devices")
+                .about("List discovered devices")
+                .arg(
+                    Arg::new("status")
+                        .short('s')
+                        .long("status")
+                        .value_name("STATUS")
+                        .help("Only devices in STATUS: candidate, accepted or ignored")
+                        .value_parser(|s: &str| s.parse::<DeviceStatus>()),
+                ),
+        )
+        .subcommand(
+            Command::new("accept")
+                .about("Accept discovered devices as managed assets")
+                .arg(Arg::new("ip").value_name("IP").required(true).num_args(1..).help("Address of the device")),
+        )
+        .subcommand(
+            Command::new("ignore")
+                .about("Ignore discovered devices")
+                .arg(Arg::new("ip").value_name("IP").required(true).num_args(1..).help("Address of the device")),
+        )
         .get_matches();
 
-    println!("{} environment manager running", NAME);
-    println!("config file is {}", GLOBAL_CONFIG_FILE);
-
-    // 创建 HostInfo 对象
-    println!("运行主机当前信息:");
-    let host_info = HostInfo::new();
-    host_info.display();
+    if let Err(e) = run(&matches) {
+        eprintln!("{} environment manager: {}", NAME, e);
+        std::process::exit(1);
+    }
 }
@@ -1,11 +1,155 @@
 extern crate clap;
-use clap::{Arg, Command};
+use std::error::Error;
+use std::io::IsTerminal;
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
 
+use clap::parser::ValueSource;
+use clap::{Arg, ArgMatches, Command};
+use regex::Regex;
+
+use minirobot::common::format::OutputFormat;
+use minirobot::database::repository::Repository;
+use minirobot::info::control::{is_protected, ProcessControl, Signal};
+use minirobot::info::error::InfoResult;
+use minirobot::info::account::AccountInfo;
+use minirobot::info::hardware::HardwareInfo;
 use minirobot::info::hostinfo::HostInfo;
+use minirobot::info::os::{OSInfo, DEFAULT_SYSCTL_KEYS};
 use minirobot::info::process::*;
+use minirobot::info::report::{Report, Section};
+use minirobot::info::sampler::Sampler;
+use minirobot::info::socket::SocketInventory;
+use minirobot::info::systemd::{Systemctl, UnitInfo};
 
 include!(concat!(env!("OUT_DIR"), "/version.rs"));
 
+// 解析 CPU 列表，如 "0,2-3"
+fn parse_cpu_list(s: &str) -> Result<Vec<usize>, String> {
+    let mut cpus = Vec::new();
+    for part in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let invalid = || format!("invalid CPU list: {}", s);
+        match part.split_once('-') {
+            Some((start, end)) => {
+                let (start, end): (usize, usize) = (start.parse().map_err(|_| invalid())?, end.parse().map_err(|_| invalid())?);
+                if start > end {
+                    return Err(invalid());
+                }
+                cpus.extend(start..=end);
+            }
+            None => cpus.push(part.parse().map_err(|_| invalid())?),
+        }
+    }
+    if cpus.is_empty() {
+        return Err(format!("invalid CPU list: {}", s));
+    }
+    Ok(cpus)
+}
+
+// 进程控制参数
+const ACTIONS: [&str; 5] = ["signal", "kill-tree", "renice", "affinity", "wait"];
+
+// 对过滤出的进程执行控制操作，init 和工具自身不在其中，审计事件写入日志，指定 --database-url 时同时写入数据库
+fn control(matches: &ArgMatches, filter: Option<&Filter>) -> Result<(), Box<dyn Error>> {
+    let json = matches.get_flag("json");
+    let all = gather_process_info();
+    let mut targets = filter_processes(&all, filter);
+    targets.retain(|process| !is_protected(process.pid));
+    if targets.is_empty() {
+        return Err("no process matches the filters".into());
+    }
+    let mut control = ProcessControl::new(matches.get_flag("dry-run"));
+    let mut reports = Vec::new();
+
+    if let Some(nice) = matches.get_one::<i32>("renice") {
+        reports.push(control.renice(&targets, *nice));
+    }
+    if let Some(cpus) = matches.get_one::<Vec<usize>>("affinity") {
+        reports.push(control.set_affinity(&targets, cpus));
+    }
+    let signal = matches.get_one::<Signal>("signal").copied();
+    if matches.get_flag("kill-tree") {
+        for root in &targets {
+            reports.push(control.kill_tree(root, &all, signal.unwrap_or(Signal::Term)));
+        }
+    } else if let Some(signal) = signal {
+        reports.push(control.signal(&targets, signal));
+    }
+    if let Some(seconds) = matches.get_one::<u64>("wait") {
+        reports.push(control.wait_exit(&targets, Duration::from_secs(*seconds)));
+    }
+
+    if let Some(database_url) = matches.get_one::<String>("database-url") {
+        let repository = Repository::new(database_url)?;
+        for event in control.take_events() {
+            repository.save_event(&event)?;
+        }
+    }
+    if json {
+        println!("{}", serde_json::to_string(&reports)?);
+    } else {
+        for report in &reports {
+            report.display();
+        }
+    }
+    if reports.iter().all(|r| r.is_success()) {
+        Ok(())
+    } else {
+        Err("some process actions failed".into())
+    }
+}
+
+// 按 --format 和 --fields 输出，text 格式未指定字段时使用各模块自带的可读输出
+fn output_report(report: &Report, output_format: OutputFormat, fields: Option<&[String]>) {
+    if output_format == OutputFormat::Text && fields.is_none() {
+        report.display();
+        return;
+    }
+    match report.render(output_format, fields) {
+        Ok(output) => print!("{}", if output.ends_with('\n') { output } else { output + "\n" }),
+        Err(e) => {
+            eprintln!("{} info: {}", NAME, e);
+            std::process::exit(2);
+        }
+    }
+}
+
+// 持续采样资源使用率，JSON 模式下每行输出一个采样
+fn watch(matches: &ArgMatches, interval: u64) -> InfoResult<()> {
+    let json = matches.get_flag("json");
+    let count = matches.get_one::<u64>("count").copied();
+    let history = *matches.get_one::<usize>("history").unwrap();
+    let mut sampler = Sampler::new(Duration::from_secs(interval), history);
+    if let Some(path) = matches.get_one::<PathBuf>("save") {
+        sampler = sampler.persist_to(path);
+    }
+    let clear = !json && std::io::stdout().is_terminal();
+
+    sampler.sample()?;
+    let mut taken = 0;
+    while count.is_none_or(|count| taken < count) {
+        thread::sleep(sampler.interval());
+        let Some(sample) = sampler.sample()? else {
+            continue;
+        };
+        taken += 1;
+        if json {
+            println!("{}", sample.to_json());
+            continue;
+        }
+        if clear {
+            print!("\x1B[2J\x1B[H");
+        }
+        sample.display();
+        let samples = sampler.samples();
+        let average = samples.iter().map(|s| s.cpu_percent).sum::<f64>() / samples.len() as f64;
+        let peak = samples.iter().map(|s| s.cpu_percent).fold(0.0, f64::max);
+        println!("CPU over last {} samples: avg {:.1}% peak {:.1}%\n", samples.len(), average, peak);
+    }
+    Ok(())
+}
+
 fn main() {
     // 创建命令行参数解析器
     let matches = Command::new(NAME)
@@ -28,6 +172,38 @@ fn main() {
                 .help("Output in JSON format")
                 .action(clap::ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("section")
+                .short('S')
+                .long("section")
+                .value_name("SECTIONS")
+                .help("Only collect and show SECTIONS of os, cpu, mem, disk, net, proc, svc (comma separated or repeated)")
+                .action(clap::ArgAction::Append)
+                .value_delimiter(',')
+                .conflicts_with_all(["pid", "proc-str", "filter-out-str", "regex", "user", "ppid", "cpu-above", "mem-above", "tree", "sort",
+                                     "signal", "kill-tree", "renice", "affinity", "wait", "sockets", "units", "hardware", "accounts",
+                                     "kernel", "watch"])
+                .value_parser(|s: &str| s.parse::<Section>()),
+        )
+        .arg(
+            Arg::new("format")
+                .short('o')
+                .long("format")
+                .value_name("FORMAT")
+                .help("Output format of host info and process lists: text, table, json, json-pretty, yaml, csv or prometheus")
+                .conflicts_with_all(["json", "tree"])
+                .value_parser(|s: &str| s.parse::<OutputFormat>()),
+        )
+        .arg(
+            Arg::new("fields")
+                .long("fields")
+                .value_name("FIELDS")
+                .help("Only output FIELDS, e.g. pid,command; nested fields are joined by '.', like link.mtu")
+                .value_delimiter(',')
+                .action(clap::ArgAction::Append)
+                .conflicts_with("tree")
+                .value_parser(clap::value_parser!(String)),
+        )
         .arg(
             Arg::new("pid")
                 .short('p')
@@ -43,40 +219,382 @@ fn main() {
                 .help("Specify the processes keyword to filter")
                 .value_parser(clap::value_parser!(String)),
         )
+        .arg(
+            Arg::new("regex")
+                .short('r')
+                .long("regex")
+                .value_name("PATTERN")
+                .help("Specify the regular expression the process command line must match")
+                .value_parser(|s: &str| Regex::new(s).map_err(|e| e.to_string())),
+        )
+        .arg(
+            Arg::new("user")
+                .short('u')
+                .long("user")
+                .value_name("USER")
+                .help("Only processes owned by USER (name or uid)")
+                .value_parser(clap::value_parser!(String)),
+        )
+        .arg(
+            Arg::new("ppid")
+                .long("ppid")
+                .value_name("PID")
+                .help("Only children of process PID")
+                .value_parser(clap::value_parser!(u32)),
+        )
+        .arg(
+            Arg::new("cpu-above")
+                .long("cpu-above")
+                .value_name("PERCENT")
+                .help("Only processes using at least PERCENT CPU")
+                .value_parser(clap::value_parser!(f32)),
+        )
+        .arg(
+            Arg::new("mem-above")
+                .long("mem-above")
+                .value_name("MB")
+                .help("Only processes with at least MB resident memory")
+                .value_parser(clap::value_parser!(u64)),
+        )
+        .arg(
+            Arg::new("any")
+                .long("any")
+                .help("Match processes satisfying any of the filters instead of all")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("tree")
+                .short('t')
+                .long("tree")
+                .help("Show processes as a parent/child tree")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("sort")
+                .short('s')
+                .long("sort")
+                .value_name("KEY")
+                .help("Sort processes by pid, cpu, mem, start or command")
+                .value_parser(|s: &str| s.parse::<SortKey>()),
+        )
+        .arg(
+            Arg::new("signal")
+                .long("signal")
+                .value_name("SIGNAL")
+                .help("Send SIGNAL (name like TERM/SIGKILL or number) to the matched processes")
+                .value_parser(|s: &str| s.parse::<Signal>()),
+        )
+        .arg(
+            Arg::new("kill-tree")
+                .long("kill-tree")
+                .help("Send --signal (default TERM) to the matched processes and all their descendants")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("renice")
+                .long("renice")
+                .value_name("NICE")
+                .help("Set the nice value (-20 to 19) of the matched processes")
+                .allow_negative_numbers(true)
+                .value_parser(clap::value_parser!(i32).range(-20..=19)),
+        )
+        .arg(
+            Arg::new("affinity")
+                .long("affinity")
+                .value_name("CPUS")
+                .help("Bind the matched processes to CPUS, e.g. 0,2-3 (Linux only)")
+                .value_parser(parse_cpu_list),
+        )
+        .arg(
+            Arg::new("wait")
+                .long("wait")
+                .value_name("SECONDS")
+                .help("Wait up to SECONDS for the matched processes to exit")
+                .value_parser(clap::value_parser!(u64)),
+        )
+        .arg(
+            Arg::new("dry-run")
+                .short('n')
+                .long("dry-run")
+                .help("Show the processes that would be affected without acting on them")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("database-url")
+                .long("database-url")
+                .value_name("URL")
+                .help("Also save the audit events of process actions to the database")
+                .value_parser(clap::value_parser!(String)),
+        )
+        .arg(
+            Arg::new("sockets")
+                .long("sockets")
+                .help("Show listening sockets and connections with their owning processes, grouped by service")
+                .conflicts_with_all(["pid", "proc-str", "filter-out-str", "regex", "user", "ppid", "cpu-above", "mem-above", "tree", "sort",
+                                     "signal", "kill-tree", "renice", "affinity", "wait"])
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("probe-banners")
+                .long("probe-banners")
+                .help("Connect to local TCP listeners and identify services from their banners")
+                .requires("sockets")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("units")
+                .long("units")
+                .value_name("PATTERN")
+                .num_args(0..=1)
+                .default_missing_value("*")
+                .help("Show systemd service units matching PATTERN (default all) with state, main PID, memory and restarts")
+                .conflicts_with_all(["pid", "proc-str", "filter-out-str", "regex", "user", "ppid", "cpu-above", "mem-above", "tree", "sort",
+                                     "signal", "kill-tree", "renice", "affinity", "wait", "sockets"]),
+        )
+        .arg(
+            Arg::new("hardware")
+                .long("hardware")
+                .help("Show DMI, PCI, USB and disk identity with the hardware fingerprint (Linux only)")
+                .conflicts_with_all(["pid", "proc-str", "filter-out-str", "regex", "user", "ppid", "cpu-above", "mem-above", "tree", "sort",
+                                     "signal", "kill-tree", "renice", "affinity", "wait", "sockets", "units"])
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("accounts")
+                .long("accounts")
+                .help("Show local users, groups, login sessions, SSH authorized keys and sudo rules (Linux only)")
+                .conflicts_with_all(["pid", "proc-str", "filter-out-str", "regex", "user", "ppid", "cpu-above", "mem-above", "tree", "sort",
+                                     "signal", "kill-tree", "renice", "affinity", "wait", "sockets", "units", "hardware"])
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("kernel")
+                .long("kernel")
+                .help("Show kernel release and command line, boot time, modules, sysctl values, SELinux/AppArmor, time zone and time sync")
+                .conflicts_with_all(["pid", "proc-str", "filter-out-str", "regex", "user", "ppid", "cpu-above", "mem-above", "tree", "sort",
+                                     "signal", "kill-tree", "renice", "affinity", "wait", "sockets", "units", "hardware", "accounts"])
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("sysctl")
+                .long("sysctl")
+                .value_name("KEY")
+                .help("sysctl parameter to show with --kernel instead of the default security baseline, can be repeated")
+                .requires("kernel")
+                .action(clap::ArgAction::Append)
+                .value_parser(clap::value_parser!(String)),
+        )
+        .arg(
+            Arg::new("watch")
+                .short('w')
+                .long("watch")
+                .value_name("SECONDS")
+                .num_args(0..=1)
+                .default_missing_value("1")
+                .help("Show CPU, load, memory, disk IO and network throughput every SECONDS (default 1)")
+                .conflicts_with_all(["pid", "proc-str", "filter-out-str", "regex", "user", "ppid", "cpu-above", "mem-above", "tree", "sort",
+                                     "signal", "kill-tree", "renice", "affinity", "wait"])
+                .value_parser(clap::value_parser!(u64).range(1..)),
+        )
+        .arg(
+            Arg::new("count")
+                .short('c')
+                .long("count")
+                .value_name("N")
+                .help("Stop after N samples in watch mode")
+                .requires("watch")
+                .value_parser(clap::value_parser!(u64)),
+        )
+        .arg(
+            Arg::new("history")
+                .long("history")
+                .value_name("N")
+                .help("Number of samples kept in memory for averages in watch mode")
+                .requires("watch")
+                .default_value("60")
+                .value_parser(clap::value_parser!(usize)),
+        )
+        .arg(
+            Arg::new("save")
+                .long("save")
+                .value_name("FILE")
+                .help("Append samples to FILE as JSON lines in watch mode")
+                .requires("watch")
+                .value_parser(clap::value_parser!(PathBuf)),
+        )
         .get_matches();
 
-    // 检查命令行参数并执行相应操作
-    let pid_string;
-    let filter = if let Some(keyword) = matches.get_one::<String>("proc-str") {
-        Some(Filter::ByKeyword(keyword))
-    } else if let Some(pid) = matches.get_one::<u32>("pid") {
-        pid_string = pid.to_string();
-        Some(Filter::ByPid(&pid_string))
-    } else {
-        None
-    };
+    if let Some(interval) = matches.get_one::<u64>("watch") {
+        if let Err(e) = watch(&matches, *interval) {
+            eprintln!("{} info: {}", NAME, e);
+            std::process::exit(1);
+        }
+        return;
+    }
 
-    let mask = if let Some(keyword) = matches.get_one::<String>("filter-out-str") {
-        Some(Filter::ByKeyword(keyword))
-    } else {
-        None
-    };
+    if matches.get_flag("sockets") {
+        let mut inventory = SocketInventory::collect();
+        if matches.get_flag("probe-banners") {
+            inventory.identify_by_banner(Duration::from_millis(500));
+        }
+        if matches.get_flag("json") {
+            println!("{}", inventory.to_json());
+        } else {
+            inventory.display();
+        }
+        return;
+    }
 
-    // 创建 HostInfo 对象
-    let host_info = HostInfo::new();
+    if matches.get_flag("hardware") {
+        let hardware = match HardwareInfo::collect() {
+            Ok(hardware) => hardware,
+            Err(e) => {
+                eprintln!("{} info: {}", NAME, e);
+                std::process::exit(1);
+            }
+        };
+        if matches.get_flag("json") {
+            println!("{}", hardware.to_json());
+        } else {
+            hardware.display();
+        }
+        return;
+    }
 
-    // 根据命令行参数获取进程信息并显示
-    if matches.get_flag("json") {
-        if let Some(filter) = filter {
-            println!("{}", get_filtered_processes_as_json(Some(filter), mask));
+    if matches.get_flag("accounts") {
+        let accounts = match AccountInfo::collect() {
+            Ok(accounts) => accounts,
+            Err(e) => {
+                eprintln!("{} info: {}", NAME, e);
+                std::process::exit(1);
+            }
+        };
+        if matches.get_flag("json") {
+            println!("{}", accounts.to_json());
         } else {
-            println!("{}", host_info.to_json());
+            accounts.display();
         }
-    } else {
-        if let Some(filter) = filter {
-            println!("{:#?}", get_filtered_processes_as_list(Some(filter), mask));
+        return;
+    }
+
+    if matches.get_flag("kernel") {
+        let keys: Vec<String> = match matches.get_many::<String>("sysctl") {
+            Some(keys) => keys.cloned().collect(),
+            None => DEFAULT_SYSCTL_KEYS.iter().map(|key| key.to_string()).collect(),
+        };
+        let os_info = OSInfo::with_sysctl_keys(&keys);
+        if matches.get_flag("json") {
+            println!("{}", serde_json::to_string(os_info.kernel()).unwrap_or_else(|_| "{}".to_string()));
+        } else {
+            os_info.kernel().display();
+        }
+        return;
+    }
+
+    if let Some(pattern) = matches.get_one::<String>("units") {
+        let units = match Systemctl::new().list_units(Some(pattern)) {
+            Ok(units) => units,
+            Err(e) => {
+                eprintln!("{} info: {}", NAME, e);
+                std::process::exit(1);
+            }
+        };
+        if matches.get_flag("json") {
+            println!("{}", serde_json::to_string(&units).unwrap_or_else(|_| "[]".to_string()));
+        } else {
+            println!("{}", UnitInfo::header());
+            for unit in &units {
+                unit.display();
+            }
+        }
+        return;
+    }
+
+    // 进程过滤条件，默认全部满足（AND），指定 --any 时满足其一（OR），排除条件始终生效
+    let mut conditions = Vec::new();
+    if let Some(pid) = matches.get_one::<u32>("pid") {
+        conditions.push(Filter::ByPid(*pid));
+    }
+    if let Some(keyword) = matches.get_one::<String>("proc-str") {
+        conditions.push(Filter::ByKeyword(keyword.clone()));
+    }
+    if let Some(regex) = matches.get_one::<Regex>("regex") {
+        conditions.push(Filter::ByRegex(regex.clone()));
+    }
+    if let Some(user) = matches.get_one::<String>("user") {
+        conditions.push(Filter::ByUser(user.clone()));
+    }
+    if let Some(ppid) = matches.get_one::<u32>("ppid") {
+        conditions.push(Filter::ByParent(*ppid));
+    }
+    if let Some(percent) = matches.get_one::<f32>("cpu-above") {
+        conditions.push(Filter::CpuAbove(*percent));
+    }
+    if let Some(megabytes) = matches.get_one::<u64>("mem-above") {
+        conditions.push(Filter::MemoryAbove(megabytes * 1024 * 1024));
+    }
+    // 仅有排除条件时会匹配几乎所有进程，不能用于控制操作
+    let selective = !conditions.is_empty();
+    let mut filters = Vec::new();
+    if selective {
+        filters.push(if matches.get_flag("any") { Filter::Or(conditions) } else { Filter::And(conditions) });
+    }
+    if let Some(keyword) = matches.get_one::<String>("filter-out-str") {
+        filters.push(Filter::Not(Box::new(Filter::ByKeyword(keyword.clone()))));
+    }
+    let filter = if filters.is_empty() { None } else { Some(Filter::And(filters)) };
+    let sort = matches.get_one::<SortKey>("sort").copied();
+    let tree = matches.get_flag("tree");
+    let action = ACTIONS.iter().any(|action| matches.value_source(action) == Some(ValueSource::CommandLine));
+
+    let fields: Option<Vec<String>> = matches.get_many::<String>("fields").map(|fields| fields.cloned().collect());
+    let output_format = matches.get_one::<OutputFormat>("format").copied()
+        .unwrap_or(if matches.get_flag("json") { OutputFormat::Json } else { OutputFormat::Text });
+    let formatted = fields.is_some() || matches.get_one::<OutputFormat>("format").is_some();
+
+    // 未指定进程相关参数时显示本机信息，指定模块、格式或字段时只采集所需模块
+    if filter.is_none() && sort.is_none() && !tree && !action {
+        let sections: Option<Vec<Section>> = matches.get_many::<Section>("section").map(|sections| sections.copied().collect());
+        if sections.is_some() || formatted {
+            let report = Report::collect(sections.as_deref().unwrap_or(&Section::ALL));
+            output_report(&report, output_format, fields.as_deref());
+            return;
+        }
+        let host_info = HostInfo::new();
+        if matches.get_flag("json") {
+            println!("{}", host_info.to_json());
         } else {
             host_info.display();
         }
+        return;
+    }
+
+    if action {
+        if !selective {
+            eprintln!("{} info: process actions require at least one process filter besides --filter-out-str", NAME);
+            std::process::exit(2);
+        }
+        if let Err(e) = control(&matches, filter.as_ref()) {
+            eprintln!("{} info: {}", NAME, e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let mut processes = get_filtered_processes_as_list(filter.as_ref());
+    if let Some(sort) = sort {
+        sort_processes(&mut processes, sort);
+    }
+    if formatted {
+        output_report(&Report::from_processes(processes), output_format, fields.as_deref());
+    } else if matches.get_flag("json") {
+        println!("{}", serde_json::to_string(&processes).unwrap_or_else(|_| "[]".to_string()));
+    } else if tree {
+        print!("{}", process_tree(&processes));
+    } else {
+        println!("{}", ProcessInfo::header());
+        for process in &processes {
+            process.display();
+        }
     }
 }
@@ -0,0 +1,135 @@
+extern crate clap;
+use std::error::Error;
+
+use clap::{Arg, ArgMatches, Command};
+use log::{info, warn};
+use tokio::sync::watch;
+
+use minirobot::common::config::{read_config, MonitorConfig};
+use minirobot::common::logger;
+use minirobot::database::connection::default_database_url;
+use minirobot::database::repository::Repository;
+use minirobot::monitor::api;
+use minirobot::monitor::monitor::{shutdown_signal, Monitor};
+
+include!(concat!(env!("OUT_DIR"), "/version.rs"));
+include!(concat!(env!("OUT_DIR"), "/configfile.rs"));
+
+fn monitor(matches: &ArgMatches) -> Result<Monitor, Box<dyn Error>> {
+    let config_file = matches.get_one::<String>("config").cloned().unwrap_or_else(|| GLOBAL_MONITOR_FILE.to_string());
+    let mut monitor = Monitor::from_config(read_config::<MonitorConfig>(&config_file))?;
+
+    // 数据库不可用时仍然监控，只是不保存事件
+    let database_url = matches.get_one::<String>("database-url").cloned().unwrap_or_else(default_database_url);
+    match Repository::new(&database_url) {
+        Ok(repository) => monitor.attach_repository(repository),
+        Err(e) => warn!("Failed to connect to database {}, events are not saved: {}", database_url, e),
+    }
+    Ok(monitor)
+}
+
+// 按各监控项的间隔持续检查，收到 SIGTERM 或 Ctrl-C 后停止
+async fn run_daemon(monitor: Monitor, listen: &str) -> Result<(), Box<dyn Error>> {
+    let (sender, receiver) = watch::channel(false);
+    let mut server_shutdown = receiver.clone();
+    let (address, server) = api::bind(monitor.state(), listen.parse()?, async move {
+        let _ = server_shutdown.changed().await;
+    })?;
+    info!("Serving monitor state on http://{}/checks", address);
+    let server = tokio::spawn(server);
+
+    tokio::spawn(async move {
+        shutdown_signal().await;
+        info!("Received shutdown signal");
+        let _ = sender.send(true);
+    });
+    monitor.run(receiver).await;
+    server.await?;
+    Ok(())
+}
+
+fn run(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let mut monitor = monitor(matches)?;
+    if matches.get_flag("once") {
+        let results = monitor.start();
+        if matches.get_flag("json") {
+            let results: Vec<serde_json::Value> = results.iter()
+                .map(|(name, result)| serde_json::json!({"name": name, "status": result.status, "message": result.message, "metrics": result.metrics}))
+                .collect();
+            println!("{}", serde_json::to_string(&results)?);
+        } else {
+            // 与 Nagios 插件输出相同："名称 状态 - 说明 | 性能数据"
+            for (name, result) in &results {
+                let perfdata = result.metrics.iter().map(|metric| metric.to_string()).collect::<Vec<String>>().join(" ");
+                let message = result.message.lines().next().unwrap_or_default();
+                if perfdata.is_empty() {
+                    println!("{} {} - {}", name, result.status, message);
+                } else {
+                    println!("{} {} - {} | {}", name, result.status, message, perfdata);
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    let listen = matches.get_one::<String>("listen").cloned().unwrap_or_else(|| monitor.config().listen.clone());
+    let runtime = tokio::runtime::Runtime::new()?;
+    let result = runtime.block_on(run_daemon(monitor, &listen));
+    // 超时的检查线程不阻塞退出
+    runtime.shutdown_background();
+    result
+}
+
+fn main() {
+    logger::init();
+
+    let matches = Command::new(NAME)
+        .version(VERSION)
+        .author(AUTHORS)
+        .about(ABOUT)
+        .after_help(COPYRIGHT)
+        .arg(
+            Arg::new("config")
+                .short('c')
+                .long("config")
+                .value_name("FILE")
+                .help(format!("Specify the monitor config file [default: {}]", GLOBAL_MONITOR_FILE))
+                .value_parser(clap::value_parser!(String)),
+        )
+        .arg(
+            Arg::new("database-url")
+                .long("database-url")
+                .value_name("URL")
+                .help("Specify the database storing events")
+                .value_parser(clap::value_parser!(String)),
+        )
+        .arg(
+            Arg::new("listen")
+                .short('l')
+                .long("listen")
+                .value_name("ADDRESS")
+                .help("Serve monitor state on ADDRESS instead of the configured listen address")
+                .conflicts_with("once")
+                .value_parser(clap::value_parser!(String)),
+        )
+        .arg(
+            Arg::new("once")
+                .long("once")
+                .help("Run every check once, print the results and exit")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("json")
+                .short('j')
+                .long("json")
+                .help("Output results in JSON format")
+                .requires("once")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .get_matches();
+
+    if let Err(e) = run(&matches) {
+        eprintln!("{} monitor: {}", NAME, e);
+        std::process::exit(1);
+    }
+}
@@ -1,10 +1,133 @@
 extern crate clap;
-use clap::{Arg, Command};
+use std::error::Error;
 
-use minirobot::info::hostinfo::HostInfo;
+use clap::{Arg, ArgMatches, Command};
+
+use minirobot::common::ds;
+use minirobot::database::connection::default_database_url;
+use minirobot::database::repository::Repository;
+use minirobot::task::history::*;
+use minirobot::task::report::{self, ReportFormat};
+use minirobot::task::task::{load_task_definition, Task};
 
 include!(concat!(env!("OUT_DIR"), "/version.rs"));
-include!(concat!(env!("OUT_DIR"), "/configfile.rs"));
+
+// 任务过滤参数（list 和 stats 共用）
+fn filter_args() -> Vec<Arg> {
+    vec![
+        Arg::new("status")
+            .long("status")
+            .value_name("STATUS")
+            .help("Filter by task status, e.g. finished, running")
+            .value_parser(clap::value_parser!(ds::TaskStatus)),
+        Arg::new("result")
+            .long("result")
+            .value_name("RESULT")
+            .help("Filter by task result: success, failed, error")
+            .value_parser(clap::value_parser!(ds::Result)),
+        Arg::new("name")
+            .short('n')
+            .long("name")
+            .value_name("NAME")
+            .help("Filter by task name keyword")
+            .value_parser(clap::value_parser!(String)),
+        Arg::new("since")
+            .long("since")
+            .value_name("TIME")
+            .help("Only tasks created at or after TIME (YYYY-MM-DD[ HH:MM:SS] or RFC3339)")
+            .value_parser(clap::value_parser!(String)),
+        Arg::new("until")
+            .long("until")
+            .value_name("TIME")
+            .help("Only tasks created before TIME (YYYY-MM-DD[ HH:MM:SS] or RFC3339)")
+            .value_parser(clap::value_parser!(String)),
+    ]
+}
+
+fn task_filter(matches: &ArgMatches) -> Result<TaskFilter, Box<dyn Error>> {
+    Ok(TaskFilter {
+        status: matches.get_one::<ds::TaskStatus>("status").cloned(),
+        result: matches.get_one::<ds::Result>("result").cloned(),
+        name: matches.get_one::<String>("name").cloned(),
+        since: matches.get_one::<String>("since").map(|s| parse_time(s)).transpose()?,
+        until: matches.get_one::<String>("until").map(|s| parse_time(s)).transpose()?,
+    })
+}
+
+// 指定 --history-dir 时使用文件目录，否则使用数据库
+fn task_store(matches: &ArgMatches) -> Result<Box<dyn TaskStore>, Box<dyn Error>> {
+    if let Some(dir) = matches.get_one::<String>("history-dir") {
+        return Ok(Box::new(TaskHistory::new(dir)));
+    }
+    let database_url = matches.get_one::<String>("database-url").cloned().unwrap_or_else(default_database_url);
+    Ok(Box::new(Repository::new(&database_url)?))
+}
+
+fn run(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let history = task_store(matches)?;
+    let json = matches.get_flag("json");
+
+    match matches.subcommand() {
+        Some(("submit", sub)) => {
+            let definition = load_task_definition(sub.get_one::<String>("file").unwrap())?;
+            let mut task = Task::from_definition(&definition);
+            task.run();
+            let record = TaskRecord::from(&task);
+            history.save(&record)?;
+            if json {
+                println!("{}", record.to_json());
+            } else {
+                record.display();
+            }
+        }
+        Some(("list", sub)) => {
+            let records = history.list(&task_filter(sub)?)?;
+            if json {
+                println!("{}", serde_json::to_string(&records)?);
+            } else {
+                display_table(&records);
+            }
+        }
+        Some(("show", sub)) => {
+            let record = history.load(sub.get_one::<String>("id").unwrap())?;
+            let tail = *sub.get_one::<usize>("tail").unwrap();
+            let log_tail = tail_log(&record.log_file, tail).unwrap_or_default();
+            if json {
+                let mut value = serde_json::to_value(&record)?;
+                value["log_tail"] = serde_json::to_value(&log_tail)?;
+                println!("{}", value);
+            } else {
+                record.display();
+                println!("Log tail ({}):", record.log_file);
+                for line in log_tail {
+                    println!("  {}", line);
+                }
+            }
+        }
+        Some(("stats", sub)) => {
+            let records = history.list(&task_filter(sub)?)?;
+            let stats = compute_stats(&records, *sub.get_one::<Period>("period").unwrap());
+            if json {
+                println!("{}", serde_json::to_string(&stats)?);
+            } else {
+                display_stats(&stats);
+            }
+        }
+        Some(("report", sub)) => {
+            let records = match sub.get_many::<String>("id") {
+                Some(ids) => ids.map(|id| history.load(id)).collect::<Result<Vec<TaskRecord>, Box<dyn Error>>>()?,
+                None => history.list(&task_filter(sub)?)?,
+            };
+            let format = *sub.get_one::<ReportFormat>("format").unwrap();
+            match sub.get_one::<String>("output") {
+                Some(output) => report::write_report(&records, format, output)?,
+                None => print!("{}", report::render(&records, format)),
+            }
+        }
+        _ => unreachable!("subcommand required"),
+    }
+    Ok(())
+}
 
 fn main() {
     // 创建命令行参数解析器
@@ -13,21 +136,96 @@ fn main() {
         .author(AUTHORS)
         .about(ABOUT)
         .after_help(COPYRIGHT)
-        // .arg(
-        //     Arg::new("ctype")
-        //         .short('c')
-        //         .long("connect-type")
-        //         .value_name("CONNECTION_TYPE")
-        //         .help("Specify the type to connect target device")
-        //         .value_parser(clap::value_parser!(String)),
-        // )
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .arg(
+            Arg::new("history-dir")
+                .long("history-dir")
+                .value_name("DIR")
+                .help("Store task run history as JSON files in DIR instead of the database")
+                .global(true)
+                .conflicts_with("database-url")
+                .value_parser(clap::value_parser!(String)),
+        )
+        .arg(
+            Arg::new("database-url")
+                .long("database-url")
+                .value_name("URL")
+                .help("Specify the database storing task run history")
+                .global(true)
+                .value_parser(clap::value_parser!(String)),
+        )
+        .arg(
+            Arg::new("json")
+                .short('j')
+                .long("json")
+                .help("Output in JSON format")
+                .global(true)
+                .action(clap::ArgAction::SetTrue),
+        )
+        .subcommand(
+            Command::new("submit")
+                .about("Run a task definition file and record the result")
+                .arg(Arg::new("file").value_name("TASK_FILE").required(true)),
+        )
+        .subcommand(
+            Command::new("list")
+                .about("List recorded tasks")
+                .args(filter_args()),
+        )
+        .subcommand(
+            Command::new("show")
+                .about("Show jobs, timings, results and log tail of one task")
+                .arg(Arg::new("id").value_name("TASK_ID").required(true).help("Task ID or unique ID prefix"))
+                .arg(
+                    Arg::new("tail")
+                        .long("tail")
+                        .value_name("LINES")
+                        .help("Number of log lines to show")
+                        .default_value("20")
+                        .value_parser(clap::value_parser!(usize)),
+                ),
+        )
+        .subcommand(
+            Command::new("stats")
+                .about("Pass/fail rates and average cost per task name")
+                .args(filter_args())
+                .arg(
+                    Arg::new("period")
+                        .long("period")
+                        .value_name("PERIOD")
+                        .help("Group by period: all, day, week, month")
+                        .default_value("all")
+                        .value_parser(clap::value_parser!(Period)),
+                ),
+        )
+        .subcommand(
+            Command::new("report")
+                .about("Export task results as JUnit XML or HTML report")
+                .arg(Arg::new("id").value_name("TASK_ID").num_args(1..).help("Tasks to report, default all tasks matching the filters"))
+                .args(filter_args())
+                .arg(
+                    Arg::new("format")
+                        .short('f')
+                        .long("format")
+                        .value_name("FORMAT")
+                        .help("Report format: junit, html")
+                        .default_value("junit")
+                        .value_parser(clap::value_parser!(ReportFormat)),
+                )
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .value_name("FILE")
+                        .help("Write report to FILE instead of stdout")
+                        .value_parser(clap::value_parser!(String)),
+                ),
+        )
         .get_matches();
 
-    println!("{} task manager running", NAME);
-    println!("config file is {}", GLOBAL_CONFIG_FILE);
-
-    // 创建 HostInfo 对象
-    println!("运行主机当前信息:");
-    let host_info = HostInfo::new();
-    host_info.display();
+    if let Err(e) = run(&matches) {
+        eprintln!("{} task manager: {}", NAME, e);
+        std::process::exit(1);
+    }
 }
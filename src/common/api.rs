@@ -10,4 +10,16 @@ pub fn format_size(size: u64) -> String {
     }
 
     format!("{:.0} {}", size, units[unit])
-}
\ No newline at end of file
+}
+
+// 将秒数转换为易读格式的函数，如 "3d 4h 5m"
+pub fn format_duration(secs: u64) -> String {
+    let (days, hours, minutes) = (secs / 86400, secs % 86400 / 3600, secs % 3600 / 60);
+    if days > 0 {
+        format!("{}d {}h {}m", days, hours, minutes)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else {
+        format!("{}m", minutes)
+    }
+}
@@ -19,6 +19,31 @@ pub struct GlobalConfig {
     pub database_url: String,
     pub peers_file: String,
     pub monitor_config: String,
+    #[serde(default)]
+    pub metrics: Option<MetricsConfig>,      // 未配置时不提供指标接口
+}
+
+// Prometheus 指标接口
+#[derive(Debug, Clone, Deserialize)]
+pub struct MetricsConfig {
+    #[serde(default = "default_metrics_listen")]
+    pub listen: String,                // 监听地址
+    #[serde(default)]
+    pub collectors: Vec<String>,       // 启用的采集器，为空时启用全部
+    #[serde(default)]
+    pub auth: Option<MetricsAuthConfig>,   // 未配置时不认证
+}
+
+fn default_metrics_listen() -> String {
+    "127.0.0.1:9102".to_string()
+}
+
+// 指标接口认证方式
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum MetricsAuthConfig {
+    Basic { username: String, password: String },
+    Bearer { token: String },
 }
 
 #[derive(Debug, Deserialize)]
@@ -57,29 +82,141 @@ pub struct MonitorConfig {
     pub disk_usage: DiskUsageMonitorConfig,
     pub open_port: OpenPortMonitorConfig,
     pub task_status: TaskStatusMonitorConfig,
+    #[serde(default)]
+    pub resource_usage: Option<ResourceUsageMonitorConfig>,   // 未配置时不监控
+    #[serde(default)]
+    pub systemd_units: Option<SystemdUnitMonitorConfig>,      // 未配置时不监控
+    #[serde(default)]
+    pub scripts: Vec<ScriptMonitorConfig>,                    // 脚本监控项
+    #[serde(default = "default_monitor_listen")]
+    pub listen: String,                // 监控状态接口监听地址
 }
 
-#[derive(Debug, Deserialize)]
+fn default_monitor_listen() -> String {
+    "127.0.0.1:9103".to_string()
+}
+
+// 监控项默认每 60 秒检查一次
+fn default_interval_secs() -> u64 {
+    60
+}
+
+// 监控项默认 30 秒未完成视为超时
+fn default_timeout_secs() -> u64 {
+    30
+}
+
+// 阈值规则，数值越大越严重，未配置的字段在覆盖规则中沿用上级规则
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ThresholdConfig {
+    #[serde(default)]
+    pub warning: Option<f64>,          // 告警阈值
+    #[serde(default)]
+    pub critical: Option<f64>,         // 严重阈值
+    #[serde(default)]
+    pub for_secs: Option<u64>,         // 持续超过阈值该时长后才告警
+    #[serde(default)]
+    pub hysteresis: Option<f64>,       // 回差，低于阈值减去回差后才恢复
+}
+
+// 按挂载点或设备名匹配的分区阈值，支持通配符 * 和 ?
+#[derive(Debug, Clone, Deserialize)]
+pub struct DiskUsageOverride {
+    pub pattern: String,
+    #[serde(flatten)]
+    pub rule: ThresholdConfig,
+}
+
+#[derive(Debug, Clone, Deserialize)]
 pub struct DiskUsageMonitorConfig {
-    pub threshold: u8,
+    #[serde(default)]
+    pub threshold: Option<u8>,         // 兼容旧配置，未配置 warning 时作为告警阈值
+    #[serde(flatten)]
+    pub rule: ThresholdConfig,
+    #[serde(default)]
+    pub include: Vec<String>,          // 监控的挂载点或设备，为空时监控所有分区
+    #[serde(default)]
+    pub exclude: Vec<String>,          // 不监控的挂载点或设备
+    #[serde(default)]
+    pub overrides: Vec<DiskUsageOverride>,  // 按顺序匹配，使用第一条匹配的规则
+    #[serde(default = "default_interval_secs")]
+    pub interval_secs: u64,            // 检查间隔秒
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,             // 检查超时秒
     pub is_active: bool,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
+pub struct ResourceUsageMonitorConfig {
+    pub cpu_threshold: u8,             // CPU 使用率告警阈值 %
+    pub memory_threshold: u8,          // 内存使用率告警阈值 %
+    #[serde(default = "default_window_secs")]
+    pub window_secs: u64,              // CPU 采样区间秒
+    #[serde(default = "default_interval_secs")]
+    pub interval_secs: u64,            // 检查间隔秒
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,             // 检查超时秒
+    pub is_active: bool,
+}
+
+fn default_window_secs() -> u64 {
+    1
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SystemdUnitMonitorConfig {
+    #[serde(default)]
+    pub units: Vec<String>,            // 监控的单元，为空时监控所有服务单元
+    #[serde(default = "default_flap_restarts")]
+    pub flap_restarts: u32,            // 两次检查之间重启次数达到该值视为频繁重启，0 不检查
+    #[serde(default = "default_interval_secs")]
+    pub interval_secs: u64,            // 检查间隔秒
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,             // 检查超时秒
+    pub is_active: bool,
+}
+
+fn default_flap_restarts() -> u32 {
+    3
+}
+
+#[derive(Debug, Clone, Deserialize)]
 pub struct OpenPortMonitorConfig {
     pub high_risk_ports: Vec<u16>,
+    #[serde(default = "default_interval_secs")]
+    pub interval_secs: u64,            // 检查间隔秒
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,             // 检查超时秒
     pub is_active: bool,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct TaskStatusMonitorConfig {
     pub default_statuses: Vec<TaskStatus>,
     pub task_list: Vec<TaskListMonitorConfig>,
+    #[serde(default = "default_interval_secs")]
+    pub interval_secs: u64,            // 检查间隔秒
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,             // 检查超时秒
     pub is_active: bool,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct TaskListMonitorConfig {
     pub name: String,
     pub statuses: Vec<TaskStatus>,
 }
+
+// 脚本监控项，脚本遵循 Nagios 插件约定：退出码 0/1/2/3 表示 OK/WARNING/CRITICAL/UNKNOWN，输出 "说明 | 性能数据"
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScriptMonitorConfig {
+    pub name: String,                  // 监控项名称，不能与其他监控项重复
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default = "default_interval_secs")]
+    pub interval_secs: u64,            // 检查间隔秒
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,             // 检查超时秒
+    pub is_active: bool,
+}
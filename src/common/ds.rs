@@ -1,4 +1,5 @@
-use std::collections::HashSet;
+use std::collections::{HashSet, VecDeque};
+use std::str::FromStr;
 
 use serde::{Serialize, Deserialize};
 
@@ -19,9 +20,49 @@ pub enum TaskStatus {
     Timeout,                           // 运行超时
     Stopped,                           // 被停止
     Cancelled,                         // 被取消
+    Skipped,                           // 被跳过（执行条件不满足）
     Unavailable,                       // 不可执行（资源不可用、设置不当等问题）
 }
 
+impl TaskStatus {
+    pub const ALL: [TaskStatus; 9] = [TaskStatus::Created, TaskStatus::Wait, TaskStatus::Running, TaskStatus::Finished,
+        TaskStatus::Timeout, TaskStatus::Stopped, TaskStatus::Cancelled, TaskStatus::Skipped, TaskStatus::Unavailable];
+}
+
+impl FromStr for TaskStatus {
+    type Err = String;
+
+    // 不区分大小写解析任务状态，用于命令行参数
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "created" => Ok(TaskStatus::Created),
+            "wait" => Ok(TaskStatus::Wait),
+            "running" => Ok(TaskStatus::Running),
+            "finished" => Ok(TaskStatus::Finished),
+            "timeout" => Ok(TaskStatus::Timeout),
+            "stopped" => Ok(TaskStatus::Stopped),
+            "cancelled" => Ok(TaskStatus::Cancelled),
+            "skipped" => Ok(TaskStatus::Skipped),
+            "unavailable" => Ok(TaskStatus::Unavailable),
+            _ => Err(format!("invalid task status: {}", s)),
+        }
+    }
+}
+
+impl FromStr for Result {
+    type Err = String;
+
+    // 不区分大小写解析结果状态，用于命令行参数
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "success" => Ok(Result::Success),
+            "failed" => Ok(Result::Failed),
+            "error" => Ok(Result::Error),
+            _ => Err(format!("invalid result: {}", s)),
+        }
+    }
+}
+
 // 运行角色
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum RunningRole {
@@ -80,3 +121,68 @@ pub struct UserTime {
     mm: String,
     ss: String,
 }
+
+// 定长环形缓冲区，写满后丢弃最旧的元素
+#[derive(Debug, Clone, PartialEq)]
+pub struct RingBuffer<T> {
+    capacity: usize,
+    items: VecDeque<T>,
+}
+
+impl<T> RingBuffer<T> {
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        RingBuffer { capacity, items: VecDeque::with_capacity(capacity) }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// 追加元素，写满时返回被挤出的最旧元素
+    pub fn push(&mut self, item: T) -> Option<T> {
+        let evicted = if self.items.len() == self.capacity { self.items.pop_front() } else { None };
+        self.items.push_back(item);
+        evicted
+    }
+
+    pub fn latest(&self) -> Option<&T> {
+        self.items.back()
+    }
+
+    /// 从旧到新遍历
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> {
+        self.items.iter()
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+}
+
+#[cfg(test)]
+mod unit_test_ds {
+    use super::*;
+
+    #[test]
+    fn test_ring_buffer_01() {
+        let mut buffer = RingBuffer::new(3);
+        assert!(buffer.is_empty() && buffer.latest().is_none());
+        for i in 1..=3 {
+            assert_eq!(buffer.push(i), None);
+        }
+        assert_eq!(buffer.push(4), Some(1));
+        assert_eq!(buffer.iter().copied().collect::<Vec<_>>(), vec![2, 3, 4]);
+        assert_eq!(buffer.latest(), Some(&4));
+        assert_eq!((buffer.len(), buffer.capacity()), (3, 3));
+        assert_eq!(RingBuffer::<u8>::new(0).capacity(), 1);
+    }
+}
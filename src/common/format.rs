@@ -0,0 +1,351 @@
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+
+use serde_json::{Map, Value};
+
+// 输出格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    #[default]
+    Text,                                    // 各模块自带的可读输出
+    Table,
+    Json,
+    JsonPretty,
+    Yaml,
+    Csv,
+    Prometheus,                              // Prometheus 文本格式
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(OutputFormat::Text),
+            "table" => Ok(OutputFormat::Table),
+            "json" => Ok(OutputFormat::Json),
+            "json-pretty" | "pretty" => Ok(OutputFormat::JsonPretty),
+            "yaml" | "yml" => Ok(OutputFormat::Yaml),
+            "csv" => Ok(OutputFormat::Csv),
+            "prometheus" | "prom" => Ok(OutputFormat::Prometheus),
+            _ => Err(format!("unknown format '{}', expect text, table, json, json-pretty, yaml, csv or prometheus", s)),
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            OutputFormat::Text => "text",
+            OutputFormat::Table => "table",
+            OutputFormat::Json => "json",
+            OutputFormat::JsonPretty => "json-pretty",
+            OutputFormat::Yaml => "yaml",
+            OutputFormat::Csv => "csv",
+            OutputFormat::Prometheus => "prometheus",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+/// 展开嵌套对象为以 '.' 连接的字段，如 {"link":{"mtu":1500}} 展开为 {"link.mtu":1500}
+///
+/// 标量数组以 ',' 连接为字符串，对象数组保留为 JSON 文本。
+pub fn flatten(value: &Value) -> Map<String, Value> {
+    let mut row = Map::new();
+    flatten_into(&mut row, "", value);
+    row
+}
+
+fn flatten_into(row: &mut Map<String, Value>, prefix: &str, value: &Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                let name = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+                flatten_into(row, &name, value);
+            }
+        }
+        Value::Array(items) if items.iter().all(|item| !item.is_object() && !item.is_array()) => {
+            let joined = items.iter().map(scalar_text).collect::<Vec<String>>().join(",");
+            row.insert(prefix.to_string(), Value::String(joined));
+        }
+        Value::Array(_) => {
+            row.insert(prefix.to_string(), Value::String(value.to_string()));
+        }
+        _ => {
+            row.insert(prefix.to_string(), value.clone());
+        }
+    }
+}
+
+/// 标量的文本形式，null 为空
+pub fn scalar_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+// 字段名匹配列名，"link" 同时匹配 "link.mtu" 等子字段
+fn field_matches(field: &str, column: &str) -> bool {
+    column == field || (column.starts_with(field) && column[field.len()..].starts_with('.'))
+}
+
+/// 按字段投影已展开的行，列顺序与 fields 一致
+pub fn project(row: &Map<String, Value>, fields: &[String]) -> Map<String, Value> {
+    let mut projected = Map::new();
+    for field in fields {
+        for (column, value) in row.iter().filter(|(column, _)| field_matches(field, column)) {
+            projected.insert(column.clone(), value.clone());
+        }
+    }
+    projected
+}
+
+/// 字段在所有行中都不存在时返回这些字段
+pub fn unknown_fields<'a>(rows: &[Map<String, Value>], fields: &'a [String]) -> Vec<&'a str> {
+    fields.iter()
+        .filter(|field| !rows.iter().any(|row| row.keys().any(|column| field_matches(field, column))))
+        .map(String::as_str)
+        .collect()
+}
+
+/// 所有行的列名，按首次出现的顺序
+pub fn columns(rows: &[Map<String, Value>]) -> Vec<String> {
+    let mut seen = BTreeSet::new();
+    let mut columns = Vec::new();
+    for column in rows.iter().flat_map(|row| row.keys()) {
+        if seen.insert(column.clone()) {
+            columns.push(column.clone());
+        }
+    }
+    columns
+}
+
+// 过长的单元格截断
+const MAX_CELL_WIDTH: usize = 60;
+
+fn cell(value: Option<&Value>) -> String {
+    let text = value.map(scalar_text).unwrap_or_default().replace(['\n', '\t'], " ");
+    if text.chars().count() > MAX_CELL_WIDTH {
+        format!("{}...", text.chars().take(MAX_CELL_WIDTH - 3).collect::<String>())
+    } else {
+        text
+    }
+}
+
+/// 对齐的表格，只有一行时以“字段 值”两列纵向显示
+pub fn to_table(rows: &[Map<String, Value>]) -> String {
+    let mut output = String::new();
+    if rows.len() == 1 {
+        let width = rows[0].keys().map(|key| key.chars().count()).max().unwrap_or(0);
+        for (key, value) in &rows[0] {
+            output.push_str(format!("{:<width$}  {}", key, cell(Some(value)), width = width).trim_end());
+            output.push('\n');
+        }
+        return output;
+    }
+    let columns = columns(rows);
+    let cells: Vec<Vec<String>> = rows.iter()
+        .map(|row| columns.iter().map(|column| cell(row.get(column))).collect())
+        .collect();
+    let widths: Vec<usize> = columns.iter().enumerate()
+        .map(|(i, column)| cells.iter().map(|row| row[i].chars().count()).chain([column.chars().count()]).max().unwrap_or(0))
+        .collect();
+    let line = |values: Vec<String>| {
+        let padded: Vec<String> = values.iter().zip(&widths).map(|(value, width)| format!("{:<width$}", value, width = *width)).collect();
+        format!("{}\n", padded.join("  ").trim_end())
+    };
+    output.push_str(&line(columns.iter().map(|column| column.to_uppercase()).collect()));
+    for row in cells {
+        output.push_str(&line(row));
+    }
+    output
+}
+
+// CSV 字段含逗号、引号或换行时加引号
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// CSV，首行为列名
+pub fn to_csv(rows: &[Map<String, Value>]) -> String {
+    let columns = columns(rows);
+    let mut output = columns.iter().map(|column| csv_field(column)).collect::<Vec<String>>().join(",");
+    output.push('\n');
+    for row in rows {
+        let fields: Vec<String> = columns.iter().map(|column| csv_field(&row.get(column).map(scalar_text).unwrap_or_default())).collect();
+        output.push_str(&fields.join(","));
+        output.push('\n');
+    }
+    output
+}
+
+// YAML 中可以不加引号的键
+fn yaml_key(key: &str) -> String {
+    if !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || "_-./".contains(c)) {
+        key.to_string()
+    } else {
+        Value::String(key.to_string()).to_string()
+    }
+}
+
+// 标量、空数组和空对象写在同一行
+fn yaml_inline(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => Some("null".to_string()),
+        Value::Bool(_) | Value::Number(_) => Some(value.to_string()),
+        // JSON 字符串也是合法的 YAML 双引号字符串
+        Value::String(_) => Some(value.to_string()),
+        Value::Array(items) if items.is_empty() => Some("[]".to_string()),
+        Value::Object(map) if map.is_empty() => Some("{}".to_string()),
+        _ => None,
+    }
+}
+
+fn yaml_lines(output: &mut String, value: &Value, indent: usize) {
+    let pad = " ".repeat(indent);
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                match yaml_inline(value) {
+                    Some(text) => output.push_str(&format!("{}{}: {}\n", pad, yaml_key(key), text)),
+                    None => {
+                        output.push_str(&format!("{}{}:\n", pad, yaml_key(key)));
+                        yaml_lines(output, value, indent + 2);
+                    }
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                match yaml_inline(item) {
+                    Some(text) => output.push_str(&format!("{}- {}\n", pad, text)),
+                    None => {
+                        // 集合元素的第一行跟在 "- " 之后
+                        let mut nested = String::new();
+                        yaml_lines(&mut nested, item, indent + 2);
+                        output.push_str(&format!("{}- {}", pad, &nested[indent + 2..]));
+                    }
+                }
+            }
+        }
+        _ => output.push_str(&format!("{}{}\n", pad, yaml_inline(value).unwrap_or_default())),
+    }
+}
+
+/// YAML 文档
+pub fn to_yaml(value: &Value) -> String {
+    let mut output = String::from("---\n");
+    yaml_lines(&mut output, value, 0);
+    output
+}
+
+/// Prometheus 指标名，非法字符替换为 '_'
+pub fn metric_name(name: &str) -> String {
+    name.chars().map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == ':' { c } else { '_' }).collect()
+}
+
+/// Prometheus 标签值转义
+pub fn label_value(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// 以 Prometheus 文本格式输出一组行
+///
+/// 每行中 labels 指定的字段作为标签，其余数值和布尔字段输出为 `<prefix>_<字段>` 的 gauge。
+pub fn to_prometheus(prefix: &str, rows: &[Map<String, Value>], labels: &[&str], fields: Option<&[String]>) -> String {
+    let mut metrics: Vec<(String, Vec<String>)> = Vec::new();
+    for row in rows {
+        let label_text = labels.iter()
+            .filter_map(|label| row.get(*label).map(|value| format!("{}=\"{}\"", metric_name(label), label_value(&scalar_text(value)))))
+            .collect::<Vec<String>>()
+            .join(",");
+        let values = match fields {
+            Some(fields) => project(row, fields),
+            None => row.clone(),
+        };
+        for (column, value) in values.iter().filter(|(column, _)| !labels.contains(&column.as_str())) {
+            let number = match value {
+                Value::Number(number) => number.to_string(),
+                Value::Bool(flag) => (*flag as u8).to_string(),
+                _ => continue,
+            };
+            let name = metric_name(&format!("{}_{}", prefix, column));
+            let sample = if label_text.is_empty() { format!("{} {}", name, number) } else { format!("{}{{{}}} {}", name, label_text, number) };
+            match metrics.iter_mut().find(|(metric, _)| *metric == name) {
+                Some((_, samples)) => samples.push(sample),
+                None => metrics.push((name, vec![sample])),
+            }
+        }
+    }
+    let mut output = String::new();
+    for (name, samples) in metrics {
+        output.push_str(&format!("# TYPE {} gauge\n", name));
+        for sample in samples {
+            output.push_str(&sample);
+            output.push('\n');
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod unit_test_format {
+    use super::*;
+    use serde_json::json;
+
+    fn rows() -> Vec<Map<String, Value>> {
+        [json!({"pid": 1, "command": "init", "link": {"mtu": 1500, "up": true}, "ips": ["10.0.0.1", "10.0.0.2"]}),
+         json!({"pid": 20, "command": "sh -c \"a,b\"", "link": {"mtu": null, "up": false}, "ips": []})]
+            .iter().map(flatten).collect()
+    }
+
+    #[test]
+    fn test_format_01() {
+        let rows = rows();
+        assert_eq!(columns(&rows), vec!["pid", "command", "link.mtu", "link.up", "ips"]);
+        assert_eq!(rows[0]["ips"], json!("10.0.0.1,10.0.0.2"));
+        let fields = vec!["command".to_string(), "link".to_string(), "nope".to_string()];
+        assert_eq!(Value::Object(project(&rows[0], &fields)), json!({"command": "init", "link.mtu": 1500, "link.up": true}));
+        assert_eq!(unknown_fields(&rows, &fields), vec!["nope"]);
+        assert!(!field_matches("link", "linkage"));
+
+        assert_eq!(to_table(&rows), "PID  COMMAND      LINK.MTU  LINK.UP  IPS\n\
+                                     1    init         1500      true     10.0.0.1,10.0.0.2\n\
+                                     20   sh -c \"a,b\"            false\n");
+        assert_eq!(to_table(&rows[..1]), "pid       1\ncommand   init\nlink.mtu  1500\nlink.up   true\nips       10.0.0.1,10.0.0.2\n");
+        assert_eq!(to_csv(&rows), "pid,command,link.mtu,link.up,ips\n\
+                                   1,init,1500,true,\"10.0.0.1,10.0.0.2\"\n\
+                                   20,\"sh -c \"\"a,b\"\"\",,false,\n");
+        assert_eq!("pretty".parse::<OutputFormat>().unwrap(), OutputFormat::JsonPretty);
+        assert!("xml".parse::<OutputFormat>().is_err());
+    }
+
+    #[test]
+    fn test_format_02() {
+        let value = json!({
+            "hostname": "vm",
+            "proc": [{"pid": 1, "tags": ["a", "b"], "cwd": ""}, 7],
+            "empty": [],
+            "nested": {"x y": null, "z": {"w": 1.5}},
+        });
+        assert_eq!(to_yaml(&value), "---\nhostname: \"vm\"\nproc:\n  - pid: 1\n    tags:\n      - \"a\"\n      - \"b\"\n    cwd: \"\"\n  - 7\n\
+empty: []\nnested:\n  \"x y\": null\n  z:\n    w: 1.5\n");
+
+        let rows = rows();
+        let text = to_prometheus("minirobot_proc", &rows, &["pid", "command"], None);
+        assert_eq!(text, "# TYPE minirobot_proc_link_mtu gauge\nminirobot_proc_link_mtu{pid=\"1\",command=\"init\"} 1500\n\
+# TYPE minirobot_proc_link_up gauge\nminirobot_proc_link_up{pid=\"1\",command=\"init\"} 1\nminirobot_proc_link_up{pid=\"20\",command=\"sh -c \\\"a,b\\\"\"} 0\n");
+        let fields = vec!["link.up".to_string()];
+        assert!(!to_prometheus("minirobot_proc", &rows, &["pid"], Some(&fields)).contains("link_mtu"));
+        assert_eq!(to_prometheus("minirobot_mem", &[flatten(&json!({"memory_total": 8}))], &[], None),
+                   "# TYPE minirobot_mem_memory_total gauge\nminirobot_mem_memory_total 8\n");
+    }
+}
@@ -1,4 +1,5 @@
 pub mod api;
 pub mod config;
 pub mod ds;
+pub mod format;
 pub mod logger;
\ No newline at end of file
@@ -0,0 +1,83 @@
+use std::error::Error;
+use std::time::Duration;
+
+use diesel::r2d2::{ConnectionManager, Pool, PooledConnection};
+use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
+
+#[cfg(not(any(feature = "sqlite", feature = "mysql")))]
+compile_error!("either feature \"sqlite\" or \"mysql\" must be enabled");
+
+// 数据库后端由 cargo feature 决定，同时启用时使用 mysql
+#[cfg(feature = "mysql")]
+pub type DbConnection = diesel::mysql::MysqlConnection;
+
+#[cfg(not(feature = "mysql"))]
+pub type DbConnection = diesel::sqlite::SqliteConnection;
+
+#[cfg(feature = "mysql")]
+pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations/mysql");
+
+#[cfg(not(feature = "mysql"))]
+pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations/sqlite");
+
+pub type DbPool = Pool<ConnectionManager<DbConnection>>;
+pub type PooledDbConnection = PooledConnection<ConnectionManager<DbConnection>>;
+
+// SQLite 每个连接需单独开启外键约束和忙等待
+#[cfg(not(feature = "mysql"))]
+#[derive(Debug)]
+struct SqliteCustomizer;
+
+#[cfg(not(feature = "mysql"))]
+impl diesel::r2d2::CustomizeConnection<DbConnection, diesel::r2d2::Error> for SqliteCustomizer {
+    fn on_acquire(&self, conn: &mut DbConnection) -> Result<(), diesel::r2d2::Error> {
+        use diesel::connection::SimpleConnection;
+        conn.batch_execute("PRAGMA foreign_keys = ON; PRAGMA busy_timeout = 5000;")
+            .map_err(diesel::r2d2::Error::QueryError)
+    }
+}
+
+/// 默认数据库地址
+pub fn default_database_url() -> String {
+    #[cfg(feature = "mysql")]
+    let url = "mysql://minirobot@localhost/minirobot".to_string();
+
+    #[cfg(all(not(feature = "mysql"), target_os = "linux"))]
+    let url = "/var/lib/minirobot/minirobot.db".to_string();
+
+    #[cfg(all(not(feature = "mysql"), not(target_os = "linux")))]
+    let url = "minirobot.db".to_string();
+
+    url
+}
+
+/// 创建连接池并执行未应用的迁移
+///
+/// SQLite 的 `:memory:` 数据库每个连接互相独立，因此连接池大小固定为 1。
+pub fn establish_pool(database_url: &str) -> Result<DbPool, Box<dyn Error>> {
+    let manager = ConnectionManager::<DbConnection>::new(database_url);
+    let builder = Pool::builder().connection_timeout(Duration::from_secs(10));
+
+    #[cfg(not(feature = "mysql"))]
+    let builder = {
+        let in_memory = database_url == ":memory:";
+        if let Some(parent) = std::path::Path::new(database_url).parent() {
+            if !in_memory && !parent.as_os_str().is_empty() {
+                std::fs::create_dir_all(parent)?;
+            }
+        }
+        let builder = builder.connection_customizer(Box::new(SqliteCustomizer));
+        if in_memory { builder.max_size(1) } else { builder }
+    };
+
+    let pool = builder.build(manager)?;
+    run_migrations(&mut *pool.get()?)?;
+    Ok(pool)
+}
+
+/// 执行未应用的迁移
+pub fn run_migrations(conn: &mut DbConnection) -> Result<(), Box<dyn Error>> {
+    conn.run_pending_migrations(MIGRATIONS)
+        .map_err(|e| format!("failed to run database migrations: {}", e))?;
+    Ok(())
+}
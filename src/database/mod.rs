@@ -1,2 +1,4 @@
+pub mod connection;
 pub mod model;
-pub mod schema;
\ No newline at end of file
+pub mod repository;
+pub mod schema;
@@ -0,0 +1,339 @@
+use std::error::Error;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use diesel::prelude::*;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::database::schema::{devices, events, host_snapshots, hosts, job_attempts, jobs, peers, tasks};
+use crate::monitor::asset::snapshot::HostSnapshot;
+use crate::monitor::env::device::Device;
+use crate::monitor::event::{parse_time, Event};
+use crate::task::history::{AttemptRecord, JobRecord, TaskRecord};
+
+/// 枚举按 serde 名称存为文本，如 `TaskStatus::Finished` 存为 `Finished`
+pub fn to_text<T: Serialize>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(text)) => text,
+        Ok(other) => other.to_string(),
+        Err(_) => String::new(),
+    }
+}
+
+/// 从文本还原枚举
+pub fn from_text<T: DeserializeOwned>(text: &str) -> Result<T, Box<dyn Error>> {
+    Ok(serde_json::from_value(serde_json::Value::String(text.to_string()))?)
+}
+
+fn from_optional_text<T: DeserializeOwned>(text: &Option<String>) -> Result<Option<T>, Box<dyn Error>> {
+    text.as_deref().map(from_text).transpose()
+}
+
+fn to_naive(time: &DateTime<Utc>) -> NaiveDateTime {
+    time.naive_utc()
+}
+
+fn to_utc(time: &NaiveDateTime) -> DateTime<Utc> {
+    time.and_utc()
+}
+
+// 主机
+#[derive(Debug, Clone, PartialEq, Queryable, Selectable, Insertable, AsChangeset)]
+#[diesel(table_name = hosts)]
+pub struct HostRow {
+    pub id: String,                          // 主机ID
+    pub hostname: String,                    // 主机名
+    pub os_type: String,                     // 系统类型
+    pub os_name: String,                     // 系统名称
+    pub os_version: String,                  // 系统版本
+    pub os_arch: String,                     // 系统架构
+    pub first_seen: NaiveDateTime,           // 首次记录时刻（UTC）
+    pub last_seen: NaiveDateTime,            // 最近记录时刻（UTC）
+}
+
+// 主机资产快照，内容以 JSON 保存
+#[derive(Debug, Clone, PartialEq, Queryable, Selectable, Insertable, AsChangeset)]
+#[diesel(table_name = host_snapshots)]
+pub struct HostSnapshotRow {
+    pub id: String,
+    pub host_id: String,
+    pub taken_at: NaiveDateTime,             // 采集时刻（UTC）
+    pub data: String,
+}
+
+// 对等节点
+#[derive(Debug, Clone, PartialEq, Queryable, Selectable, Insertable, AsChangeset)]
+#[diesel(table_name = peers)]
+pub struct PeerRow {
+    pub address: String,                     // 节点地址
+    pub success: bool,                       // 最近一次通信是否成功
+    pub last_seen: NaiveDateTime,            // 最近通信时刻（UTC）
+}
+
+// 网络发现的设备，开放端口以 JSON 保存
+#[derive(Debug, Clone, PartialEq, Queryable, Selectable, Insertable, AsChangeset)]
+#[diesel(table_name = devices)]
+pub struct DeviceRow {
+    pub ip: String,                          // IPv4 地址
+    pub mac: Option<String>,                 // MAC 地址
+    pub methods: String,                     // 发现方式，逗号分隔
+    pub ports: String,                       // 开放端口
+    pub status: String,                      // 纳管状态
+    pub first_seen: NaiveDateTime,           // 首次发现时刻（UTC）
+    pub last_seen: NaiveDateTime,            // 最近发现时刻（UTC）
+}
+
+// 任务
+#[derive(Debug, Clone, PartialEq, Queryable, Selectable, Insertable, AsChangeset)]
+#[diesel(table_name = tasks)]
+pub struct TaskRow {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub status: String,
+    pub result: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub started_at: Option<NaiveDateTime>,
+    pub finished_at: Option<NaiveDateTime>,
+    pub cost_ms: Option<i64>,
+    pub log_file: String,
+}
+
+// 作业，ID 为 `<task_id>/<seq>`
+#[derive(Debug, Clone, PartialEq, Queryable, Selectable, Insertable, AsChangeset)]
+#[diesel(table_name = jobs)]
+pub struct JobRow {
+    pub id: String,
+    pub task_id: String,
+    pub seq: i32,
+    pub name: String,
+    pub status: String,
+    pub result: Option<String>,
+    pub message: String,
+    pub cost_ms: Option<i64>,
+}
+
+// 作业的单次命令执行，ID 为 `<job_id>/<seq>`
+#[derive(Debug, Clone, PartialEq, Queryable, Selectable, Insertable, AsChangeset)]
+#[diesel(table_name = job_attempts)]
+pub struct JobAttemptRow {
+    pub id: String,
+    pub job_id: String,
+    pub task_id: String,
+    pub seq: i32,
+    pub cmd: String,
+    pub args: String,                        // JSON 数组
+    pub check_str: Option<String>,
+    pub status: Option<i32>,
+    pub stdout: Option<String>,
+    pub stderr: Option<String>,
+    pub result: Option<String>,
+    pub cost_ms: Option<i64>,
+}
+
+// 事件
+#[derive(Debug, Clone, PartialEq, Queryable, Selectable, Insertable, AsChangeset)]
+#[diesel(table_name = events)]
+pub struct EventRow {
+    pub id: String,
+    pub name: String,
+    pub timestamp: NaiveDateTime,
+    pub description: String,
+    pub priority: String,
+    pub severity: String,
+    pub category: String,
+    pub status: String,
+    pub progress: String,
+    pub subject: String,
+    pub fingerprint: String,
+    pub count: i64,
+    pub first_seen: Option<NaiveDateTime>,   // 旧版本记录为空，视为与 timestamp 相同
+    pub last_seen: Option<NaiveDateTime>,
+    pub resolved_at: Option<NaiveDateTime>,
+    pub acknowledged_until: Option<NaiveDateTime>,
+    pub silenced_until: Option<NaiveDateTime>,
+}
+
+impl HostSnapshotRow {
+    pub fn from_snapshot(host_id: &str, snapshot: &HostSnapshot) -> Result<Self, Box<dyn Error>> {
+        Ok(HostSnapshotRow {
+            id: snapshot.id.clone(),
+            host_id: host_id.to_string(),
+            taken_at: to_naive(&snapshot.taken_at),
+            data: serde_json::to_string(snapshot)?,
+        })
+    }
+
+    pub fn into_snapshot(self) -> Result<HostSnapshot, Box<dyn Error>> {
+        let mut snapshot: HostSnapshot = serde_json::from_str(&self.data)?;
+        snapshot.taken_at = to_utc(&self.taken_at);
+        Ok(snapshot)
+    }
+}
+
+impl TaskRow {
+    pub fn from_record(record: &TaskRecord) -> Self {
+        TaskRow {
+            id: record.id.clone(),
+            name: record.name.clone(),
+            description: record.description.clone(),
+            status: to_text(&record.status),
+            result: record.result.as_ref().map(to_text),
+            created_at: to_naive(&record.created_at),
+            started_at: record.started_at.as_ref().map(to_naive),
+            finished_at: record.finished_at.as_ref().map(to_naive),
+            cost_ms: record.cost_ms.map(|ms| ms as i64),
+            log_file: record.log_file.clone(),
+        }
+    }
+
+    pub fn into_record(self, jobs: Vec<JobRecord>) -> Result<TaskRecord, Box<dyn Error>> {
+        Ok(TaskRecord {
+            status: from_text(&self.status)?,
+            result: from_optional_text(&self.result)?,
+            created_at: to_utc(&self.created_at),
+            started_at: self.started_at.as_ref().map(to_utc),
+            finished_at: self.finished_at.as_ref().map(to_utc),
+            cost_ms: self.cost_ms.map(|ms| ms as u64),
+            id: self.id,
+            name: self.name,
+            description: self.description,
+            log_file: self.log_file,
+            jobs,
+        })
+    }
+}
+
+impl JobRow {
+    pub fn from_record(task_id: &str, seq: usize, record: &JobRecord) -> Self {
+        JobRow {
+            id: format!("{}/{}", task_id, seq),
+            task_id: task_id.to_string(),
+            seq: seq as i32,
+            name: record.name.clone(),
+            status: to_text(&record.status),
+            result: record.result.as_ref().map(to_text),
+            message: record.message.clone(),
+            cost_ms: record.cost_ms.map(|ms| ms as i64),
+        }
+    }
+
+    pub fn into_record(self, attempts: Vec<AttemptRecord>) -> Result<JobRecord, Box<dyn Error>> {
+        Ok(JobRecord {
+            status: from_text(&self.status)?,
+            result: from_optional_text(&self.result)?,
+            cost_ms: self.cost_ms.map(|ms| ms as u64),
+            name: self.name,
+            message: self.message,
+            attempts,
+        })
+    }
+}
+
+impl JobAttemptRow {
+    pub fn from_record(task_id: &str, job_id: &str, seq: usize, record: &AttemptRecord) -> Self {
+        JobAttemptRow {
+            id: format!("{}/{}", job_id, seq),
+            job_id: job_id.to_string(),
+            task_id: task_id.to_string(),
+            seq: seq as i32,
+            cmd: record.cmd.clone(),
+            args: serde_json::to_string(&record.args).unwrap_or_else(|_| "[]".to_string()),
+            check_str: record.check_str.clone(),
+            status: record.status,
+            stdout: record.stdout.clone(),
+            stderr: record.stderr.clone(),
+            result: record.result.as_ref().map(to_text),
+            cost_ms: record.cost_ms.map(|ms| ms as i64),
+        }
+    }
+
+    pub fn into_record(self) -> Result<AttemptRecord, Box<dyn Error>> {
+        Ok(AttemptRecord {
+            id: self.seq as u32,
+            args: serde_json::from_str(&self.args)?,
+            result: from_optional_text(&self.result)?,
+            cost_ms: self.cost_ms.map(|ms| ms as u64),
+            cmd: self.cmd,
+            check_str: self.check_str,
+            status: self.status,
+            stdout: self.stdout,
+            stderr: self.stderr,
+        })
+    }
+}
+
+impl EventRow {
+    pub fn from_event(event: &Event) -> Self {
+        let timestamp = parse_time(event.timestamp()).unwrap_or_else(|| Utc::now().naive_utc());
+        let time = |text: Option<&str>| text.and_then(parse_time);
+        EventRow {
+            id: event.id().to_string(),
+            name: event.name().to_string(),
+            timestamp,
+            description: event.description().to_string(),
+            priority: to_text(event.priority()),
+            severity: to_text(event.severity()),
+            category: event.category().to_string(),
+            status: to_text(event.status()),
+            progress: event.progress().to_string(),
+            subject: event.subject().to_string(),
+            fingerprint: event.fingerprint().to_string(),
+            count: event.count() as i64,
+            first_seen: time(Some(event.first_seen())),
+            last_seen: time(Some(event.last_seen())),
+            resolved_at: time(event.resolved_at()),
+            acknowledged_until: time(event.acknowledged_until()),
+            silenced_until: time(event.silenced_until()),
+        }
+    }
+
+    pub fn into_event(self) -> Result<Event, Box<dyn Error>> {
+        let value = serde_json::json!({
+            "id": self.id,
+            "name": self.name,
+            "timestamp": self.timestamp.to_string(),
+            "description": self.description,
+            "priority": self.priority,
+            "severity": self.severity,
+            "category": self.category,
+            "status": self.status,
+            "progress": self.progress,
+            "subject": self.subject,
+            "fingerprint": self.fingerprint,
+            "count": self.count.max(1),
+            "first_seen": self.first_seen.unwrap_or(self.timestamp).to_string(),
+            "last_seen": self.last_seen.unwrap_or(self.timestamp).to_string(),
+            "resolved_at": self.resolved_at.map(|time| time.to_string()),
+            "acknowledged_until": self.acknowledged_until.map(|time| time.to_string()),
+            "silenced_until": self.silenced_until.map(|time| time.to_string()),
+        });
+        Ok(serde_json::from_value(value)?)
+    }
+}
+
+impl DeviceRow {
+    pub fn from_device(device: &Device) -> Result<Self, Box<dyn Error>> {
+        Ok(DeviceRow {
+            ip: device.ip.to_string(),
+            mac: device.mac.clone(),
+            methods: device.methods.join(","),
+            ports: serde_json::to_string(&device.open_ports)?,
+            status: to_text(&device.status),
+            first_seen: to_naive(&device.first_seen),
+            last_seen: to_naive(&device.last_seen),
+        })
+    }
+
+    pub fn into_device(self) -> Result<Device, Box<dyn Error>> {
+        Ok(Device {
+            ip: self.ip.parse()?,
+            mac: self.mac,
+            methods: self.methods.split(',').filter(|m| !m.is_empty()).map(str::to_string).collect(),
+            open_ports: serde_json::from_str(&self.ports)?,
+            status: from_text(&self.status)?,
+            first_seen: to_utc(&self.first_seen),
+            last_seen: to_utc(&self.last_seen),
+        })
+    }
+}
@@ -0,0 +1,730 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+use crate::database::connection::{establish_pool, DbPool, PooledDbConnection};
+use crate::database::model::*;
+use crate::database::schema::{devices, events, host_snapshots, hosts, job_attempts, jobs, peers, tasks};
+use crate::info::os::OSInfo;
+use crate::info::package::PackageQuery;
+use crate::monitor::asset::snapshot::{self, HostPackage, HostSnapshot, SnapshotDiff};
+use crate::monitor::env::device::{Device, DeviceStatus};
+use crate::monitor::event::{Event, Status as EventStatus};
+use crate::task::history::{TaskFilter, TaskRecord, TaskStore};
+
+// 单条 SQL 中 IN 列表的最大长度，避免超出 SQLite 绑定参数上限
+const IN_CHUNK_SIZE: usize = 500;
+
+// 数据访问接口，内部持有连接池，可在线程间克隆共享
+#[derive(Clone)]
+pub struct Repository {
+    pool: DbPool,
+}
+
+impl fmt::Debug for Repository {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.pool.state();
+        write!(f, "Repository {{ connections: {}, idle: {} }}", state.connections, state.idle_connections)
+    }
+}
+
+impl Repository {
+    /// 连接数据库并执行迁移
+    pub fn new(database_url: &str) -> Result<Self, Box<dyn Error>> {
+        Ok(Self {
+            pool: establish_pool(database_url)?,
+        })
+    }
+
+    pub fn from_pool(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    pub fn pool(&self) -> &DbPool {
+        &self.pool
+    }
+
+    pub fn conn(&self) -> Result<PooledDbConnection, Box<dyn Error>> {
+        Ok(self.pool.get()?)
+    }
+
+    /// 保存任务及其作业、命令执行记录，已存在则整体覆盖
+    pub fn save_task(&self, record: &TaskRecord) -> Result<(), Box<dyn Error>> {
+        let task_row = TaskRow::from_record(record);
+        let mut job_rows = Vec::new();
+        let mut attempt_rows = Vec::new();
+        for (seq, job) in record.jobs.iter().enumerate() {
+            let job_row = JobRow::from_record(&record.id, seq, job);
+            for (attempt_seq, attempt) in job.attempts.iter().enumerate() {
+                attempt_rows.push(JobAttemptRow::from_record(&record.id, &job_row.id, attempt_seq, attempt));
+            }
+            job_rows.push(job_row);
+        }
+
+        self.conn()?.transaction::<_, Box<dyn Error>, _>(|conn| {
+            delete_task_rows(conn, &record.id)?;
+            diesel::insert_into(tasks::table).values(&task_row).execute(conn)?;
+            diesel::insert_into(jobs::table).values(&job_rows).execute(conn)?;
+            diesel::insert_into(job_attempts::table).values(&attempt_rows).execute(conn)?;
+            Ok(())
+        })
+    }
+
+    /// 按任务ID或唯一的ID前缀读取任务
+    pub fn load_task(&self, id: &str) -> Result<TaskRecord, Box<dyn Error>> {
+        let mut conn = self.conn()?;
+        let mut rows: Vec<TaskRow> = tasks::table
+            .filter(tasks::id.eq(id))
+            .select(TaskRow::as_select())
+            .load(&mut conn)?;
+        if rows.is_empty() {
+            rows = tasks::table
+                .filter(tasks::id.like(format!("{}%", escape_like(id))).escape('\\'))
+                .limit(2)
+                .select(TaskRow::as_select())
+                .load(&mut conn)?;
+        }
+        match rows.len() {
+            0 => Err(format!("task {} not found in database", id).into()),
+            1 => Ok(assemble_tasks(&mut conn, rows)?.remove(0)),
+            _ => Err(format!("task id prefix {} is ambiguous", id).into()),
+        }
+    }
+
+    /// 按创建时刻排序列出满足过滤条件的任务
+    pub fn list_tasks(&self, filter: &TaskFilter) -> Result<Vec<TaskRecord>, Box<dyn Error>> {
+        let mut conn = self.conn()?;
+        let mut query = tasks::table.into_boxed();
+        if let Some(status) = &filter.status {
+            query = query.filter(tasks::status.eq(to_text(status)));
+        }
+        if let Some(result) = &filter.result {
+            query = query.filter(tasks::result.eq(to_text(result)));
+        }
+        if let Some(name) = &filter.name {
+            query = query.filter(tasks::name.like(format!("%{}%", escape_like(name))).escape('\\'));
+        }
+        if let Some(since) = &filter.since {
+            query = query.filter(tasks::created_at.ge(since.naive_utc()));
+        }
+        if let Some(until) = &filter.until {
+            query = query.filter(tasks::created_at.lt(until.naive_utc()));
+        }
+        let rows = query
+            .order(tasks::created_at.asc())
+            .select(TaskRow::as_select())
+            .load(&mut conn)?;
+        assemble_tasks(&mut conn, rows)
+    }
+
+    /// 删除任务及其作业记录
+    pub fn delete_task(&self, id: &str) -> Result<bool, Box<dyn Error>> {
+        self.conn()?.transaction::<_, Box<dyn Error>, _>(|conn| Ok(delete_task_rows(conn, id)? > 0))
+    }
+
+    /// 保存事件，已存在则覆盖
+    pub fn save_event(&self, event: &Event) -> Result<(), Box<dyn Error>> {
+        let row = EventRow::from_event(event);
+        self.conn()?.transaction::<_, Box<dyn Error>, _>(|conn| {
+            diesel::delete(events::table.filter(events::id.eq(&row.id))).execute(conn)?;
+            diesel::insert_into(events::table).values(&row).execute(conn)?;
+            Ok(())
+        })
+    }
+
+    /// 按时间顺序列出事件
+    pub fn list_events(&self, since: Option<DateTime<Utc>>, limit: Option<i64>) -> Result<Vec<Event>, Box<dyn Error>> {
+        let mut query = events::table.into_boxed();
+        if let Some(since) = since {
+            query = query.filter(events::timestamp.ge(since.naive_utc()));
+        }
+        if let Some(limit) = limit {
+            query = query.limit(limit);
+        }
+        let rows: Vec<EventRow> = query
+            .order(events::timestamp.asc())
+            .select(EventRow::as_select())
+            .load(&mut self.conn()?)?;
+        rows.into_iter().map(EventRow::into_event).collect()
+    }
+
+    /// 列出未解决的告警，即有告警指纹且未处理或处理中的事件
+    pub fn list_open_alerts(&self) -> Result<Vec<Event>, Box<dyn Error>> {
+        let statuses = [to_text(&EventStatus::Pending), to_text(&EventStatus::InProgress)];
+        let rows: Vec<EventRow> = events::table
+            .filter(events::fingerprint.ne(""))
+            .filter(events::status.eq_any(statuses))
+            .order(events::timestamp.asc())
+            .select(EventRow::as_select())
+            .load(&mut self.conn()?)?;
+        rows.into_iter().map(EventRow::into_event).collect()
+    }
+
+    /// 记录主机，按主机名匹配已有记录并刷新系统信息和最近记录时刻
+    pub fn upsert_host(&self, hostname: &str, os_info: &OSInfo) -> Result<HostRow, Box<dyn Error>> {
+        let row = HostRow {
+            id: Uuid::new_v4().to_string(),
+            hostname: hostname.to_string(),
+            os_type: os_info.os_type().to_string(),
+            os_name: os_info.os_name().to_string(),
+            os_version: os_info.os_version().to_string(),
+            os_arch: os_info.os_arch().to_string(),
+            first_seen: Utc::now().naive_utc(),
+            last_seen: Utc::now().naive_utc(),
+        };
+        self.conn()?.transaction::<_, Box<dyn Error>, _>(|conn| upsert_host_row(conn, row))
+    }
+
+    pub fn find_host(&self, hostname: &str) -> Result<Option<HostRow>, Box<dyn Error>> {
+        Ok(hosts::table
+            .filter(hosts::hostname.eq(hostname))
+            .select(HostRow::as_select())
+            .first(&mut self.conn()?)
+            .optional()?)
+    }
+
+    pub fn list_hosts(&self) -> Result<Vec<HostRow>, Box<dyn Error>> {
+        Ok(hosts::table
+            .order(hosts::hostname.asc())
+            .select(HostRow::as_select())
+            .load(&mut self.conn()?)?)
+    }
+
+    /// 保存主机快照，同时按快照内容记录主机
+    pub fn save_snapshot(&self, snapshot: &HostSnapshot) -> Result<(), Box<dyn Error>> {
+        let taken_at = snapshot.taken_at.naive_utc();
+        let host = HostRow {
+            id: Uuid::new_v4().to_string(),
+            hostname: snapshot.hostname.clone(),
+            os_type: snapshot.os_type.clone(),
+            os_name: snapshot.os_name.clone(),
+            os_version: snapshot.os_version.clone(),
+            os_arch: snapshot.os_arch.clone(),
+            first_seen: taken_at,
+            last_seen: taken_at,
+        };
+        self.conn()?.transaction::<_, Box<dyn Error>, _>(|conn| {
+            let host = upsert_host_row(conn, host)?;
+            let row = HostSnapshotRow::from_snapshot(&host.id, snapshot)?;
+            diesel::delete(host_snapshots::table.find(&row.id)).execute(conn)?;
+            diesel::insert_into(host_snapshots::table).values(&row).execute(conn)?;
+            Ok(())
+        })
+    }
+
+    /// 按快照ID或唯一的ID前缀读取快照
+    pub fn load_snapshot(&self, id: &str) -> Result<HostSnapshot, Box<dyn Error>> {
+        let mut rows: Vec<HostSnapshotRow> = host_snapshots::table
+            .filter(host_snapshots::id.like(format!("{}%", escape_like(id))).escape('\\'))
+            .limit(2)
+            .select(HostSnapshotRow::as_select())
+            .load(&mut self.conn()?)?;
+        match rows.len() {
+            0 => Err(format!("snapshot {} not found in database", id).into()),
+            1 => rows.remove(0).into_snapshot(),
+            _ => Err(format!("snapshot id prefix {} is ambiguous", id).into()),
+        }
+    }
+
+    /// 按采集时刻排序列出快照，可按主机名和时间范围 [since, until) 过滤
+    pub fn list_snapshots(&self, hostname: Option<&str>, since: Option<DateTime<Utc>>,
+                          until: Option<DateTime<Utc>>) -> Result<Vec<HostSnapshot>, Box<dyn Error>> {
+        let mut query = host_snapshots::table.inner_join(hosts::table).into_boxed();
+        if let Some(hostname) = hostname {
+            query = query.filter(hosts::hostname.eq(hostname.to_string()));
+        }
+        if let Some(since) = since {
+            query = query.filter(host_snapshots::taken_at.ge(since.naive_utc()));
+        }
+        if let Some(until) = until {
+            query = query.filter(host_snapshots::taken_at.lt(until.naive_utc()));
+        }
+        let rows: Vec<HostSnapshotRow> = query
+            .order(host_snapshots::taken_at.asc())
+            .select(HostSnapshotRow::as_select())
+            .load(&mut self.conn()?)?;
+        rows.into_iter().map(HostSnapshotRow::into_snapshot).collect()
+    }
+
+    /// 主机在指定时刻（含）之前的最后一个快照，未指定时刻时取最新快照
+    pub fn latest_snapshot(&self, hostname: &str, at: Option<DateTime<Utc>>) -> Result<Option<HostSnapshot>, Box<dyn Error>> {
+        let mut query = host_snapshots::table
+            .inner_join(hosts::table)
+            .filter(hosts::hostname.eq(hostname.to_string()))
+            .into_boxed();
+        if let Some(at) = at {
+            query = query.filter(host_snapshots::taken_at.le(at.naive_utc()));
+        }
+        let row: Option<HostSnapshotRow> = query
+            .order(host_snapshots::taken_at.desc())
+            .select(HostSnapshotRow::as_select())
+            .first(&mut self.conn()?)
+            .optional()?;
+        row.map(HostSnapshotRow::into_snapshot).transpose()
+    }
+
+    /// 各主机最新快照中满足查询条件的软件包，如 "openssl<3.0.12" 查找 openssl 低于 3.0.12 的主机
+    pub fn find_packages(&self, query: &PackageQuery, hostname: Option<&str>) -> Result<Vec<HostPackage>, Box<dyn Error>> {
+        let mut found = Vec::new();
+        for host in self.list_hosts()?.iter().filter(|host| hostname.is_none_or(|name| host.hostname == name)) {
+            if let Some(snapshot) = self.latest_snapshot(&host.hostname, None)? {
+                found.extend(snapshot::find_packages(&snapshot, query));
+            }
+        }
+        Ok(found)
+    }
+
+    /// 最新快照硬件指纹相同的其他主机的最新快照，用于识别重装系统或修改主机名后的同一台机器
+    pub fn find_by_fingerprint(&self, fingerprint: &str, exclude_hostname: &str) -> Result<Option<HostSnapshot>, Box<dyn Error>> {
+        for host in self.list_hosts()?.iter().filter(|host| host.hostname != exclude_hostname) {
+            if let Some(snapshot) = self.latest_snapshot(&host.hostname, None)? {
+                if snapshot.fingerprint.as_deref() == Some(fingerprint) {
+                    return Ok(Some(snapshot));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// 主机自 since 起的变更：以 since 时刻的快照为基线，依次比对之后的相邻快照，省略无变化的比对
+    pub fn host_changes(&self, hostname: &str, since: DateTime<Utc>,
+                        until: Option<DateTime<Utc>>) -> Result<Vec<SnapshotDiff>, Box<dyn Error>> {
+        let mut snapshots: Vec<HostSnapshot> = self.latest_snapshot(hostname, Some(since))?.into_iter().collect();
+        let base_id = snapshots.first().map(|base| base.id.clone());
+        snapshots.extend(self.list_snapshots(Some(hostname), Some(since), until)?
+            .into_iter()
+            .filter(|s| base_id.as_ref() != Some(&s.id)));
+        Ok(snapshots.windows(2)
+            .map(|pair| snapshot::diff(&pair[0], &pair[1]))
+            .filter(|diff| !diff.is_empty())
+            .collect())
+    }
+
+    /// 记录对等节点最近一次通信结果
+    pub fn upsert_peer(&self, address: &str, success: bool) -> Result<PeerRow, Box<dyn Error>> {
+        let row = PeerRow {
+            address: address.to_string(),
+            success,
+            last_seen: Utc::now().naive_utc(),
+        };
+        self.conn()?.transaction::<_, Box<dyn Error>, _>(|conn| {
+            let updated = diesel::update(peers::table.find(&row.address)).set(&row).execute(conn)?;
+            if updated == 0 {
+                diesel::insert_into(peers::table).values(&row).execute(conn)?;
+            }
+            Ok(())
+        })?;
+        Ok(row)
+    }
+
+    pub fn list_peers(&self) -> Result<Vec<PeerRow>, Box<dyn Error>> {
+        Ok(peers::table
+            .order(peers::address.asc())
+            .select(PeerRow::as_select())
+            .load(&mut self.conn()?)?)
+    }
+
+    /// 保存网络发现的设备，已有记录保留首次发现时刻和纳管状态
+    pub fn upsert_device(&self, device: &Device) -> Result<Device, Box<dyn Error>> {
+        let mut device = device.clone();
+        self.conn()?.transaction::<_, Box<dyn Error>, _>(|conn| {
+            let previous = devices::table
+                .find(device.ip.to_string())
+                .select(DeviceRow::as_select())
+                .first(conn)
+                .optional()?;
+            match previous {
+                Some(previous) => {
+                    device.merge_previous(&previous.into_device()?);
+                    let row = DeviceRow::from_device(&device)?;
+                    diesel::update(devices::table.find(&row.ip)).set(&row).execute(conn)?;
+                }
+                None => {
+                    diesel::insert_into(devices::table).values(DeviceRow::from_device(&device)?).execute(conn)?;
+                }
+            }
+            Ok(())
+        })?;
+        Ok(device)
+    }
+
+    pub fn list_devices(&self, status: Option<DeviceStatus>) -> Result<Vec<Device>, Box<dyn Error>> {
+        let mut query = devices::table.select(DeviceRow::as_select()).into_boxed();
+        if let Some(status) = status {
+            query = query.filter(devices::status.eq(to_text(&status)));
+        }
+        let mut devices = query.load(&mut self.conn()?)?
+            .into_iter()
+            .map(DeviceRow::into_device)
+            .collect::<Result<Vec<Device>, Box<dyn Error>>>()?;
+        // 按地址数值排序，文本排序会把 10.0.0.10 排在 10.0.0.2 前面
+        devices.sort_by_key(|device| device.ip);
+        Ok(devices)
+    }
+
+    /// 修改设备纳管状态，设备不存在时返回 false
+    pub fn set_device_status(&self, ip: &str, status: DeviceStatus) -> Result<bool, Box<dyn Error>> {
+        let updated = diesel::update(devices::table.find(ip))
+            .set(devices::status.eq(to_text(&status)))
+            .execute(&mut self.conn()?)?;
+        Ok(updated > 0)
+    }
+}
+
+impl TaskStore for Repository {
+    fn save(&self, record: &TaskRecord) -> Result<(), Box<dyn Error>> {
+        self.save_task(record)
+    }
+
+    fn load(&self, id: &str) -> Result<TaskRecord, Box<dyn Error>> {
+        self.load_task(id)
+    }
+
+    fn list(&self, filter: &TaskFilter) -> Result<Vec<TaskRecord>, Box<dyn Error>> {
+        self.list_tasks(filter)
+    }
+}
+
+// 转义 LIKE 模式中的通配符，查询时以 \ 为转义字符
+fn escape_like(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+// 按主机名更新或新增主机，首次和最近记录时刻取两者范围的并集，系统信息以较新的记录为准
+fn upsert_host_row(conn: &mut PooledDbConnection, mut row: HostRow) -> Result<HostRow, Box<dyn Error>> {
+    let existing: Option<HostRow> = hosts::table
+        .filter(hosts::hostname.eq(&row.hostname))
+        .select(HostRow::as_select())
+        .first(conn)
+        .optional()?;
+    match existing {
+        Some(existing) => {
+            let first_seen = row.first_seen.min(existing.first_seen);
+            let last_seen = row.last_seen.max(existing.last_seen);
+            if row.last_seen < existing.last_seen {
+                row = existing;
+            } else {
+                row.id = existing.id;
+            }
+            row.first_seen = first_seen;
+            row.last_seen = last_seen;
+            diesel::update(hosts::table.find(&row.id)).set(&row).execute(conn)?;
+        }
+        None => {
+            diesel::insert_into(hosts::table).values(&row).execute(conn)?;
+        }
+    }
+    Ok(row)
+}
+
+fn delete_task_rows(conn: &mut PooledDbConnection, id: &str) -> Result<usize, Box<dyn Error>> {
+    diesel::delete(job_attempts::table.filter(job_attempts::task_id.eq(id))).execute(conn)?;
+    diesel::delete(jobs::table.filter(jobs::task_id.eq(id))).execute(conn)?;
+    Ok(diesel::delete(tasks::table.filter(tasks::id.eq(id))).execute(conn)?)
+}
+
+// 为任务行加载作业和命令执行记录，保持任务行原有顺序
+fn assemble_tasks(conn: &mut PooledDbConnection, rows: Vec<TaskRow>) -> Result<Vec<TaskRecord>, Box<dyn Error>> {
+    let ids: Vec<String> = rows.iter().map(|row| row.id.clone()).collect();
+    let mut job_rows: Vec<JobRow> = Vec::new();
+    let mut attempt_rows: Vec<JobAttemptRow> = Vec::new();
+    for chunk in ids.chunks(IN_CHUNK_SIZE) {
+        job_rows.extend(jobs::table
+            .filter(jobs::task_id.eq_any(chunk))
+            .select(JobRow::as_select())
+            .load(conn)?);
+        attempt_rows.extend(job_attempts::table
+            .filter(job_attempts::task_id.eq_any(chunk))
+            .select(JobAttemptRow::as_select())
+            .load(conn)?);
+    }
+
+    let mut attempts_by_job: HashMap<String, Vec<JobAttemptRow>> = HashMap::new();
+    for attempt in attempt_rows {
+        attempts_by_job.entry(attempt.job_id.clone()).or_default().push(attempt);
+    }
+    let mut jobs_by_task: HashMap<String, Vec<JobRow>> = HashMap::new();
+    for job in job_rows {
+        jobs_by_task.entry(job.task_id.clone()).or_default().push(job);
+    }
+
+    rows.into_iter().map(|row| {
+        let mut job_rows = jobs_by_task.remove(&row.id).unwrap_or_default();
+        job_rows.sort_by_key(|job| job.seq);
+        let jobs = job_rows.into_iter().map(|job| {
+            let mut attempts = attempts_by_job.remove(&job.id).unwrap_or_default();
+            attempts.sort_by_key(|attempt| attempt.seq);
+            let attempts = attempts.into_iter().map(JobAttemptRow::into_record).collect::<Result<Vec<_>, _>>()?;
+            job.into_record(attempts)
+        }).collect::<Result<Vec<_>, _>>()?;
+        row.into_record(jobs)
+    }).collect()
+}
+
+#[cfg(test)]
+mod unit_test_repository {
+    use super::*;
+    use chrono::Duration as ChronoDuration;
+
+    use crate::common::ds::{self, TaskStatus};
+    use crate::monitor::asset::snapshot::unit_test_snapshot::{package, snapshot};
+    use crate::monitor::event::{Priority, Severity, Status};
+    use crate::task::history::{AttemptRecord, JobRecord};
+
+    fn repository() -> Repository {
+        Repository::new(":memory:").expect("failed to open in-memory database")
+    }
+
+    fn record(name: &str, result: ds::Result, days_ago: i64) -> TaskRecord {
+        let created_at = Utc::now() - ChronoDuration::days(days_ago);
+        TaskRecord {
+            id: Uuid::new_v4().to_string(),
+            name: name.to_string(),
+            description: "desc".to_string(),
+            status: TaskStatus::Finished,
+            result: Some(result),
+            created_at,
+            started_at: Some(created_at),
+            finished_at: Some(created_at + ChronoDuration::seconds(2)),
+            cost_ms: Some(2000),
+            log_file: "/tmp/task.log".to_string(),
+            jobs: vec![
+                JobRecord {
+                    name: "probe".to_string(),
+                    status: TaskStatus::Finished,
+                    result: Some(ds::Result::Success),
+                    message: String::new(),
+                    cost_ms: Some(1500),
+                    attempts: (0..2).map(|id| AttemptRecord {
+                        id,
+                        cmd: "echo".to_string(),
+                        args: vec!["hello".to_string(), id.to_string()],
+                        check_str: Some("hello".to_string()),
+                        status: Some(0),
+                        stdout: Some(format!("hello {}", id)),
+                        stderr: None,
+                        result: Some(ds::Result::Success),
+                        cost_ms: Some(3),
+                    }).collect(),
+                },
+                JobRecord {
+                    name: "later".to_string(),
+                    status: TaskStatus::Skipped,
+                    result: None,
+                    message: "condition not met".to_string(),
+                    cost_ms: Some(0),
+                    attempts: vec![],
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_repository_01() {
+        let repo = repository();
+        let task = record("backup", ds::Result::Success, 0);
+        repo.save_task(&task).unwrap();
+
+        let loaded = repo.load_task(&task.id).unwrap();
+        assert_eq!(loaded.jobs, task.jobs);
+        assert_eq!(loaded.id, task.id);
+        assert_eq!(loaded.result, Some(ds::Result::Success));
+        assert!((loaded.created_at - task.created_at).num_milliseconds().abs() < 1);
+
+        // 覆盖保存
+        let mut updated = task.clone();
+        updated.jobs.truncate(1);
+        updated.result = Some(ds::Result::Failed);
+        repo.save_task(&updated).unwrap();
+        let loaded = repo.load_task(&task.id[..8]).unwrap();
+        assert_eq!(loaded.jobs.len(), 1);
+        assert_eq!(loaded.result, Some(ds::Result::Failed));
+
+        assert!(repo.delete_task(&task.id).unwrap());
+        assert!(repo.load_task(&task.id).is_err());
+    }
+
+    #[test]
+    fn test_repository_02() {
+        let repo = repository();
+        let old = record("backup", ds::Result::Success, 5);
+        let new = record("backup", ds::Result::Failed, 0);
+        let other = record("health-check", ds::Result::Success, 1);
+        for r in [&new, &old, &other] {
+            repo.save(r).unwrap();
+        }
+
+        let all = repo.list(&TaskFilter::default()).unwrap();
+        assert_eq!(all.iter().map(|r| r.id.clone()).collect::<Vec<_>>(), vec![old.id.clone(), other.id.clone(), new.id.clone()]);
+        assert_eq!(all[0].jobs[0].attempts.len(), 2);
+
+        let filter = TaskFilter { name: Some("back".to_string()), result: Some(ds::Result::Failed), ..Default::default() };
+        assert_eq!(repo.list_tasks(&filter).unwrap()[0].id, new.id);
+        let filter = TaskFilter { since: Some(Utc::now() - ChronoDuration::days(2)), until: Some(Utc::now() - ChronoDuration::hours(1)), ..Default::default() };
+        assert_eq!(repo.list_tasks(&filter).unwrap()[0].id, other.id);
+        let filter = TaskFilter { status: Some(TaskStatus::Running), ..Default::default() };
+        assert!(repo.list_tasks(&filter).unwrap().is_empty());
+
+        // 名称和ID前缀中的 %、_ 按字面匹配
+        let filter = TaskFilter { name: Some("health_check".to_string()), ..Default::default() };
+        assert!(repo.list_tasks(&filter).unwrap().is_empty());
+        let filter = TaskFilter { name: Some("%".to_string()), ..Default::default() };
+        assert!(repo.list_tasks(&filter).unwrap().is_empty());
+        assert!(repo.load_task("%").is_err());
+        assert!(repo.load_task("_").is_err());
+        assert_eq!(repo.load_task(&other.id[..8]).unwrap().id, other.id);
+    }
+
+    #[test]
+    fn test_repository_03() {
+        let repo = repository();
+        let mut event = Event::new("磁盘使用率监控", "/ 95%", Priority::Low, Severity::Warning, "本地主机监控");
+        repo.save_event(&event).unwrap();
+        event.set_status(Status::Resolved);
+        repo.save_event(&event).unwrap();
+
+        let events = repo.list_events(None, None).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].id(), event.id());
+        assert_eq!(events[0].status(), &Status::Resolved);
+        assert_eq!(events[0].severity(), &Severity::Warning);
+        assert!(repo.list_events(Some(Utc::now() + ChronoDuration::hours(1)), None).unwrap().is_empty());
+
+        let mut alert = Event::new("磁盘使用率监控", "/data 95%", Priority::Low, Severity::Warning, "本地主机监控")
+            .with_subject("/data");
+        alert.set_fingerprint("disk_usage:/data");
+        alert.repeat(&alert.clone());
+        alert.silence(Utc::now().naive_utc() + ChronoDuration::hours(1));
+        repo.save_event(&alert).unwrap();
+        let alerts = repo.list_open_alerts().unwrap();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].count(), 2);
+        assert_eq!(alerts[0].subject(), "/data");
+        assert_eq!(alerts[0].silenced_until(), alert.silenced_until());
+        assert_eq!(alerts[0].first_seen(), alert.first_seen());
+        alert.resolve("自动解决");
+        repo.save_event(&alert).unwrap();
+        assert!(repo.list_open_alerts().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_repository_04() {
+        let repo = repository();
+        let os_info = OSInfo::new();
+        let first = repo.upsert_host("node-1", &os_info).unwrap();
+        let second = repo.upsert_host("node-1", &os_info).unwrap();
+        assert_eq!(first.id, second.id);
+        assert_eq!(first.first_seen, second.first_seen);
+        assert_eq!(repo.list_hosts().unwrap().len(), 1);
+        assert!(repo.find_host("node-2").unwrap().is_none());
+
+        repo.upsert_peer("10.0.0.2:50051", false).unwrap();
+        repo.upsert_peer("10.0.0.2:50051", true).unwrap();
+        let peers = repo.list_peers().unwrap();
+        assert_eq!(peers.len(), 1);
+        assert!(peers[0].success);
+    }
+
+    #[test]
+    fn test_repository_05() {
+        let repo = repository();
+        let base = snapshot("node-1", 48);
+        let mut same = snapshot("node-1", 30);
+        same.id = Uuid::new_v4().to_string();
+        let mut upgraded = same.clone();
+        upgraded.id = Uuid::new_v4().to_string();
+        upgraded.taken_at = Utc::now() - ChronoDuration::hours(2);
+        upgraded.os_version = "24.04".to_string();
+        for s in [&upgraded, &base, &same, &snapshot("node-2", 1)] {
+            repo.save_snapshot(s).unwrap();
+        }
+
+        assert_eq!(repo.list_snapshots(Some("node-1"), None, None).unwrap().len(), 3);
+        assert_eq!(repo.list_snapshots(None, Some(Utc::now() - ChronoDuration::hours(3)), None).unwrap().len(), 2);
+        assert_eq!(repo.load_snapshot(&base.id[..8]).unwrap().id, base.id);
+        assert_eq!(repo.latest_snapshot("node-1", None).unwrap().unwrap().id, upgraded.id);
+        assert_eq!(repo.latest_snapshot("node-1", Some(Utc::now() - ChronoDuration::hours(40))).unwrap().unwrap().id, base.id);
+
+        // 主机系统信息取最新快照，首次记录时刻取最早快照
+        let host = repo.find_host("node-1").unwrap().unwrap();
+        assert_eq!(host.os_version, "24.04");
+        assert_eq!(host.first_seen.and_utc().timestamp(), base.taken_at.timestamp());
+
+        let changes = repo.host_changes("node-1", Utc::now() - ChronoDuration::hours(40), None).unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].from_id, same.id);
+        assert_eq!(changes[0].to_id, upgraded.id);
+        assert_eq!(changes[0].changes[0].to.as_deref(), Some("24.04"));
+        assert!(repo.host_changes("node-1", Utc::now() - ChronoDuration::hours(1), None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_repository_06() {
+        let repo = repository();
+        let mut old = snapshot("node-1", 10);
+        old.packages = vec![package("openssl", "3.0.9-1")];
+        let mut upgraded = snapshot("node-1", 1);
+        upgraded.packages = vec![package("openssl", "3.0.11-1~deb12u2")];
+        let mut outdated = snapshot("node-2", 1);
+        outdated.packages = vec![package("libssl3", "3.0.9-1"), package("curl", "7.88.1-10")];
+        for s in [&old, &upgraded, &outdated] {
+            repo.save_snapshot(s).unwrap();
+        }
+
+        // 只查各主机的最新快照
+        let found = repo.find_packages(&"openssl<3.0.11".parse().unwrap(), None).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].hostname, "node-2");
+        assert_eq!(found[0].package.name, "libssl3");
+        assert_eq!(repo.find_packages(&"openssl".parse().unwrap(), None).unwrap().len(), 2);
+        assert_eq!(repo.find_packages(&"openssl".parse().unwrap(), Some("node-1")).unwrap()[0].snapshot_id, upgraded.id);
+    }
+
+    #[test]
+    fn test_repository_07() {
+        let repo = repository();
+        let mut old = snapshot("node-1", 10);
+        old.fingerprint = Some("ab12".to_string());
+        let mut renamed = snapshot("db-1", 1);
+        renamed.fingerprint = Some("ab12".to_string());
+        repo.save_snapshot(&old).unwrap();
+        repo.save_snapshot(&snapshot("node-2", 5)).unwrap();
+
+        assert_eq!(repo.find_by_fingerprint("ab12", &renamed.hostname).unwrap().unwrap().id, old.id);
+        assert!(repo.find_by_fingerprint("ab12", "node-1").unwrap().is_none());
+        assert!(repo.find_by_fingerprint("cd34", "db-1").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_repository_08() {
+        use crate::monitor::env::device::OpenPort;
+
+        let repo = repository();
+        let mut device = Device::new("10.0.0.10".parse().unwrap());
+        device.mac = Some("52:54:00:12:34:56".to_string());
+        device.add_method("arp");
+        let saved = repo.upsert_device(&device).unwrap();
+        repo.upsert_device(&Device::new("10.0.0.2".parse().unwrap())).unwrap();
+        assert!(repo.set_device_status("10.0.0.10", DeviceStatus::Accepted).unwrap());
+        assert!(!repo.set_device_status("10.0.0.99", DeviceStatus::Ignored).unwrap());
+
+        // 再次发现时保留状态、首次发现时刻和 MAC
+        let mut again = Device::new("10.0.0.10".parse().unwrap());
+        again.add_method("tcp");
+        again.open_ports.push(OpenPort { port: 22, service: Some("ssh".to_string()), banner: Some("SSH-2.0-OpenSSH_9.6".to_string()) });
+        let merged = repo.upsert_device(&again).unwrap();
+        assert_eq!(merged.status, DeviceStatus::Accepted);
+        assert_eq!(merged.mac, device.mac);
+
+        let devices = repo.list_devices(None).unwrap();
+        assert_eq!(devices.iter().map(|d| d.ip.to_string()).collect::<Vec<_>>(), vec!["10.0.0.2", "10.0.0.10"]);
+        assert_eq!(devices[1].open_ports, again.open_ports);
+        assert_eq!(devices[1].methods, vec!["tcp"]);
+        assert_eq!(devices[1].first_seen.timestamp_micros(), saved.first_seen.timestamp_micros());
+        assert_eq!(repo.list_devices(Some(DeviceStatus::Candidate)).unwrap().len(), 1);
+    }
+}
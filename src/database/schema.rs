@@ -0,0 +1,125 @@
+// 数据库表结构，与 migrations/{sqlite,mysql} 保持一致
+
+diesel::table! {
+    hosts (id) {
+        id -> Text,
+        hostname -> Text,
+        os_type -> Text,
+        os_name -> Text,
+        os_version -> Text,
+        os_arch -> Text,
+        first_seen -> Timestamp,
+        last_seen -> Timestamp,
+    }
+}
+
+diesel::table! {
+    host_snapshots (id) {
+        id -> Text,
+        host_id -> Text,
+        taken_at -> Timestamp,
+        data -> Text,
+    }
+}
+
+diesel::table! {
+    peers (address) {
+        address -> Text,
+        success -> Bool,
+        last_seen -> Timestamp,
+    }
+}
+
+diesel::table! {
+    tasks (id) {
+        id -> Text,
+        name -> Text,
+        description -> Text,
+        status -> Text,
+        result -> Nullable<Text>,
+        created_at -> Timestamp,
+        started_at -> Nullable<Timestamp>,
+        finished_at -> Nullable<Timestamp>,
+        cost_ms -> Nullable<BigInt>,
+        log_file -> Text,
+    }
+}
+
+diesel::table! {
+    jobs (id) {
+        id -> Text,
+        task_id -> Text,
+        seq -> Integer,
+        name -> Text,
+        status -> Text,
+        result -> Nullable<Text>,
+        message -> Text,
+        cost_ms -> Nullable<BigInt>,
+    }
+}
+
+diesel::table! {
+    job_attempts (id) {
+        id -> Text,
+        job_id -> Text,
+        task_id -> Text,
+        seq -> Integer,
+        cmd -> Text,
+        args -> Text,
+        check_str -> Nullable<Text>,
+        status -> Nullable<Integer>,
+        stdout -> Nullable<Text>,
+        stderr -> Nullable<Text>,
+        result -> Nullable<Text>,
+        cost_ms -> Nullable<BigInt>,
+    }
+}
+
+diesel::table! {
+    events (id) {
+        id -> Text,
+        name -> Text,
+        timestamp -> Timestamp,
+        description -> Text,
+        priority -> Text,
+        severity -> Text,
+        category -> Text,
+        status -> Text,
+        progress -> Text,
+        subject -> Text,
+        fingerprint -> Text,
+        count -> BigInt,
+        first_seen -> Nullable<Timestamp>,
+        last_seen -> Nullable<Timestamp>,
+        resolved_at -> Nullable<Timestamp>,
+        acknowledged_until -> Nullable<Timestamp>,
+        silenced_until -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    devices (ip) {
+        ip -> Text,
+        mac -> Nullable<Text>,
+        methods -> Text,
+        ports -> Text,
+        status -> Text,
+        first_seen -> Timestamp,
+        last_seen -> Timestamp,
+    }
+}
+
+diesel::joinable!(host_snapshots -> hosts (host_id));
+diesel::joinable!(jobs -> tasks (task_id));
+diesel::joinable!(job_attempts -> jobs (job_id));
+
+diesel::allow_tables_to_appear_in_same_query!(
+    hosts,
+    host_snapshots,
+    peers,
+    tasks,
+    jobs,
+    job_attempts,
+    events,
+    devices,
+);
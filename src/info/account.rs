@@ -0,0 +1,496 @@
+//! 本地账号安全清单：用户、组、密码有效期、登录会话、SSH 授权公钥和 sudo 规则
+//!
+//! 读取 /etc/passwd、/etc/group、/etc/shadow（仅 root 可读）、utmp/wtmp、~/.ssh/authorized_keys 和 /etc/sudoers，
+//! 各文件以根目录为参数拼接路径，便于以夹具目录测试。
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use colored::Colorize;
+use ring::digest::{digest, SHA256};
+use serde::{Deserialize, Serialize};
+
+#[cfg(not(target_os = "linux"))]
+use crate::info::error::InfoError;
+use crate::info::error::{read_file, InfoResult};
+
+// 最近登录记录保留条数
+const RECENT_LOGINS: usize = 20;
+
+// utmp 记录，Linux glibc 布局：类型、PID、终端、ID、用户、主机、退出状态、会话、时间、地址
+const UTMP_SIZE: usize = 384;
+const UT_LINE: (usize, usize) = (8, 32);
+const UT_USER: (usize, usize) = (44, 32);
+const UT_HOST: (usize, usize) = (76, 256);
+const UT_TV_SEC: usize = 340;
+const BOOT_TIME: i16 = 2;
+const USER_PROCESS: i16 = 7;
+const DEAD_PROCESS: i16 = 8;
+
+// shadow 中表示不限制的天数
+const NEVER_EXPIRES: u64 = 99999;
+
+// 密码有效期，来自 /etc/shadow
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PasswordAge {
+    pub status: String,                      // set、locked、none（空密码）
+    pub last_change: Option<NaiveDate>,
+    pub min_days: Option<u64>,
+    pub max_days: Option<u64>,               // 99999 即永不过期时为 None
+    pub warn_days: Option<u64>,
+    pub inactive_days: Option<u64>,
+    pub expires: Option<NaiveDate>,          // 账号过期日期
+}
+
+// 本地用户
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct User {
+    pub name: String,
+    pub uid: u32,
+    pub gid: u32,
+    pub gecos: String,
+    pub home: String,
+    pub shell: String,
+    pub groups: Vec<String>,                 // 附加组
+    pub password: Option<PasswordAge>,       // 非 root 运行无法读取 /etc/shadow 时为 None
+}
+
+impl User {
+    /// 是否可交互登录，shell 为 nologin 或 false 时不可登录
+    pub fn can_login(&self) -> bool {
+        !self.shell.is_empty() && !self.shell.ends_with("nologin") && !self.shell.ends_with("false")
+    }
+}
+
+// 本地组
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Group {
+    pub name: String,
+    pub gid: u32,
+    pub members: Vec<String>,
+}
+
+// 登录会话
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Session {
+    pub user: String,
+    pub line: String,                        // 终端，如 pts/0
+    pub host: String,                        // 远程主机，本地登录为空
+    pub pid: u32,
+    pub login: DateTime<Utc>,
+    pub logout: Option<DateTime<Utc>>,       // 仍在登录或异常断开时为 None
+}
+
+// SSH 授权公钥
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct AuthorizedKey {
+    pub user: String,
+    pub key_type: String,                    // ssh-ed25519、ssh-rsa...
+    pub fingerprint: String,                 // 与 ssh-keygen -l 一致，如 SHA256:6yBq...
+    pub comment: String,
+    pub options: String,                     // 如 from="10.0.0.0/8",no-port-forwarding
+}
+
+// sudo 规则，续行已合并
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SudoRule {
+    pub file: String,
+    pub rule: String,
+}
+
+/// 解析 /etc/passwd
+pub fn parse_passwd(content: &str) -> Vec<User> {
+    content.lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split(':').collect();
+            if fields.len() < 7 {
+                return None;
+            }
+            Some(User {
+                name: fields[0].to_string(),
+                uid: fields[2].parse().ok()?,
+                gid: fields[3].parse().ok()?,
+                gecos: fields[4].to_string(),
+                home: fields[5].to_string(),
+                shell: fields[6].to_string(),
+                groups: Vec::new(),
+                password: None,
+            })
+        })
+        .collect()
+}
+
+/// 解析 /etc/group
+pub fn parse_group(content: &str) -> Vec<Group> {
+    content.lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split(':').collect();
+            if fields.len() < 4 {
+                return None;
+            }
+            Some(Group {
+                name: fields[0].to_string(),
+                gid: fields[2].parse().ok()?,
+                members: fields[3].split(',').filter(|m| !m.is_empty()).map(str::to_string).collect(),
+            })
+        })
+        .collect()
+}
+
+// shadow 中以 1970-01-01 起的天数表示日期
+fn epoch_days(days: u64) -> Option<NaiveDate> {
+    NaiveDate::from_ymd_opt(1970, 1, 1)?.checked_add_days(Days::new(days))
+}
+
+/// 解析 /etc/shadow，返回用户名到密码有效期的映射
+pub fn parse_shadow(content: &str) -> HashMap<String, PasswordAge> {
+    content.lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split(':').collect();
+            if fields.len() < 8 {
+                return None;
+            }
+            let days = |index: usize| fields[index].parse::<u64>().ok();
+            let status = match fields[1] {
+                "" => "none",
+                hash if hash.starts_with('!') || hash.starts_with('*') => "locked",
+                _ => "set",
+            };
+            Some((fields[0].to_string(), PasswordAge {
+                status: status.to_string(),
+                last_change: days(2).and_then(epoch_days),
+                min_days: days(3),
+                max_days: days(4).filter(|days| *days < NEVER_EXPIRES),
+                warn_days: days(5),
+                inactive_days: days(6),
+                expires: days(7).and_then(epoch_days),
+            }))
+        })
+        .collect()
+}
+
+// utmp 中以 NUL 结尾的定长字符串
+fn utmp_str(record: &[u8], (offset, len): (usize, usize)) -> String {
+    let field = &record[offset..offset + len];
+    let end = field.iter().position(|byte| *byte == 0).unwrap_or(len);
+    String::from_utf8_lossy(&field[..end]).into_owned()
+}
+
+// (类型, 会话)，会话的 logout 为空
+fn parse_utmp(data: &[u8]) -> Vec<(i16, Session)> {
+    data.chunks_exact(UTMP_SIZE)
+        .filter_map(|record| {
+            let kind = i16::from_ne_bytes([record[0], record[1]]);
+            let pid = i32::from_ne_bytes(record[4..8].try_into().ok()?);
+            let seconds = i32::from_ne_bytes(record[UT_TV_SEC..UT_TV_SEC + 4].try_into().ok()?);
+            Some((kind, Session {
+                user: utmp_str(record, UT_USER),
+                line: utmp_str(record, UT_LINE),
+                host: utmp_str(record, UT_HOST),
+                pid: pid.max(0) as u32,
+                login: DateTime::from_timestamp(seconds as i64, 0)?,
+                logout: None,
+            }))
+        })
+        .collect()
+}
+
+/// 当前登录会话，来自 utmp
+pub fn parse_sessions(utmp: &[u8]) -> Vec<Session> {
+    parse_utmp(utmp).into_iter()
+        .filter(|(kind, session)| *kind == USER_PROCESS && !session.user.is_empty())
+        .map(|(_, session)| session)
+        .collect()
+}
+
+/// 最近的登录记录，来自 wtmp，新的在前，与 last 命令一致：同一终端的退出记录或重启结束会话
+pub fn parse_logins(wtmp: &[u8], limit: usize) -> Vec<Session> {
+    let mut open: BTreeMap<String, Session> = BTreeMap::new();
+    let mut logins = Vec::new();
+    for (kind, record) in parse_utmp(wtmp) {
+        match kind {
+            USER_PROCESS => {
+                if let Some(previous) = open.insert(record.line.clone(), record) {
+                    logins.push(previous);
+                }
+            }
+            DEAD_PROCESS => {
+                if let Some(mut session) = open.remove(&record.line) {
+                    session.logout = Some(record.login);
+                    logins.push(session);
+                }
+            }
+            BOOT_TIME => {
+                for (_, mut session) in std::mem::take(&mut open) {
+                    session.logout = Some(record.login);
+                    logins.push(session);
+                }
+            }
+            _ => {}
+        }
+    }
+    logins.extend(open.into_values());
+    logins.sort_by_key(|session| std::cmp::Reverse(session.login));
+    logins.truncate(limit);
+    logins
+}
+
+/// 解析 authorized_keys 中的一行，注释、空行和无法解码的公钥返回 None
+pub fn parse_authorized_key(user: &str, line: &str) -> Option<AuthorizedKey> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    // 选项中可能有带空格的引号字符串，按公钥类型定位
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    let index = tokens.iter().position(|token| {
+        token.starts_with("ssh-") || token.starts_with("ecdsa-") || token.starts_with("sk-")
+    })?;
+    let blob = general_purpose::STANDARD.decode(tokens.get(index + 1)?).ok()?;
+    let hash = digest(&SHA256, &blob);
+    Some(AuthorizedKey {
+        user: user.to_string(),
+        key_type: tokens[index].to_string(),
+        fingerprint: format!("SHA256:{}", general_purpose::STANDARD_NO_PAD.encode(hash.as_ref())),
+        comment: tokens[index + 2..].join(" "),
+        options: tokens[..index].join(" "),
+    })
+}
+
+// 根目录下的绝对路径
+fn rooted(root: &Path, path: &str) -> PathBuf {
+    root.join(path.trim_start_matches('/'))
+}
+
+/// 各用户 ~/.ssh/authorized_keys 和 authorized_keys2 中的公钥
+pub fn read_authorized_keys(root: &Path, users: &[User]) -> Vec<AuthorizedKey> {
+    let mut keys = Vec::new();
+    for user in users.iter().filter(|user| !user.home.is_empty()) {
+        for name in ["authorized_keys", "authorized_keys2"] {
+            let Ok(content) = fs::read_to_string(rooted(root, &user.home).join(".ssh").join(name)) else {
+                continue;
+            };
+            keys.extend(content.lines().filter_map(|line| parse_authorized_key(&user.name, line)));
+        }
+    }
+    keys
+}
+
+/// 读取 sudoers 文件及其包含的文件，与 sudo 一致：includedir 按文件名排序，跳过以 ~ 结尾或含 . 的文件
+pub fn read_sudoers(root: &Path, path: &str) -> Vec<SudoRule> {
+    let mut rules = Vec::new();
+    collect_sudoers(root, path, &mut rules, 0);
+    rules
+}
+
+fn collect_sudoers(root: &Path, path: &str, rules: &mut Vec<SudoRule>, depth: usize) {
+    // sudo 限制包含深度，防止循环包含
+    if depth > 8 {
+        return;
+    }
+    let Ok(content) = fs::read_to_string(rooted(root, path)) else {
+        return;
+    };
+    let parent = Path::new(path).parent().unwrap_or(Path::new("/"));
+    let mut pending = String::new();
+    for line in content.lines() {
+        let line = line.trim();
+        if let Some(continued) = line.strip_suffix('\\') {
+            pending.push_str(continued.trim_end());
+            pending.push(' ');
+            continue;
+        }
+        let line = std::mem::take(&mut pending) + line;
+        let directive = line.split_once(char::is_whitespace)
+            .filter(|(keyword, _)| ["#include", "@include", "#includedir", "@includedir"].contains(keyword));
+        if let Some((keyword, target)) = directive {
+            let target = parent.join(target.trim());
+            let target = target.to_string_lossy();
+            if keyword.ends_with("dir") {
+                let Ok(entries) = fs::read_dir(rooted(root, &target)) else {
+                    continue;
+                };
+                let mut names: Vec<String> = entries.flatten().map(|e| e.file_name().to_string_lossy().into_owned())
+                    .filter(|name| !name.ends_with('~') && !name.contains('.'))
+                    .collect();
+                names.sort();
+                for name in names {
+                    collect_sudoers(root, &format!("{}/{}", target, name), rules, depth + 1);
+                }
+            } else {
+                collect_sudoers(root, &target, rules, depth + 1);
+            }
+        } else if !line.is_empty() && !line.starts_with('#') {
+            rules.push(SudoRule { file: path.to_string(), rule: line.split_whitespace().collect::<Vec<_>>().join(" ") });
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountInfo {
+    pub users: Vec<User>,
+    pub groups: Vec<Group>,
+    pub sessions: Vec<Session>,              // 当前登录
+    pub recent_logins: Vec<Session>,         // 最近登录，新的在前
+    pub authorized_keys: Vec<AuthorizedKey>,
+    pub sudoers: Vec<SudoRule>,
+}
+
+impl AccountInfo {
+    /// 创建新的 AccountInfo 实例，采集失败时为空
+    pub fn new() -> Self {
+        Self::collect().unwrap_or_else(|e| {
+            log::warn!("failed to collect account info: {}", e);
+            AccountInfo::default()
+        })
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn collect() -> InfoResult<Self> {
+        Self::collect_from(Path::new("/"))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn collect() -> InfoResult<Self> {
+        Err(InfoError::Unsupported("account inventory".to_string()))
+    }
+
+    /// 以 root 为根目录采集，passwd 和 group 必须可读，其余文件不存在或无权限时为空
+    pub fn collect_from(root: &Path) -> InfoResult<Self> {
+        let mut users = parse_passwd(&read_file(&rooted(root, "/etc/passwd"))?);
+        let groups = parse_group(&read_file(&rooted(root, "/etc/group"))?);
+        let shadow = fs::read_to_string(rooted(root, "/etc/shadow")).map(|content| parse_shadow(&content)).unwrap_or_default();
+        for user in &mut users {
+            user.groups = groups.iter().filter(|g| g.members.contains(&user.name)).map(|g| g.name.clone()).collect();
+            user.password = shadow.get(&user.name).cloned();
+        }
+        let sessions = fs::read(rooted(root, "/var/run/utmp")).map(|data| parse_sessions(&data)).unwrap_or_default();
+        let recent_logins = fs::read(rooted(root, "/var/log/wtmp")).map(|data| parse_logins(&data, RECENT_LOGINS)).unwrap_or_default();
+        let authorized_keys = read_authorized_keys(root, &users);
+        Ok(AccountInfo {
+            users,
+            groups,
+            sessions,
+            recent_logins,
+            authorized_keys,
+            sudoers: read_sudoers(root, "/etc/sudoers"),
+        })
+    }
+
+    pub fn display(&self) -> String {
+        let mut output = format!("Users: {} ({} can login), groups: {}\n", self.users.len(),
+                                 self.users.iter().filter(|u| u.can_login()).count(), self.groups.len());
+        for user in self.users.iter().filter(|u| u.can_login()) {
+            let password = user.password.as_ref().map_or("N/A".to_string(), |p| match (&p.last_change, p.max_days) {
+                (Some(changed), Some(max)) => format!("{} changed {} max {}d", p.status, changed, max),
+                (Some(changed), None) => format!("{} changed {}", p.status, changed),
+                _ => p.status.clone(),
+            });
+            output.push_str(&format!("  {:<16} uid {:<6} {:<20} password {} groups {}\n", user.name.green(), user.uid, user.shell,
+                                     password, user.groups.join(",")));
+        }
+        for key in &self.authorized_keys {
+            output.push_str(&format!("Key {} {} {} {}\n", key.user.green(), key.key_type, key.fingerprint.blue(), key.comment));
+        }
+        for session in &self.sessions {
+            output.push_str(&format!("Session {} {} {} since {}\n", session.user.green(), session.line, session.host,
+                                     session.login.format("%Y-%m-%d %H:%M:%S")));
+        }
+        for rule in &self.sudoers {
+            output.push_str(&format!("Sudo {} ({})\n", rule.rule.yellow(), rule.file));
+        }
+        println!("{}", output);
+        output
+    }
+
+    /// 转换为 JSON 字符串
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|err| {
+            eprintln!("序列化账号信息到 JSON 失败: {}", err);
+            "{}".to_string()
+        })
+    }
+}
+
+#[cfg(test)]
+mod unit_test_account {
+    use super::*;
+
+    fn root() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/accounts/rootfs")
+    }
+
+    fn utmp_record(kind: i16, line: &str, user: &str, host: &str, seconds: i32) -> Vec<u8> {
+        let mut record = vec![0u8; UTMP_SIZE];
+        record[..2].copy_from_slice(&kind.to_ne_bytes());
+        record[4..8].copy_from_slice(&1234i32.to_ne_bytes());
+        record[UT_LINE.0..UT_LINE.0 + line.len()].copy_from_slice(line.as_bytes());
+        record[UT_USER.0..UT_USER.0 + user.len()].copy_from_slice(user.as_bytes());
+        record[UT_HOST.0..UT_HOST.0 + host.len()].copy_from_slice(host.as_bytes());
+        record[UT_TV_SEC..UT_TV_SEC + 4].copy_from_slice(&seconds.to_ne_bytes());
+        record
+    }
+
+    #[test]
+    fn test_account_01() {
+        let info = AccountInfo::collect_from(&root()).unwrap();
+        assert_eq!(info.users.len(), 5);
+        assert_eq!(info.users.iter().filter(|u| u.can_login()).map(|u| u.name.as_str()).collect::<Vec<_>>(), ["root", "alice", "deploy"]);
+        let alice = &info.users[3];
+        assert_eq!(alice.groups, ["sudo", "docker"]);
+        let password = alice.password.as_ref().unwrap();
+        assert_eq!(password.status, "set");
+        assert_eq!(password.last_change, NaiveDate::from_ymd_opt(2024, 1, 1));
+        assert_eq!(password.max_days, Some(90));
+        assert_eq!(password.inactive_days, Some(14));
+        assert_eq!(info.users[0].password.as_ref().unwrap().status, "locked");
+        let deploy = info.users[4].password.as_ref().unwrap();
+        assert_eq!((deploy.status.as_str(), deploy.max_days, deploy.expires), ("none", None, NaiveDate::from_ymd_opt(2025, 1, 1)));
+
+        let keys: Vec<(&str, &str, &str)> = info.authorized_keys.iter()
+            .map(|k| (k.user.as_str(), k.fingerprint.as_str(), k.comment.as_str())).collect();
+        assert_eq!(keys, [
+            ("root", "SHA256:rjLbv2a2u/er+9ut5xHyEcojGDhVCbx9PDuusV8e/5I", "deploy@ci"),
+            ("alice", "SHA256:6yBqIm+bDnQzsRCYdUPZGNcRz8FfKbWoNKVI79ueQXI", "alice@laptop"),
+            ("alice", "SHA256:rjLbv2a2u/er+9ut5xHyEcojGDhVCbx9PDuusV8e/5I", "deploy@ci"),
+        ]);
+        assert_eq!(info.authorized_keys[2].options, r#"from="10.0.0.0/8",no-port-forwarding"#);
+
+        let rules: Vec<&str> = info.sudoers.iter().map(|r| r.rule.as_str()).collect();
+        assert_eq!(rules[2..], [
+            "root ALL=(ALL:ALL) ALL",
+            "%sudo ALL=(ALL:ALL) ALL",
+            "Cmnd_Alias RESTART = /usr/bin/systemctl restart nginx, /usr/bin/systemctl restart app",
+            "deploy ALL=(root) NOPASSWD: RESTART",
+        ]);
+        assert_eq!(info.sudoers[5].file, "/etc/sudoers.d/deploy");
+        assert!(info.sessions.is_empty() && info.recent_logins.is_empty());
+    }
+
+    #[test]
+    fn test_account_02() {
+        let utmp = [
+            utmp_record(BOOT_TIME, "~", "reboot", "", 1_700_000_000),
+            utmp_record(USER_PROCESS, "tty1", "root", "", 1_700_000_100),
+            utmp_record(USER_PROCESS, "pts/0", "alice", "10.0.0.5", 1_700_000_200),
+            utmp_record(DEAD_PROCESS, "pts/0", "", "", 1_700_000_300),
+            utmp_record(USER_PROCESS, "pts/1", "deploy", "10.0.0.9", 1_700_000_400),
+            utmp_record(BOOT_TIME, "~", "reboot", "", 1_700_000_500),
+            utmp_record(USER_PROCESS, "pts/0", "alice", "10.0.0.5", 1_700_000_600),
+        ].concat();
+        let sessions = parse_sessions(&utmp);
+        assert_eq!(sessions.iter().map(|s| s.user.as_str()).collect::<Vec<_>>(), ["root", "alice", "deploy", "alice"]);
+        assert_eq!(sessions[1].host, "10.0.0.5");
+        assert_eq!(sessions[1].pid, 1234);
+
+        let logins = parse_logins(&utmp, 3);
+        let summary: Vec<(&str, Option<i64>)> = logins.iter().map(|s| (s.user.as_str(), s.logout.map(|t| t.timestamp()))).collect();
+        assert_eq!(summary, [("alice", None), ("deploy", Some(1_700_000_500)), ("alice", Some(1_700_000_300))]);
+
+        assert_eq!(parse_authorized_key("root", "# comment"), None);
+        assert_eq!(parse_authorized_key("root", "ssh-ed25519 not-base64!"), None);
+    }
+}
@@ -0,0 +1,462 @@
+//! 进程控制：发送信号、结束进程树、调整优先级和 CPU 亲和性、等待退出
+//!
+//! 每个操作生成一个 `Event` 用于审计；dry-run 模式只列出受影响的进程，不实际执行。
+
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use colored::Colorize;
+use serde::Serialize;
+
+use crate::info::process::ProcessInfo;
+use crate::monitor::event::{Event, Priority, Severity};
+
+// 信号
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+    Hup,
+    Int,
+    Quit,
+    Kill,
+    Usr1,
+    Usr2,
+    Term,
+    Cont,
+    Stop,
+    Other(i32),                              // 按编号发送，仅 Unix
+}
+
+impl Signal {
+    /// 信号编号，非 Unix 平台为 None
+    #[cfg(unix)]
+    pub fn number(&self) -> Option<i32> {
+        Some(match self {
+            Signal::Hup => libc::SIGHUP,
+            Signal::Int => libc::SIGINT,
+            Signal::Quit => libc::SIGQUIT,
+            Signal::Kill => libc::SIGKILL,
+            Signal::Usr1 => libc::SIGUSR1,
+            Signal::Usr2 => libc::SIGUSR2,
+            Signal::Term => libc::SIGTERM,
+            Signal::Cont => libc::SIGCONT,
+            Signal::Stop => libc::SIGSTOP,
+            Signal::Other(number) => *number,
+        })
+    }
+
+    #[cfg(not(unix))]
+    pub fn number(&self) -> Option<i32> {
+        None
+    }
+}
+
+impl fmt::Display for Signal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Signal::Hup => write!(f, "SIGHUP"),
+            Signal::Int => write!(f, "SIGINT"),
+            Signal::Quit => write!(f, "SIGQUIT"),
+            Signal::Kill => write!(f, "SIGKILL"),
+            Signal::Usr1 => write!(f, "SIGUSR1"),
+            Signal::Usr2 => write!(f, "SIGUSR2"),
+            Signal::Term => write!(f, "SIGTERM"),
+            Signal::Cont => write!(f, "SIGCONT"),
+            Signal::Stop => write!(f, "SIGSTOP"),
+            Signal::Other(number) => write!(f, "signal {}", number),
+        }
+    }
+}
+
+impl FromStr for Signal {
+    type Err = String;
+
+    // 不区分大小写，支持 TERM、SIGTERM 和编号
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let upper = s.to_uppercase();
+        let name = upper.strip_prefix("SIG").unwrap_or(&upper);
+        match name {
+            "HUP" => Ok(Signal::Hup),
+            "INT" => Ok(Signal::Int),
+            "QUIT" => Ok(Signal::Quit),
+            "KILL" => Ok(Signal::Kill),
+            "USR1" => Ok(Signal::Usr1),
+            "USR2" => Ok(Signal::Usr2),
+            "TERM" => Ok(Signal::Term),
+            "CONT" => Ok(Signal::Cont),
+            "STOP" => Ok(Signal::Stop),
+            _ => match name.parse::<i32>() {
+                Ok(number) if number > 0 => Ok(Signal::Other(number)),
+                _ => Err(format!("invalid signal: {}", s)),
+            },
+        }
+    }
+}
+
+// 单个进程的操作结果
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ActionResult {
+    pub pid: u32,
+    pub command: String,
+    pub error: Option<String>,               // 成功或 dry-run 时为 None
+}
+
+// 一次操作的结果
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ControlReport {
+    pub action: String,
+    pub dry_run: bool,
+    pub results: Vec<ActionResult>,
+}
+
+impl ControlReport {
+    pub fn is_success(&self) -> bool {
+        self.results.iter().all(|r| r.error.is_none())
+    }
+
+    pub fn display(&self) -> String {
+        let mut output = format!("{}{} ({} process(es))", if self.dry_run { "[dry-run] " } else { "" }, self.action, self.results.len());
+        for result in &self.results {
+            let status = match &result.error {
+                None if self.dry_run => "would apply".yellow().to_string(),
+                None => "ok".green().to_string(),
+                Some(e) => format!("failed: {}", e).red().to_string(),
+            };
+            output.push_str(&format!("\n  {:>7} {:<30} {}", result.pid, result.command, status));
+        }
+        println!("{}", output);
+        output
+    }
+
+    /// 转换为 JSON 字符串
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|err| {
+            eprintln!("序列化进程控制结果到 JSON 失败: {}", err);
+            "{}".to_string()
+        })
+    }
+
+    // 审计事件：dry-run 为调试级别，有失败为警告级别
+    fn to_event(&self) -> Event {
+        let targets = self.results.iter().map(|r| match &r.error {
+            None => format!("{}({})", r.pid, r.command),
+            Some(e) => format!("{}({}) 失败: {}", r.pid, r.command, e),
+        }).collect::<Vec<_>>().join(", ");
+        let severity = if self.dry_run {
+            Severity::Debug
+        } else if self.is_success() {
+            Severity::Info
+        } else {
+            Severity::Warning
+        };
+        Event::new("进程控制",
+            &format!("{}{}: {}", if self.dry_run { "[dry-run] " } else { "" }, self.action, targets),
+            Priority::Medium,
+            severity,
+            "进程控制"
+        )
+    }
+}
+
+fn command_of(process: &ProcessInfo) -> String {
+    process.command.rsplit(['/', '\\']).next().unwrap_or_default().to_string()
+}
+
+/// init 和当前进程不参与控制操作，避免误杀系统和工具自身
+pub fn is_protected(pid: u32) -> bool {
+    pid == 1 || pid == std::process::id()
+}
+
+/// root 的全部后代，按深度从深到浅排列
+pub fn descendants(root: u32, processes: &[ProcessInfo]) -> Vec<ProcessInfo> {
+    let mut levels: Vec<Vec<ProcessInfo>> = Vec::new();
+    let mut visited = HashSet::from([root]);
+    let mut parents = vec![root];
+    while !parents.is_empty() {
+        let children: Vec<ProcessInfo> = processes.iter()
+            .filter(|p| p.parent_pid.is_some_and(|ppid| parents.contains(&ppid)) && visited.insert(p.pid))
+            .cloned()
+            .collect();
+        parents = children.iter().map(|p| p.pid).collect();
+        if !children.is_empty() {
+            levels.push(children);
+        }
+    }
+    levels.into_iter().rev().flatten().collect()
+}
+
+#[cfg(unix)]
+fn send_signal(pid: u32, signal: Signal) -> Result<(), String> {
+    let number = signal.number().ok_or_else(|| format!("{} is not supported", signal))?;
+    // SAFETY: kill 只读取参数，无内存安全问题
+    if unsafe { libc::kill(pid as libc::pid_t, number) } != 0 {
+        return Err(std::io::Error::last_os_error().to_string());
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+fn send_signal(pid: u32, signal: Signal) -> Result<(), String> {
+    // Windows 无信号，TERM 请求关闭窗口，KILL 强制结束
+    let pid = pid.to_string();
+    let args: Vec<&str> = match signal {
+        Signal::Term | Signal::Int => vec!["/PID", &pid],
+        Signal::Kill => vec!["/F", "/PID", &pid],
+        _ => return Err(format!("{} is not supported on Windows", signal)),
+    };
+    crate::info::error::run_command("taskkill", &args).map(|_| ()).map_err(|e| e.to_string())
+}
+
+#[cfg(unix)]
+fn set_nice(pid: u32, nice: i32) -> Result<(), String> {
+    // SAFETY: setpriority 只读取参数，无内存安全问题
+    if unsafe { libc::setpriority(libc::PRIO_PROCESS as _, pid as libc::id_t, nice) } != 0 {
+        return Err(std::io::Error::last_os_error().to_string());
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn set_nice(_pid: u32, _nice: i32) -> Result<(), String> {
+    Err("renice is not supported on this platform".to_string())
+}
+
+#[cfg(target_os = "linux")]
+fn set_affinity(pid: u32, cpus: &[usize]) -> Result<(), String> {
+    // SAFETY: cpu_set_t 为纯数据结构，全零即空集合；CPU_SET 越界由下方检查保证
+    let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    let max = 8 * std::mem::size_of::<libc::cpu_set_t>();
+    for &cpu in cpus {
+        if cpu >= max {
+            return Err(format!("cpu {} out of range", cpu));
+        }
+        unsafe { libc::CPU_SET(cpu, &mut set) };
+    }
+    // SAFETY: set 为合法的 cpu_set_t，长度与其大小一致
+    if unsafe { libc::sched_setaffinity(pid as libc::pid_t, std::mem::size_of::<libc::cpu_set_t>(), &set) } != 0 {
+        return Err(std::io::Error::last_os_error().to_string());
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn set_affinity(_pid: u32, _cpus: &[usize]) -> Result<(), String> {
+    Err("CPU affinity is not supported on this platform".to_string())
+}
+
+/// 进程是否仍然存在，僵尸进程视为已退出
+#[cfg(target_os = "linux")]
+pub fn is_alive(pid: u32) -> bool {
+    match std::fs::read_to_string(format!("/proc/{}/stat", pid)) {
+        // 第三列为状态，comm 可能含空格，从最后一个 ')' 之后取
+        Ok(stat) => stat.rsplit_once(')').and_then(|(_, rest)| rest.split_whitespace().next()).is_some_and(|state| state != "Z"),
+        Err(_) => false,
+    }
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+pub fn is_alive(pid: u32) -> bool {
+    // SAFETY: 信号 0 只检查进程是否存在
+    unsafe { libc::kill(pid as libc::pid_t, 0) == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM) }
+}
+
+#[cfg(not(unix))]
+pub fn is_alive(pid: u32) -> bool {
+    let mut system = sysinfo::System::new();
+    system.refresh_process(sysinfo::Pid::from_u32(pid))
+}
+
+// 进程控制器，记录每次操作的审计事件
+#[derive(Debug, Default)]
+pub struct ProcessControl {
+    dry_run: bool,
+    events: Vec<Event>,
+}
+
+impl ProcessControl {
+    pub fn new(dry_run: bool) -> Self {
+        ProcessControl { dry_run, events: Vec::new() }
+    }
+
+    pub fn dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// 已产生的审计事件
+    pub fn events(&self) -> &Vec<Event> {
+        &self.events
+    }
+
+    /// 取出审计事件，如写入数据库后调用
+    pub fn take_events(&mut self) -> Vec<Event> {
+        std::mem::take(&mut self.events)
+    }
+
+    fn apply<F: Fn(u32) -> Result<(), String>>(&mut self, action: String, targets: &[ProcessInfo], operation: F) -> ControlReport {
+        let results = targets.iter().map(|process| ActionResult {
+            pid: process.pid,
+            command: command_of(process),
+            error: if self.dry_run { None } else { operation(process.pid).err() },
+        }).collect();
+        let report = ControlReport { action, dry_run: self.dry_run, results };
+        let event = report.to_event();
+        log::info!("{}", event.description());
+        self.events.push(event);
+        report
+    }
+
+    /// 向进程发送信号
+    pub fn signal(&mut self, targets: &[ProcessInfo], signal: Signal) -> ControlReport {
+        self.apply(format!("send {}", signal), targets, |pid| send_signal(pid, signal))
+    }
+
+    /// 向 root 及其全部后代发送信号，先后代（由深到浅）后 root，processes 为当前进程列表，跳过 init 和当前进程
+    pub fn kill_tree(&mut self, root: &ProcessInfo, processes: &[ProcessInfo], signal: Signal) -> ControlReport {
+        let mut targets = descendants(root.pid, processes);
+        targets.push(root.clone());
+        targets.retain(|process| !is_protected(process.pid));
+        self.apply(format!("send {} to tree of {}", signal, root.pid), &targets, |pid| send_signal(pid, signal))
+    }
+
+    /// 调整 nice 值（-20 ~ 19，越小优先级越高），降低 nice 通常需要 root 权限
+    pub fn renice(&mut self, targets: &[ProcessInfo], nice: i32) -> ControlReport {
+        self.apply(format!("renice to {}", nice), targets, |pid| set_nice(pid, nice))
+    }
+
+    /// 将进程绑定到指定 CPU（仅 Linux）
+    pub fn set_affinity(&mut self, targets: &[ProcessInfo], cpus: &[usize]) -> ControlReport {
+        let list = cpus.iter().map(|c| c.to_string()).collect::<Vec<_>>().join(",");
+        self.apply(format!("set CPU affinity to {}", list), targets, |pid| set_affinity(pid, cpus))
+    }
+
+    /// 等待进程退出，超时仍存在的进程记为失败
+    pub fn wait_exit(&mut self, targets: &[ProcessInfo], timeout: Duration) -> ControlReport {
+        let deadline = Instant::now() + timeout;
+        if !self.dry_run {
+            while Instant::now() < deadline && targets.iter().any(|p| is_alive(p.pid)) {
+                thread::sleep(Duration::from_millis(100));
+            }
+        }
+        self.apply(format!("wait for exit within {}s", timeout.as_secs_f64()), targets, |pid| {
+            if is_alive(pid) { Err("still running".to_string()) } else { Ok(()) }
+        })
+    }
+}
+
+#[cfg(test)]
+mod unit_test_control {
+    use super::*;
+    use std::process::{Child, Command};
+    use chrono::Utc;
+
+    fn process(pid: u32, parent_pid: Option<u32>, command: &str) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            parent_pid,
+            command: command.to_string(),
+            exe_path: String::new(),
+            full_command: command.to_string(),
+            user: "root".to_string(),
+            uid: Some(0),
+            state: "Sleeping".to_string(),
+            start_time: Utc::now(),
+            cpu_percent: 0.0,
+            rss: 0,
+            vsz: 0,
+            threads: None,
+            open_fds: None,
+            cwd: String::new(),
+            listening: vec![],
+        }
+    }
+
+    fn spawn_sleep() -> (Child, ProcessInfo) {
+        let child = Command::new("sleep").arg("30").spawn().unwrap();
+        let info = process(child.id(), Some(std::process::id()), "/bin/sleep");
+        (child, info)
+    }
+
+    #[test]
+    fn test_control_01() {
+        assert_eq!("term".parse::<Signal>(), Ok(Signal::Term));
+        assert_eq!("SIGKILL".parse::<Signal>(), Ok(Signal::Kill));
+        assert_eq!("10".parse::<Signal>(), Ok(Signal::Other(10)));
+        assert!("SIGFOO".parse::<Signal>().is_err() && "0".parse::<Signal>().is_err());
+        assert_eq!(Signal::Hup.to_string(), "SIGHUP");
+
+        let processes = vec![
+            process(1, None, "/sbin/init"),
+            process(10, Some(1), "/usr/sbin/sshd"),
+            process(20, Some(10), "sshd"),
+            process(30, Some(20), "bash"),
+            process(31, Some(20), "bash"),
+            process(40, Some(30), "vim"),
+            process(50, Some(1), "cron"),
+        ];
+        let tree: Vec<u32> = descendants(10, &processes).iter().map(|p| p.pid).collect();
+        assert_eq!(tree, vec![40, 30, 31, 20]);
+        assert!(descendants(50, &processes).is_empty());
+
+        assert!(is_protected(1) && is_protected(std::process::id()) && !is_protected(10));
+        let mut processes = processes;
+        processes.push(process(std::process::id(), Some(40), "minirobot_info"));
+        let report = ProcessControl::new(true).kill_tree(&processes[0], &processes, Signal::Kill);
+        let pids: Vec<u32> = report.results.iter().map(|result| result.pid).collect();
+        assert_eq!(pids, vec![40, 30, 31, 20, 10, 50]);
+    }
+
+    #[test]
+    fn test_control_02() {
+        // dry-run 不影响进程，仍然产生审计事件
+        let (mut child, info) = spawn_sleep();
+        let targets = [info];
+        let mut control = ProcessControl::new(true);
+        let report = control.signal(&targets, Signal::Kill);
+        assert!(report.dry_run && report.is_success());
+        assert!(report.display().contains("would apply"));
+        assert!(is_alive(targets[0].pid));
+        assert_eq!(control.events().len(), 1);
+        assert_eq!(control.events()[0].severity(), &Severity::Debug);
+        assert!(control.events()[0].description().starts_with("[dry-run] send SIGKILL"));
+        child.kill().unwrap();
+        child.wait().unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_control_03() {
+        let (mut child, info) = spawn_sleep();
+        let targets = [info];
+        let mut control = ProcessControl::new(false);
+        assert!(control.renice(&targets, 5).is_success());
+
+        let report = control.signal(&targets, Signal::Term);
+        assert!(report.is_success(), "{:?}", report);
+        child.wait().unwrap();
+        assert!(control.wait_exit(&targets, Duration::from_secs(2)).is_success());
+
+        // 进程已退出，发送信号失败并记为警告
+        let report = control.signal(&targets, Signal::Term);
+        assert!(!report.is_success() && report.to_json().contains("\"error\":"));
+        let events = control.take_events();
+        assert_eq!(events.len(), 4);
+        assert_eq!(events[3].severity(), &Severity::Warning);
+        assert!(control.events().is_empty());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_control_04() {
+        let (mut child, info) = spawn_sleep();
+        let targets = [info];
+        let mut control = ProcessControl::new(false);
+        assert!(control.set_affinity(&targets, &[0]).is_success());
+        assert!(!control.set_affinity(&targets, &[100_000]).is_success());
+        let report = control.wait_exit(&targets, Duration::from_millis(200));
+        assert_eq!(report.results[0].error.as_deref(), Some("still running"));
+        child.kill().unwrap();
+        child.wait().unwrap();
+    }
+}
@@ -1,52 +1,58 @@
-use std::process::Command;
-use std::str;
-
 use colored::Colorize;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_json;
 
-#[derive(Debug, Serialize)]
+use crate::info::error::InfoResult;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CpuInfo {
-    cpu_socket_count: String,
-    cpu_core_count: String,
-    cpu_thread_count: String,
+    cpu_socket_count: u32,
+    cpu_core_count: u32,
+    cpu_thread_count: u32,
 }
 
 impl CpuInfo {
-    /// 创建新的 CpuInfo 实例
+    /// 创建新的 CpuInfo 实例，采集失败时各项为 0
     pub fn new() -> CpuInfo {
-        // 获取 CPU 信息
-        let (cpu_socket_count, cpu_core_count, cpu_thread_count) = get_cpu_info();
+        Self::collect().unwrap_or_else(|e| {
+            log::warn!("采集 CPU 信息失败: {}", e);
+            CpuInfo::default()
+        })
+    }
 
-        CpuInfo {
+    /// 采集 CPU 信息
+    pub fn collect() -> InfoResult<CpuInfo> {
+        let (cpu_socket_count, cpu_core_count, cpu_thread_count) = get_cpu_info()?;
+
+        Ok(CpuInfo {
             cpu_socket_count,
             cpu_core_count,
             cpu_thread_count,
-        }
+        })
     }
 
     /// 获取 CPU 插槽数
-    pub fn cpu_socket_count(&self) -> &str {
-        &self.cpu_socket_count
+    pub fn cpu_socket_count(&self) -> u32 {
+        self.cpu_socket_count
     }
 
     /// 获取 CPU 核心数
-    pub fn cpu_core_count(&self) -> &str {
-        &self.cpu_core_count
+    pub fn cpu_core_count(&self) -> u32 {
+        self.cpu_core_count
     }
 
     /// 获取 CPU 线程数
-    pub fn cpu_thread_count(&self) -> &str {
-        &self.cpu_thread_count
+    pub fn cpu_thread_count(&self) -> u32 {
+        self.cpu_thread_count
     }
 
     /// 显示 CPU 信息
     pub fn display(&self) -> String {
         let output = format!(
             "CPU:\n  Socket(s): {}\n  Core(s): {}\n  Thread(s): {}",
-            self.cpu_socket_count.green().to_string(),
-            self.cpu_core_count.green().to_string(),
-            self.cpu_thread_count.green().to_string()
+            self.cpu_socket_count.to_string().green(),
+            self.cpu_core_count.to_string().green(),
+            self.cpu_thread_count.to_string().green()
         );
         println!("{}\n", output);
         output
@@ -62,144 +68,52 @@ impl CpuInfo {
 }
 
 #[cfg(target_os = "windows")]
-fn get_cpu_info() -> (String, String, String) {
-    let mut cpu_socket_count: u32 = 0;
-    let mut cpu_core_count = 0;
-    let mut cpu_thread_count = 0;
-
-    // 执行 wmic 命令获取 CPU 信息
-    let output = Command::new("wmic")
-        .arg("cpu")
-        .arg("get")
-        .arg("NumberOfCores,NumberOfLogicalProcessors,DeviceID")
-        .output()
-        .expect("failed to execute process");
-    let output_str = String::from_utf8_lossy(&output.stdout);
-
-    for line in output_str.lines().skip(1) { // 跳过标题行
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.len() >= 3 {
-            if let Ok(cores) = parts[0].parse::<u32>() {
-                cpu_core_count += cores;
-            }
-            if let Ok(logical_processors) = parts[1].parse::<u32>() {
-                cpu_thread_count += logical_processors;
-            }
-            cpu_socket_count += 1; // 每个 DeviceID 代表一个 CPU
+fn get_cpu_info() -> InfoResult<(u32, u32, u32)> {
+    use crate::info::error::{run_command, InfoError};
+
+    let mut cpu_socket_count = 0u32;
+    let mut cpu_core_count = 0u32;
+    let mut cpu_thread_count = 0u32;
+
+    let output = run_command("wmic", &["cpu", "get", "NumberOfCores,NumberOfLogicalProcessors", "/format:csv"])?;
+    // 每行对应一个 CPU 插槽：Node,NumberOfCores,NumberOfLogicalProcessors
+    for line in output.lines().map(str::trim).filter(|l| !l.is_empty()).skip(1) {
+        let parts: Vec<&str> = line.split(',').collect();
+        if parts.len() < 3 {
+            return Err(InfoError::parse("cpu counts", line));
         }
+        cpu_core_count += parts[1].trim().parse::<u32>().map_err(|_| InfoError::parse("NumberOfCores", parts[1]))?;
+        cpu_thread_count += parts[2].trim().parse::<u32>().map_err(|_| InfoError::parse("NumberOfLogicalProcessors", parts[2]))?;
+        cpu_socket_count += 1;
     }
 
-    (
-        cpu_socket_count.to_string(),
-        cpu_core_count.to_string(),
-        cpu_thread_count.to_string(),
-    )
+    Ok((cpu_socket_count, cpu_core_count, cpu_thread_count))
 }
 
 #[cfg(target_os = "linux")]
-fn get_cpu_info() -> (String, String, String) {
-    let mut cpu_socket_count: u32 = 0;
-
-    // 执行 lscpu 命令获取 CPU 信息
-    let output = Command::new("lscpu")
-        .output()
-        .expect("Failed to execute lscpu command");
-    let output_str = String::from_utf8_lossy(&output.stdout);
-    let lines: Vec<&str> = output_str.lines().collect();
-    let mut core = 0;
-    let mut thread = 0;
-
-    for line in lines {
-        let parts: Vec<&str> = line.split(":").map(|s| s.trim()).collect();
-        if parts.len() == 2 {
-            match parts[0] {
-                "Socket(s)" => {
-                    if let Ok(count) = parts[1].parse::<u32>() {
-                        cpu_socket_count = count;
-                    }
-                }
-                "Core(s) per socket" => {
-                    if let Ok(count) = parts[1].parse::<u32>() {
-                        core = count;
-                    }
-                }
-                "Thread(s) per core" => {
-                    if let Ok(count) = parts[1].parse::<u32>() {
-                        thread = count;
-                    }
-                }
-                _ => {}
-            }
-        }
-    }
+fn get_cpu_info() -> InfoResult<(u32, u32, u32)> {
+    use std::path::Path;
+    use crate::info::error::read_file;
+    use crate::info::procfs;
 
-    let cpu_core_count = cpu_socket_count * core;
-    let cpu_thread_count = cpu_core_count * thread;
+    let counts = procfs::parse_cpuinfo(&read_file(&Path::new(procfs::PROC_ROOT).join("cpuinfo"))?);
 
-    (
-        cpu_socket_count.to_string(),
-        cpu_core_count.to_string(),
-        cpu_thread_count.to_string(),
-    )
+    Ok((counts.sockets, counts.cores, counts.threads))
 }
 
 #[cfg(target_os = "macos")]
-fn get_cpu_info() -> (String, String, String) {
-    let mut cpu_socket_count: u32 = 0;
-    let mut cpu_core_count = 0;
-    let mut cpu_thread_count = 0;
-
-    // 执行 sysctl -a 命令获取 CPU 信息
-    let output = Command::new("sysctl")
-        .arg("-a")
-        .output()
-        .expect("Failed to execute sysctl command");
-    let output_str = String::from_utf8_lossy(&output.stdout);
-    let lines: Vec<&str> = output_str.lines().collect();
-    let mut physical_cpu_count = None;
-    let mut core_count = None;
-    let mut thread_count = None;
-
-    // 解析每一行，提取所需的信息
-    for line in lines {
-        let parts: Vec<&str> = line.split(":").map(|s| s.trim()).collect();
-        if parts.len() == 2 {
-            match parts[0] {
-                "hw.physicalcpu" => {
-                    if let Ok(count) = parts[1].parse::<u32>() {
-                        physical_cpu_count = Some(count);
-                    }
-                }
-                "hw.ncpu" => {
-                    if let Ok(count) = parts[1].parse::<u32>() {
-                        core_count = Some(count);
-                    }
-                }
-                "hw.logicalcpu" => {
-                    if let Ok(count) = parts[1].parse::<u32>() {
-                        thread_count = Some(count);
-                    }
-                }
-                _ => {}
-            }
-        }
+fn get_cpu_info() -> InfoResult<(u32, u32, u32)> {
+    use crate::info::error::{run_command, InfoError};
+
+    // 依次输出 hw.packages、hw.physicalcpu、hw.logicalcpu，每行一个值
+    let output = run_command("sysctl", &["-n", "hw.packages", "hw.physicalcpu", "hw.logicalcpu"])?;
+    let values = output.lines()
+        .map(|line| line.trim().parse::<u32>().map_err(|_| InfoError::parse("sysctl cpu count", line)))
+        .collect::<InfoResult<Vec<u32>>>()?;
+    match values[..] {
+        [sockets, cores, threads] => Ok((sockets, cores, threads)),
+        _ => Err(InfoError::parse("sysctl cpu counts", &output)),
     }
-
-    // 输出结果
-    match (physical_cpu_count, core_count, thread_count) {
-        (Some(physical), Some(core), Some(thread)) => {
-            cpu_socket_count = physical;
-            cpu_core_count = core;
-            cpu_thread_count = thread;
-        }
-        _ => eprintln!("无法找到所有必要的信息"),
-    }
-
-    (
-        cpu_socket_count.to_string(),
-        cpu_core_count.to_string(),
-        cpu_thread_count.to_string(),
-    )
 }
 
 #[cfg(test)]
@@ -230,7 +144,7 @@ mod unit_test_cpu {
         let cpu_info = CpuInfo::new();
         println!("调用 cpu_socket_count 方法:");
         let socket = cpu_info.cpu_socket_count();
-        assert!(socket.parse::<u64>().unwrap_or(0) >= 1, "CPU 插槽数无效");
+        assert!(socket >= 1, "CPU 插槽数无效");
     }
 
     #[test]
@@ -238,7 +152,7 @@ mod unit_test_cpu {
         let cpu_info = CpuInfo::new();
         println!("调用 cpu_core_count 方法:");
         let core = cpu_info.cpu_core_count();
-        assert!(core.parse::<u64>().unwrap_or(0) >= 1, "CPU 核心数无效");
+        assert!(core >= 1, "CPU 核心数无效");
     }
 
     #[test]
@@ -246,6 +160,6 @@ mod unit_test_cpu {
         let cpu_info = CpuInfo::new();
         println!("调用 cpu_thread_count 方法:");
         let thread = cpu_info.cpu_thread_count();
-        assert!(thread.parse::<u64>().unwrap_or(0) >= 1, "CPU 线程数无效");
+        assert!(thread >= 1, "CPU 线程数无效");
     }
 }
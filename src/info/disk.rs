@@ -1,54 +1,95 @@
-use std::collections::HashMap;
-use std::process::Command;
-use std::str;
-
 use colored::Colorize;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_json;
 
-#[derive(Debug, Serialize)]
+use crate::common::api::format_size;
+use crate::info::error::InfoResult;
+
+// 块设备
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockDevice {
+    pub name: String,
+    pub size: u64,                           // 字节
+    pub model: String,                       // 未知时为 N/A
+    pub rotational: Option<bool>,            // 是否机械盘，未知时为 None
+}
+
+// inode 用量
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Inodes {
+    pub total: u64,
+    pub used: u64,
+    pub free: u64,
+}
+
+// 已挂载分区，容量单位字节
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Partition {
+    pub mount: String,                       // 挂载点，Windows 为盘符
+    pub device: String,
+    pub fs_type: String,
+    pub total: u64,
+    pub used: u64,
+    pub free: u64,                           // 普通用户可用空间
+    pub inodes: Option<Inodes>,              // Windows 及不支持 inode 的文件系统为 None
+}
+
+impl Partition {
+    /// 使用率，口径与 df 一致：used / (used + free) 向上取整
+    pub fn use_percent(&self) -> u8 {
+        let usable = self.used + self.free;
+        if usable == 0 {
+            0
+        } else {
+            (self.used * 100).div_ceil(usable).min(100) as u8
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct DiskInfo {
-    // disk items: name, total bytes
-    block_device_info: Vec<HashMap<String, String>>,
-    // partition items: partition, free, total, use%
-    partition_info: Vec<HashMap<String, String>>,
+    block_devices: Vec<BlockDevice>,
+    partitions: Vec<Partition>,
 }
 
 impl DiskInfo {
+    /// 创建新的 DiskInfo 实例，采集失败时为空
     pub fn new() -> DiskInfo {
-        // Get disk information
-        let (block_device_info, partition_info) = get_disk_info();
+        Self::collect().unwrap_or_else(|e| {
+            log::warn!("采集磁盘信息失败: {}", e);
+            DiskInfo::default()
+        })
+    }
 
-        DiskInfo {
-            block_device_info,
-            partition_info,
-        }
+    /// 采集块设备及分区信息
+    pub fn collect() -> InfoResult<DiskInfo> {
+        Ok(DiskInfo {
+            block_devices: get_block_devices()?,
+            partitions: get_partitions()?,
+        })
     }
-    
-    pub fn block_device_info(&self) -> &Vec<HashMap<String, String>> {
-        &self.block_device_info
+
+    pub fn block_devices(&self) -> &Vec<BlockDevice> {
+        &self.block_devices
     }
 
-    pub fn partition_info(&self) -> &Vec<HashMap<String, String>> {
-        &self.partition_info
+    pub fn partitions(&self) -> &Vec<Partition> {
+        &self.partitions
     }
 
     pub fn display(&self) -> String {
         let mut output = String::from("Disk Size Info:");
-        for map in &self.block_device_info {
-            if let Some(device) = map.get("name") {
-                let total = map.get("total").unwrap().green().to_string();
-                let model = map.get("model").unwrap().blue().to_string();
-                output.push_str(&format!("\n  disk {:<4}: {:>10} total ({})", device, total, model));
-            }
+        for device in &self.block_devices {
+            output.push_str(&format!("\n  disk {:<4}: {:>10} total ({})",
+                device.name, format_size(device.size).green(), device.model.blue()));
         }
-        output.push_str("\n\nPartition (Linux/MacOS 1k-blocks) / (Windows Size) Info:");
-        for map in &self.partition_info {
-            if let Some(partition) = map.get("partition") {
-                let free = map.get("free").unwrap().green().to_string();
-                let total = map.get("total").unwrap().blue().to_string();
-                let percentage = map.get("use%").unwrap().yellow().to_string();
-                output.push_str(&format!("\n  {:<15}:{:>20} free{:>20} total, use {:>4}", partition, free, total, percentage))}
+        output.push_str("\n\nPartition Info:");
+        for partition in &self.partitions {
+            output.push_str(&format!("\n  {:<15}:{:>12} free{:>12} total, use {:>4}",
+                partition.mount,
+                format_size(partition.free).green(),
+                format_size(partition.total).blue(),
+                format!("{}%", partition.use_percent()).yellow()));
         }
         println!("{}\n", output);
         output
@@ -64,173 +105,175 @@ impl DiskInfo {
 }
 
 #[cfg(target_os = "windows")]
-fn get_disk_info() -> (Vec<HashMap<String, String>>, Vec<HashMap<String, String>>) {
-    use regex::Regex;
-    use crate::local::utils::format_size;
-
-    let mut block_device_info: Vec<HashMap<String, String>>= Vec::new();
-    let mut partition_info: Vec<HashMap<String, String>> = Vec::new();
+fn get_block_devices() -> InfoResult<Vec<BlockDevice>> {
+    use crate::info::error::{run_command, InfoError};
 
-    // 获取磁盘设备信息
-    let output = Command::new("wmic")
-        .arg("diskdrive")
-        .arg("where")
-        .arg("MediaType='Fixed hard disk media'")
-        .arg("get")
-        .arg("Caption,Size,MediaType,Model")
-        .output()
-        .expect("Failed to execute wmic command");
-    let output_str = str::from_utf8(&output.stdout).expect("Failed to convert output to string");
-    // 定义正则表达式来匹配 Caption、Size、MediaType 和 Model
-    let re = Regex::new(r"(.+?)\s+(\d+)\s+(.+?)\s+(.+)")
-        .expect("Failed to compile regex");
-    for cap in re.captures_iter(output_str) {
-        let media_type = cap[3].trim();
-        if media_type == "Fixed hard disk media" {
-            block_device_info.push(HashMap::from([
-                ("name".to_string(), cap[1].trim().to_string()),
-                ("total".to_string(), format_size(cap[2].parse().expect("Failed to parse size")).to_string()),
-                ("type".to_string(), media_type.to_string()),
-                ("model".to_string(), cap[4].trim().to_string())
-            ]));
+    // csv 列按字母排序：Node,Caption,MediaType,Model,Size
+    let output = run_command("wmic", &["diskdrive", "get", "Caption,MediaType,Model,Size", "/format:csv"])?;
+    let mut devices = Vec::new();
+    for line in output.lines().map(str::trim).filter(|l| !l.is_empty()).skip(1) {
+        let parts: Vec<&str> = line.split(',').collect();
+        if parts.len() < 5 || parts[2] != "Fixed hard disk media" {
+            continue;
         }
+        devices.push(BlockDevice {
+            name: parts[1].to_string(),
+            size: parts[4].parse().map_err(|_| InfoError::parse("disk size", parts[4]))?,
+            model: if parts[3].is_empty() { "N/A".to_string() } else { parts[3].to_string() },
+            rotational: None,
+        });
     }
+    Ok(devices)
+}
 
-    // 执行 wmic 命令来获取逻辑磁盘信息
-    let output = Command::new("wmic")
-        .arg("logicaldisk")
-        .arg("where")
-        .arg("DriveType=3")
-        .arg("get")
-        .arg("DeviceID,Size,FreeSpace")
-        .output()
-        .expect("Failed to execute wmic command");
-    let output_str = str::from_utf8(&output.stdout).expect("Failed to convert output to string");
-    // 定义正则表达式来匹配 DeviceID、Size 和 FreeSpace
-    let re = Regex::new(r"(\w:)\s+(\d+)\s+(\d+)")
-        .expect("Failed to compile regex");
-    for cap in re.captures_iter(output_str) {
-        let device_id = cap[1].trim().to_string();
-        let total_size: u64 = cap[2].parse().expect("Failed to parse total size");
-        let free_space: u64 = cap[3].parse().expect("Failed to parse free space");
-        let used_space = total_size - free_space;
-        let usage_percentage = (used_space as f64 / total_size as f64) * 100.0;
+#[cfg(target_os = "windows")]
+fn get_partitions() -> InfoResult<Vec<Partition>> {
+    use crate::info::error::{run_command, InfoError};
 
-        partition_info.push(HashMap::from([
-            ("partition".to_string(), device_id),
-            ("free".to_string(), format_size(free_space)),
-            ("total".to_string(), format_size(total_size)),
-            ("use%".to_string(), format!("{}%", usage_percentage))
-        ]));
+    // 仅本地磁盘，csv 列：Node,DeviceID,FileSystem,FreeSpace,Size
+    let output = run_command("wmic", &["logicaldisk", "where", "DriveType=3", "get", "DeviceID,FileSystem,FreeSpace,Size", "/format:csv"])?;
+    let mut partitions = Vec::new();
+    for line in output.lines().map(str::trim).filter(|l| !l.is_empty()).skip(1) {
+        let parts: Vec<&str> = line.split(',').collect();
+        if parts.len() < 5 {
+            return Err(InfoError::parse("logical disk", line));
+        }
+        let free: u64 = parts[3].parse().map_err(|_| InfoError::parse("free space", parts[3]))?;
+        let total: u64 = parts[4].parse().map_err(|_| InfoError::parse("disk size", parts[4]))?;
+        partitions.push(Partition {
+            mount: parts[1].to_string(),
+            device: parts[1].to_string(),
+            fs_type: parts[2].to_string(),
+            total,
+            used: total.saturating_sub(free),
+            free,
+            inodes: None,
+        });
     }
+    Ok(partitions)
+}
+
+#[cfg(target_os = "linux")]
+fn get_block_devices() -> InfoResult<Vec<BlockDevice>> {
+    use std::path::Path;
+    use crate::info::error::InfoError;
+    use crate::info::procfs;
 
-    (block_device_info, partition_info)
+    let sys_block = Path::new(procfs::SYS_ROOT).join("block");
+    let devices = procfs::read_block_devices(Path::new(procfs::SYS_ROOT)).map_err(|e| InfoError::io(&sys_block, e))?;
+    Ok(devices.into_iter().map(|device| BlockDevice {
+        name: device.name,
+        size: device.size,
+        model: if device.model.is_empty() { "N/A".to_string() } else { device.model },
+        rotational: Some(device.rotational),
+    }).collect())
 }
 
 #[cfg(target_os = "linux")]
-fn get_disk_info() -> (Vec<HashMap<String, String>>, Vec<HashMap<String, String>>) {
-    let mut block_device_info: Vec<HashMap<String, String>>= Vec::new();
-    let mut partition_info: Vec<HashMap<String, String>> = Vec::new();
-    let output = Command::new("lsblk")
-        .arg("-d")  // 只显示磁盘设备
-        .arg("-o")
-        .arg("NAME,SIZE,TYPE,MODEL")
-        .output()
-        .expect("Failed to execute lsblk");
-    let output_str = str::from_utf8(&output.stdout).expect("Failed to convert output to string");
-    for line in output_str.lines().skip(1) { // 跳过标题行
-        let columns: Vec<&str> = line.split_whitespace().collect();
-        if columns.len() >= 4 {
-            block_device_info.push(HashMap::from([
-                ("name".to_string(), columns[0].to_string()),
-                ("total".to_string(),columns[1].to_string()),
-                ("type".to_string(), columns[2].to_string()),
-                ("model".to_string(), columns[3..columns.len()].join(" ").to_string())
-            ]));
+fn get_partitions() -> InfoResult<Vec<Partition>> {
+    use std::path::Path;
+    use crate::info::error::read_file;
+    use crate::info::{procfs, virt};
+
+    let mounts = procfs::parse_mountinfo(&read_file(&Path::new(procfs::PROC_ROOT).join("self/mountinfo"))?);
+    // 容器内 /etc/hosts 等单个文件由宿主机分区绑定挂载，其容量为宿主机分区，不计入
+    let in_container = virt::detect_container(Path::new("/")).is_some();
+    let mut partitions = Vec::new();
+    for mount in procfs::storage_mounts(&mounts) {
+        if in_container && !Path::new(&mount.mount_point).is_dir() {
+            continue;
         }
+        // 无权限或已失效的挂载点跳过
+        let usage = match procfs::statvfs(&mount.mount_point) {
+            Ok(usage) if usage.total > 0 => usage,
+            _ => continue,
+        };
+        partitions.push(Partition {
+            mount: mount.mount_point,
+            device: mount.source,
+            fs_type: mount.fs_type,
+            total: usage.total,
+            used: usage.used,
+            free: usage.available,
+            inodes: (usage.files > 0).then(|| Inodes {
+                total: usage.files,
+                used: usage.files.saturating_sub(usage.files_free),
+                free: usage.files_free,
+            }),
+        });
     }
+    Ok(partitions)
+}
 
-    let output = Command::new("df")
-        .arg("-xtmpfs")
-        .arg("-xefi")
-        .arg("-xefivarfs")
-        .output()
-        .expect("Failed to execute df");
+#[cfg(target_os = "macos")]
+fn get_block_devices() -> InfoResult<Vec<BlockDevice>> {
+    use crate::info::error::run_command;
 
-    let output_str = String::from_utf8_lossy(&output.stdout);
-    let lines: Vec<&str> = output_str.lines().collect();
-    for line in lines.iter().skip(1) {
-        let parts: Vec<&str> = line.trim().split_whitespace().collect();
-        if parts.len() >= 6 {
-            partition_info.push(HashMap::from([
-                ("partition".to_string(), parts[5].to_string()),
-                ("free".to_string(), parts[3].to_string()),
-                ("total".to_string(), parts[1].to_string()),
-                ("use%".to_string(), parts[4].to_string())
-            ]));
-        }
+    // 物理磁盘行形如 "/dev/disk0 (internal, physical):"
+    let list = run_command("diskutil", &["list"])?;
+    let mut devices = Vec::new();
+    for line in list.lines().filter(|l| l.starts_with("/dev/disk") && l.contains("physical")) {
+        let path = line.split_whitespace().next().unwrap_or_default();
+        let info = run_command("diskutil", &["info", path])?;
+        let field = |name: &str| info.lines()
+            .filter_map(|l| l.trim().split_once(':'))
+            .find(|(key, _)| key.trim() == name)
+            .map(|(_, value)| value.trim().to_string());
+        // 形如 "500.3 GB (500277790720 Bytes) (exactly 977105060 512-Byte-Units)"
+        let size = field("Disk Size")
+            .and_then(|s| s.split('(').nth(1).and_then(|s| s.split_whitespace().next()).and_then(|s| s.parse().ok()))
+            .unwrap_or(0);
+        devices.push(BlockDevice {
+            name: path.trim_start_matches("/dev/").to_string(),
+            size,
+            model: field("Device / Media Name").unwrap_or_else(|| "N/A".to_string()),
+            rotational: field("Solid State").map(|s| s != "Yes"),
+        });
     }
-
-    (block_device_info, partition_info)
+    Ok(devices)
 }
 
 #[cfg(target_os = "macos")]
-fn get_disk_info() -> (Vec<HashMap<String, String>>, Vec<HashMap<String, String>>) {
-    let mut block_device_info: Vec<HashMap<String, String>>= Vec::new();
-    let mut partition_info: Vec<HashMap<String, String>> = Vec::new();
-    // 获取物理磁盘设备和大小
-    let output = Command::new("diskutil")
-        .arg("list")
-        .output()
-        .expect("Failed to execute diskutil command");
-    let output_str = str::from_utf8(&output.stdout).expect("Failed to parse diskutil output");
-    for line in output_str.lines() {
-        if line.contains("disk") {
-            let disk_name = line.split_whitespace().next().unwrap_or("").to_string();
-            let d_output = Command::new("sh")
-                .arg("-c")
-                .arg(format!("df /dev/{}", disk_name))
-                .output()
-                .expect("Failed to execute df command");
-            let tmp_str = str::from_utf8(&d_output.stdout).unwrap();
-            let parts: Vec<&str> = tmp_str.split_whitespace().collect();
-            if parts.len() >= 9 {
-                block_device_info.push(HashMap::from([
-                    ("name".to_string(), disk_name),
-                    ("total".to_string(), parts[8].to_string())
-                ]));
-            }
-        }
-    }
+fn get_partitions() -> InfoResult<Vec<Partition>> {
+    use std::collections::HashMap;
+    use crate::info::error::{run_command, InfoError};
 
-    // 获取分区挂载和分区大小及剩余空间大小（以字节为单位）
-    let output = Command::new("df")
-        .arg("-k") // 设置块大小为1字节
-        .output()
-        .expect("Failed to execute df command");
-    let output_str = str::from_utf8(&output.stdout).expect("Failed to convert output to string");
-    let re = Regex::new(r"^(/[\w/]+)\s+(\d+)\s+(\d+)\s+(\d+)\s+(\d+)%\s+(/.*)$")
-        .expect("Failed to compile regex");
-    for cap in re.captures_iter(output_str) {
-        let total_size: u64 = cap[2].parse().expect("Failed to parse total size");
-        let available_space: u64 = cap[4].parse().expect("Failed to parse available space");
-        let usage_percentage: u8 = cap[5].parse().expect("Failed to parse usage percentage");
-        let mounted_on = cap[6].trim();
-        partition_info.push(HashMap::from([
-            ("partition".to_string(), mounted_on.to_string()),
-            ("free".to_string(), available_space.to_string()),
-            ("total".to_string(), total_size.to_string()),
-            ("use%".to_string(), format!("{}%", usage_percentage))
-        ]));
-    }
+    // mount 输出形如 "/dev/disk3s1s1 on / (apfs, sealed, local, read-only, journaled)"
+    let mount_output = run_command("mount", &[])?;
+    let fs_types: HashMap<&str, &str> = mount_output.lines().filter_map(|line| {
+        let (_, rest) = line.split_once(" on ")?;
+        let (mount, options) = rest.rsplit_once(" (")?;
+        Some((mount, options.split(',').next()?.trim_end_matches(')')))
+    }).collect();
 
-    (block_device_info, partition_info)
+    // 列：Filesystem 1024-blocks Used Available Capacity iused ifree %iused Mounted on
+    let output = run_command("df", &["-k", "-i"])?;
+    let mut partitions = Vec::new();
+    for line in output.lines().skip(1).filter(|l| l.starts_with("/dev/")) {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() < 9 {
+            return Err(InfoError::parse("df output", line));
+        }
+        let number = |index: usize| parts[index].parse::<u64>().map_err(|_| InfoError::parse("df output", line));
+        let (inodes_used, inodes_free) = (number(5)?, number(6)?);
+        let mount = parts[8..].join(" ");
+        partitions.push(Partition {
+            fs_type: fs_types.get(mount.as_str()).copied().unwrap_or_default().to_string(),
+            mount,
+            device: parts[0].to_string(),
+            total: number(1)? * 1024,
+            used: number(2)? * 1024,
+            free: number(3)? * 1024,
+            inodes: Some(Inodes { total: inodes_used + inodes_free, used: inodes_used, free: inodes_free }),
+        });
+    }
+    Ok(partitions)
 }
 
 #[cfg(test)]
 mod unit_test_disk {
     use super::*;
-    
+
     #[test]
     fn test_disk_info_01() {
         let disk_info = DiskInfo::new();
@@ -241,33 +284,27 @@ mod unit_test_disk {
 
     #[test]
     fn test_disk_info_02() {
-        let disk_info = DiskInfo::new();
-        println!("calling fn block_device_info:");
-        let disk_size_info = disk_info.block_device_info();
-        assert!(disk_size_info.len() > 0);
-        for map in disk_size_info {
-            for key in vec!["name", "total", "type", "model"] {
-                if let Some(value) = map.get(key) {
-                    assert!(!value.is_empty(), "failed since some disk device info invalid.");
-                } else {
-                    assert!(!key.is_empty(), "failed since some disk device info not exists.");
-                }
-            }
+        let disk_info = DiskInfo::collect().unwrap();
+        println!("calling fn block_devices:");
+        let block_devices = disk_info.block_devices();
+        assert!(!block_devices.is_empty());
+        for device in block_devices {
+            assert!(!device.name.is_empty() && !device.model.is_empty(), "failed since some disk device info invalid.");
+            assert!(device.size > 0, "failed since disk {} size is 0", device.name);
         }
     }
 
     #[test]
     fn test_disk_info_03() {
         let disk_info = DiskInfo::new();
-        println!("calling fn partition_info:");
-        let partition_info = disk_info.partition_info();
-        for map in partition_info {
-            for key in vec!["partition", "free", "total", "use%"] {
-                if let Some(value) = map.get(key) {
-                    assert!(!value.is_empty(), "failed since some disk partition info invalid");
-                } else {
-                    assert!(!key.is_empty(), "failed since some disk partition info not exists");
-                }
+        println!("calling fn partitions:");
+        let partitions = disk_info.partitions();
+        assert!(!partitions.is_empty());
+        for partition in partitions {
+            assert!(!partition.mount.is_empty(), "failed since some disk partition info invalid");
+            assert!(partition.used <= partition.total && partition.use_percent() <= 100);
+            if let Some(inodes) = partition.inodes {
+                assert_eq!(inodes.total, inodes.used + inodes.free);
             }
         }
     }
@@ -279,7 +316,26 @@ mod unit_test_disk {
         let disk_info_json = disk_info.to_json();
         println!("json output: {}", disk_info_json);
         assert!(disk_info_json.contains("[{")
-            && disk_info_json.contains("block_device_info")
-            && disk_info_json.contains("partition_info"));
+            && disk_info_json.contains("block_devices")
+            && disk_info_json.contains("partitions"));
+        let parsed: DiskInfo = serde_json::from_str(&disk_info_json).unwrap();
+        assert_eq!(parsed, disk_info);
+    }
+
+    #[test]
+    fn test_disk_info_05() {
+        let partition = |used: u64, free: u64| Partition {
+            mount: "/".to_string(),
+            device: "/dev/sda1".to_string(),
+            fs_type: "ext4".to_string(),
+            total: used + free + 5,
+            used,
+            free,
+            inodes: None,
+        };
+        assert_eq!(partition(22, 77).use_percent(), 23);
+        assert_eq!(partition(0, 100).use_percent(), 0);
+        assert_eq!(partition(100, 0).use_percent(), 100);
+        assert_eq!(partition(0, 0).use_percent(), 0);
     }
 }
@@ -0,0 +1,91 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+// 主机信息采集错误
+#[derive(Debug)]
+pub enum InfoError {
+    Io { path: String, source: io::Error },                             // 读取文件失败
+    Command { command: String, source: io::Error },                     // 命令无法执行，如命令不存在
+    CommandFailed { command: String, status: Option<i32>, stderr: String }, // 命令返回失败
+    Parse { what: String, value: String },                              // 内容无法解析
+    Unsupported(String),                                                // 当前平台不支持
+}
+
+pub type InfoResult<T> = Result<T, InfoError>;
+
+impl fmt::Display for InfoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InfoError::Io { path, source } => write!(f, "failed to read {}: {}", path, source),
+            InfoError::Command { command, source } => write!(f, "failed to execute {}: {}", command, source),
+            InfoError::CommandFailed { command, status, stderr } => match status {
+                Some(code) => write!(f, "{} exited with status {}: {}", command, code, stderr.trim()),
+                None => write!(f, "{} terminated by signal: {}", command, stderr.trim()),
+            },
+            InfoError::Parse { what, value } => write!(f, "failed to parse {} from {:?}", what, value),
+            InfoError::Unsupported(what) => write!(f, "{} is not supported on this platform", what),
+        }
+    }
+}
+
+impl Error for InfoError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            InfoError::Io { source, .. } | InfoError::Command { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl InfoError {
+    pub fn io(path: &Path, source: io::Error) -> Self {
+        InfoError::Io { path: path.display().to_string(), source }
+    }
+
+    pub fn parse(what: &str, value: &str) -> Self {
+        InfoError::Parse { what: what.to_string(), value: value.to_string() }
+    }
+}
+
+/// 读取文本文件
+pub fn read_file(path: &Path) -> InfoResult<String> {
+    std::fs::read_to_string(path).map_err(|e| InfoError::io(path, e))
+}
+
+/// 执行命令并返回标准输出，命令不存在或返回非零状态时报错
+pub fn run_command(program: &str, args: &[&str]) -> InfoResult<String> {
+    let command = std::iter::once(program).chain(args.iter().copied()).collect::<Vec<_>>().join(" ");
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|source| InfoError::Command { command: command.clone(), source })?;
+    if !output.status.success() {
+        return Err(InfoError::CommandFailed {
+            command,
+            status: output.status.code(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod unit_test_error {
+    use super::*;
+
+    #[test]
+    fn test_info_error_01() {
+        let err = read_file(Path::new("/nonexistent/minirobot")).unwrap_err();
+        assert!(matches!(err, InfoError::Io { .. }));
+        assert!(err.to_string().starts_with("failed to read /nonexistent/minirobot"));
+        assert!(err.source().is_some());
+
+        let err = run_command("minirobot-no-such-command", &["-v"]).unwrap_err();
+        assert!(err.to_string().starts_with("failed to execute minirobot-no-such-command -v"));
+
+        assert_eq!(InfoError::parse("size", "abc").to_string(), "failed to parse size from \"abc\"");
+    }
+}
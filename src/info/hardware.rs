@@ -0,0 +1,505 @@
+//! 硬件资产信息：DMI/SMBIOS、PCI、USB 设备和磁盘标识，以及用于识别同一台机器的硬件指纹
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use colored::Colorize;
+use ring::digest::{digest, SHA256};
+use serde::{Deserialize, Serialize};
+
+#[cfg(not(target_os = "linux"))]
+use crate::info::error::InfoError;
+use crate::info::error::{read_file, InfoResult};
+#[cfg(target_os = "linux")]
+use crate::info::procfs;
+
+// pci.ids 常见位置
+const PCI_IDS_PATHS: [&str; 3] = ["/usr/share/hwdata/pci.ids", "/usr/share/misc/pci.ids", "/usr/share/pci.ids"];
+
+// 无 pci.ids 时使用的常见厂商
+const PCI_VENDORS: [(u16, &str); 16] = [
+    (0x1002, "Advanced Micro Devices, Inc. [AMD/ATI]"), (0x1022, "Advanced Micro Devices, Inc. [AMD]"),
+    (0x1077, "QLogic Corp."), (0x10de, "NVIDIA Corporation"), (0x10ec, "Realtek Semiconductor Co., Ltd."),
+    (0x1137, "Cisco Systems Inc"), (0x144d, "Samsung Electronics Co Ltd"), (0x14e4, "Broadcom Inc. and subsidiaries"),
+    (0x15ad, "VMware"), (0x15b3, "Mellanox Technologies"), (0x1af4, "Red Hat, Inc."), (0x1b36, "Red Hat, Inc."),
+    (0x1d0f, "Amazon.com, Inc."), (0x19e5, "Huawei Technologies Co., Ltd."), (0x8086, "Intel Corporation"),
+    (0x1000, "Broadcom / LSI"),
+];
+
+// PCI 设备大类，取 class 的最高字节
+const PCI_CLASSES: [(u8, &str); 14] = [
+    (0x00, "Unclassified device"), (0x01, "Mass storage controller"), (0x02, "Network controller"),
+    (0x03, "Display controller"), (0x04, "Multimedia controller"), (0x05, "Memory controller"), (0x06, "Bridge"),
+    (0x07, "Communication controller"), (0x08, "Generic system peripheral"), (0x09, "Input device controller"),
+    (0x0b, "Processor"), (0x0c, "Serial bus controller"), (0x0d, "Wireless controller"), (0x12, "Processing accelerators"),
+];
+
+// 厂商未填写时的占位序列号/UUID，不能用于识别机器
+const PLACEHOLDERS: [&str; 10] = [
+    "", "0", "none", "default string", "to be filled by o.e.m.", "system serial number", "not specified",
+    "not applicable", "03000200-0400-0500-0006-000700080009", "00000000-0000-0000-0000-000000000000",
+];
+
+fn is_placeholder(value: &str) -> bool {
+    let value = value.trim().to_lowercase();
+    PLACEHOLDERS.contains(&value.as_str()) || value.chars().all(|c| c == 'f' || c == '-')
+}
+
+// 读取 sysfs 属性，不存在或无权限（如非 root 读取序列号）时为空
+fn read_attr(path: &Path) -> String {
+    fs::read_to_string(path).map(|s| s.trim().to_string()).unwrap_or_default()
+}
+
+// 读取十六进制属性，如 "0x8086"
+fn read_hex(path: &Path) -> u32 {
+    u32::from_str_radix(read_attr(path).trim_start_matches("0x"), 16).unwrap_or(0)
+}
+
+// DMI/SMBIOS 信息（/sys/class/dmi/id）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Dmi {
+    pub sys_vendor: String,
+    pub product_name: String,
+    pub product_version: String,
+    pub product_serial: String,              // 需要 root 权限
+    pub product_uuid: String,                // 需要 root 权限
+    pub board_vendor: String,
+    pub board_name: String,
+    pub board_serial: String,                // 需要 root 权限
+    pub chassis_type: String,
+    pub bios_vendor: String,
+    pub bios_version: String,
+    pub bios_date: String,
+}
+
+/// 读取 sys_root/class/dmi/id，无 DMI 的平台（如部分 ARM）返回 None
+pub fn read_dmi(sys_root: &Path) -> Option<Dmi> {
+    let dir = sys_root.join("class/dmi/id");
+    if !dir.is_dir() {
+        return None;
+    }
+    let read = |name: &str| read_attr(&dir.join(name));
+    Some(Dmi {
+        sys_vendor: read("sys_vendor"),
+        product_name: read("product_name"),
+        product_version: read("product_version"),
+        product_serial: read("product_serial"),
+        product_uuid: read("product_uuid").to_lowercase(),
+        board_vendor: read("board_vendor"),
+        board_name: read("board_name"),
+        board_serial: read("board_serial"),
+        chassis_type: read("chassis_type"),
+        bios_vendor: read("bios_vendor"),
+        bios_version: read("bios_version"),
+        bios_date: read("bios_date"),
+    })
+}
+
+// PCI 厂商和设备名称库，来自 pci.ids
+#[derive(Debug, Clone, Default)]
+pub struct PciIds {
+    vendors: HashMap<u16, String>,
+    devices: HashMap<(u16, u16), String>,
+}
+
+impl PciIds {
+    /// 读取系统的 pci.ids，不存在时只含内置的常见厂商
+    pub fn load() -> Self {
+        PCI_IDS_PATHS.iter()
+            .find_map(|path| fs::read_to_string(path).ok())
+            .map(|text| Self::parse(&text))
+            .unwrap_or_default()
+    }
+
+    /// 解析 pci.ids：厂商行顶格，设备行以一个制表符开头，子系统行以两个制表符开头
+    pub fn parse(text: &str) -> Self {
+        let mut ids = PciIds::default();
+        let mut vendor = None;
+        for line in text.lines() {
+            if line.starts_with('#') || line.trim().is_empty() {
+                continue;
+            }
+            // 设备类别段（C 开头）之后不再是厂商
+            if line.starts_with("C ") {
+                break;
+            }
+            let parse = |s: &str| s.split_once("  ").and_then(|(id, name)| Some((u16::from_str_radix(id.trim(), 16).ok()?, name.trim().to_string())));
+            if let Some(device_line) = line.strip_prefix('\t') {
+                if device_line.starts_with('\t') {
+                    continue;
+                }
+                if let (Some(vendor), Some((device, name))) = (vendor, parse(device_line)) {
+                    ids.devices.insert((vendor, device), name);
+                }
+            } else if let Some((id, name)) = parse(line) {
+                vendor = Some(id);
+                ids.vendors.insert(id, name);
+            }
+        }
+        ids
+    }
+
+    pub fn vendor(&self, vendor: u16) -> String {
+        self.vendors.get(&vendor).cloned()
+            .or_else(|| PCI_VENDORS.iter().find(|(id, _)| *id == vendor).map(|(_, name)| name.to_string()))
+            .unwrap_or_default()
+    }
+
+    pub fn device(&self, vendor: u16, device: u16) -> String {
+        self.devices.get(&(vendor, device)).cloned().unwrap_or_default()
+    }
+}
+
+// PCI 设备
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PciDevice {
+    pub slot: String,                        // 如 0000:00:1f.6
+    pub vendor_id: u16,
+    pub device_id: u16,
+    pub class: u32,                          // 如 0x020000
+    pub class_name: String,
+    pub vendor: String,                      // 名称库中没有时为空
+    pub device: String,
+    pub driver: String,                      // 未绑定驱动时为空
+}
+
+/// 读取 sys_root/bus/pci/devices
+pub fn read_pci_devices(sys_root: &Path, ids: &PciIds) -> Vec<PciDevice> {
+    let Ok(entries) = fs::read_dir(sys_root.join("bus/pci/devices")) else {
+        return Vec::new();
+    };
+    let mut devices: Vec<PciDevice> = entries.flatten().map(|entry| {
+        let path = entry.path();
+        let vendor_id = read_hex(&path.join("vendor")) as u16;
+        let device_id = read_hex(&path.join("device")) as u16;
+        let class = read_hex(&path.join("class"));
+        PciDevice {
+            slot: entry.file_name().to_string_lossy().into_owned(),
+            vendor_id,
+            device_id,
+            class,
+            class_name: PCI_CLASSES.iter().find(|(id, _)| *id as u32 == class >> 16).map(|(_, name)| name.to_string()).unwrap_or_default(),
+            vendor: ids.vendor(vendor_id),
+            device: ids.device(vendor_id, device_id),
+            driver: fs::read_link(path.join("driver")).ok()
+                .and_then(|link| link.file_name().map(|name| name.to_string_lossy().into_owned()))
+                .unwrap_or_default(),
+        }
+    }).collect();
+    devices.sort_by(|a, b| a.slot.cmp(&b.slot));
+    devices
+}
+
+// USB 设备
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsbDevice {
+    pub port: String,                        // 总线拓扑位置，如 usb1、1-1.2
+    pub vendor_id: u16,
+    pub product_id: u16,
+    pub manufacturer: String,
+    pub product: String,
+    pub serial: String,
+}
+
+/// 读取 sys_root/bus/usb/devices，跳过接口（如 1-1:1.0）
+pub fn read_usb_devices(sys_root: &Path) -> Vec<UsbDevice> {
+    let Ok(entries) = fs::read_dir(sys_root.join("bus/usb/devices")) else {
+        return Vec::new();
+    };
+    let mut devices: Vec<UsbDevice> = entries.flatten()
+        .filter(|entry| entry.path().join("idVendor").exists())
+        .map(|entry| {
+            let path = entry.path();
+            UsbDevice {
+                port: entry.file_name().to_string_lossy().into_owned(),
+                vendor_id: read_hex(&path.join("idVendor")) as u16,
+                product_id: read_hex(&path.join("idProduct")) as u16,
+                manufacturer: read_attr(&path.join("manufacturer")),
+                product: read_attr(&path.join("product")),
+                serial: read_attr(&path.join("serial")),
+            }
+        }).collect();
+    devices.sort_by(|a, b| a.port.cmp(&b.port));
+    devices
+}
+
+// 磁盘标识
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiskIdentity {
+    pub name: String,
+    pub model: String,
+    pub serial: String,                      // 未知时为空
+    pub wwn: String,                         // World Wide Name，如 naa.5000c500a1b2c3d4、eui.0025388b...
+    pub rotational: bool,
+    pub size: u64,                           // 字节
+    #[serde(default)]
+    pub removable: bool,                     // 可移动介质或 USB 连接的磁盘
+}
+
+// 磁盘序列号：NVMe 为 device/serial，SCSI/SATA 取 VPD 80 页（4 字节页头之后）
+fn read_disk_serial(block: &Path) -> String {
+    let serial = read_attr(&block.join("device/serial"));
+    if !serial.is_empty() {
+        return serial;
+    }
+    fs::read(block.join("device/vpd_pg80"))
+        .map(|page| String::from_utf8_lossy(page.get(4..).unwrap_or_default()).trim_matches(|c: char| c.is_whitespace() || c == '\0').to_string())
+        .unwrap_or_default()
+}
+
+/// 读取 sys_root/block 下物理磁盘的标识
+#[cfg(target_os = "linux")]
+pub fn read_disk_identities(sys_root: &Path) -> Vec<DiskIdentity> {
+    let devices = procfs::read_block_devices(sys_root).unwrap_or_default();
+    devices.into_iter().map(|device| {
+        let block = sys_root.join("block").join(&device.name);
+        let wwn = [block.join("wwid"), block.join("device/wwid")].iter()
+            .map(|path| read_attr(path))
+            .find(|wwn| !wwn.is_empty())
+            .unwrap_or_default();
+        // USB 移动硬盘的 removable 通常为 0，按设备路径判断
+        let usb = fs::canonicalize(block.join("device")).is_ok_and(|path| path.to_string_lossy().contains("/usb"));
+        DiskIdentity {
+            serial: read_disk_serial(&block),
+            wwn,
+            removable: read_attr(&block.join("removable")) == "1" || usb,
+            name: device.name,
+            model: device.model,
+            rotational: device.rotational,
+            size: device.size,
+        }
+    }).collect()
+}
+
+/// 物理网卡（有 device 链接）的出厂 MAC 地址，不含虚拟网卡和随机生成、手动设置的地址
+pub fn read_physical_macs(sys_root: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(sys_root.join("class/net")) else {
+        return Vec::new();
+    };
+    // addr_assign_type 为 0 表示出厂地址，旧内核没有该属性时视为出厂地址
+    let permanent = |path: &Path| matches!(read_attr(&path.join("addr_assign_type")).as_str(), "" | "0");
+    let mut macs: Vec<String> = entries.flatten()
+        .filter(|entry| entry.path().join("device").exists() && permanent(&entry.path()))
+        .map(|entry| read_attr(&entry.path().join("address")).to_lowercase())
+        .filter(|mac| !mac.is_empty() && mac != "00:00:00:00:00:00")
+        .collect();
+    macs.sort();
+    macs.dedup();
+    macs
+}
+
+// 硬件指纹：由不随系统重装、主机名变化的硬件标识计算
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fingerprint {
+    pub id: String,                          // 标识拼接后的 SHA-256，十六进制
+    pub sources: Vec<String>,                // 参与计算的标识，如 dmi.product_uuid、disk.sda.serial
+}
+
+// 硬件信息
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HardwareInfo {
+    pub dmi: Option<Dmi>,
+    pub pci: Vec<PciDevice>,
+    pub usb: Vec<UsbDevice>,
+    pub disks: Vec<DiskIdentity>,
+    pub macs: Vec<String>,                   // 物理网卡 MAC
+}
+
+impl HardwareInfo {
+    pub fn new() -> Self {
+        Self::collect().unwrap_or_else(|e| {
+            log::warn!("failed to collect hardware info: {}", e);
+            HardwareInfo::default()
+        })
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn collect() -> InfoResult<Self> {
+        Ok(Self::collect_from(Path::new(procfs::SYS_ROOT), &PciIds::load()))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn collect() -> InfoResult<Self> {
+        Err(InfoError::Unsupported("hardware inventory".to_string()))
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn collect_from(sys_root: &Path, ids: &PciIds) -> Self {
+        HardwareInfo {
+            dmi: read_dmi(sys_root),
+            pci: read_pci_devices(sys_root, ids),
+            usb: read_usb_devices(sys_root),
+            disks: read_disk_identities(sys_root),
+            macs: read_physical_macs(sys_root),
+        }
+    }
+
+    /// 硬件指纹，依次取主板 UUID、产品序列号、主板序列号中第一个有效值；
+    /// 都没有时才使用固定磁盘的序列号/WWN 和物理网卡出厂 MAC，不含可移动磁盘、PCI、USB 设备。没有任何可用标识时返回 None
+    pub fn fingerprint(&self) -> Option<Fingerprint> {
+        let dmi = self.dmi.as_ref().and_then(|dmi| {
+            [("product_uuid", &dmi.product_uuid), ("product_serial", &dmi.product_serial), ("board_serial", &dmi.board_serial)]
+                .into_iter()
+                .find(|(_, value)| !is_placeholder(value))
+        });
+        let identifiers: Vec<(String, String)> = match dmi {
+            Some((name, value)) => vec![(format!("dmi.{}", name), value.clone())],
+            // 没有主板标识时，更换磁盘、网卡会改变指纹
+            None => self.fallback_identifiers(),
+        };
+        if identifiers.is_empty() {
+            return None;
+        }
+
+        // 只以标识值计算，磁盘改名（sda 变为 sdb）不影响指纹
+        let mut values: Vec<&str> = identifiers.iter().map(|(_, value)| value.as_str()).collect();
+        values.sort();
+        let hash = digest(&SHA256, values.join("\n").as_bytes());
+        Some(Fingerprint {
+            id: hash.as_ref().iter().map(|byte| format!("{:02x}", byte)).collect(),
+            sources: identifiers.into_iter().map(|(name, _)| name).collect(),
+        })
+    }
+
+    // 固定磁盘的序列号/WWN 和物理网卡出厂 MAC
+    fn fallback_identifiers(&self) -> Vec<(String, String)> {
+        let mut identifiers = Vec::new();
+        for disk in self.disks.iter().filter(|disk| !disk.removable) {
+            if !is_placeholder(&disk.serial) {
+                identifiers.push((format!("disk.{}.serial", disk.name), disk.serial.clone()));
+            }
+            if !is_placeholder(&disk.wwn) {
+                identifiers.push((format!("disk.{}.wwn", disk.name), disk.wwn.clone()));
+            }
+        }
+        for (index, mac) in self.macs.iter().enumerate() {
+            identifiers.push((format!("nic.{}.mac", index), mac.clone()));
+        }
+        identifiers
+    }
+
+    pub fn display(&self) -> String {
+        let mut output = String::new();
+        if let Some(dmi) = &self.dmi {
+            output.push_str(&format!("System: {} {} {} (serial {}, uuid {})\n", dmi.sys_vendor.green(), dmi.product_name.green(),
+                                     dmi.product_version, dmi.product_serial, dmi.product_uuid));
+            output.push_str(&format!("Board: {} {} (serial {})\n", dmi.board_vendor, dmi.board_name, dmi.board_serial));
+            output.push_str(&format!("BIOS: {} {} {}\n", dmi.bios_vendor, dmi.bios_version, dmi.bios_date));
+        }
+        for disk in &self.disks {
+            output.push_str(&format!("Disk {}: {} serial {} wwn {} {}\n", disk.name.green(), disk.model, disk.serial, disk.wwn,
+                                     if disk.rotational { "HDD" } else { "SSD" }));
+        }
+        for device in &self.pci {
+            output.push_str(&format!("PCI {} [{:04x}:{:04x}] {}: {} {} ({})\n", device.slot, device.vendor_id, device.device_id,
+                                     device.class_name, device.vendor, device.device, device.driver));
+        }
+        for device in &self.usb {
+            output.push_str(&format!("USB {} [{:04x}:{:04x}] {} {} {}\n", device.port, device.vendor_id, device.product_id,
+                                     device.manufacturer, device.product, device.serial));
+        }
+        match self.fingerprint() {
+            Some(fingerprint) => output.push_str(&format!("Fingerprint: {} ({})", fingerprint.id.blue(), fingerprint.sources.join(", "))),
+            None => output.push_str("Fingerprint: N/A"),
+        }
+        println!("{}\n", output);
+        output
+    }
+
+    /// 转换为 JSON 字符串，含指纹
+    pub fn to_json(&self) -> String {
+        let mut value = serde_json::to_value(self).unwrap_or_default();
+        value["fingerprint"] = serde_json::to_value(self.fingerprint()).unwrap_or_default();
+        serde_json::to_string(&value).unwrap_or_else(|err| {
+            eprintln!("序列化硬件信息到 JSON 失败: {}", err);
+            "{}".to_string()
+        })
+    }
+}
+
+/// 读取 pci.ids 文件
+pub fn load_pci_ids(path: &Path) -> InfoResult<PciIds> {
+    Ok(PciIds::parse(&read_file(path)?))
+}
+
+#[cfg(test)]
+mod unit_test_hardware {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn sys_root() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/procfs/sys")
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_hardware_01() {
+        let ids = load_pci_ids(&PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/pci.ids")).unwrap();
+        assert_eq!(ids.vendor(0x8086), "Intel Corporation");
+        assert_eq!(ids.device(0x8086, 0x15bc), "Ethernet Connection (7) I219-V");
+        assert_eq!(ids.vendor(0x10de), "NVIDIA Corporation", "pci.ids 中没有时使用内置厂商");
+        assert_eq!(ids.device(0x10de, 0x1eb8), "");
+
+        let hardware = HardwareInfo::collect_from(&sys_root(), &ids);
+        let dmi = hardware.dmi.as_ref().unwrap();
+        assert_eq!(dmi.sys_vendor, "Dell Inc.");
+        assert_eq!(dmi.product_uuid, "4c4c4544-0042-3510-8052-b4c04f4e3332");
+        assert_eq!(dmi.board_serial, "Default string");
+
+        assert_eq!(hardware.pci.len(), 2);
+        assert_eq!(hardware.pci[0].slot, "0000:00:1f.6");
+        assert_eq!(hardware.pci[0].class_name, "Network controller");
+        assert_eq!(hardware.pci[0].device, "Ethernet Connection (7) I219-V");
+        assert_eq!(hardware.pci[0].driver, "e1000e");
+        assert_eq!(hardware.pci[1].vendor, "NVIDIA Corporation");
+
+        assert_eq!(hardware.usb.len(), 2);
+        assert_eq!(hardware.usb[0].port, "1-1");
+        assert_eq!((hardware.usb[0].vendor_id, hardware.usb[0].product_id), (0x046d, 0xc52b));
+        assert_eq!(hardware.usb[1].port, "usb1");
+
+        let nvme = hardware.disks.iter().find(|d| d.name == "nvme0n1").unwrap();
+        assert_eq!(nvme.serial, "S4EWNX0N123456");
+        assert_eq!(nvme.wwn, "eui.0025388b71b2c3d4");
+        let sda = hardware.disks.iter().find(|d| d.name == "sda").unwrap();
+        assert_eq!(sda.serial, "ZA1B2C3D");
+        assert_eq!(sda.wwn, "naa.5000c500a1b2c3d4");
+        assert_eq!(hardware.macs, vec!["3c:ec:ef:12:34:56"]);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_hardware_02() {
+        let hardware = HardwareInfo::collect_from(&sys_root(), &PciIds::default());
+        let fingerprint = hardware.fingerprint().unwrap();
+        assert_eq!(fingerprint.id.len(), 64);
+        assert!(fingerprint.sources.contains(&"dmi.product_uuid".to_string()));
+        assert_eq!(fingerprint.sources, vec!["dmi.product_uuid".to_string()]);
+
+        // 有主板标识时，PCI、USB、磁盘和网卡变化都不影响指纹
+        let mut changed = hardware.clone();
+        changed.usb.clear();
+        changed.pci.pop();
+        changed.disks.pop();
+        changed.macs = vec!["3c:ec:ef:00:00:01".to_string()];
+        assert_eq!(changed.fingerprint().unwrap().id, fingerprint.id);
+        let mut dmi = hardware.dmi.clone().unwrap();
+        dmi.product_uuid = "03000200-0400-0500-0006-000700080009".to_string();
+        changed.dmi = Some(dmi);
+        assert_eq!(changed.fingerprint().unwrap().sources, vec!["dmi.product_serial".to_string()]);
+
+        // 没有主板标识时回退到固定磁盘和网卡，忽略可移动磁盘，磁盘改名不影响指纹
+        let mut fallback = hardware.clone();
+        fallback.dmi = None;
+        let id = fallback.fingerprint().unwrap().id;
+        assert!(fallback.fingerprint().unwrap().sources.iter().all(|source| !source.starts_with("dmi.")));
+        fallback.disks[0].name = "sdz".to_string();
+        fallback.disks.push(DiskIdentity { name: "sdc".to_string(), serial: "USB0001".to_string(), removable: true, ..Default::default() });
+        assert_eq!(fallback.fingerprint().unwrap().id, id);
+        fallback.macs = vec!["3c:ec:ef:00:00:01".to_string()];
+        assert_ne!(fallback.fingerprint().unwrap().id, id);
+        assert_eq!(HardwareInfo::default().fingerprint(), None);
+        assert!(hardware.to_json().contains(&fingerprint.id));
+    }
+}
@@ -1,8 +1,11 @@
+use std::thread;
+
 use colored::Colorize;
 use serde::Serialize;
 use serde_json;
 
 use crate::info;
+use crate::info::report::joined;
 
 #[derive(Debug, Serialize)]
 pub struct HostInfo {
@@ -13,26 +16,82 @@ pub struct HostInfo {
     mem_info: info::memory::MemInfo,
     net_info: Vec<info::network::NetworkInterface>,
     active_net_info: Vec<info::network::NetworkInterface>,
+    network_facts: info::network::NetworkFacts,
     process_info: Vec<info::process::ProcessInfo>,
     service_info: Vec<info::service::Service>,
+    account_info: info::account::AccountInfo,
 }
 
 impl HostInfo {
+    /// 各模块并行采集
     pub fn new() -> Self {
-        let (net_info , active_net_info) = info::network::get_nics();
-        let process_info = info::process::gather_process_info();
-        let service_info = info::service::get_services();
-        HostInfo {
-            hostname: info::hostname::hostname(),
-            os_info: info::os::OSInfo::new(),
-            cpu_info: info::cpu::CpuInfo::new(),
-            disk_info: info::disk::DiskInfo::new(),
-            mem_info: info::memory::MemInfo::new(),
-            net_info,
-            active_net_info,
-            process_info,
-            service_info,
-        }
+        thread::scope(|scope| {
+            let nics = scope.spawn(info::network::get_nics);
+            let process_info = scope.spawn(info::process::gather_process_info);
+            let service_info = scope.spawn(info::service::get_services);
+            let os_info = scope.spawn(info::os::OSInfo::new);
+            let disk_info = scope.spawn(info::disk::DiskInfo::new);
+            let network_facts = scope.spawn(info::network::NetworkFacts::new);
+            let account_info = scope.spawn(info::account::AccountInfo::new);
+            let (net_info, active_net_info) = joined(nics);
+            HostInfo {
+                hostname: info::hostname::hostname(),
+                os_info: joined(os_info),
+                cpu_info: info::cpu::CpuInfo::new(),
+                disk_info: joined(disk_info),
+                mem_info: info::memory::MemInfo::new(),
+                net_info,
+                active_net_info,
+                network_facts: joined(network_facts),
+                process_info: joined(process_info),
+                service_info: joined(service_info),
+                account_info: joined(account_info),
+            }
+        })
+    }
+
+    pub fn hostname(&self) -> &str {
+        &self.hostname
+    }
+
+    pub fn os_info(&self) -> &info::os::OSInfo {
+        &self.os_info
+    }
+
+    pub fn cpu_info(&self) -> &info::cpu::CpuInfo {
+        &self.cpu_info
+    }
+
+    pub fn disk_info(&self) -> &info::disk::DiskInfo {
+        &self.disk_info
+    }
+
+    pub fn mem_info(&self) -> &info::memory::MemInfo {
+        &self.mem_info
+    }
+
+    pub fn net_info(&self) -> &Vec<info::network::NetworkInterface> {
+        &self.net_info
+    }
+
+    pub fn active_net_info(&self) -> &Vec<info::network::NetworkInterface> {
+        &self.active_net_info
+    }
+
+    pub fn network_facts(&self) -> &info::network::NetworkFacts {
+        &self.network_facts
+    }
+
+    pub fn process_info(&self) -> &Vec<info::process::ProcessInfo> {
+        &self.process_info
+    }
+
+    pub fn service_info(&self) -> &Vec<info::service::Service> {
+        &self.service_info
+    }
+
+    pub fn account_info(&self) -> &info::account::AccountInfo {
+        &self.account_info
     }
 
     pub fn display(&self) -> String {
@@ -45,6 +104,7 @@ impl HostInfo {
         for nic in &self.net_info {
             output.push_str(&nic.display());
         }
+        output.push_str(&self.network_facts.display());
         for service in &self.service_info {
             output.push_str(&service.display());
         }
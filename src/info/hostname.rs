@@ -1,27 +1,27 @@
 #[cfg(target_os = "windows")]
 pub fn hostname() -> String {
     let mut hostname = String::new();
-    match env::var("COMPUTERNAME") {
+    match std::env::var("COMPUTERNAME") {
         Ok(computer_name) => hostname = computer_name,
         Err(e) => eprintln!("无法获取主机名: {}", e),
     }
     hostname
 }
 
-#[cfg(target_os = "linux")]
+// 获取内核中的主机名，容器内的 /etc/hostname 常与实际主机名不一致
+#[cfg(unix)]
 pub fn hostname() -> String {
-    let mut hostname = String::new();
-    if let Ok(content) = std::fs::read_to_string("/etc/hostname") {
-        hostname = content.trim().to_string();
-    }
-    hostname
+    hostname::get().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default()
 }
 
-#[cfg(target_os = "macos")]
-pub fn hostname() -> String {
-    let mut hostname = String::new();
-    if let Ok(content) = std::fs::read_to_string("/etc/hostname") {
-        hostname = content.trim().to_string();
+#[cfg(test)]
+mod unit_test_hostname {
+    use super::*;
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_hostname_01() {
+        let kernel_hostname = std::fs::read_to_string("/proc/sys/kernel/hostname").unwrap();
+        assert_eq!(hostname(), kernel_hostname.trim());
     }
-    hostname
 }
@@ -1,71 +1,83 @@
-use std::process::Command;
-use std::str;
-
 use colored::Colorize;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_json;
 
 use crate::common::api::format_size;
+use crate::info::error::InfoResult;
+use crate::info::virt::CgroupLimits;
 
-#[derive(Debug, Serialize)]
+// 内存信息，单位字节
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct MemInfo {
-    memory_total: String,
-    memory_using: String,
-    memory_free: String,
-    swap_total: String,
-    swap_using: String,
-    swap_free: String
+    memory_total: u64,
+    memory_used: u64,
+    memory_free: u64,
+    swap_total: u64,
+    swap_used: u64,
+    swap_free: u64,
 }
 
 impl MemInfo {
+    /// 创建新的 MemInfo 实例，采集失败时各项为 0
     pub fn new() -> MemInfo {
+        Self::collect().unwrap_or_else(|e| {
+            log::warn!("采集内存信息失败: {}", e);
+            MemInfo::default()
+        })
+    }
 
-        // Get memory information
-        let (memory_total, memory_using, memory_free,
-             swap_total, swap_using, swap_free) = get_memory_info();
+    /// 采集内存信息
+    pub fn collect() -> InfoResult<MemInfo> {
+        get_memory_info()
+    }
 
-        MemInfo {
-            memory_total,
-            memory_using,
-            memory_free,
-            swap_total,
-            swap_using,
-            swap_free
-        }
+    pub fn memory_total(&self) -> u64 {
+        self.memory_total
     }
 
-    pub fn memory_total(&self) -> &str {
-        &self.memory_total
+    pub fn memory_used(&self) -> u64 {
+        self.memory_used
     }
 
-    pub fn memory_using(&self) -> &str {
-        &self.memory_using
+    pub fn memory_free(&self) -> u64 {
+        self.memory_free
     }
 
-    pub fn memory_free(&self) -> &str {
-        &self.memory_free
+    pub fn swap_total(&self) -> u64 {
+        self.swap_total
     }
 
-    pub fn swap_total(&self) -> &str {
-        &self.swap_total
+    pub fn swap_used(&self) -> u64 {
+        self.swap_used
     }
 
-    pub fn swap_using(&self) -> &str {
-        &self.swap_using
+    pub fn swap_free(&self) -> u64 {
+        self.swap_free
     }
 
-    pub fn swap_free(&self) -> &str {
-        &self.swap_free
+    /// 按 cgroup 内存限制计算容器内可用内存：总量为限制值，已用为 cgroup 用量，未限制时不变
+    pub fn with_cgroup_limits(&self, limits: &CgroupLimits) -> MemInfo {
+        let memory_total = limits.effective_memory(self.memory_total);
+        if memory_total == self.memory_total {
+            return self.clone();
+        }
+        let memory_used = limits.memory_usage.unwrap_or(self.memory_used).min(memory_total);
+        MemInfo {
+            memory_total,
+            memory_used,
+            memory_free: memory_total - memory_used,
+            ..self.clone()
+        }
     }
 
     pub fn display(&self) -> String {
         let output = format!("Memory:\n  {} free {} using {} total\n  swap {} free {} using {} total",
-            &format_size(self.memory_free.parse::<u64>().unwrap()).green().to_string(),
-            &format_size(self.memory_using.parse::<u64>().unwrap()).yellow().to_string(),
-            &format_size(self.memory_total.parse::<u64>().unwrap()).blue().to_string(),
-            &format_size(self.swap_free.parse::<u64>().unwrap()).green().to_string(),
-            &format_size(self.swap_using.parse::<u64>().unwrap()).yellow().to_string(),
-            &format_size(self.swap_total.parse::<u64>().unwrap()).blue().to_string(),
+            format_size(self.memory_free).green(),
+            format_size(self.memory_used).yellow(),
+            format_size(self.memory_total).blue(),
+            format_size(self.swap_free).green(),
+            format_size(self.swap_used).yellow(),
+            format_size(self.swap_total).blue(),
         );
         println!("{}\n", output);
         output
@@ -81,175 +93,111 @@ impl MemInfo {
 }
 
 #[cfg(target_os = "windows")]
-fn get_memory_info() -> (String, String, String, String, String, String) {
-    let mut mem_total = 0u128;
-    let mut mem_using = 0u128;
-    let mut mem_free = 0u128;
-    let mut swap_total = 0u128;
-    let mut swap_using = 0u128;
-    let mut swap_free = 0u128;
-    // 获取物理内存信息
-    let physical_memory_output = Command::new("wmic")
-        .arg("OS")
-        .arg("get")
-        .arg("TotalVisibleMemorySize,FreePhysicalMemory")
-        .arg("/format:csv")
-        .output()
-        .expect("Failed to execute command");
-    let physical_memory_info = String::from_utf8_lossy(&physical_memory_output.stdout);
-    for line in physical_memory_info.lines().skip(1) {
-        let parts: Vec<&str> = line.split(',').collect();
-        if parts.len() == 3 {
-            mem_total = parts[1].trim().parse::<u64>().unwrap_or(0);
-            mem_free = parts[2].trim().parse::<u64>().unwrap_or(0);
-            mem_using = mem_total - mem_free;
-        }
+fn get_memory_info() -> InfoResult<MemInfo> {
+    use crate::info::error::{run_command, InfoError};
+
+    let parse = |what: &str, value: &str| value.trim().parse::<u64>().map_err(|_| InfoError::parse(what, value));
+
+    // 单位 KB：Node,FreePhysicalMemory,TotalVisibleMemorySize
+    let output = run_command("wmic", &["OS", "get", "TotalVisibleMemorySize,FreePhysicalMemory", "/format:csv"])?;
+    let line = output.lines().map(str::trim).filter(|l| !l.is_empty()).nth(1)
+        .ok_or_else(|| InfoError::parse("physical memory", &output))?;
+    let parts: Vec<&str> = line.split(',').collect();
+    if parts.len() < 3 {
+        return Err(InfoError::parse("physical memory", line));
     }
-
-    let swap_memory_output = Command::new("wmic")
-        .arg("pagefile")
-        .arg("list")
-        .arg("full")
-        .output()
-        .expect("Failed to execute command");
-    let swap_memory_info = String::from_utf8_lossy(&swap_memory_output.stdout);
-    for line in swap_memory_info.lines() {
-        if line.starts_with("AllocatedBaseSize=") {
-            let value = line.split('=').nth(1).unwrap().trim().parse::<u64>().unwrap_or(0);
-            swap_total += value * 1024 * 1024; // 转换为Bytes
-        } else if line.starts_with("CurrentUsage=") {
-            let value = line.split('=').nth(1).unwrap().trim().parse::<u64>().unwrap_or(0);
-            swap_using += value * 1024 * 1024; // 转换为Bytes
+    let memory_free = parse("FreePhysicalMemory", parts[1])? * 1024;
+    let memory_total = parse("TotalVisibleMemorySize", parts[2])? * 1024;
+
+    // 单位 MB
+    let mut swap_total = 0u64;
+    let mut swap_used = 0u64;
+    for line in run_command("wmic", &["pagefile", "list", "full"])?.lines() {
+        if let Some(value) = line.trim().strip_prefix("AllocatedBaseSize=") {
+            swap_total += parse("AllocatedBaseSize", value)? * 1024 * 1024;
+        } else if let Some(value) = line.trim().strip_prefix("CurrentUsage=") {
+            swap_used += parse("CurrentUsage", value)? * 1024 * 1024;
         }
     }
-    swap_free = swap_total - swap_using;
-    (
-        mem_total.to_string(),
-        mem_using.to_string(),
-        mem_free.to_string(),
-        swap_total.to_string(),
-        swap_using.to_string(),
-        swap_free.to_string()
-    )
+
+    Ok(MemInfo {
+        memory_total,
+        memory_used: memory_total.saturating_sub(memory_free),
+        memory_free,
+        swap_total,
+        swap_used,
+        swap_free: swap_total.saturating_sub(swap_used),
+    })
 }
 
 #[cfg(target_os = "linux")]
-fn get_memory_info() -> (String, String, String, String, String, String) {
-    let mut mem_total = 0u128;
-    let mut mem_using = 0u128;
-    let mut mem_free = 0u128;
-    let mut swap_total = 0u128;
-    let mut swap_using = 0u128;
-    let mut swap_free = 0u128;
-    let output = Command::new("free")
-        .arg("--bytes")
-        .output()
-        .expect("Failed to execute free command");
-    let output_str = String::from_utf8_lossy(&output.stdout);
-    let lines: Vec<&str> = output_str.lines().collect();
-    for line in lines {
-        let parts: Vec<&str> = line.trim().split_whitespace().collect();
-        if parts.len() >= 2 {
-            match parts[0] {
-                "Mem:" => {
-                    mem_total = parts[1].parse().unwrap_or(0);
-                    mem_using = parts[2].parse().unwrap_or(0);
-                    mem_free = parts[3].parse().unwrap_or(0);
-                }
-                "Swap:" => {
-                    swap_total = parts[1].parse().unwrap_or(0);
-                    swap_using = parts[2].parse().unwrap_or(0);
-                    swap_free = parts[3].parse().unwrap_or(0);
-                }
-                _ => {}
-            }
-        }
+fn get_memory_info() -> InfoResult<MemInfo> {
+    use std::path::Path;
+    use crate::info::error::read_file;
+    use crate::info::{procfs, virt};
+
+    let mem = procfs::parse_meminfo(&read_file(&Path::new(procfs::PROC_ROOT).join("meminfo"))?);
+
+    let info = MemInfo {
+        memory_total: mem.mem_total,
+        memory_used: mem.mem_used,
+        memory_free: mem.mem_free,
+        swap_total: mem.swap_total,
+        swap_used: mem.swap_used,
+        swap_free: mem.swap_free,
+    };
+    // 容器内 /proc/meminfo 为宿主机内存，改用 cgroup 限制
+    let root = Path::new("/");
+    if virt::detect_container(root).is_some() {
+        return Ok(info.with_cgroup_limits(&virt::read_cgroup_limits(root)));
     }
-    (
-        mem_total.to_string(),
-        mem_using.to_string(),
-        mem_free.to_string(),
-        swap_total.to_string(),
-        swap_using.to_string(),
-        swap_free.to_string()
-    )
+    Ok(info)
 }
 
 #[cfg(target_os = "macos")]
-fn get_memory_info() -> (String, String, String, String, String, String) {
-    let mut mem_total = 0u128;
-    let mut mem_using = 0u128;
-    let mut mem_free = 0u128;
-    let mut swap_total = 0u128;
-    let mut swap_using = 0u128;
-    let mut swap_free = 0u128;
-    // 获取物理内存信息
-    let physical_memory_output = Command::new("sysctl")
-        .arg("hw.memsize")
-        .output()
-        .expect("Failed to execute command");
-    let physical_memory_info = String::from_utf8_lossy(&physical_memory_output.stdout);
-    mem_total = physical_memory_info
-        .split(':')
-        .nth(1)
-        .unwrap()
-        .trim()
-        .parse::<u64>()
-        .unwrap();
-
-    // 获取vm_stat信息
-    let vm_stat_output = Command::new("vm_stat")
-        .output()
-        .expect("Failed to execute command");
-    let vm_stat_info = String::from_utf8_lossy(&vm_stat_output.stdout);
-
-    let mut page_size = 0u128;
-    let mut free_pages = 0u128;
-    let mut active_pages = 0u128;
-    let mut speculative_pages = 0u128;
-    let mut inactive_pages = 0u128;
-    let mut wired_pages = 0u128;
-
-    for line in vm_stat_info.lines() {
-        if line.starts_with("Mach Virtual Memory Statistics") {
-            continue;
-        }
-        let parts: Vec<&str> = line.split(':').collect();
-        let key = parts[0].trim();
-        let value = parts[1].trim().trim_end_matches('.').replace(".", "").parse::<u64>().unwrap();
-        match key {
-            "Pages free" => free_pages = value,
-            "Pages active" => active_pages = value,
-            "Pages speculative" => speculative_pages = value,
-            "Pages inactive" => inactive_pages = value,
-            "Pages wired down" => wired_pages = value,
-            "Page size of" => page_size = value,
-            _ => {},
+fn get_memory_info() -> InfoResult<MemInfo> {
+    use crate::info::error::{run_command, InfoError};
+
+    let output = run_command("sysctl", &["-n", "hw.memsize"])?;
+    let memory_total = output.trim().parse::<u64>().map_err(|_| InfoError::parse("hw.memsize", &output))?;
+
+    // 首行形如 "Mach Virtual Memory Statistics: (page size of 16384 bytes)"，其余为 "Pages free: 12345."
+    let vm_stat = run_command("vm_stat", &[])?;
+    let mut page_size = 4096u64;
+    let mut pages = std::collections::HashMap::new();
+    for line in vm_stat.lines() {
+        if let Some(rest) = line.split("page size of").nth(1) {
+            let size = rest.split_whitespace().next().unwrap_or_default();
+            page_size = size.parse().map_err(|_| InfoError::parse("vm_stat page size", line))?;
+        } else if let Some((key, value)) = line.split_once(':') {
+            if let Ok(count) = value.trim().trim_end_matches('.').parse::<u64>() {
+                pages.insert(key.trim().to_string(), count);
+            }
         }
     }
-
-    mem_free = free_pages * page_size;
-    mem_using = (active_pages + speculative_pages + inactive_pages + wired_pages) * page_size;
-
-    // 获取交换空间信息
-    let swap_info_output = Command::new("sysctl")
-        .arg("vm.swapusage")
-        .output()
-        .expect("Failed to execute command");
-
-    let swap_info = String::from_utf8_lossy(&swap_info_output.stdout);
-    let swap_parts: Vec<&str> = swap_info.split(',').collect();
-    swap_total = swap_parts[0].split_whitespace().nth(2).unwrap().parse::<u64>().unwrap() * 1024 * 1024;
-    swap_using = swap_parts[1].split_whitespace().nth(2).unwrap().parse::<u64>().unwrap() * 1024 * 1024;
-    swap_free = swap_parts[2].split_whitespace().nth(2).unwrap().parse::<u64>().unwrap() * 1024 * 1024;
-    (
-        mem_total.to_string(),
-        mem_using.to_string(),
-        mem_free.to_string(),
-        swap_total.to_string(),
-        swap_using.to_string(),
-        swap_free.to_string()
-    )
+    let page_count = |key: &str| pages.get(key).copied().unwrap_or(0);
+    let memory_free = (page_count("Pages free") + page_count("Pages speculative")) * page_size;
+    let memory_used = (page_count("Pages active") + page_count("Pages wired down")
+        + page_count("Pages occupied by compressor")) * page_size;
+
+    // 形如 "total = 2048.00M  used = 1024.50M  free = 1023.50M  (encrypted)"
+    let swap_usage = run_command("sysctl", &["-n", "vm.swapusage"])?;
+    let swap_value = |name: &str| -> InfoResult<u64> {
+        let value = swap_usage.split(name).nth(1)
+            .and_then(|rest| rest.trim_start_matches([' ', '=']).split_whitespace().next())
+            .ok_or_else(|| InfoError::parse(name, &swap_usage))?;
+        let megabytes = value.trim_end_matches('M').parse::<f64>().map_err(|_| InfoError::parse(name, value))?;
+        Ok((megabytes * 1024.0 * 1024.0) as u64)
+    };
+
+    Ok(MemInfo {
+        memory_total,
+        memory_used,
+        memory_free,
+        swap_total: swap_value("total")?,
+        swap_used: swap_value("used")?,
+        swap_free: swap_value("free")?,
+    })
 }
 
 #[cfg(test)]
@@ -271,54 +219,78 @@ mod unit_test_memory {
         let json_output = mem_info.to_json();
         assert!(json_output.contains("{")
             && json_output.contains("memory_total")
-            && json_output.contains("memory_using")
+            && json_output.contains("memory_used")
             && json_output.contains("memory_free")
             && json_output.contains("swap_total")
-            && json_output.contains("swap_using")
+            && json_output.contains("swap_used")
             && json_output.contains("swap_free"),
             "memory info json some field missing"
-        )
+        );
+        let parsed: MemInfo = serde_json::from_str(&json_output).unwrap();
+        assert_eq!(parsed, mem_info);
     }
 
     #[test]
     fn test_memory_info_03() {
-        let mem_info = MemInfo::new();
+        let mem_info = MemInfo::collect().unwrap();
         println!("calling fn memory_total");
-        assert!(mem_info.memory_total().ne("0 bytes"), "physical memory total is 0");
+        assert!(mem_info.memory_total() > 0, "physical memory total is 0");
     }
 
     #[test]
     fn test_memory_info_04() {
         let mem_info = MemInfo::new();
-        println!("calling fn memory_using");
-        assert!(mem_info.memory_using().ne("0 bytes"), "physical memory using is 0");
+        println!("calling fn memory_used");
+        assert!(mem_info.memory_used() > 0, "physical memory used is 0");
     }
 
     #[test]
     fn test_memory_info_05() {
         let mem_info = MemInfo::new();
         println!("calling fn memory_free");
-        assert!(mem_info.memory_free().ne("0 bytes"), "physical memory free is 0");
+        assert!(mem_info.memory_free() > 0, "physical memory free is 0");
     }
 
     #[test]
     fn test_memory_info_06() {
         let mem_info = MemInfo::new();
         println!("calling fn swap_total");
-        assert!(mem_info.swap_total().len() > 0, "swap memory total empty");
+        assert_eq!(mem_info.swap_total(), mem_info.swap_used() + mem_info.swap_free(), "swap memory total mismatch");
     }
 
     #[test]
     fn test_memory_info_07() {
         let mem_info = MemInfo::new();
-        println!("calling fn swap_using");
-        assert!(mem_info.swap_using().len() > 0, "swap memory using empty");
+        println!("calling fn swap_used");
+        assert!(mem_info.swap_used() <= mem_info.swap_total(), "swap memory used exceeds total");
     }
 
     #[test]
     fn test_memory_info_08() {
         let mem_info = MemInfo::new();
         println!("calling fn swap_free");
-        assert!(mem_info.swap_free().len() > 0, "swap memory free empty");
+        assert!(mem_info.swap_free() <= mem_info.swap_total(), "swap memory free exceeds total");
+    }
+
+    #[test]
+    fn test_memory_info_09() {
+        let host = MemInfo {
+            memory_total: 8 << 30,
+            memory_used: 6 << 30,
+            memory_free: 2 << 30,
+            swap_total: 1 << 30,
+            swap_used: 0,
+            swap_free: 1 << 30,
+        };
+        let limits = CgroupLimits { version: 2, memory_limit: Some(512 << 20), memory_usage: Some(100 << 20), ..Default::default() };
+        let container = host.with_cgroup_limits(&limits);
+        assert_eq!(container.memory_total(), 512 << 20);
+        assert_eq!(container.memory_used(), 100 << 20);
+        assert_eq!(container.memory_free(), 412 << 20);
+        assert_eq!(container.swap_total(), 1 << 30);
+
+        // 未限制或限制大于主机内存时不变
+        assert_eq!(host.with_cgroup_limits(&CgroupLimits::default()), host);
+        assert_eq!(host.with_cgroup_limits(&CgroupLimits { memory_limit: Some(16 << 30), ..Default::default() }), host);
     }
 }
@@ -1,9 +1,21 @@
+pub mod account;
+pub mod control;
 pub mod cpu;
 pub mod disk;
+pub mod error;
+pub mod hardware;
 pub mod hostinfo;
 pub mod hostname;
 pub mod memory;
 pub mod network;
 pub mod os;
+pub mod package;
 pub mod process;
-pub mod service;
\ No newline at end of file
+#[cfg(target_os = "linux")]
+pub mod procfs;
+pub mod report;
+pub mod sampler;
+pub mod service;
+pub mod socket;
+pub mod systemd;
+pub mod virt;
@@ -1,18 +1,46 @@
-use std::net::IpAddr;
+use std::fs;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::path::Path;
 use std::str;
 
 use colored::Colorize;
 use pnet::datalink;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_json;
 
+use crate::common::api::format_size;
+#[cfg(not(target_os = "linux"))]
+use crate::info::error::InfoError;
+use crate::info::error::InfoResult;
+use crate::info::sampler::NicCounter;
+
+// 路由标志，见 linux/route.h
+const RTF_UP: u32 = 0x0001;
+const RTF_REJECT: u32 = 0x0200;
+
+// 链路信息，目前仅 Linux 采集
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinkInfo {
+    pub mtu: Option<u32>,
+    pub speed_mbps: Option<u32>,             // 无线、虚拟网卡或未连接时为 None
+    pub duplex: String,                      // full、half，未知时为空
+    pub driver: String,
+    pub kind: String,                        // bond、bridge、vlan、wlan 等，普通以太网卡为空
+    pub master: Option<String>,              // 所属 bond 或网桥
+    pub members: Vec<String>,                // bond 的从属网卡或网桥端口
+    pub vlan_id: Option<u16>,
+    pub vlan_parent: Option<String>,
+    pub counters: Option<NicCounter>,        // 来自 /sys/class/net/<nic>/statistics
+}
+
 #[derive(Debug, Serialize)]
 pub struct NetworkInterface {
     name: String,
     mac: String,
     status: String,
-    ipv4: Vec<String>,
-    ipv6: Vec<String>,
+    ipv4: Vec<String>,                       // CIDR，如 10.0.0.2/24
+    ipv6: Vec<String>,                       // CIDR，如 fe80::1/64
+    link: LinkInfo,
 }
 
 impl NetworkInterface {
@@ -23,6 +51,7 @@ impl NetworkInterface {
             status: status.to_string(),
             ipv4: ipv4.to_vec(),
             ipv6: ipv6.to_vec(),
+            link: LinkInfo::default(),
         }
     }
 
@@ -46,6 +75,10 @@ impl NetworkInterface {
         &self.ipv6
     }
 
+    pub fn link(&self) -> &LinkInfo {
+        &self.link
+    }
+
     pub fn display(&self) -> String {
         let mut ipv4 = "".to_string();
         for n in self.ipv4.iter().map(|n| n.green().to_string()).collect::<Vec<String>>() {
@@ -55,7 +88,7 @@ impl NetworkInterface {
         for n in self.ipv6.iter().map(|n| n.green().to_string()).collect::<Vec<String>>() {
             ipv6.push_str(&format!(" {}", n));
         }
-        let output;
+        let mut output;
         if self.status.eq("UP") {
             output = format!("NIC {} status {}\n  MAC: {}\n  IPv4: {}\n  IPv6: {}",
                 &self.name.green().to_string(),
@@ -73,6 +106,34 @@ impl NetworkInterface {
                 &ipv6
             );
         }
+        let link = &self.link;
+        let mut details = Vec::new();
+        if let Some(mtu) = link.mtu {
+            details.push(format!("mtu {}", mtu));
+        }
+        if let Some(speed) = link.speed_mbps {
+            details.push(format!("speed {}Mb/s {}", speed, link.duplex).trim().to_string());
+        }
+        if !link.driver.is_empty() {
+            details.push(format!("driver {}", link.driver));
+        }
+        if !link.kind.is_empty() {
+            details.push(format!("{} {}", link.kind, link.members.join(",")).trim().to_string());
+        }
+        if let (Some(id), Some(parent)) = (link.vlan_id, &link.vlan_parent) {
+            details.push(format!("vlan {} on {}", id, parent));
+        }
+        if let Some(master) = &link.master {
+            details.push(format!("master {}", master));
+        }
+        if !details.is_empty() {
+            output.push_str(&format!("\n  Link: {}", details.join(", ")));
+        }
+        if let Some(counters) = &link.counters {
+            output.push_str(&format!("\n  RX: {} {} packets {} errors {} dropped\n  TX: {} {} packets {} errors {} dropped",
+                format_size(counters.rx_bytes), counters.rx_packets, counters.rx_errors, counters.rx_drops,
+                format_size(counters.tx_bytes), counters.tx_packets, counters.tx_errors, counters.tx_drops));
+        }
         println!("{}\n", output);
         output
     }
@@ -97,21 +158,313 @@ pub fn get_nics() -> (Vec<NetworkInterface>, Vec<NetworkInterface>) {
         let mut ipv6_addrs = Vec::new();
 
         for ip in interface.ips {
+            let cidr = format!("{}/{}", ip.ip(), ip.prefix());
             match ip.ip() {
-                IpAddr::V4(ipv4) => ipv4_addrs.push(ipv4.to_string()),
-                IpAddr::V6(ipv6) => ipv6_addrs.push(ipv6.to_string()),
+                IpAddr::V4(_) => ipv4_addrs.push(cidr),
+                IpAddr::V6(_) => ipv6_addrs.push(cidr),
             }
         }
 
-        interfaces_info.push(NetworkInterface::new(&name, &mac, &status, &ipv4_addrs, &ipv6_addrs));
+        let mut nic = NetworkInterface::new(&name, &mac, &status, &ipv4_addrs, &ipv6_addrs);
+        nic.link = get_link_info(&name);
         if status == "UP".to_string() && (ipv4_addrs.len() != 0 || ipv6_addrs.len() != 0) {
-            active_interfaces_info.push(NetworkInterface::new(&name, &mac, &status, &ipv4_addrs, &ipv6_addrs));
+            let mut active_nic = NetworkInterface::new(&name, &mac, &status, &ipv4_addrs, &ipv6_addrs);
+            active_nic.link = nic.link.clone();
+            active_interfaces_info.push(active_nic);
         }
+        interfaces_info.push(nic);
     }
 
     (interfaces_info, active_interfaces_info)
 }
 
+#[cfg(target_os = "linux")]
+fn get_link_info(name: &str) -> LinkInfo {
+    read_link_info(Path::new("/"), name)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_link_info(_name: &str) -> LinkInfo {
+    LinkInfo::default()
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    fs::read_to_string(path).ok().map(|s| s.trim().to_string()).filter(|s| !s.is_empty())
+}
+
+// 符号链接目标的文件名，如 device/driver -> ../../bus/pci/drivers/e1000e 为 e1000e
+fn link_name(path: &Path) -> Option<String> {
+    fs::read_link(path).ok()?.file_name().map(|name| name.to_string_lossy().into_owned())
+}
+
+/// 以 root 为根目录读取网卡链路信息：/sys/class/net/<nic> 下的属性及 /proc/net/vlan/config
+pub fn read_link_info(root: &Path, name: &str) -> LinkInfo {
+    let dir = root.join("sys/class/net").join(name);
+    let number = |attr: &str| read_trimmed(&dir.join(attr)).and_then(|value| value.parse::<i64>().ok());
+    let kind = read_trimmed(&dir.join("uevent"))
+        .and_then(|uevent| uevent.lines().find_map(|line| line.strip_prefix("DEVTYPE=")).map(str::to_string))
+        .unwrap_or_default();
+    let mut members: Vec<String> = match kind.as_str() {
+        "bond" => read_trimmed(&dir.join("bonding/slaves")).map_or(Vec::new(), |slaves| {
+            slaves.split_whitespace().map(str::to_string).collect()
+        }),
+        "bridge" => fs::read_dir(dir.join("brif")).map_or(Vec::new(), |entries| {
+            entries.flatten().map(|entry| entry.file_name().to_string_lossy().into_owned()).collect()
+        }),
+        _ => Vec::new(),
+    };
+    members.sort();
+    // 每行形如 "eth0.100       | 100  | eth0"
+    let vlan = read_trimmed(&root.join("proc/net/vlan/config")).and_then(|config| config.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split('|').map(str::trim).collect();
+        (fields.len() == 3 && fields[0] == name).then(|| (fields[1].parse::<u16>().ok(), fields[2].to_string()))
+    }));
+    let statistics = dir.join("statistics");
+    let counters = statistics.is_dir().then(|| {
+        let counter = |name: &str| read_trimmed(&statistics.join(name)).and_then(|v| v.parse().ok()).unwrap_or(0);
+        NicCounter {
+            name: name.to_string(),
+            rx_bytes: counter("rx_bytes"),
+            rx_packets: counter("rx_packets"),
+            rx_errors: counter("rx_errors"),
+            rx_drops: counter("rx_dropped"),
+            tx_bytes: counter("tx_bytes"),
+            tx_packets: counter("tx_packets"),
+            tx_errors: counter("tx_errors"),
+            tx_drops: counter("tx_dropped"),
+        }
+    });
+    LinkInfo {
+        mtu: number("mtu").and_then(|mtu| u32::try_from(mtu).ok()),
+        // 未连接时 speed 为 -1 或读取报错
+        speed_mbps: number("speed").filter(|speed| *speed > 0).and_then(|speed| u32::try_from(speed).ok()),
+        duplex: read_trimmed(&dir.join("duplex")).filter(|duplex| duplex != "unknown").unwrap_or_default(),
+        driver: link_name(&dir.join("device/driver")).unwrap_or_default(),
+        kind,
+        master: link_name(&dir.join("master")),
+        members,
+        vlan_id: vlan.as_ref().and_then(|(id, _)| *id),
+        vlan_parent: vlan.map(|(_, parent)| parent),
+        counters,
+    }
+}
+
+// 路由
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Route {
+    pub destination: String,                 // CIDR，默认路由为 0.0.0.0/0 或 ::/0
+    pub gateway: Option<String>,             // 直连路由为 None
+    pub interface: String,
+    pub metric: u32,
+}
+
+impl Route {
+    pub fn is_default(&self) -> bool {
+        self.destination == "0.0.0.0/0" || self.destination == "::/0"
+    }
+}
+
+/// 解析 /proc/net/route，地址为小端序十六进制
+pub fn parse_route(content: &str) -> Vec<Route> {
+    let ipv4 = |hex: &str| u32::from_str_radix(hex, 16).ok().map(|value| Ipv4Addr::from(value.to_le_bytes()));
+    content.lines().skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 8 {
+                return None;
+            }
+            let flags = u32::from_str_radix(fields[3], 16).ok()?;
+            if flags & RTF_UP == 0 || flags & RTF_REJECT != 0 {
+                return None;
+            }
+            let gateway = ipv4(fields[2])?;
+            let prefix = u32::from_str_radix(fields[7], 16).ok()?.count_ones();
+            Some(Route {
+                destination: format!("{}/{}", ipv4(fields[1])?, prefix),
+                gateway: (!gateway.is_unspecified()).then(|| gateway.to_string()),
+                interface: fields[0].to_string(),
+                metric: fields[6].parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+/// 解析 /proc/net/ipv6_route，跳过回环网卡上的本机地址路由
+pub fn parse_ipv6_route(content: &str) -> Vec<Route> {
+    let ipv6 = |hex: &str| u128::from_str_radix(hex, 16).ok().map(Ipv6Addr::from);
+    content.lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 10 || fields[9] == "lo" {
+                return None;
+            }
+            let flags = u32::from_str_radix(fields[8], 16).ok()?;
+            if flags & RTF_UP == 0 || flags & RTF_REJECT != 0 {
+                return None;
+            }
+            let gateway = ipv6(fields[4])?;
+            Some(Route {
+                destination: format!("{}/{}", ipv6(fields[0])?, u8::from_str_radix(fields[1], 16).ok()?),
+                gateway: (!gateway.is_unspecified()).then(|| gateway.to_string()),
+                interface: fields[9].to_string(),
+                metric: u32::from_str_radix(fields[5], 16).ok()?,
+            })
+        })
+        .collect()
+}
+
+// DNS 配置
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DnsConfig {
+    pub nameservers: Vec<String>,
+    pub search: Vec<String>,                 // 搜索域，无 search 时为 domain
+}
+
+/// 解析 resolv.conf，search 和 domain 以最后出现的为准
+pub fn parse_resolv_conf(content: &str) -> DnsConfig {
+    let mut dns = DnsConfig::default();
+    for line in content.lines() {
+        let mut fields = line.split_whitespace();
+        match fields.next() {
+            Some("nameserver") => dns.nameservers.extend(fields.next().map(str::to_string)),
+            Some("search") | Some("domain") => dns.search = fields.map(str::to_string).collect(),
+            _ => {}
+        }
+    }
+    dns
+}
+
+// 邻居表项，IPv4 为 ARP，IPv6 为 NDP
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Neighbor {
+    pub ip: String,
+    pub mac: String,                         // 未解析时为空
+    pub interface: String,
+    pub state: String,                       // 与 ip neigh 一致，如 REACHABLE、STALE、PERMANENT、INCOMPLETE
+}
+
+/// 解析 /proc/net/arp
+pub fn parse_arp(content: &str) -> Vec<Neighbor> {
+    content.lines().skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 6 {
+                return None;
+            }
+            // ATF_COM 0x2 已解析，ATF_PERM 0x4 静态
+            let flags = u32::from_str_radix(fields[2].trim_start_matches("0x"), 16).ok()?;
+            let state = if flags & 0x4 != 0 { "PERMANENT" } else if flags & 0x2 != 0 { "REACHABLE" } else { "INCOMPLETE" };
+            Some(Neighbor {
+                ip: fields[0].to_string(),
+                mac: if flags & 0x2 != 0 { fields[3].to_string() } else { String::new() },
+                interface: fields[5].to_string(),
+                state: state.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// 解析 ip neigh show 输出，如 "fe80::1 dev eth0 lladdr 52:54:00:12:34:56 router REACHABLE"
+pub fn parse_ip_neigh(output: &str) -> Vec<Neighbor> {
+    output.lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let value = |key: &str| fields.iter().position(|field| *field == key).and_then(|index| fields.get(index + 1));
+            Some(Neighbor {
+                ip: fields.first()?.to_string(),
+                mac: value("lladdr").map_or(String::new(), |mac| mac.to_string()),
+                interface: value("dev")?.to_string(),
+                state: fields.last()?.to_string(),
+            })
+        })
+        .collect()
+}
+
+// 主机网络配置：路由、DNS 和邻居表
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetworkFacts {
+    pub routes: Vec<Route>,
+    pub dns: DnsConfig,
+    pub neighbors: Vec<Neighbor>,
+}
+
+impl NetworkFacts {
+    /// 创建新的 NetworkFacts 实例，采集失败时为空
+    pub fn new() -> Self {
+        Self::collect().unwrap_or_else(|e| {
+            log::warn!("failed to collect network facts: {}", e);
+            NetworkFacts::default()
+        })
+    }
+
+    /// 采集路由、DNS 和邻居表，IPv6 邻居通过 ip -6 neigh 查询，未安装 iproute2 时只有 ARP 表
+    #[cfg(target_os = "linux")]
+    pub fn collect() -> InfoResult<Self> {
+        use crate::info::error::run_command;
+
+        let mut facts = Self::collect_from(Path::new("/"));
+        match run_command("ip", &["-6", "neigh", "show"]) {
+            Ok(output) => facts.neighbors.extend(parse_ip_neigh(&output)),
+            Err(e) => log::debug!("failed to query IPv6 neighbors: {}", e),
+        }
+        Ok(facts)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn collect() -> InfoResult<Self> {
+        Err(InfoError::Unsupported("network facts".to_string()))
+    }
+
+    /// 以 root 为根目录读取 proc/net 和 resolv.conf
+    pub fn collect_from(root: &Path) -> Self {
+        let read = |path: &str| fs::read_to_string(root.join(path)).unwrap_or_default();
+        let mut routes = parse_route(&read("proc/net/route"));
+        routes.extend(parse_ipv6_route(&read("proc/net/ipv6_route")));
+        routes.sort_by_key(|route| (!route.is_default(), route.metric));
+        // systemd-resolved 的本地存根只有 127.0.0.53，实际上游服务器在 /run/systemd/resolve/resolv.conf
+        let mut dns = parse_resolv_conf(&read("etc/resolv.conf"));
+        if dns.nameservers == ["127.0.0.53"] {
+            let upstream = parse_resolv_conf(&read("run/systemd/resolve/resolv.conf"));
+            if !upstream.nameservers.is_empty() {
+                dns = upstream;
+            }
+        }
+        NetworkFacts {
+            routes,
+            dns,
+            neighbors: parse_arp(&read("proc/net/arp")),
+        }
+    }
+
+    /// 默认网关
+    pub fn default_gateways(&self) -> Vec<&Route> {
+        self.routes.iter().filter(|route| route.is_default()).collect()
+    }
+
+    pub fn display(&self) -> String {
+        let mut output = String::from("Routes:");
+        for route in &self.routes {
+            output.push_str(&format!("\n  {} via {} dev {} metric {}", route.destination.green(),
+                                     route.gateway.as_deref().unwrap_or("-"), route.interface, route.metric));
+        }
+        output.push_str(&format!("\nDNS:\n  Nameservers: {}\n  Search: {}", self.dns.nameservers.join(" ").green(), self.dns.search.join(" ")));
+        output.push_str("\nNeighbors:");
+        for neighbor in &self.neighbors {
+            output.push_str(&format!("\n  {} {} dev {} {}", neighbor.ip, neighbor.mac, neighbor.interface, neighbor.state));
+        }
+        println!("{}\n", output);
+        output
+    }
+
+    /// 转换为 JSON 字符串
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|err| {
+            eprintln!("序列化网络配置到 JSON 失败: {}", err);
+            "{}".to_string()
+        })
+    }
+}
+
 
 #[cfg(test)]
 mod unit_test_network {
@@ -221,4 +574,47 @@ mod unit_test_network {
         let output = net_info.ipv6();
         assert!(output.join(" ").contains("::"), "failed since nic ipv6 mismatch");
     }
+
+    #[test]
+    fn test_network_info_08() {
+        let facts = NetworkFacts::collect_from(&std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/procfs"));
+        let routes: Vec<(&str, Option<&str>, &str, u32)> = facts.routes.iter()
+            .map(|r| (r.destination.as_str(), r.gateway.as_deref(), r.interface.as_str(), r.metric)).collect();
+        assert_eq!(routes, [
+            ("0.0.0.0/0", Some("10.0.0.1"), "eth0", 100),
+            ("::/0", Some("fe80::1"), "eth0", 1024),
+            ("172.17.0.0/16", None, "docker0", 0),
+            ("10.0.0.0/24", None, "eth0", 100),
+            ("fe80::/64", None, "eth0", 256),
+        ]);
+        assert_eq!(facts.default_gateways().len(), 2);
+        assert_eq!(facts.dns.nameservers, ["10.0.0.1", "2001:db8::53"]);
+        assert_eq!(facts.dns.search, ["corp.example.com", "example.com"]);
+        let neighbors: Vec<(&str, &str, &str)> = facts.neighbors.iter().map(|n| (n.ip.as_str(), n.mac.as_str(), n.state.as_str())).collect();
+        assert_eq!(neighbors, [
+            ("10.0.0.1", "52:54:00:12:34:56", "REACHABLE"),
+            ("10.0.0.9", "", "INCOMPLETE"),
+            ("10.0.0.254", "52:54:00:ab:cd:ef", "PERMANENT"),
+        ]);
+        let ndp = parse_ip_neigh("fe80::1 dev eth0 lladdr 52:54:00:12:34:56 router REACHABLE\nfe80::2 dev eth0 FAILED\n");
+        assert_eq!((ndp[0].mac.as_str(), ndp[1].mac.as_str(), ndp[1].state.as_str()), ("52:54:00:12:34:56", "", "FAILED"));
+    }
+
+    #[test]
+    fn test_network_info_09() {
+        let root = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/procfs");
+        let eth0 = read_link_info(&root, "eth0");
+        assert_eq!((eth0.mtu, eth0.speed_mbps, eth0.duplex.as_str(), eth0.driver.as_str()), (Some(1500), Some(1000), "full", "e1000e"));
+        assert_eq!(eth0.master.as_deref(), Some("bond0"));
+        assert_eq!(eth0.counters.as_ref().map(|c| (c.rx_bytes, c.tx_drops)), Some((1000000, 4)));
+        let bond = read_link_info(&root, "bond0");
+        assert_eq!((bond.kind.as_str(), bond.members.clone()), ("bond", vec!["eth0".to_string()]));
+        let vlan = read_link_info(&root, "eth0.100");
+        assert_eq!((vlan.kind.as_str(), vlan.vlan_id, vlan.vlan_parent.as_deref(), vlan.speed_mbps), ("vlan", Some(100), Some("eth0"), None));
+
+        let mut nic = NetworkInterface::new("eth0", "52:54:00:00:00:01", "UP", &vec!["10.0.0.2/24".to_string()], &vec![]);
+        nic.link = eth0;
+        let output = nic.display();
+        assert!(output.contains("10.0.0.2/24") && output.contains("speed 1000Mb/s full") && output.contains("master bond0"));
+    }
 }
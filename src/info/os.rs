@@ -1,21 +1,180 @@
+use std::collections::BTreeMap;
 use std::env;
 use std::fs;
+use std::path::Path;
 use std::str;
 
+use chrono::{DateTime, Local, Utc};
 use colored::Colorize;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_json;
 
+use crate::common::api::{format_duration, format_size};
+use crate::info::cpu::CpuInfo;
+use crate::info::virt::Virtualization;
+
+// 默认查询的 sysctl 参数，多为安全基线检查项
+pub const DEFAULT_SYSCTL_KEYS: [&str; 16] = [
+    "kernel.randomize_va_space", "kernel.kptr_restrict", "kernel.dmesg_restrict", "kernel.yama.ptrace_scope",
+    "fs.protected_symlinks", "fs.protected_hardlinks", "fs.suid_dumpable", "fs.file-max",
+    "net.ipv4.ip_forward", "net.ipv4.tcp_syncookies", "net.ipv4.conf.all.rp_filter", "net.ipv4.conf.all.accept_redirects",
+    "net.ipv6.conf.all.disable_ipv6", "net.core.somaxconn", "vm.swappiness", "vm.overcommit_memory",
+];
+
+// 已加载的内核模块
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KernelModule {
+    pub name: String,
+    pub size: u64,                           // 字节
+    pub used_by: Vec<String>,
+}
+
+// 内核、启动及系统安全配置
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KernelInfo {
+    pub release: String,                     // 同 uname -r
+    pub cmdline: String,                     // 内核启动参数
+    pub boot_time: Option<DateTime<Utc>>,
+    pub uptime_secs: u64,
+    pub modules: Vec<KernelModule>,
+    pub sysctl: BTreeMap<String, String>,    // 不存在的参数不列出
+    pub selinux: String,                     // enforcing、permissive、disabled
+    pub apparmor: String,                    // enabled、disabled
+    pub time_synchronized: Option<bool>,     // 系统时钟是否已由 NTP 同步，无法获取时为 None
+    pub timezone: String,                    // 如 Asia/Shanghai
+}
+
+impl KernelInfo {
+    pub fn display(&self) -> String {
+        let mut output = format!("Kernel:\n  Release: {}\n  Cmdline: {}\n  Boot: {} (up {})\n  SELinux: {}\n  AppArmor: {}\n  Time zone: {}\n  Time synchronized: {}",
+            self.release.green(),
+            self.cmdline,
+            self.boot_time.map_or("N/A".to_string(), |time| time.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S").to_string()),
+            format_duration(self.uptime_secs),
+            self.selinux,
+            self.apparmor,
+            self.timezone,
+            self.time_synchronized.map_or("N/A".to_string(), |synced| if synced { "yes".green().to_string() } else { "no".red().to_string() }),
+        );
+        for (key, value) in &self.sysctl {
+            output.push_str(&format!("\n  {} = {}", key, value.yellow()));
+        }
+        output.push_str(&format!("\n  Modules ({}): {}", self.modules.len(),
+                                 self.modules.iter().map(|m| m.name.as_str()).collect::<Vec<_>>().join(" ")));
+        println!("{}\n", output);
+        output
+    }
+}
+
+/// 解析 /proc/modules，每行形如 "nf_nat 49152 2 xt_MASQUERADE,nft_chain_nat, Live 0x0"
+pub fn parse_modules(content: &str) -> Vec<KernelModule> {
+    content.lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            Some(KernelModule {
+                name: fields.first()?.to_string(),
+                size: fields.get(1)?.parse().ok()?,
+                used_by: fields.get(3).map_or(Vec::new(), |used| {
+                    used.split(',').filter(|m| !m.is_empty() && *m != "-").map(str::to_string).collect()
+                }),
+            })
+        })
+        .collect()
+}
+
+/// 读取 sysctl 参数，如 net.ipv4.ip_forward 对应 proc/sys/net/ipv4/ip_forward，多个值以空格分隔
+pub fn read_sysctl(root: &Path, keys: &[String]) -> BTreeMap<String, String> {
+    keys.iter()
+        .filter_map(|key| {
+            let value = fs::read_to_string(root.join("proc/sys").join(key.replace('.', "/"))).ok()?;
+            Some((key.clone(), value.split_whitespace().collect::<Vec<_>>().join(" ")))
+        })
+        .collect()
+}
+
+/// 以 root 为根目录读取内核信息，不含需要系统调用的时钟同步状态
+pub fn read_kernel_info(root: &Path, sysctl_keys: &[String]) -> KernelInfo {
+    let read = |path: &str| fs::read_to_string(root.join(path)).map(|s| s.trim().to_string()).unwrap_or_default();
+    let boot_time = read("proc/stat").lines()
+        .find_map(|line| line.strip_prefix("btime "))
+        .and_then(|btime| btime.trim().parse::<i64>().ok())
+        .and_then(|btime| DateTime::from_timestamp(btime, 0));
+    let uptime_secs = read("proc/uptime").split_whitespace().next()
+        .and_then(|uptime| uptime.parse::<f64>().ok())
+        .map_or(0, |uptime| uptime as u64);
+    let selinux = match read("sys/fs/selinux/enforce").as_str() {
+        "1" => "enforcing",
+        "0" => "permissive",
+        _ => "disabled",
+    };
+    let apparmor = if read("sys/module/apparmor/parameters/enabled") == "Y" { "enabled" } else { "disabled" };
+    // /etc/timezone 仅 Debian 系有，其余取 /etc/localtime 链接目标中 zoneinfo 之后的部分
+    let mut timezone = read("etc/timezone");
+    if timezone.is_empty() {
+        timezone = fs::read_link(root.join("etc/localtime")).ok()
+            .and_then(|target| target.to_string_lossy().split_once("zoneinfo/").map(|(_, zone)| zone.to_string()))
+            .unwrap_or_default();
+    }
+    KernelInfo {
+        release: read("proc/sys/kernel/osrelease"),
+        cmdline: read("proc/cmdline"),
+        boot_time,
+        uptime_secs,
+        modules: parse_modules(&read("proc/modules")),
+        sysctl: read_sysctl(root, sysctl_keys),
+        selinux: selinux.to_string(),
+        apparmor: apparmor.to_string(),
+        time_synchronized: None,
+        timezone,
+    }
+}
+
+/// 系统时钟是否已同步，与 timedatectl 的 NTPSynchronized 一致：adjtimex 返回 TIME_ERROR 表示未同步
+#[cfg(target_os = "linux")]
+pub fn time_synchronized() -> Option<bool> {
+    let mut timex: libc::timex = unsafe { std::mem::zeroed() };
+    // SAFETY: modes 为 0 时只读取内核时钟状态，timex 为可写结构
+    let state = unsafe { libc::adjtimex(&mut timex) };
+    (state >= 0).then_some(state != libc::TIME_ERROR)
+}
+
+#[cfg(target_os = "linux")]
+fn get_kernel_info(sysctl_keys: &[String]) -> KernelInfo {
+    KernelInfo {
+        time_synchronized: time_synchronized(),
+        ..read_kernel_info(Path::new("/"), sysctl_keys)
+    }
+}
+
+// 其他平台暂只采集内核版本
+#[cfg(not(target_os = "linux"))]
+fn get_kernel_info(_sysctl_keys: &[String]) -> KernelInfo {
+    use crate::info::error::run_command;
+
+    KernelInfo {
+        release: run_command("uname", &["-r"]).map(|release| release.trim().to_string()).unwrap_or_default(),
+        ..Default::default()
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct OSInfo {
     os_type: String,
     os_name: String,
     os_version: String,
     os_arch: String,
+    virtualization: Virtualization,
+    kernel: KernelInfo,
 }
 
 impl OSInfo {
     pub fn new() -> OSInfo {
+        let keys: Vec<String> = DEFAULT_SYSCTL_KEYS.iter().map(|key| key.to_string()).collect();
+        Self::with_sysctl_keys(&keys)
+    }
+
+    /// 指定查询的 sysctl 参数
+    pub fn with_sysctl_keys(sysctl_keys: &[String]) -> OSInfo {
         // Get OS type
         let os_type = get_os_type();
 
@@ -30,6 +189,8 @@ impl OSInfo {
             os_name,
             os_version,
             os_arch,
+            virtualization: Virtualization::detect(),
+            kernel: get_kernel_info(sysctl_keys),
         }
     }
 
@@ -49,13 +210,37 @@ impl OSInfo {
         &self.os_version
     }
 
+    pub fn virtualization(&self) -> &Virtualization {
+        &self.virtualization
+    }
+
+    pub fn kernel(&self) -> &KernelInfo {
+        &self.kernel
+    }
+
     pub fn display(&self) -> String {
-        let output = format!("OS:\n  Type: {}\n  Name: {}\n  Version: {}\n  Arch: {}",
+        let mut output = format!("OS:\n  Type: {}\n  Name: {}\n  Version: {}\n  Arch: {}\n  Kernel: {}\n  Uptime: {}\n  Virtualization: {}",
             &self.os_type.green().to_string(),
             &self.os_name.green().to_string(),
             &self.os_version.green().to_string(),
-            &self.os_arch.green().to_string()
+            &self.os_arch.green().to_string(),
+            &self.kernel.release.green().to_string(),
+            format_duration(self.kernel.uptime_secs),
+            &self.virtualization.summary().green().to_string()
         );
+        // 容器内的 cgroup 限制即实际可用的 CPU 和内存
+        let cgroup = &self.virtualization.cgroup;
+        if self.virtualization.is_container() {
+            let cpus = if cgroup.cpu_quota.is_none() && cgroup.cpuset.is_none() {
+                "unlimited".to_string()
+            } else {
+                // 取不到主机线程数时只按配额和 cpuset 计算
+                let host_threads = Some(CpuInfo::new().cpu_thread_count()).filter(|threads| *threads > 0).unwrap_or(u32::MAX);
+                format!("{:.2}", cgroup.effective_cpus(host_threads))
+            };
+            let memory = cgroup.memory_limit.map_or("unlimited".to_string(), format_size);
+            output.push_str(&format!("\n  Cgroup v{}: CPU {} memory {}", cgroup.version, cpus.yellow(), memory.yellow()));
+        }
         println!("{}\n", output);
         output
     }
@@ -165,7 +350,8 @@ mod unit_test_os {
         assert!(output.contains("os_type")
             && output.contains("os_name")
             && output.contains("os_version")
-            && output.contains("os_arch"),
+            && output.contains("os_arch")
+            && output.contains("virtualization"),
             "failed since some os info missing"
         )
     }
@@ -201,4 +387,23 @@ mod unit_test_os {
         let output = os_info.os_arch();
         assert!(output.len() > 0, "failed since os arch missing")
     }
+
+    #[test]
+    fn test_os_info_07() {
+        let root = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/procfs");
+        let keys: Vec<String> = ["net.ipv4.ip_forward", "net.ipv4.tcp_rmem", "kernel.no_such_key"].iter().map(|k| k.to_string()).collect();
+        let kernel = read_kernel_info(&root, &keys);
+        assert_eq!(kernel.release, "6.1.0-18-amd64");
+        assert_eq!(kernel.cmdline, "BOOT_IMAGE=/boot/vmlinuz-6.1.0-18-amd64 root=UUID=1b2c ro quiet");
+        assert_eq!(kernel.boot_time.map(|time| time.timestamp()), Some(1700000000));
+        assert_eq!(kernel.uptime_secs, 273906);
+        assert_eq!(kernel.sysctl.into_iter().collect::<Vec<_>>(), [
+            ("net.ipv4.ip_forward".to_string(), "1".to_string()),
+            ("net.ipv4.tcp_rmem".to_string(), "4096 131072 6291456".to_string()),
+        ]);
+        assert_eq!((kernel.selinux.as_str(), kernel.apparmor.as_str(), kernel.timezone.as_str()), ("disabled", "enabled", "Asia/Shanghai"));
+        assert_eq!(kernel.modules.len(), 3);
+        assert_eq!(kernel.modules[0].used_by, ["xt_MASQUERADE", "nft_chain_nat"]);
+        assert!(kernel.modules[2].used_by.is_empty());
+    }
 }
@@ -0,0 +1,399 @@
+//! 已安装软件包清单：dpkg、rpm、apk、pacman
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::info::error::{read_file, run_command, InfoError, InfoResult};
+
+// rpm 查询格式：名称、[epoch:]version-release、架构、源码包
+pub const RPM_QUERY_FORMAT: &str = "%{NAME}\\t%|EPOCH?{%{EPOCH}:}:{}|%{VERSION}-%{RELEASE}\\t%{ARCH}\\t%{SOURCERPM}\\n";
+
+// 包管理器
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PackageManager {
+    Dpkg,
+    Rpm,
+    Apk,
+    Pacman,
+}
+
+impl fmt::Display for PackageManager {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            PackageManager::Dpkg => "dpkg",
+            PackageManager::Rpm => "rpm",
+            PackageManager::Apk => "apk",
+            PackageManager::Pacman => "pacman",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// 已安装的软件包
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Package {
+    pub name: String,
+    pub version: String,               // 含 epoch 和发行版修订号，如 1:3.0.11-1~deb12u2
+    pub arch: String,
+    pub source: String,                // 源码包名，安全公告通常按源码包发布；本地数据库不记录安装来源仓库
+    pub manager: PackageManager,
+}
+
+// 按空行分隔的记录
+fn records(text: &str) -> impl Iterator<Item = &str> {
+    text.split("\n\n").map(str::trim).filter(|record| !record.is_empty())
+}
+
+/// 解析 dpkg 状态文件（/var/lib/dpkg/status），只保留已安装的包
+pub fn parse_dpkg_status(text: &str) -> Vec<Package> {
+    let mut packages = Vec::new();
+    for record in records(text) {
+        // 续行以空白开头，如 Description 的详细说明
+        let fields: HashMap<&str, &str> = record.lines()
+            .filter(|line| !line.starts_with([' ', '\t']))
+            .filter_map(|line| line.split_once(':'))
+            .map(|(key, value)| (key, value.trim()))
+            .collect();
+        if !fields.get("Status").is_some_and(|status| status.ends_with(" installed")) {
+            continue;
+        }
+        let (Some(name), Some(version)) = (fields.get("Package"), fields.get("Version")) else {
+            continue;
+        };
+        // Source 形如 "openssl" 或 "openssl (3.0.11-1)"，缺省与包名相同
+        let source = fields.get("Source").and_then(|source| source.split_whitespace().next()).unwrap_or(name);
+        packages.push(Package {
+            name: name.to_string(),
+            version: version.to_string(),
+            arch: fields.get("Architecture").copied().unwrap_or_default().to_string(),
+            source: source.to_string(),
+            manager: PackageManager::Dpkg,
+        });
+    }
+    packages
+}
+
+/// 解析 rpm -qa --queryformat RPM_QUERY_FORMAT 的输出
+pub fn parse_rpm_query(text: &str) -> Vec<Package> {
+    text.lines().filter_map(|line| {
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() < 4 {
+            return None;
+        }
+        let none = |value: &str| if value == "(none)" { String::new() } else { value.to_string() };
+        // 源码包形如 openssl-3.0.7-24.el9.src.rpm，去掉版本和修订号
+        let source = fields[3].rsplitn(3, '-').nth(2).map_or_else(|| fields[0].to_string(), str::to_string);
+        Some(Package {
+            name: fields[0].to_string(),
+            version: fields[1].to_string(),
+            arch: none(fields[2]),
+            source,
+            manager: PackageManager::Rpm,
+        })
+    }).collect()
+}
+
+/// 解析 apk 数据库（/lib/apk/db/installed）
+pub fn parse_apk_installed(text: &str) -> Vec<Package> {
+    records(text).filter_map(|record| {
+        let fields: HashMap<&str, &str> = record.lines().filter_map(|line| line.split_once(':')).collect();
+        let name = fields.get("P")?;
+        Some(Package {
+            name: name.to_string(),
+            version: fields.get("V")?.to_string(),
+            arch: fields.get("A").copied().unwrap_or_default().to_string(),
+            source: fields.get("o").unwrap_or(name).to_string(),
+            manager: PackageManager::Apk,
+        })
+    }).collect()
+}
+
+/// 解析 pacman 本地数据库中单个包的 desc 文件（/var/lib/pacman/local/<包>/desc）
+pub fn parse_pacman_desc(text: &str) -> Option<Package> {
+    // 字段形如 "%NAME%\nopenssl"，多值字段每行一个值
+    let fields: HashMap<&str, &str> = records(text)
+        .filter_map(|record| record.split_once('\n'))
+        .map(|(key, value)| (key.trim_matches('%'), value.lines().next().unwrap_or_default()))
+        .collect();
+    let name = fields.get("NAME")?;
+    Some(Package {
+        name: name.to_string(),
+        version: fields.get("VERSION")?.to_string(),
+        arch: fields.get("ARCH").copied().unwrap_or_default().to_string(),
+        source: fields.get("BASE").unwrap_or(name).to_string(),
+        manager: PackageManager::Pacman,
+    })
+}
+
+/// 本机已安装的软件包
+#[cfg(target_os = "linux")]
+pub fn installed_packages() -> InfoResult<Vec<Package>> {
+    installed_packages_from(Path::new("/"))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn installed_packages() -> InfoResult<Vec<Package>> {
+    Err(InfoError::Unsupported("package inventory".to_string()))
+}
+
+/// 读取 root 下各包管理器的数据库，可用于读取容器或挂载的镜像，一个数据库都没有时报错
+pub fn installed_packages_from(root: &Path) -> InfoResult<Vec<Package>> {
+    let mut packages = Vec::new();
+    let mut found = false;
+
+    let dpkg_status = root.join("var/lib/dpkg/status");
+    if dpkg_status.is_file() {
+        found = true;
+        packages.extend(parse_dpkg_status(&read_file(&dpkg_status)?));
+    }
+
+    let apk_installed = root.join("lib/apk/db/installed");
+    if apk_installed.is_file() {
+        found = true;
+        packages.extend(parse_apk_installed(&read_file(&apk_installed)?));
+    }
+
+    let pacman_local = root.join("var/lib/pacman/local");
+    if pacman_local.is_dir() {
+        found = true;
+        let entries = std::fs::read_dir(&pacman_local).map_err(|e| InfoError::io(&pacman_local, e))?;
+        for entry in entries.flatten() {
+            let desc = entry.path().join("desc");
+            if desc.is_file() {
+                packages.extend(parse_pacman_desc(&read_file(&desc)?));
+            }
+        }
+    }
+
+    if root.join("var/lib/rpm").is_dir() || root.join("usr/lib/sysimage/rpm").is_dir() {
+        found = true;
+        let root = root.to_string_lossy();
+        packages.extend(parse_rpm_query(&run_command("rpm", &["--root", &root, "-qa", "--queryformat", RPM_QUERY_FORMAT])?));
+    }
+
+    if !found {
+        return Err(InfoError::Unsupported("package inventory without dpkg, rpm, apk or pacman database".to_string()));
+    }
+    packages.sort();
+    Ok(packages)
+}
+
+// 字符排序权重：~ 最小，比结尾还小，字母其次，其余符号最大
+fn char_order(c: Option<u8>) -> i32 {
+    match c {
+        None => 0,
+        Some(b'~') => -1,
+        Some(c) if c.is_ascii_digit() => 0,
+        Some(c) if c.is_ascii_alphabetic() => c as i32,
+        Some(c) => c as i32 + 256,
+    }
+}
+
+// 按 dpkg 规则比较版本片段：非数字部分逐字符比较，数字部分按数值比较
+fn compare_fragment(a: &str, b: &str) -> Ordering {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    let is_digit = |s: &[u8], i: usize| s.get(i).is_some_and(u8::is_ascii_digit);
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        while (i < a.len() && !is_digit(a, i)) || (j < b.len() && !is_digit(b, j)) {
+            let (ac, bc) = (char_order(a.get(i).copied()), char_order(b.get(j).copied()));
+            if ac != bc {
+                return ac.cmp(&bc);
+            }
+            i += 1;
+            j += 1;
+        }
+        while a.get(i) == Some(&b'0') {
+            i += 1;
+        }
+        while b.get(j) == Some(&b'0') {
+            j += 1;
+        }
+        let mut first_difference = Ordering::Equal;
+        while is_digit(a, i) && is_digit(b, j) {
+            if first_difference == Ordering::Equal {
+                first_difference = a[i].cmp(&b[j]);
+            }
+            i += 1;
+            j += 1;
+        }
+        if is_digit(a, i) {
+            return Ordering::Greater;
+        }
+        if is_digit(b, j) {
+            return Ordering::Less;
+        }
+        if first_difference != Ordering::Equal {
+            return first_difference;
+        }
+    }
+    Ordering::Equal
+}
+
+// 拆分为 (epoch, 上游版本, 修订号)
+fn split_version(version: &str) -> (u64, &str, &str) {
+    let (epoch, rest) = match version.split_once(':') {
+        Some((epoch, rest)) if epoch.chars().all(|c| c.is_ascii_digit()) => (epoch.parse().unwrap_or(0), rest),
+        _ => (0, version),
+    };
+    match rest.rsplit_once('-') {
+        Some((upstream, revision)) => (epoch, upstream, revision),
+        None => (epoch, rest, ""),
+    }
+}
+
+/// 比较两个版本号，按 dpkg 规则（epoch、上游版本、修订号依次比较，~ 表示预发布），rpm、apk、pacman 的版本号大体适用
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let (a_epoch, a_upstream, a_revision) = split_version(a);
+    let (b_epoch, b_upstream, b_revision) = split_version(b);
+    a_epoch.cmp(&b_epoch)
+        .then_with(|| compare_fragment(a_upstream, b_upstream))
+        .then_with(|| compare_fragment(a_revision, b_revision))
+}
+
+// 版本比较运算符
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VersionOp {
+    Less,
+    LessEqual,
+    Equal,
+    NotEqual,
+    GreaterEqual,
+    Greater,
+}
+
+impl VersionOp {
+    fn accepts(&self, ordering: Ordering) -> bool {
+        match self {
+            VersionOp::Less => ordering == Ordering::Less,
+            VersionOp::LessEqual => ordering != Ordering::Greater,
+            VersionOp::Equal => ordering == Ordering::Equal,
+            VersionOp::NotEqual => ordering != Ordering::Equal,
+            VersionOp::GreaterEqual => ordering != Ordering::Less,
+            VersionOp::Greater => ordering == Ordering::Greater,
+        }
+    }
+}
+
+// 软件包查询，如 "openssl<3.0.8"、"openssl >= 1:3.0"、"openssl"（任意版本）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageQuery {
+    pub name: String,
+    pub constraint: Option<(VersionOp, String)>,
+}
+
+impl PackageQuery {
+    /// 包名或源码包名相同且版本满足条件
+    pub fn matches(&self, package: &Package) -> bool {
+        (package.name == self.name || package.source == self.name)
+            && self.constraint.as_ref().is_none_or(|(op, version)| op.accepts(compare_versions(&package.version, version)))
+    }
+}
+
+impl FromStr for PackageQuery {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // 长运算符在前，避免 "<=" 被识别为 "<"
+        const OPERATORS: [(&str, VersionOp); 7] = [
+            ("<=", VersionOp::LessEqual), (">=", VersionOp::GreaterEqual), ("!=", VersionOp::NotEqual), ("==", VersionOp::Equal),
+            ("<", VersionOp::Less), (">", VersionOp::Greater), ("=", VersionOp::Equal),
+        ];
+        let position = s.find(['<', '>', '=', '!']);
+        let (name, constraint) = match position {
+            Some(position) => {
+                let (name, rest) = s.split_at(position);
+                let (symbol, op) = OPERATORS.iter().find(|(symbol, _)| rest.starts_with(symbol))
+                    .ok_or_else(|| format!("invalid operator in package query: {}", s))?;
+                let version = rest[symbol.len()..].trim();
+                if version.is_empty() {
+                    return Err(format!("missing version in package query: {}", s));
+                }
+                (name.trim(), Some((*op, version.to_string())))
+            }
+            None => (s.trim(), None),
+        };
+        if name.is_empty() {
+            return Err(format!("missing package name in query: {}", s));
+        }
+        Ok(PackageQuery { name: name.to_string(), constraint })
+    }
+}
+
+#[cfg(test)]
+mod unit_test_package {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixtures() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/packages")
+    }
+
+    #[test]
+    fn test_package_01() {
+        let root = fixtures().join("rootfs");
+        let packages = installed_packages_from(&root).unwrap();
+        let names: Vec<(&str, PackageManager)> = packages.iter().map(|p| (p.name.as_str(), p.manager)).collect();
+        assert_eq!(names, vec![
+            ("busybox", PackageManager::Apk),
+            ("libssl3", PackageManager::Dpkg),
+            ("libssl3", PackageManager::Apk),
+            ("openssh-server", PackageManager::Dpkg),
+            ("openssl", PackageManager::Dpkg),
+            ("openssl", PackageManager::Pacman),
+        ]);
+        let libssl = &packages[1];
+        assert_eq!(libssl.version, "3.0.11-1~deb12u2");
+        assert_eq!(libssl.arch, "amd64");
+        assert_eq!(libssl.source, "openssl");
+        assert_eq!(packages[2].source, "openssl");
+        assert_eq!(packages[5].version, "3.1.4-1");
+        assert_eq!(packages[5].arch, "x86_64");
+
+        assert!(installed_packages_from(&fixtures().join("missing")).is_err());
+    }
+
+    #[test]
+    fn test_package_02() {
+        let text = read_file(&fixtures().join("rpm-qa.txt")).unwrap();
+        let packages = parse_rpm_query(&text);
+        assert_eq!(packages.len(), 3);
+        assert_eq!(packages[0].name, "openssl-libs");
+        assert_eq!(packages[0].version, "1:3.0.7-24.el9");
+        assert_eq!(packages[0].source, "openssl");
+        assert_eq!(packages[2].arch, "");
+        assert_eq!(packages[2].source, "gpg-pubkey");
+    }
+
+    #[test]
+    fn test_package_03() {
+        assert_eq!(compare_versions("3.0.11-1~deb12u2", "3.0.11-1"), Ordering::Less);
+        assert_eq!(compare_versions("1:3.0.7-24.el9", "3.1.0-1"), Ordering::Greater);
+        assert_eq!(compare_versions("3.0.10", "3.0.9"), Ordering::Greater);
+        assert_eq!(compare_versions("1.0~rc1", "1.0"), Ordering::Less);
+        assert_eq!(compare_versions("1.0a", "1.0+"), Ordering::Less);
+        assert_eq!(compare_versions("3.1.4-r0", "3.1.4-r1"), Ordering::Less);
+        assert_eq!(compare_versions("1.02", "1.2"), Ordering::Equal);
+
+        let query: PackageQuery = "openssl < 3.0.12".parse().unwrap();
+        assert_eq!(query.constraint, Some((VersionOp::Less, "3.0.12".to_string())));
+        let package = Package {
+            name: "libssl3".to_string(),
+            version: "3.0.11-1~deb12u2".to_string(),
+            arch: "amd64".to_string(),
+            source: "openssl".to_string(),
+            manager: PackageManager::Dpkg,
+        };
+        assert!(query.matches(&package));
+        assert!(!"openssl>=3.0.11-1".parse::<PackageQuery>().unwrap().matches(&package));
+        assert!("libssl3".parse::<PackageQuery>().unwrap().matches(&package));
+        assert!("openssl<".parse::<PackageQuery>().is_err());
+        assert!("<1.0".parse::<PackageQuery>().is_err());
+    }
+}
@@ -1,29 +1,54 @@
-use serde::Serialize;
-use sysinfo::System;
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
 
-#[derive(Debug, Clone, Serialize)]
+use chrono::{DateTime, Local, TimeZone, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sysinfo::{Pid, Process, System, Users};
+
+use crate::common::api::format_size;
+use crate::info::service;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ProcessInfo {
-    pub pid: String,
+    pub pid: u32,
+    pub parent_pid: Option<u32>,
     pub command: String,
-    #[serde(skip)]
     pub exe_path: String,
-    #[serde(skip)]
     pub full_command: String,
+    pub user: String,                        // 用户名，无法解析时为 uid
+    pub uid: Option<u32>,                    // Windows 为 None
+    pub state: String,
+    pub start_time: DateTime<Utc>,
+    pub cpu_percent: f32,                    // 可超过 100%（多线程）
+    pub rss: u64,                            // 常驻内存，字节
+    pub vsz: u64,                            // 虚拟内存，字节
+    pub threads: Option<u32>,                // 仅 Linux
+    pub open_fds: Option<u32>,               // 仅 Linux，无权限时为 None
+    pub cwd: String,
+    pub listening: Vec<String>,              // 监听地址，形如 "tcp 0.0.0.0:22"
 }
 
 impl ProcessInfo {
-    pub fn new(pid: &str, command: &str, exe_path: &str, full_command: &str) -> Self {
-        Self {
-            pid: pid.to_string(),
-            command: command.to_string(),
-            exe_path: exe_path.to_string(),
-            full_command: full_command.to_string(),
-        }
+    /// 表头，与 display 列对齐
+    pub fn header() -> String {
+        format!("{:>7} {:>7} {:<12} {:<8} {:>6} {:>10} {:>10} {:>4} {:<19} {:<}",
+                "PID", "PPID", "USER", "STATE", "CPU%", "RSS", "VSZ", "THR", "STARTED", "COMMAND")
     }
 
     pub fn display(&self) -> String {
-        let output = format!("{:>10} {:<15} {:<30} {:<}", &self.pid, &self.command, &self.exe_path, &self.full_command);
-        print!("{}\n", output);
+        let output = format!("{:>7} {:>7} {:<12} {:<8} {:>6.1} {:>10} {:>10} {:>4} {:<19} {:<}",
+            self.pid,
+            self.parent_pid.map(|p| p.to_string()).unwrap_or_else(|| "-".to_string()),
+            self.user,
+            self.state,
+            self.cpu_percent,
+            format_size(self.rss),
+            format_size(self.vsz),
+            self.threads.map(|t| t.to_string()).unwrap_or_else(|| "-".to_string()),
+            self.start_time.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S"),
+            if self.full_command.is_empty() { &self.command } else { &self.full_command });
+        println!("{}", output);
         output
     }
 
@@ -35,55 +60,311 @@ impl ProcessInfo {
     }
 }
 
+#[cfg(unix)]
+fn uid_of(process: &Process) -> Option<u32> {
+    process.user_id().map(|uid| **uid)
+}
+
+#[cfg(not(unix))]
+fn uid_of(_process: &Process) -> Option<u32> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn open_fd_count(pid: u32) -> Option<u32> {
+    std::fs::read_dir(format!("/proc/{}/fd", pid)).ok().map(|entries| entries.count() as u32)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn open_fd_count(_pid: u32) -> Option<u32> {
+    None
+}
+
+// 各进程的监听地址：TCP 取 LISTEN 状态，UDP 无状态，均视为监听
+fn listening_by_pid() -> HashMap<u32, Vec<String>> {
+    let mut map: HashMap<u32, Vec<String>> = HashMap::new();
+    for s in service::get_services() {
+        if s.is_listening() {
+            let address = format!("{} {}:{}", s.protocol(), s.ip_address(), s.port());
+            let entry = map.entry(s.pid()).or_default();
+            if !entry.contains(&address) {
+                entry.push(address);
+            }
+        }
+    }
+    map
+}
+
 pub fn gather_process_info() -> Vec<ProcessInfo> {
     let mut system = System::new_all();
-    system.refresh_all();
-    system.processes().values().map(|process| {
-        let pid = process.pid().to_string();
-        let command = process.cmd().join(" ").split_whitespace().next().unwrap_or_default().to_string();
-        let exe_path = process.exe().map_or_else(|| String::new(), |p| p.display().to_string());
-        let full_command = process.cmd().join(" ");
+    // CPU 使用率为两次刷新间的增量
+    std::thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+    system.refresh_processes();
+    let users = Users::new_with_refreshed_list();
+    let listening = listening_by_pid();
 
-        ProcessInfo::new(&pid, &command, &exe_path, &full_command)
-    }).collect()
+    let mut processes: Vec<ProcessInfo> = system.processes().values()
+        // Linux 上线程也以进程形式列出，只保留主线程
+        .filter(|process| process.thread_kind().is_none())
+        .map(|process| {
+            let pid = process.pid().as_u32();
+            let full_command = process.cmd().join(" ");
+            let command = match full_command.split_whitespace().next() {
+                Some(command) => command.to_string(),
+                None => process.name().to_string(),
+            };
+            let uid = uid_of(process);
+            let user = process.user_id()
+                .and_then(|uid| users.get_user_by_id(uid))
+                .map(|user| user.name().to_string())
+                .or_else(|| uid.map(|uid| uid.to_string()))
+                .unwrap_or_default();
+
+            ProcessInfo {
+                pid,
+                parent_pid: process.parent().map(Pid::as_u32),
+                command,
+                exe_path: process.exe().map(|p| p.display().to_string()).unwrap_or_default(),
+                full_command,
+                user,
+                uid,
+                state: process.status().to_string(),
+                start_time: Utc.timestamp_opt(process.start_time() as i64, 0).single().unwrap_or_default(),
+                cpu_percent: process.cpu_usage(),
+                rss: process.memory(),
+                vsz: process.virtual_memory(),
+                threads: process.tasks().map(|tasks| tasks.len() as u32 + 1),
+                open_fds: open_fd_count(pid),
+                cwd: process.cwd().map(|p| p.display().to_string()).unwrap_or_default(),
+                listening: listening.get(&pid).cloned().unwrap_or_default(),
+            }
+        }).collect();
+    processes.sort_by_key(|p| p.pid);
+    processes
 }
 
+// 进程过滤条件，可以 And、Or、Not 组合
 #[derive(Debug, Clone)]
-pub enum Filter<'a> {
-    ByPid(&'a str),
-    ByKeyword(&'a str),
-    ByMask(&'a str),
+pub enum Filter {
+    ByPid(u32),
+    ByKeyword(String),                       // 命令或完整命令行包含关键词
+    ByRegex(Regex),                          // 完整命令行匹配正则，命令行为空时匹配命令名
+    ByUser(String),                          // 用户名或 uid
+    ByParent(u32),
+    CpuAbove(f32),                           // CPU 使用率不低于该值 %
+    MemoryAbove(u64),                        // 常驻内存不低于该值，字节
+    And(Vec<Filter>),
+    Or(Vec<Filter>),
+    Not(Box<Filter>),
+}
+
+impl Filter {
+    pub fn matches(&self, process: &ProcessInfo) -> bool {
+        match self {
+            Filter::ByPid(pid) => process.pid == *pid,
+            Filter::ByKeyword(keyword) => process.command.contains(keyword.as_str()) || process.full_command.contains(keyword.as_str()),
+            Filter::ByRegex(regex) => {
+                if process.full_command.is_empty() {
+                    regex.is_match(&process.command)
+                } else {
+                    regex.is_match(&process.full_command)
+                }
+            }
+            Filter::ByUser(user) => &process.user == user || process.uid.is_some_and(|uid| uid.to_string() == *user),
+            Filter::ByParent(ppid) => process.parent_pid == Some(*ppid),
+            Filter::CpuAbove(percent) => process.cpu_percent >= *percent,
+            Filter::MemoryAbove(bytes) => process.rss >= *bytes,
+            Filter::And(filters) => filters.iter().all(|f| f.matches(process)),
+            Filter::Or(filters) => filters.iter().any(|f| f.matches(process)),
+            Filter::Not(filter) => !filter.matches(process),
+        }
+    }
+}
+
+// 排序字段，CPU、内存、启动时间按降序，其余按升序
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    Pid,
+    Cpu,
+    Memory,
+    Start,
+    Command,
+}
+
+impl FromStr for SortKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "pid" => Ok(SortKey::Pid),
+            "cpu" => Ok(SortKey::Cpu),
+            "mem" | "memory" | "rss" => Ok(SortKey::Memory),
+            "start" | "time" => Ok(SortKey::Start),
+            "command" | "name" => Ok(SortKey::Command),
+            _ => Err(format!("invalid sort key: {}", s)),
+        }
+    }
 }
 
-pub fn get_filtered_processes_as_json(filter: Option<Filter>, mask: Option<Filter>) -> String {
-    let processes = gather_process_info();
-    let filtered_processes = filter_processes(&processes, filter, mask);
+pub fn sort_processes(processes: &mut [ProcessInfo], key: SortKey) {
+    match key {
+        SortKey::Pid => processes.sort_by_key(|p| p.pid),
+        SortKey::Cpu => processes.sort_by(|a, b| b.cpu_percent.total_cmp(&a.cpu_percent)),
+        SortKey::Memory => processes.sort_by_key(|p| std::cmp::Reverse(p.rss)),
+        SortKey::Start => processes.sort_by_key(|p| std::cmp::Reverse(p.start_time)),
+        SortKey::Command => processes.sort_by(|a, b| a.command.cmp(&b.command)),
+    }
+}
+
+pub fn filter_processes(processes: &[ProcessInfo], filter: Option<&Filter>) -> Vec<ProcessInfo> {
+    processes.iter().filter(|process| filter.is_none_or(|f| f.matches(process))).cloned().collect()
+}
+
+pub fn get_filtered_processes_as_json(filter: Option<&Filter>) -> String {
+    let filtered_processes = get_filtered_processes_as_list(filter);
     serde_json::to_string(&filtered_processes).unwrap_or_else(|_| "[]".to_string())
 }
 
-pub fn get_filtered_processes_as_list(filter: Option<Filter>, mask: Option<Filter>) -> Vec<ProcessInfo> {
-    let processes = gather_process_info();
-    filter_processes(&processes, filter, mask)
+pub fn get_filtered_processes_as_list(filter: Option<&Filter>) -> Vec<ProcessInfo> {
+    filter_processes(&gather_process_info(), filter)
 }
 
-fn filter_processes(processes: &[ProcessInfo], filter: Option<Filter>, mask: Option<Filter>) -> Vec<ProcessInfo> {
-    processes.iter().filter(|process| {
-        // 根据filter决定是否包含进程
-        let include = match &filter {
-            Some(Filter::ByPid(pid)) => &process.pid == pid,
-            Some(Filter::ByKeyword(keyword)) => process.command.contains(keyword) || process.full_command.contains(keyword),
-            Some(Filter::ByMask(_)) => true,
-            None => true,
+/// 以父子关系渲染进程树，父进程不在列表中的进程作为根，同级按 processes 中的顺序排列
+pub fn process_tree(processes: &[ProcessInfo]) -> String {
+    let pids: Vec<u32> = processes.iter().map(|p| p.pid).collect();
+    let mut children: BTreeMap<u32, Vec<&ProcessInfo>> = BTreeMap::new();
+    let mut roots = Vec::new();
+    for process in processes {
+        match process.parent_pid.filter(|ppid| *ppid != process.pid && pids.contains(ppid)) {
+            Some(ppid) => children.entry(ppid).or_default().push(process),
+            None => roots.push(process),
+        }
+    }
+
+    fn label(process: &ProcessInfo) -> String {
+        let command = if process.full_command.is_empty() { &process.command } else { &process.full_command };
+        format!("{} [{}] {}", process.pid, process.user, command)
+    }
+
+    fn render(output: &mut String, process: &ProcessInfo, prefix: &str, children: &BTreeMap<u32, Vec<&ProcessInfo>>) {
+        let Some(kids) = children.get(&process.pid) else {
+            return;
         };
+        for (i, child) in kids.iter().enumerate() {
+            let last = i + 1 == kids.len();
+            output.push_str(&format!("{}{}{}\n", prefix, if last { "└─ " } else { "├─ " }, label(child)));
+            render(output, child, &format!("{}{}", prefix, if last { "   " } else { "│  " }), children);
+        }
+    }
 
-        // 如果include为true，再根据mask决定是否排除进程
-        if include {
-            match &mask {
-                Some(Filter::ByKeyword(mask_keyword)) => !process.full_command.contains(mask_keyword),
-                _ => true,
-            }
-        } else {
-            false
+    let mut output = String::new();
+    for root in roots {
+        output.push_str(&label(root));
+        output.push('\n');
+        render(&mut output, root, "", &children);
+    }
+    output
+}
+
+#[cfg(test)]
+mod unit_test_process {
+    use super::*;
+
+    fn process(pid: u32, parent_pid: Option<u32>, user: &str, command: &str, cpu_percent: f32, rss: u64) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            parent_pid,
+            command: command.split_whitespace().next().unwrap_or_default().to_string(),
+            exe_path: String::new(),
+            full_command: command.to_string(),
+            user: user.to_string(),
+            uid: Some(if user == "root" { 0 } else { 1000 }),
+            state: "Sleeping".to_string(),
+            start_time: Utc.timestamp_opt(1_700_000_000 + pid as i64, 0).unwrap(),
+            cpu_percent,
+            rss,
+            vsz: rss * 4,
+            threads: Some(1),
+            open_fds: None,
+            cwd: "/".to_string(),
+            listening: vec![],
         }
-    }).cloned().collect()
+    }
+
+    fn processes() -> Vec<ProcessInfo> {
+        vec![
+            process(1, None, "root", "/sbin/init", 0.1, 10 << 20),
+            process(100, Some(1), "root", "/usr/sbin/sshd -D", 0.0, 8 << 20),
+            process(200, Some(100), "alice", "sshd: alice@pts/0", 0.5, 6 << 20),
+            process(201, Some(200), "alice", "-bash", 0.0, 4 << 20),
+            process(300, Some(201), "alice", "python3 train.py --epochs 10", 180.0, 2 << 30),
+            process(400, Some(1), "root", "/usr/bin/dockerd", 2.0, 100 << 20),
+        ]
+    }
+
+    fn pids(processes: &[ProcessInfo]) -> Vec<u32> {
+        processes.iter().map(|p| p.pid).collect()
+    }
+
+    #[test]
+    fn test_process_info_01() {
+        let processes = gather_process_info();
+        assert!(!processes.is_empty());
+        let me = processes.iter().find(|p| p.pid == std::process::id()).expect("current process not found");
+        assert!(me.parent_pid.is_some() && !me.user.is_empty() && me.rss > 0);
+        let json = me.to_json();
+        assert!(json.contains("\"exe_path\"") && json.contains("\"full_command\"") && json.contains("\"parent_pid\""));
+        assert!(me.display().contains(&me.pid.to_string()));
+    }
+
+    #[test]
+    fn test_process_info_02() {
+        let processes = processes();
+        let filter = Filter::And(vec![
+            Filter::ByUser("alice".to_string()),
+            Filter::Not(Box::new(Filter::ByKeyword("sshd".to_string()))),
+        ]);
+        assert_eq!(pids(&filter_processes(&processes, Some(&filter))), vec![201, 300]);
+
+        let filter = Filter::Or(vec![Filter::CpuAbove(100.0), Filter::MemoryAbove(50 << 20)]);
+        assert_eq!(pids(&filter_processes(&processes, Some(&filter))), vec![300, 400]);
+
+        let filter = Filter::ByRegex(Regex::new(r"^/usr/s?bin/").unwrap());
+        assert_eq!(pids(&filter_processes(&processes, Some(&filter))), vec![100, 400]);
+
+        assert_eq!(pids(&filter_processes(&processes, Some(&Filter::ByParent(1)))), vec![100, 400]);
+        assert_eq!(pids(&filter_processes(&processes, Some(&Filter::ByUser("0".to_string())))), vec![1, 100, 400]);
+        assert_eq!(pids(&filter_processes(&processes, Some(&Filter::ByPid(201)))), vec![201]);
+        assert_eq!(filter_processes(&processes, None).len(), 6);
+    }
+
+    #[test]
+    fn test_process_info_03() {
+        let mut processes = processes();
+        sort_processes(&mut processes, "cpu".parse().unwrap());
+        assert_eq!(pids(&processes)[..2], [300, 400]);
+        sort_processes(&mut processes, SortKey::Memory);
+        assert_eq!(pids(&processes)[..2], [300, 400]);
+        sort_processes(&mut processes, SortKey::Start);
+        assert_eq!(processes[0].pid, 400);
+        sort_processes(&mut processes, SortKey::Pid);
+        assert_eq!(pids(&processes), vec![1, 100, 200, 201, 300, 400]);
+        assert!("size".parse::<SortKey>().is_err());
+    }
+
+    #[test]
+    fn test_process_info_04() {
+        let tree = process_tree(&processes());
+        assert_eq!(tree, "\
+1 [root] /sbin/init
+├─ 100 [root] /usr/sbin/sshd -D
+│  └─ 200 [alice] sshd: alice@pts/0
+│     └─ 201 [alice] -bash
+│        └─ 300 [alice] python3 train.py --epochs 10
+└─ 400 [root] /usr/bin/dockerd
+");
+        // 父进程被过滤掉时作为根
+        let alice = filter_processes(&processes(), Some(&Filter::ByUser("alice".to_string())));
+        assert!(process_tree(&alice).starts_with("200 [alice] sshd: alice@pts/0\n└─ 201"));
+    }
 }
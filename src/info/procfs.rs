@@ -0,0 +1,608 @@
+//! Linux 原生信息采集，直接读取 `/proc` 和 `/sys`，不依赖外部命令
+//!
+//! 解析函数只处理文本内容，读取函数以根目录为参数，便于以夹具文件测试。
+
+use std::collections::{BTreeSet, HashMap};
+use std::ffi::CString;
+use std::fs;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::path::Path;
+
+pub const PROC_ROOT: &str = "/proc";
+pub const SYS_ROOT: &str = "/sys";
+
+// 不对应块设备存储的伪文件系统，statvfs 无意义，autofs 还会触发自动挂载
+const PSEUDO_FS_TYPES: [&str; 21] = [
+    "autofs", "binfmt_misc", "bpf", "cgroup", "cgroup2", "configfs", "debugfs", "devpts", "devtmpfs",
+    "efivarfs", "fusectl", "hugetlbfs", "mqueue", "nsfs", "proc", "pstore", "ramfs", "rpc_pipefs",
+    "securityfs", "sysfs", "tmpfs",
+];
+
+// CPU 数量
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CpuCounts {
+    pub sockets: u32,
+    pub cores: u32,
+    pub threads: u32,
+}
+
+// 内存用量，单位字节，口径与 free 命令一致
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MemCounts {
+    pub mem_total: u64,
+    pub mem_used: u64,
+    pub mem_free: u64,
+    pub swap_total: u64,
+    pub swap_used: u64,
+    pub swap_free: u64,
+}
+
+// 挂载点
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mount {
+    pub source: String,
+    pub mount_point: String,
+    pub fs_type: String,
+}
+
+// 文件系统容量，单位字节，口径与 df 命令一致
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FsUsage {
+    pub total: u64,
+    pub used: u64,
+    pub available: u64,
+    pub files: u64,                          // inode 总数，部分文件系统为 0
+    pub files_free: u64,
+}
+
+impl FsUsage {
+    /// 使用率，向上取整
+    pub fn use_percent(&self) -> u64 {
+        let usable = self.used + self.available;
+        if usable == 0 {
+            0
+        } else {
+            (self.used * 100).div_ceil(usable)
+        }
+    }
+}
+
+// 块设备
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockDevice {
+    pub name: String,
+    pub size: u64,                           // 字节
+    pub model: String,
+    pub rotational: bool,
+}
+
+// /proc/net/{tcp,tcp6,udp,udp6} 中的一条套接字
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SocketEntry {
+    pub protocol: String,                    // tcp, tcp6, udp, udp6
+    pub local_ip: IpAddr,
+    pub local_port: u16,
+    pub remote_ip: IpAddr,
+    pub remote_port: u16,
+    pub state: String,                       // 与 netstat 一致，UDP 未连接时为空
+    pub inode: u64,
+}
+
+impl SocketEntry {
+    pub fn local_address(&self) -> String {
+        format!("{}:{}", self.local_ip, self.local_port)
+    }
+
+    /// 对端地址，端口为 0 时以 * 表示
+    pub fn remote_address(&self) -> String {
+        if self.remote_port == 0 {
+            format!("{}:*", self.remote_ip)
+        } else {
+            format!("{}:{}", self.remote_ip, self.remote_port)
+        }
+    }
+}
+
+/// 解析 /proc/cpuinfo
+///
+/// 插槽数按 `physical id` 去重，核心数按 (`physical id`, `core id`) 去重，线程数为 `processor` 条目数。
+/// 虚拟机和部分架构不提供拓扑字段，此时视为单插槽，每个线程一个核心。
+pub fn parse_cpuinfo(content: &str) -> CpuCounts {
+    let mut threads = 0u32;
+    let mut sockets = BTreeSet::new();
+    let mut cores = BTreeSet::new();
+    let mut physical_id = None;
+    for line in content.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        match key.trim() {
+            "processor" => {
+                threads += 1;
+                physical_id = None;
+            }
+            "physical id" => {
+                physical_id = Some(value.trim().to_string());
+                sockets.insert(value.trim().to_string());
+            }
+            "core id" => {
+                cores.insert((physical_id.clone().unwrap_or_default(), value.trim().to_string()));
+            }
+            _ => {}
+        }
+    }
+    CpuCounts {
+        sockets: if sockets.is_empty() { threads.min(1) } else { sockets.len() as u32 },
+        cores: if cores.is_empty() { threads } else { cores.len() as u32 },
+        threads,
+    }
+}
+
+/// 解析 /proc/meminfo，已用内存不含缓冲和缓存
+pub fn parse_meminfo(content: &str) -> MemCounts {
+    let fields: HashMap<&str, u64> = content.lines().filter_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        let mut parts = value.split_whitespace();
+        let number: u64 = parts.next()?.parse().ok()?;
+        let bytes = match parts.next() {
+            Some("kB") => number * 1024,
+            _ => number,
+        };
+        Some((key.trim(), bytes))
+    }).collect();
+    let get = |key: &str| fields.get(key).copied().unwrap_or(0);
+
+    let mem_total = get("MemTotal");
+    let mem_free = get("MemFree");
+    let cache = get("Buffers") + get("Cached") + get("SReclaimable");
+    let swap_total = get("SwapTotal");
+    let swap_free = get("SwapFree");
+    MemCounts {
+        mem_total,
+        mem_used: mem_total.saturating_sub(mem_free).saturating_sub(cache),
+        mem_free,
+        swap_total,
+        swap_used: swap_total.saturating_sub(swap_free),
+        swap_free,
+    }
+}
+
+// mountinfo 中空格、制表符、换行和反斜杠以八进制转义
+fn unescape_octal(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut output = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\\' && i + 3 < bytes.len() && bytes[i + 1..i + 4].iter().all(|b| (b'0'..=b'7').contains(b)) {
+            output.push(u8::from_str_radix(&field[i + 1..i + 4], 8).unwrap_or(b'?'));
+            i += 4;
+        } else {
+            output.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8_lossy(&output).into_owned()
+}
+
+/// 解析 /proc/self/mountinfo
+///
+/// 格式：`id parent major:minor root mount_point options [optional...] - fs_type source super_options`
+pub fn parse_mountinfo(content: &str) -> Vec<Mount> {
+    content.lines().filter_map(|line| {
+        let (left, right) = line.split_once(" - ")?;
+        let mount_point = left.split_whitespace().nth(4)?;
+        let mut right = right.split_whitespace();
+        let fs_type = right.next()?;
+        let source = right.next().unwrap_or("none");
+        Some(Mount {
+            source: unescape_octal(source),
+            mount_point: unescape_octal(mount_point),
+            fs_type: fs_type.to_string(),
+        })
+    }).collect()
+}
+
+/// 需要统计容量的挂载点：排除伪文件系统，同一挂载点只保留最后一次挂载
+pub fn storage_mounts(mounts: &[Mount]) -> Vec<Mount> {
+    let mut result: Vec<Mount> = Vec::new();
+    for mount in mounts {
+        if PSEUDO_FS_TYPES.contains(&mount.fs_type.as_str()) {
+            continue;
+        }
+        result.retain(|m| m.mount_point != mount.mount_point);
+        result.push(mount.clone());
+    }
+    result
+}
+
+/// 文件系统容量，对应 df 的 Size、Used、Avail 列及 df -i 的 inode 数
+pub fn statvfs(path: &str) -> io::Result<FsUsage> {
+    let c_path = CString::new(path).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    // SAFETY: c_path 为合法的以 NUL 结尾的字符串，stat 为可写的 statvfs 结构
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let fragment = stat.f_frsize as u64;
+    Ok(FsUsage {
+        total: stat.f_blocks as u64 * fragment,
+        used: (stat.f_blocks as u64).saturating_sub(stat.f_bfree as u64) * fragment,
+        available: stat.f_bavail as u64 * fragment,
+        files: stat.f_files as u64,
+        files_free: stat.f_ffree as u64,
+    })
+}
+
+/// 读取 /sys/block 下的物理块设备，跳过 loop、ram、zram、dm 等无 device 链接的虚拟设备
+pub fn read_block_devices(sys_root: &Path) -> io::Result<Vec<BlockDevice>> {
+    let mut devices = Vec::new();
+    for entry in fs::read_dir(sys_root.join("block"))? {
+        let path = entry?.path();
+        if !path.join("device").exists() {
+            continue;
+        }
+        let read = |name: &str| fs::read_to_string(path.join(name)).map(|s| s.trim().to_string()).unwrap_or_default();
+        devices.push(BlockDevice {
+            name: path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default(),
+            size: read("size").parse::<u64>().unwrap_or(0) * 512,
+            model: read("device/model"),
+            rotational: read("queue/rotational") == "1",
+        });
+    }
+    devices.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(devices)
+}
+
+/// 与 lsblk 一致的容量表示，如 `256G`、`238.5G`、`497M`
+pub fn format_block_size(size: u64) -> String {
+    let units = ["B", "K", "M", "G", "T", "P"];
+    let mut value = size as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < units.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    let text = format!("{:.1}", value);
+    format!("{}{}", text.trim_end_matches(".0"), units[unit])
+}
+
+fn tcp_state(code: &str) -> &'static str {
+    match code {
+        "01" => "ESTABLISHED",
+        "02" => "SYN_SENT",
+        "03" => "SYN_RECV",
+        "04" => "FIN_WAIT1",
+        "05" => "FIN_WAIT2",
+        "06" => "TIME_WAIT",
+        "07" => "CLOSE",
+        "08" => "CLOSE_WAIT",
+        "09" => "LAST_ACK",
+        "0A" => "LISTEN",
+        "0B" => "CLOSING",
+        _ => "UNKNOWN",
+    }
+}
+
+// 内核按主机字节序逐 32 位输出网络字节序的地址
+fn parse_hex_ip(hex: &str) -> Option<IpAddr> {
+    match hex.len() {
+        8 => Some(IpAddr::V4(Ipv4Addr::from(u32::from_str_radix(hex, 16).ok()?.to_ne_bytes()))),
+        32 => {
+            let mut octets = [0u8; 16];
+            for i in 0..4 {
+                let word = u32::from_str_radix(&hex[i * 8..i * 8 + 8], 16).ok()?;
+                octets[i * 4..i * 4 + 4].copy_from_slice(&word.to_ne_bytes());
+            }
+            Some(IpAddr::V6(Ipv6Addr::from(octets)))
+        }
+        _ => None,
+    }
+}
+
+fn parse_hex_address(field: &str) -> Option<(IpAddr, u16)> {
+    let (ip, port) = field.split_once(':')?;
+    Some((parse_hex_ip(ip)?, u16::from_str_radix(port, 16).ok()?))
+}
+
+/// 解析 /proc/net/{tcp,tcp6,udp,udp6}，protocol 为文件名
+pub fn parse_net_sockets(content: &str, protocol: &str) -> Vec<SocketEntry> {
+    content.lines().skip(1).filter_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 10 {
+            return None;
+        }
+        let (local_ip, local_port) = parse_hex_address(fields[1])?;
+        let (remote_ip, remote_port) = parse_hex_address(fields[2])?;
+        let state = if protocol.starts_with("udp") {
+            if fields[3] == "01" { "ESTABLISHED" } else { "" }
+        } else {
+            tcp_state(fields[3])
+        };
+        Some(SocketEntry {
+            protocol: protocol.to_string(),
+            local_ip,
+            local_port,
+            remote_ip,
+            remote_port,
+            state: state.to_string(),
+            inode: fields[9].parse().ok()?,
+        })
+    }).collect()
+}
+
+/// 读取全部 TCP/UDP 套接字，不存在的文件（如未启用 IPv6）跳过
+pub fn read_net_sockets(proc_root: &Path) -> Vec<SocketEntry> {
+    ["tcp", "tcp6", "udp", "udp6"].iter().flat_map(|protocol| {
+        fs::read_to_string(proc_root.join("net").join(protocol))
+            .map(|content| parse_net_sockets(&content, protocol))
+            .unwrap_or_default()
+    }).collect()
+}
+
+/// 遍历 /proc/<pid>/fd 建立套接字 inode 到进程号的映射，无权限读取的进程跳过
+pub fn socket_inode_pids(proc_root: &Path) -> HashMap<u64, u32> {
+    let mut map = HashMap::new();
+    let Ok(entries) = fs::read_dir(proc_root) else {
+        return map;
+    };
+    for entry in entries.flatten() {
+        let Some(pid) = entry.file_name().to_str().and_then(|name| name.parse::<u32>().ok()) else {
+            continue;
+        };
+        let Ok(fds) = fs::read_dir(entry.path().join("fd")) else {
+            continue;
+        };
+        for fd in fds.flatten() {
+            let Ok(target) = fs::read_link(fd.path()) else {
+                continue;
+            };
+            let target = target.to_string_lossy();
+            if let Some(inode) = target.strip_prefix("socket:[").and_then(|s| s.strip_suffix(']')) {
+                if let Ok(inode) = inode.parse() {
+                    map.entry(inode).or_insert(pid);
+                }
+            }
+        }
+    }
+    map
+}
+
+// /proc/stat 中一个 CPU 的累计时间，单位 jiffies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CpuTimes {
+    pub busy: u64,
+    pub idle: u64,                           // idle + iowait
+}
+
+impl CpuTimes {
+    pub fn total(&self) -> u64 {
+        self.busy + self.idle
+    }
+}
+
+// /proc/diskstats 中一个块设备的累计计数
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DiskStat {
+    pub name: String,
+    pub reads: u64,                          // 完成的读请求数
+    pub read_sectors: u64,                   // 512 字节扇区
+    pub writes: u64,
+    pub write_sectors: u64,
+}
+
+// /proc/net/dev 中一个网卡的累计计数
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct NetDevStat {
+    pub name: String,
+    pub rx_bytes: u64,
+    pub rx_packets: u64,
+    pub rx_errors: u64,
+    pub rx_drops: u64,
+    pub tx_bytes: u64,
+    pub tx_packets: u64,
+    pub tx_errors: u64,
+    pub tx_drops: u64,
+}
+
+/// 解析 /proc/stat 的 cpu 行，返回 (总计, 各核心)
+///
+/// 列为 user nice system idle iowait irq softirq steal guest guest_nice，guest 已计入 user，不重复累加。
+pub fn parse_stat(content: &str) -> (CpuTimes, Vec<CpuTimes>) {
+    let mut total = CpuTimes::default();
+    let mut cores = Vec::new();
+    for line in content.lines().filter(|l| l.starts_with("cpu")) {
+        let mut fields = line.split_whitespace();
+        let name = fields.next().unwrap_or_default();
+        let values: Vec<u64> = fields.take(8).filter_map(|v| v.parse().ok()).collect();
+        if values.len() < 4 {
+            continue;
+        }
+        let idle = values[3] + values.get(4).copied().unwrap_or(0);
+        let times = CpuTimes { busy: values.iter().sum::<u64>() - idle, idle };
+        if name == "cpu" {
+            total = times;
+        } else {
+            cores.push(times);
+        }
+    }
+    (total, cores)
+}
+
+/// 解析 /proc/loadavg 的 1、5、15 分钟平均负载
+pub fn parse_loadavg(content: &str) -> Option<(f64, f64, f64)> {
+    let mut fields = content.split_whitespace().map(|v| v.parse::<f64>().ok());
+    Some((fields.next()??, fields.next()??, fields.next()??))
+}
+
+/// 解析 /proc/pressure/{cpu,memory,io} 的 avg10，返回 (some, full)
+///
+/// 格式：`some avg10=0.12 avg60=0.05 avg300=0.01 total=12345`，cpu 在旧内核上没有 full 行。
+pub fn parse_pressure(content: &str) -> (Option<f64>, Option<f64>) {
+    let avg10 = |kind: &str| content.lines()
+        .find(|l| l.starts_with(kind))
+        .and_then(|l| l.split_whitespace().find_map(|f| f.strip_prefix("avg10=")))
+        .and_then(|v| v.parse().ok());
+    (avg10("some"), avg10("full"))
+}
+
+/// 解析 /proc/diskstats
+///
+/// 格式：`major minor name reads reads_merged sectors_read ms_reading writes writes_merged sectors_written ...`
+pub fn parse_diskstats(content: &str) -> Vec<DiskStat> {
+    content.lines().filter_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 10 {
+            return None;
+        }
+        Some(DiskStat {
+            name: fields[2].to_string(),
+            reads: fields[3].parse().ok()?,
+            read_sectors: fields[5].parse().ok()?,
+            writes: fields[7].parse().ok()?,
+            write_sectors: fields[9].parse().ok()?,
+        })
+    }).collect()
+}
+
+/// 解析 /proc/net/dev，跳过两行表头
+///
+/// 接收列为 bytes packets errs drop fifo frame compressed multicast，其后 8 列为发送。
+pub fn parse_net_dev(content: &str) -> Vec<NetDevStat> {
+    content.lines().skip(2).filter_map(|line| {
+        let (name, values) = line.split_once(':')?;
+        let values: Vec<u64> = values.split_whitespace().filter_map(|v| v.parse().ok()).collect();
+        if values.len() < 16 {
+            return None;
+        }
+        Some(NetDevStat {
+            name: name.trim().to_string(),
+            rx_bytes: values[0],
+            rx_packets: values[1],
+            rx_errors: values[2],
+            rx_drops: values[3],
+            tx_bytes: values[8],
+            tx_packets: values[9],
+            tx_errors: values[10],
+            tx_drops: values[11],
+        })
+    }).collect()
+}
+
+#[cfg(test)]
+mod unit_test_procfs {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixture(path: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/procfs").join(path)
+    }
+
+    fn read_fixture(path: &str) -> String {
+        fs::read_to_string(fixture(path)).unwrap()
+    }
+
+    #[test]
+    fn test_procfs_01() {
+        assert_eq!(parse_cpuinfo(&read_fixture("proc/cpuinfo")), CpuCounts { sockets: 2, cores: 4, threads: 8 });
+        // 虚拟机无拓扑字段
+        assert_eq!(parse_cpuinfo("processor\t: 0\nmodel name\t: vCPU\n\nprocessor\t: 1\n"), CpuCounts { sockets: 1, cores: 2, threads: 2 });
+        assert_eq!(parse_cpuinfo(""), CpuCounts::default());
+    }
+
+    #[test]
+    fn test_procfs_02() {
+        let mem = parse_meminfo(&read_fixture("proc/meminfo"));
+        assert_eq!(mem.mem_total, 8_000_000 * 1024);
+        assert_eq!(mem.mem_free, 1_000_000 * 1024);
+        assert_eq!(mem.mem_used, (8_000_000 - 1_000_000 - 200_000 - 3_000_000 - 300_000) * 1024);
+        assert_eq!(mem.swap_total, 2_000_000 * 1024);
+        assert_eq!(mem.swap_used, 500_000 * 1024);
+    }
+
+    #[test]
+    fn test_procfs_03() {
+        let mounts = parse_mountinfo(&read_fixture("proc/self/mountinfo"));
+        assert_eq!(mounts.len(), 8);
+        assert_eq!(mounts[6].mount_point, "/mnt/my disk");
+        let storage: Vec<(String, String)> = storage_mounts(&mounts).into_iter().map(|m| (m.mount_point, m.fs_type)).collect();
+        assert_eq!(storage, vec![
+            ("/".to_string(), "ext4".to_string()),
+            ("/boot".to_string(), "ext4".to_string()),
+            ("/mnt/my disk".to_string(), "xfs".to_string()),
+            ("/data".to_string(), "btrfs".to_string()),
+        ]);
+
+        let usage = statvfs("/").unwrap();
+        assert!(usage.total > 0 && usage.total >= usage.used);
+        assert!(statvfs("/nonexistent/minirobot").is_err());
+        assert_eq!(FsUsage { total: 100, used: 22, available: 77, ..Default::default() }.use_percent(), 23);
+    }
+
+    #[test]
+    fn test_procfs_04() {
+        let devices = read_block_devices(&fixture("sys")).unwrap();
+        assert_eq!(devices, vec![
+            BlockDevice { name: "nvme0n1".to_string(), size: 500_118_192 * 512, model: "Samsung SSD 970".to_string(), rotational: false },
+            BlockDevice { name: "sda".to_string(), size: 41_943_040 * 512, model: "VBOX HARDDISK".to_string(), rotational: true },
+        ]);
+        assert_eq!(format_block_size(41_943_040 * 512), "20G");
+        assert_eq!(format_block_size(500_118_192 * 512), "238.5G");
+        assert_eq!(format_block_size(0), "0B");
+    }
+
+    #[test]
+    fn test_procfs_05() {
+        let tcp = parse_net_sockets(&read_fixture("proc/net/tcp"), "tcp");
+        assert_eq!(tcp.len(), 3);
+        assert_eq!(tcp[0].local_address(), "127.0.0.1:631");
+        assert_eq!(tcp[0].state, "LISTEN");
+        assert_eq!(tcp[1].local_address(), "0.0.0.0:22");
+        assert_eq!(tcp[1].remote_address(), "0.0.0.0:*");
+        assert_eq!(tcp[1].inode, 23456);
+        assert_eq!(tcp[2].local_address(), "192.168.1.10:22");
+        assert_eq!(tcp[2].remote_address(), "192.168.1.20:51234");
+        assert_eq!(tcp[2].state, "ESTABLISHED");
+
+        let tcp6 = parse_net_sockets(&read_fixture("proc/net/tcp6"), "tcp6");
+        assert_eq!(tcp6[0].local_address(), ":::22");
+        assert_eq!(tcp6[1].local_address(), "::1:631");
+        assert_eq!(tcp6[2].local_ip, "fe80::1".parse::<IpAddr>().unwrap());
+
+        let udp = parse_net_sockets(&read_fixture("proc/net/udp"), "udp");
+        assert_eq!(udp[0].local_address(), "0.0.0.0:68");
+        assert_eq!(udp[0].state, "");
+
+        let all = read_net_sockets(&fixture("proc"));
+        assert_eq!(all.len(), tcp.len() + tcp6.len() + udp.len());
+
+        let pids = socket_inode_pids(&fixture("proc"));
+        assert_eq!(pids.get(&23456), Some(&1234));
+        assert_eq!(pids.get(&34567), Some(&1234));
+        assert_eq!(pids.len(), 2);
+    }
+
+    #[test]
+    fn test_procfs_06() {
+        let (total, cores) = parse_stat(&read_fixture("proc/stat"));
+        assert_eq!(total, CpuTimes { busy: 1000 + 20 + 300 + 10 + 5 + 15, idle: 8000 + 150 });
+        assert_eq!(cores.len(), 2);
+        assert_eq!(cores[1], CpuTimes { busy: 500 + 10 + 150 + 5 + 2 + 8, idle: 4000 + 75 });
+        assert_eq!(total.total(), 9500);
+
+        assert_eq!(parse_loadavg(&read_fixture("proc/loadavg")), Some((0.52, 0.58, 1.5)));
+        assert_eq!(parse_loadavg("bad"), None);
+        assert_eq!(parse_pressure(&read_fixture("proc/pressure/memory")), (Some(1.25), Some(0.5)));
+        assert_eq!(parse_pressure(""), (None, None));
+
+        let disks = parse_diskstats(&read_fixture("proc/diskstats"));
+        assert_eq!(disks.len(), 3);
+        assert_eq!(disks[0], DiskStat { name: "sda".to_string(), reads: 1200, read_sectors: 96000, writes: 800, write_sectors: 64000 });
+
+        let nics = parse_net_dev(&read_fixture("proc/net/dev"));
+        assert_eq!(nics.len(), 2);
+        assert_eq!(nics[1].name, "eth0");
+        assert_eq!((nics[1].rx_bytes, nics[1].rx_packets, nics[1].rx_errors, nics[1].rx_drops), (1_000_000, 1000, 1, 2));
+        assert_eq!((nics[1].tx_bytes, nics[1].tx_packets, nics[1].tx_errors, nics[1].tx_drops), (500_000, 800, 3, 4));
+    }
+}
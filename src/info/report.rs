@@ -0,0 +1,297 @@
+use std::fmt;
+use std::str::FromStr;
+use std::thread::{self, ScopedJoinHandle};
+
+use colored::Colorize;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::common::format::{self, OutputFormat};
+use crate::info;
+
+// 本机信息的模块
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Section {
+    Os,
+    Cpu,
+    Mem,
+    Disk,
+    Net,
+    Proc,
+    Svc,
+}
+
+impl Section {
+    pub const ALL: [Section; 7] = [Section::Os, Section::Cpu, Section::Mem, Section::Disk, Section::Net, Section::Proc, Section::Svc];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Section::Os => "os",
+            Section::Cpu => "cpu",
+            Section::Mem => "mem",
+            Section::Disk => "disk",
+            Section::Net => "net",
+            Section::Proc => "proc",
+            Section::Svc => "svc",
+        }
+    }
+
+    // 区分多行数据的字段，输出 Prometheus 指标时作为标签
+    fn labels(&self) -> &'static [&'static str] {
+        match self {
+            Section::Disk => &["mount", "device", "fs_type"],
+            Section::Net => &["name"],
+            Section::Proc => &["pid", "command", "user"],
+            Section::Svc => &["protocol", "ip_address", "port", "status"],
+            _ => &[],
+        }
+    }
+}
+
+impl FromStr for Section {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Section::ALL.iter()
+            .find(|section| section.name() == s.to_lowercase())
+            .copied()
+            .ok_or_else(|| format!("unknown section '{}', expect os, cpu, mem, disk, net, proc or svc", s))
+    }
+}
+
+impl fmt::Display for Section {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+// 等待采集线程结束，采集线程 panic 时在当前线程继续 panic
+pub(crate) fn joined<T>(handle: ScopedJoinHandle<'_, T>) -> T {
+    handle.join().unwrap_or_else(|e| std::panic::resume_unwind(e))
+}
+
+// 按模块采集的本机信息，只包含选中的模块
+#[derive(Debug, Default, Serialize)]
+pub struct Report {
+    hostname: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    os: Option<info::os::OSInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cpu: Option<info::cpu::CpuInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mem: Option<info::memory::MemInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    disk: Option<info::disk::DiskInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    net: Option<Vec<info::network::NetworkInterface>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    proc: Option<Vec<info::process::ProcessInfo>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    svc: Option<Vec<info::service::Service>>,
+}
+
+impl Report {
+    /// 并行采集选中的模块，未选中的模块不采集
+    pub fn collect(sections: &[Section]) -> Self {
+        let selected = |section: Section| sections.contains(&section);
+        thread::scope(|scope| {
+            let os = selected(Section::Os).then(|| scope.spawn(info::os::OSInfo::new));
+            let cpu = selected(Section::Cpu).then(|| scope.spawn(info::cpu::CpuInfo::new));
+            let mem = selected(Section::Mem).then(|| scope.spawn(info::memory::MemInfo::new));
+            let disk = selected(Section::Disk).then(|| scope.spawn(info::disk::DiskInfo::new));
+            let net = selected(Section::Net).then(|| scope.spawn(|| info::network::get_nics().0));
+            let proc = selected(Section::Proc).then(|| scope.spawn(info::process::gather_process_info));
+            let svc = selected(Section::Svc).then(|| scope.spawn(info::service::get_services));
+            let hostname = info::hostname::hostname();
+            Report {
+                hostname,
+                os: os.map(joined),
+                cpu: cpu.map(joined),
+                mem: mem.map(joined),
+                disk: disk.map(joined),
+                net: net.map(joined),
+                proc: proc.map(joined),
+                svc: svc.map(joined),
+            }
+        })
+    }
+
+    /// 只包含进程模块的报告，用于输出过滤后的进程
+    pub fn from_processes(processes: Vec<info::process::ProcessInfo>) -> Self {
+        Report {
+            hostname: info::hostname::hostname(),
+            proc: Some(processes),
+            ..Default::default()
+        }
+    }
+
+    pub fn hostname(&self) -> &str {
+        &self.hostname
+    }
+
+    /// 已采集的模块
+    pub fn sections(&self) -> Vec<Section> {
+        Section::ALL.iter().copied().filter(|section| self.value(*section).is_some()).collect()
+    }
+
+    fn value(&self, section: Section) -> Option<Value> {
+        let value = match section {
+            Section::Os => self.os.as_ref().map(serde_json::to_value),
+            Section::Cpu => self.cpu.as_ref().map(serde_json::to_value),
+            Section::Mem => self.mem.as_ref().map(serde_json::to_value),
+            Section::Disk => self.disk.as_ref().map(serde_json::to_value),
+            Section::Net => self.net.as_ref().map(serde_json::to_value),
+            Section::Proc => self.proc.as_ref().map(serde_json::to_value),
+            Section::Svc => self.svc.as_ref().map(serde_json::to_value),
+        };
+        value.and_then(|value| value.ok())
+    }
+
+    /// 模块数据展开后的行：列表每项一行，单个对象为一行，磁盘以分区为行
+    pub fn rows(&self, section: Section) -> Vec<Map<String, Value>> {
+        let value = match (section, self.value(section)) {
+            (Section::Disk, Some(mut value)) => value["partitions"].take(),
+            (_, Some(value)) => value,
+            (_, None) => return vec![],
+        };
+        match &value {
+            Value::Array(items) => items.iter().map(format::flatten).collect(),
+            _ => vec![format::flatten(&value)],
+        }
+    }
+
+    /// 各模块自带的可读输出
+    pub fn display(&self) -> String {
+        let mut output = format!("\nHostname: {}", &self.hostname.green().bold().to_string());
+        println!("{}\n", output);
+        if let Some(os) = &self.os {
+            output.push_str(&os.display());
+        }
+        if let Some(cpu) = &self.cpu {
+            output.push_str(&cpu.display());
+        }
+        if let Some(mem) = &self.mem {
+            output.push_str(&mem.display());
+        }
+        if let Some(disk) = &self.disk {
+            output.push_str(&disk.display());
+        }
+        for nic in self.net.iter().flatten() {
+            output.push_str(&nic.display());
+        }
+        if let Some(processes) = &self.proc {
+            println!("{}", info::process::ProcessInfo::header());
+            for process in processes {
+                output.push_str(&process.display());
+            }
+        }
+        for service in self.svc.iter().flatten() {
+            output.push_str(&service.display());
+        }
+        output
+    }
+
+    /// 按格式输出，指定 fields 时只输出这些字段（如 "pid,command"，"link" 包括 "link.mtu" 等子字段）
+    ///
+    /// JSON 和 YAML 未指定字段时输出完整结构，指定字段时输出各模块投影后的行；text 与 table 相同，各模块自带的可读输出见 display。
+    pub fn render(&self, output_format: OutputFormat, fields: Option<&[String]>) -> Result<String, String> {
+        let sections = self.sections();
+        if let Some(fields) = fields {
+            let rows: Vec<Map<String, Value>> = sections.iter().flat_map(|section| self.rows(*section)).collect();
+            let unknown = format::unknown_fields(&rows, fields);
+            if !unknown.is_empty() {
+                return Err(format!("unknown fields: {}", unknown.join(",")));
+            }
+        }
+        let projected = |section: Section| -> Vec<Map<String, Value>> {
+            let rows = self.rows(section);
+            match fields {
+                Some(fields) => rows.iter().map(|row| format::project(row, fields)).filter(|row| !row.is_empty()).collect(),
+                None => rows,
+            }
+        };
+        let structured = || -> Value {
+            match fields {
+                Some(_) => {
+                    let mut map = Map::new();
+                    map.insert("hostname".to_string(), Value::String(self.hostname.clone()));
+                    for section in &sections {
+                        let rows = projected(*section);
+                        if !rows.is_empty() {
+                            map.insert(section.name().to_string(), Value::Array(rows.into_iter().map(Value::Object).collect()));
+                        }
+                    }
+                    Value::Object(map)
+                }
+                None => serde_json::to_value(self).unwrap_or_default(),
+            }
+        };
+        // 多个模块的表格和 CSV 之间空一行，表格前加模块名
+        let blocks = |render: fn(&[Map<String, Value>]) -> String, titled: bool| -> String {
+            sections.iter()
+                .map(|section| (section, projected(*section)))
+                .filter(|(_, rows)| !rows.is_empty())
+                .map(|(section, rows)| if titled && sections.len() > 1 { format!("[{}]\n{}", section, render(&rows)) } else { render(&rows) })
+                .collect::<Vec<String>>()
+                .join("\n")
+        };
+
+        Ok(match output_format {
+            OutputFormat::Text | OutputFormat::Table => blocks(format::to_table, true),
+            OutputFormat::Csv => blocks(format::to_csv, false),
+            OutputFormat::Json => structured().to_string(),
+            OutputFormat::JsonPretty => serde_json::to_string_pretty(&structured()).unwrap_or_default(),
+            OutputFormat::Yaml => format::to_yaml(&structured()),
+            OutputFormat::Prometheus => {
+                let mut output = format!("# TYPE minirobot_info gauge\nminirobot_info{{hostname=\"{}\"}} 1\n", self.hostname);
+                for section in &sections {
+                    output.push_str(&format::to_prometheus(&format!("minirobot_{}", section), &self.rows(*section), section.labels(), fields));
+                }
+                output
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod unit_test_report {
+    use super::*;
+
+    #[test]
+    fn test_report_01() {
+        assert_eq!("proc".parse::<Section>().unwrap(), Section::Proc);
+        assert!("gpu".parse::<Section>().is_err());
+
+        let report = Report::collect(&[Section::Mem, Section::Disk]);
+        assert_eq!(report.sections(), vec![Section::Mem, Section::Disk]);
+        let value: Value = serde_json::from_str(&report.render(OutputFormat::Json, None).unwrap()).unwrap();
+        assert!(value.get("mem").is_some() && value.get("proc").is_none());
+        assert_eq!(report.rows(Section::Mem).len(), 1);
+
+        let fields = vec!["memory_total".to_string()];
+        let prometheus = report.render(OutputFormat::Prometheus, Some(&fields)).unwrap();
+        assert!(prometheus.contains("\nminirobot_mem_memory_total "));
+        assert!(!prometheus.contains("minirobot_mem_swap_total"));
+        assert!(report.render(OutputFormat::Csv, Some(&["nope".to_string()])).is_err());
+    }
+
+    #[test]
+    fn test_report_02() {
+        let processes = info::process::gather_process_info();
+        let count = processes.len();
+        let report = Report::from_processes(processes);
+        let fields = vec!["pid".to_string(), "command".to_string()];
+        let csv = report.render(OutputFormat::Csv, Some(&fields)).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "pid,command");
+        assert_eq!(lines.len(), count + 1);
+        assert!(lines.contains(&format!("{},{}", std::process::id(), report.proc.as_ref().unwrap().iter()
+            .find(|p| p.pid == std::process::id()).unwrap().command).as_str()));
+
+        let yaml = report.render(OutputFormat::Yaml, Some(&fields)).unwrap();
+        assert!(yaml.starts_with("---\nhostname: "));
+        assert!(yaml.contains("\nproc:\n  - pid: "));
+        let table = report.render(OutputFormat::Table, Some(&fields)).unwrap();
+        assert!(table.starts_with("PID"));
+    }
+}
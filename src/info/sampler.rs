@@ -0,0 +1,445 @@
+//! 资源使用率时序采样
+//!
+//! 按固定间隔读取 CPU、磁盘、网卡的累计计数，以相邻两次的差值计算速率，结果保存在环形缓冲区中，
+//! 可选追加写入 JSON Lines 文件。
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local, Utc};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+
+use crate::common::api::format_size;
+use crate::common::ds::RingBuffer;
+use crate::info::error::{InfoError, InfoResult};
+
+// CPU 累计时间，单位 jiffies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CpuTicks {
+    pub busy: u64,
+    pub idle: u64,
+}
+
+// 磁盘累计读写
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DiskCounter {
+    pub name: String,
+    pub reads: u64,
+    pub read_bytes: u64,
+    pub writes: u64,
+    pub write_bytes: u64,
+}
+
+// 网卡累计收发
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct NicCounter {
+    pub name: String,
+    pub rx_bytes: u64,
+    pub rx_packets: u64,
+    pub rx_errors: u64,
+    pub rx_drops: u64,
+    pub tx_bytes: u64,
+    pub tx_packets: u64,
+    pub tx_errors: u64,
+    pub tx_drops: u64,
+}
+
+// 一次读取的累计计数及瞬时值
+#[derive(Debug, Clone)]
+pub struct Counters {
+    pub instant: Instant,
+    pub timestamp: DateTime<Utc>,
+    pub cpu_total: CpuTicks,
+    pub cpu_cores: Vec<CpuTicks>,
+    pub load: LoadAverage,
+    pub memory: MemoryPressure,
+    pub disks: Vec<DiskCounter>,
+    pub nics: Vec<NicCounter>,
+}
+
+// 平均负载
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct LoadAverage {
+    pub one: f64,
+    pub five: f64,
+    pub fifteen: f64,
+}
+
+// 内存压力
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct MemoryPressure {
+    pub used_percent: f64,
+    pub available: u64,                      // 字节
+    pub swap_used_percent: f64,
+    pub psi_some: Option<f64>,               // /proc/pressure/memory 10 秒平均，内核未启用 PSI 时为 None
+    pub psi_full: Option<f64>,
+}
+
+// 磁盘速率
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiskRate {
+    pub name: String,
+    pub read_iops: f64,
+    pub write_iops: f64,
+    pub read_bytes_per_sec: f64,
+    pub write_bytes_per_sec: f64,
+}
+
+// 网卡速率，错误和丢包为区间内的增量
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NicRate {
+    pub name: String,
+    pub rx_bytes_per_sec: f64,
+    pub tx_bytes_per_sec: f64,
+    pub rx_packets_per_sec: f64,
+    pub tx_packets_per_sec: f64,
+    pub rx_errors: u64,
+    pub tx_errors: u64,
+    pub rx_drops: u64,
+    pub tx_drops: u64,
+}
+
+// 一个采样区间的结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Sample {
+    pub timestamp: DateTime<Utc>,            // 区间结束时间
+    pub interval_secs: f64,
+    pub cpu_percent: f64,
+    pub cpu_core_percent: Vec<f64>,
+    pub load: LoadAverage,
+    pub memory: MemoryPressure,
+    pub disks: Vec<DiskRate>,
+    pub nics: Vec<NicRate>,
+}
+
+fn percent(part: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 * 100.0 / total as f64
+    }
+}
+
+fn cpu_percent(old: &CpuTicks, new: &CpuTicks) -> f64 {
+    let busy = new.busy.saturating_sub(old.busy);
+    let idle = new.idle.saturating_sub(old.idle);
+    percent(busy, busy + idle)
+}
+
+impl Sample {
+    /// 以两次计数的差值计算速率，计数回绕或设备重置时按 0 处理，只在一侧出现的设备忽略
+    pub fn between(old: &Counters, new: &Counters) -> Sample {
+        let secs = new.instant.saturating_duration_since(old.instant).as_secs_f64();
+        let rate = |old: u64, new: u64| if secs > 0.0 { new.saturating_sub(old) as f64 / secs } else { 0.0 };
+
+        let disks = new.disks.iter().filter_map(|n| {
+            let o = old.disks.iter().find(|o| o.name == n.name)?;
+            Some(DiskRate {
+                name: n.name.clone(),
+                read_iops: rate(o.reads, n.reads),
+                write_iops: rate(o.writes, n.writes),
+                read_bytes_per_sec: rate(o.read_bytes, n.read_bytes),
+                write_bytes_per_sec: rate(o.write_bytes, n.write_bytes),
+            })
+        }).collect();
+
+        let nics = new.nics.iter().filter_map(|n| {
+            let o = old.nics.iter().find(|o| o.name == n.name)?;
+            Some(NicRate {
+                name: n.name.clone(),
+                rx_bytes_per_sec: rate(o.rx_bytes, n.rx_bytes),
+                tx_bytes_per_sec: rate(o.tx_bytes, n.tx_bytes),
+                rx_packets_per_sec: rate(o.rx_packets, n.rx_packets),
+                tx_packets_per_sec: rate(o.tx_packets, n.tx_packets),
+                rx_errors: n.rx_errors.saturating_sub(o.rx_errors),
+                tx_errors: n.tx_errors.saturating_sub(o.tx_errors),
+                rx_drops: n.rx_drops.saturating_sub(o.rx_drops),
+                tx_drops: n.tx_drops.saturating_sub(o.tx_drops),
+            })
+        }).collect();
+
+        Sample {
+            timestamp: new.timestamp,
+            interval_secs: secs,
+            cpu_percent: cpu_percent(&old.cpu_total, &new.cpu_total),
+            cpu_core_percent: old.cpu_cores.iter().zip(new.cpu_cores.iter()).map(|(o, n)| cpu_percent(o, n)).collect(),
+            load: new.load,
+            memory: new.memory,
+            disks,
+            nics,
+        }
+    }
+
+    pub fn display(&self) -> String {
+        let mut output = format!("{} CPU {} load {:.2} {:.2} {:.2} mem {} swap {:.1}%",
+            self.timestamp.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S"),
+            format!("{:.1}%", self.cpu_percent).yellow(),
+            self.load.one, self.load.five, self.load.fifteen,
+            format!("{:.1}%", self.memory.used_percent).yellow(),
+            self.memory.swap_used_percent);
+        if let Some(some) = self.memory.psi_some {
+            output.push_str(&format!(" psi {:.2}/{:.2}", some, self.memory.psi_full.unwrap_or(0.0)));
+        }
+        output.push_str(&format!("\n  cores: {}", self.cpu_core_percent.iter()
+            .map(|p| format!("{:.0}%", p)).collect::<Vec<_>>().join(" ")));
+        for disk in &self.disks {
+            output.push_str(&format!("\n  disk {:<10} read {:>8.1}/s {:>10}/s  write {:>8.1}/s {:>10}/s",
+                disk.name, disk.read_iops, format_size(disk.read_bytes_per_sec as u64).green(),
+                disk.write_iops, format_size(disk.write_bytes_per_sec as u64).blue()));
+        }
+        for nic in &self.nics {
+            output.push_str(&format!("\n  nic  {:<10} rx {:>10}/s {:>8.1} pkt/s  tx {:>10}/s {:>8.1} pkt/s  err {}/{} drop {}/{}",
+                nic.name, format_size(nic.rx_bytes_per_sec as u64).green(), nic.rx_packets_per_sec,
+                format_size(nic.tx_bytes_per_sec as u64).blue(), nic.tx_packets_per_sec,
+                nic.rx_errors, nic.tx_errors, nic.rx_drops, nic.tx_drops));
+        }
+        println!("{}\n", output);
+        output
+    }
+
+    /// 转换为 JSON 字符串
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|err| {
+            eprintln!("序列化采样数据到 JSON 失败: {}", err);
+            "{}".to_string()
+        })
+    }
+}
+
+/// 读取当前累计计数
+#[cfg(target_os = "linux")]
+pub fn read_counters() -> InfoResult<Counters> {
+    read_counters_from(Path::new(crate::info::procfs::PROC_ROOT), Path::new(crate::info::procfs::SYS_ROOT))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn read_counters() -> InfoResult<Counters> {
+    Err(InfoError::Unsupported("resource sampling".to_string()))
+}
+
+/// 从指定的 /proc 和 /sys 读取计数，磁盘只统计 /sys/block 下的整盘设备，排除 loop 和 ram
+#[cfg(target_os = "linux")]
+pub fn read_counters_from(proc_root: &Path, sys_root: &Path) -> InfoResult<Counters> {
+    use crate::info::error::read_file;
+    use crate::info::procfs;
+
+    let instant = Instant::now();
+    let ticks = |t: procfs::CpuTimes| CpuTicks { busy: t.busy, idle: t.idle };
+    let (cpu_total, cpu_cores) = procfs::parse_stat(&read_file(&proc_root.join("stat"))?);
+
+    let loadavg = read_file(&proc_root.join("loadavg"))?;
+    let (one, five, fifteen) = procfs::parse_loadavg(&loadavg).ok_or_else(|| InfoError::parse("loadavg", &loadavg))?;
+
+    let mem = procfs::parse_meminfo(&read_file(&proc_root.join("meminfo"))?);
+    let (psi_some, psi_full) = std::fs::read_to_string(proc_root.join("pressure/memory"))
+        .map(|content| procfs::parse_pressure(&content))
+        .unwrap_or((None, None));
+
+    let disks = procfs::parse_diskstats(&read_file(&proc_root.join("diskstats"))?).into_iter()
+        .filter(|d| !d.name.starts_with("loop") && !d.name.starts_with("ram") && sys_root.join("block").join(&d.name).exists())
+        .map(|d| DiskCounter {
+            name: d.name,
+            reads: d.reads,
+            read_bytes: d.read_sectors * 512,
+            writes: d.writes,
+            write_bytes: d.write_sectors * 512,
+        }).collect();
+
+    let nics = procfs::parse_net_dev(&read_file(&proc_root.join("net/dev"))?).into_iter()
+        .map(|n| NicCounter {
+            name: n.name,
+            rx_bytes: n.rx_bytes,
+            rx_packets: n.rx_packets,
+            rx_errors: n.rx_errors,
+            rx_drops: n.rx_drops,
+            tx_bytes: n.tx_bytes,
+            tx_packets: n.tx_packets,
+            tx_errors: n.tx_errors,
+            tx_drops: n.tx_drops,
+        }).collect();
+
+    Ok(Counters {
+        instant,
+        timestamp: Utc::now(),
+        cpu_total: ticks(cpu_total),
+        cpu_cores: cpu_cores.into_iter().map(ticks).collect(),
+        load: LoadAverage { one, five, fifteen },
+        memory: MemoryPressure {
+            used_percent: percent(mem.mem_used, mem.mem_total),
+            available: mem.mem_total.saturating_sub(mem.mem_used),
+            swap_used_percent: percent(mem.swap_used, mem.swap_total),
+            psi_some,
+            psi_full,
+        },
+        disks,
+        nics,
+    })
+}
+
+/// 间隔 window 读取两次计数，返回该区间的采样
+pub fn measure(window: Duration) -> InfoResult<Sample> {
+    let old = read_counters()?;
+    thread::sleep(window);
+    Ok(Sample::between(&old, &read_counters()?))
+}
+
+/// 读取 JSON Lines 格式的采样记录，跳过无法解析的行
+pub fn load_samples(path: &Path) -> InfoResult<Vec<Sample>> {
+    let content = crate::info::error::read_file(path)?;
+    Ok(content.lines().filter_map(|line| serde_json::from_str(line).ok()).collect())
+}
+
+// 定时采样器
+#[derive(Debug)]
+pub struct Sampler {
+    interval: Duration,
+    previous: Option<Counters>,
+    samples: RingBuffer<Sample>,
+    persist_path: Option<PathBuf>,
+}
+
+impl Sampler {
+    /// interval 为采样间隔，capacity 为内存中保留的采样数
+    pub fn new(interval: Duration, capacity: usize) -> Self {
+        Sampler {
+            interval,
+            previous: None,
+            samples: RingBuffer::new(capacity),
+            persist_path: None,
+        }
+    }
+
+    /// 每个采样同时追加写入 path（JSON Lines）
+    pub fn persist_to(mut self, path: &Path) -> Self {
+        self.persist_path = Some(path.to_path_buf());
+        self
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    pub fn samples(&self) -> &RingBuffer<Sample> {
+        &self.samples
+    }
+
+    /// 读取一次计数，与上一次计数组成采样；首次调用只记录基准，返回 None
+    pub fn sample(&mut self) -> InfoResult<Option<Sample>> {
+        let counters = read_counters()?;
+        Ok(self.record(counters))
+    }
+
+    fn record(&mut self, counters: Counters) -> Option<Sample> {
+        let sample = self.previous.as_ref().map(|previous| Sample::between(previous, &counters));
+        self.previous = Some(counters);
+        let sample = sample?;
+        if let Some(path) = &self.persist_path {
+            if let Err(e) = append_sample(path, &sample) {
+                log::warn!("保存采样数据失败: {}", e);
+            }
+        }
+        self.samples.push(sample.clone());
+        Some(sample)
+    }
+
+    /// 持续采样，每得到一个采样调用一次 on_sample；count 为采样数上限，None 表示不限
+    pub fn run<F: FnMut(&Sample)>(&mut self, count: Option<u64>, mut on_sample: F) -> InfoResult<()> {
+        let mut taken = 0;
+        if self.previous.is_none() {
+            self.sample()?;
+        }
+        while count.is_none_or(|count| taken < count) {
+            thread::sleep(self.interval);
+            if let Some(sample) = self.sample()? {
+                on_sample(&sample);
+                taken += 1;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn append_sample(path: &Path, sample: &Sample) -> InfoResult<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path).map_err(|e| InfoError::io(path, e))?;
+    writeln!(file, "{}", sample.to_json()).map_err(|e| InfoError::io(path, e))
+}
+
+#[cfg(test)]
+mod unit_test_sampler {
+    use super::*;
+
+    fn counters(base: Instant, secs_later: u64, busy: u64, idle: u64, read_bytes: u64, rx_bytes: u64) -> Counters {
+        Counters {
+            instant: base + Duration::from_secs(secs_later),
+            timestamp: Utc::now(),
+            cpu_total: CpuTicks { busy: busy * 2, idle: idle * 2 },
+            cpu_cores: vec![CpuTicks { busy, idle }, CpuTicks { busy, idle }],
+            load: LoadAverage { one: 0.5, five: 0.4, fifteen: 0.3 },
+            memory: MemoryPressure { used_percent: 40.0, available: 6 << 30, ..Default::default() },
+            disks: vec![DiskCounter { name: "sda".to_string(), reads: read_bytes / 4096, read_bytes, ..Default::default() }],
+            nics: vec![NicCounter { name: "eth0".to_string(), rx_bytes, rx_packets: rx_bytes / 1000, rx_drops: rx_bytes / 100_000, ..Default::default() }],
+        }
+    }
+
+    #[test]
+    fn test_sampler_01() {
+        let base = Instant::now();
+        let old = counters(base, 0, 100, 900, 0, 0);
+        let new = counters(base, 2, 150, 1050, 8192 * 100, 2_000_000);
+        let sample = Sample::between(&old, &new);
+        assert_eq!(sample.interval_secs, 2.0);
+        assert_eq!(sample.cpu_percent, 25.0);
+        assert_eq!(sample.cpu_core_percent, vec![25.0, 25.0]);
+        assert_eq!(sample.disks[0].read_bytes_per_sec, 409_600.0);
+        assert_eq!(sample.disks[0].read_iops, 100.0);
+        assert_eq!(sample.nics[0].rx_bytes_per_sec, 1_000_000.0);
+        assert_eq!(sample.nics[0].rx_packets_per_sec, 1000.0);
+        assert_eq!(sample.nics[0].rx_drops, 20);
+        assert!(sample.display().contains("nic  eth0"));
+
+        // 计数回绕按 0 处理
+        let sample = Sample::between(&new, &counters(base, 3, 10, 10, 0, 0));
+        assert_eq!((sample.cpu_percent, sample.nics[0].rx_bytes_per_sec), (0.0, 0.0));
+    }
+
+    #[test]
+    fn test_sampler_02() {
+        let base = Instant::now();
+        let path = std::env::temp_dir().join(format!("minirobot-samples-{}.jsonl", uuid::Uuid::new_v4()));
+        let mut sampler = Sampler::new(Duration::from_millis(10), 2).persist_to(&path);
+        assert!(sampler.record(counters(base, 0, 100, 900, 0, 0)).is_none());
+        for i in 1..=3 {
+            assert!(sampler.record(counters(base, i, 100 + i * 10, 900 + i * 90, 0, 0)).is_some());
+        }
+        assert_eq!(sampler.samples().len(), 2);
+        let saved = load_samples(&path).unwrap();
+        assert_eq!(saved.len(), 3);
+        assert_eq!(&saved[2], sampler.samples().latest().unwrap());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_sampler_03() {
+        let fixture = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/procfs");
+        let counters = read_counters_from(&fixture.join("proc"), &fixture.join("sys")).unwrap();
+        assert_eq!(counters.cpu_cores.len(), 2);
+        assert_eq!(counters.load.fifteen, 1.5);
+        assert_eq!(counters.memory.psi_some, Some(1.25));
+        // sda1 为分区、loop0 为回环设备，均不统计
+        assert_eq!(counters.disks.iter().map(|d| d.name.as_str()).collect::<Vec<_>>(), vec!["sda"]);
+        assert_eq!(counters.disks[0].read_bytes, 96000 * 512);
+        assert_eq!(counters.nics.len(), 2);
+
+        let mut sampler = Sampler::new(Duration::from_millis(50), 10);
+        let mut samples = Vec::new();
+        sampler.run(Some(2), |sample| samples.push(sample.clone())).unwrap();
+        assert_eq!(samples.len(), 2);
+        assert!(samples.iter().all(|s| (0.0..=100.0).contains(&s.cpu_percent) && s.interval_secs > 0.0));
+        assert!(measure(Duration::from_millis(50)).is_ok());
+    }
+}
@@ -1,10 +1,11 @@
+#[cfg(not(target_os = "linux"))]
 use std::process::Command;
 use std::str;
 
 use colored::Colorize;
 use serde::Serialize;
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct Service {
     ip_address: String,      // IP地址
     port: u16,               // 端口号
@@ -12,6 +13,7 @@ pub struct Service {
     status: String,          // 服务状态
     pid: u32,                // 进程号
     remote_address: String,  // 对端地址
+    loopback: bool,          // 本地或对端为回环地址
 }
 
 impl Service {
@@ -23,6 +25,7 @@ impl Service {
             status: status.to_string(),
             pid: *pid,
             remote_address: remote_address.to_string(),
+            loopback: is_loopback(ip_address) || is_loopback(remote_address),
         }
     }
 
@@ -56,6 +59,16 @@ impl Service {
         &self.remote_address
     }
 
+    // 是否为回环地址上的连接
+    pub fn is_loopback(&self) -> bool {
+        self.loopback
+    }
+
+    // TCP 为 LISTEN 状态，UDP 未连接，均视为监听
+    pub fn is_listening(&self) -> bool {
+        self.status.starts_with("LISTEN") || (self.protocol.starts_with("udp") && self.status.is_empty())
+    }
+
     pub fn display(&self) -> String {
         let output;
         let l_net_info = format!("{}:{} / {}", &self.ip_address, &self.port, &self.protocol);
@@ -78,40 +91,48 @@ impl Service {
     }
 }
 
+// 地址是否为回环地址，支持 "127.0.0.1"、"[::1]:22"、"::1:631" 等形式
+fn is_loopback(address: &str) -> bool {
+    let address = address.trim_start_matches('[');
+    address.starts_with("127.") || address.starts_with("::1]") || address == "::1" || address.starts_with("::1:")
+        || address.starts_with("::ffff:127.")
+}
+
 #[cfg(target_os = "windows")]
 pub fn get_services() -> Vec<Service> {
-    let output = Command::new("netstat")
-        .arg("-ano")
-        .output()
-        .expect("Failed to execute command");
+    let output = match Command::new("netstat").arg("-ano").output() {
+        Ok(output) => output,
+        Err(e) => {
+            log::warn!("执行 netstat 失败: {}", e);
+            return Vec::new();
+        }
+    };
     let output_str = String::from_utf8_lossy(&output.stdout);
 
     let mut services = Vec::new();
 
     for line in output_str.lines().skip(4) {
         let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() < 4 {
+            continue;
+        }
 
         let local_address = parts[1].to_string();
         let remote_address = parts[2].to_string();
-        if local_address.starts_with("127.0.0.") || local_address.starts_with("[::1]")
-            || remote_address.starts_with("127.0.0.") || remote_address.starts_with("[::1]") {
-            continue;
-        }
         let protocol = if parts[0].starts_with("TCP") { "tcp" } else { "udp" }.to_string();
 
-        let status = String::new();
-        let pid = 0u32;
-        if parts.len() >= 5 {
-            status = parts[3].to_string();
-            pid = parts[4].parse().unwrap_or(0u32);
+        // UDP 无状态列
+        let (status, pid) = if parts.len() >= 5 {
+            (parts[3].to_string(), parts[4].parse().unwrap_or(0u32))
         } else {
-            pid = parts[3].parse().unwrap_or(0u32)
-        }
+            (String::new(), parts[3].parse().unwrap_or(0u32))
+        };
 
-        let local_parts: Vec<&str> = local_address.rsplitn(2, ':').collect();
-        if local_parts.len() == 2 {
-            let ip_address = local_parts[1].trim_start_matches('[').trim_end_matches(']').to_string();
-            let port: u16 = local_parts[0].parse().expect("Invalid port");
+        if let Some((ip_address, port)) = local_address.rsplit_once(':') {
+            let Ok(port) = port.parse::<u16>() else {
+                continue;
+            };
+            let ip_address = ip_address.trim_start_matches('[').trim_end_matches(']').to_string();
             services.push(Service::new(
                 ip_address.as_str(),
                 &port,
@@ -128,62 +149,33 @@ pub fn get_services() -> Vec<Service> {
 
 #[cfg(target_os = "linux")]
 pub fn get_services() -> Vec<Service> {
-    let output = Command::new("netstat")
-        .arg("-tupn")
-        .arg("--all")
-        .output()
-        .expect("Failed to execute command");
-    let output_str = String::from_utf8_lossy(&output.stdout);
-
-    let mut services = Vec::new();
-
-    for line in output_str.lines().skip(2) {
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.len() >= 7 {
-            let local_address = parts[3].to_string();
-            let remote_address = parts[4].to_string();
-            if local_address.starts_with("127.0.0.") || local_address.starts_with("::1:") ||
-                remote_address.starts_with("127.0.0.") || remote_address.starts_with("::1:") {
-                continue;
-            }
-
-            let protocol = parts[0].to_string();
-            let test_str = parts[5].to_string();
-            let mut status = String::new();
-            let pid;
-            if test_str.contains('/') {
-                pid = parts[5].split('/').next().unwrap_or("0").parse().unwrap_or(0u32);
-            } else {
-                status = parts[5].to_string();
-                pid = parts[6].split('/').next().unwrap_or("0").parse().unwrap_or(0u32);
-            }
-            let local_parts: Vec<&str> = local_address.rsplitn(2, ':').collect();
-            if local_parts.len() == 2 {
-                let ip_address = local_parts[1].to_string();
-                let port = local_parts[0].parse().expect("Invalid port!");
-                services.push(Service::new(
-                    ip_address.as_str(),
-                    &port,
-                    protocol.as_str(),
-                    status.as_str(),
-                    &pid,
-                    remote_address.as_str(),
-                ))
-            }
-        }
-    }
-
-    services
+    use std::path::Path;
+    use crate::info::procfs;
+
+    let proc_root = Path::new(procfs::PROC_ROOT);
+    let pids = procfs::socket_inode_pids(proc_root);
+
+    procfs::read_net_sockets(proc_root).into_iter()
+        .map(|socket| Service::new(
+            &socket.local_ip.to_string(),
+            &socket.local_port,
+            &socket.protocol,
+            &socket.state,
+            pids.get(&socket.inode).unwrap_or(&0u32),
+            &socket.remote_address(),
+        ))
+        .collect()
 }
 
 #[cfg(target_os = "macos")]
 pub fn get_services() -> Vec<Service> {
-    let output = Command::new("lsof")
-        .arg("-i")
-        .arg("-n")
-        .arg("-P")
-        .output()
-        .expect("Failed to execute command");
+    let output = match Command::new("lsof").arg("-i").arg("-n").arg("-P").output() {
+        Ok(output) => output,
+        Err(e) => {
+            log::warn!("执行 lsof 失败: {}", e);
+            return Vec::new();
+        }
+    };
     let output_str = String::from_utf8_lossy(&output.stdout);
 
     let mut services = Vec::new();
@@ -195,32 +187,20 @@ pub fn get_services() -> Vec<Service> {
             let protocol = parts[7].to_string();
             let addresses = parts[8];
 
-            let mut local_address = String::new();
-            let mut remote_address = String::new();
-
-            if let Some((local, remote)) = addresses.split_once("->") {
-                local_address = local.to_string();
-                remote_address = remote.to_string();
-            } else {
-                local_address = addresses.to_string();
-            }
-            if local_address.starts_with("127.0.0.") || local_address.starts_with("::1:")
-                || remote_address.starts_with("127.0.0.") || remote_address.starts_with("::1:") {
-                continue;
-            }
-
-            let local_parts: Vec<&str> = local_address.split(':').collect();
-            if local_parts.len() == 2 {
-                let ip_address = local_parts[0].to_string();
-                let port: u16 = local_parts[1].parse().expect("Invalid port");
-                services.push(Service {
-                    ip_address,
-                    port,
-                    protocol,
-                    status: String::new(), // `lsof` doesn't provide status
-                    pid,
-                    remote_address,
-                });
+            let (local_address, remote_address) = match addresses.split_once("->") {
+                Some((local, remote)) => (local.to_string(), remote.to_string()),
+                None => (addresses.to_string(), String::new()),
+            };
+            // 状态形如 "(LISTEN)"，UDP 无状态
+            let status = parts.get(9).map(|s| s.trim_matches(|c| c == '(' || c == ')').to_string()).unwrap_or_default();
+
+            // IPv6 形如 [::1]:631，端口为 * 时跳过
+            if let Some((ip_address, port)) = local_address.rsplit_once(':') {
+                let Ok(port) = port.parse::<u16>() else {
+                    continue;
+                };
+                let ip_address = ip_address.trim_start_matches('[').trim_end_matches(']');
+                services.push(Service::new(ip_address, &port, &protocol.to_lowercase(), &status, &pid, &remote_address));
             }
         }
     }
@@ -0,0 +1,323 @@
+//! 套接字清单：监听端口和已建立连接，关联所属进程并识别服务
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::Read;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpStream};
+use std::time::Duration;
+
+use colored::Colorize;
+use serde::Serialize;
+
+use crate::info::process::{gather_process_info, ProcessInfo};
+use crate::info::service::{get_services, Service};
+
+// 套接字所属进程
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SocketOwner {
+    pub pid: u32,
+    pub name: String,                        // 可执行文件名，如 sshd
+    pub exe_path: String,
+    pub user: String,
+}
+
+impl SocketOwner {
+    fn from_process(process: &ProcessInfo) -> Self {
+        let path = if process.exe_path.is_empty() { &process.command } else { &process.exe_path };
+        SocketOwner {
+            pid: process.pid,
+            name: path.rsplit(['/', '\\']).next().unwrap_or_default().to_string(),
+            exe_path: process.exe_path.clone(),
+            user: process.user.clone(),
+        }
+    }
+}
+
+// 一个套接字
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Socket {
+    pub protocol: String,                    // tcp, tcp6, udp, udp6
+    pub local_ip: String,
+    pub local_port: u16,
+    pub remote_address: String,
+    pub state: String,
+    pub listening: bool,
+    pub loopback: bool,
+    pub owner: Option<SocketOwner>,          // 无权限查看或内核套接字时为 None
+    pub service: Option<String>,             // 识别出的服务，如 ssh、http
+}
+
+impl Socket {
+    /// 本地地址，IPv6 以方括号包围
+    pub fn local_address(&self) -> String {
+        if self.local_ip.contains(':') {
+            format!("[{}]:{}", self.local_ip, self.local_port)
+        } else {
+            format!("{}:{}", self.local_ip, self.local_port)
+        }
+    }
+
+    /// 描述，如 "sshd on 0.0.0.0:22"，无所属进程时以服务名或 "unknown" 代替
+    pub fn describe(&self) -> String {
+        let name = self.owner.as_ref().map(|o| o.name.clone())
+            .or_else(|| self.service.clone())
+            .unwrap_or_else(|| "unknown".to_string());
+        format!("{} on {}", name, self.local_address())
+    }
+}
+
+// 常见端口对应的服务
+const WELL_KNOWN_PORTS: [(u16, &str); 33] = [
+    (20, "ftp-data"), (21, "ftp"), (22, "ssh"), (23, "telnet"), (25, "smtp"), (53, "dns"), (67, "dhcp"),
+    (68, "dhcp"), (69, "tftp"), (80, "http"), (110, "pop3"), (111, "rpcbind"), (123, "ntp"), (137, "netbios"), (139, "netbios"),
+    (143, "imap"), (161, "snmp"), (389, "ldap"), (443, "https"), (445, "smb"), (465, "smtps"), (514, "syslog"),
+    (587, "smtp"), (631, "ipp"), (993, "imaps"), (995, "pop3s"), (1433, "mssql"), (1521, "oracle"),
+    (3306, "mysql"), (3389, "rdp"), (5432, "postgresql"), (6379, "redis"), (27017, "mongodb"),
+];
+
+// 常见的非标准 HTTP 端口
+const HTTP_ALT_PORTS: [u16; 4] = [8000, 8008, 8080, 8888];
+
+/// 按端口猜测服务
+pub fn guess_service_by_port(port: u16) -> Option<&'static str> {
+    if HTTP_ALT_PORTS.contains(&port) {
+        return Some("http");
+    }
+    WELL_KNOWN_PORTS.iter().find(|(p, _)| *p == port).map(|(_, name)| *name)
+}
+
+/// 按连接后服务端首先发送的欢迎信息猜测服务
+pub fn guess_service_by_banner(banner: &[u8]) -> Option<&'static str> {
+    let text = String::from_utf8_lossy(banner);
+    let upper = text.to_uppercase();
+    if text.starts_with("SSH-") {
+        Some("ssh")
+    } else if text.starts_with("HTTP/") {
+        Some("http")
+    } else if text.starts_with("220") && upper.contains("FTP") {
+        Some("ftp")
+    } else if text.starts_with("220") && upper.contains("SMTP") {
+        Some("smtp")
+    } else if text.starts_with("+OK") {
+        Some("pop3")
+    } else if text.starts_with("* OK") {
+        Some("imap")
+    } else if text.starts_with("-ERR") || text.starts_with("-NOAUTH") {
+        Some("redis")
+    } else if banner.len() > 5 && banner[4] == 0x0a && (upper.contains("MYSQL") || upper.contains("MARIADB") || text[5..].starts_with(|c: char| c.is_ascii_digit())) {
+        // MySQL 握手包：3 字节长度 + 1 字节序号 + 协议版本 10 + 以 NUL 结尾的版本号
+        Some("mysql")
+    } else {
+        None
+    }
+}
+
+/// 连接 address 读取欢迎信息，服务端不主动发送时返回 None
+pub fn probe_banner(address: SocketAddr, timeout: Duration) -> Option<Vec<u8>> {
+    let mut stream = TcpStream::connect_timeout(&address, timeout).ok()?;
+    stream.set_read_timeout(Some(timeout)).ok()?;
+    let mut buffer = [0u8; 256];
+    match stream.read(&mut buffer) {
+        Ok(n) if n > 0 => Some(buffer[..n].to_vec()),
+        _ => None,
+    }
+}
+
+// 套接字清单
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct SocketInventory {
+    pub listening: Vec<Socket>,
+    pub connections: Vec<Socket>,            // 已建立或正在关闭的连接
+}
+
+impl SocketInventory {
+    /// 采集本机套接字并关联进程
+    pub fn collect() -> Self {
+        Self::from_parts(&get_services(), &gather_process_info())
+    }
+
+    /// 由套接字和进程列表生成清单，按端口猜测服务
+    pub fn from_parts(services: &[Service], processes: &[ProcessInfo]) -> Self {
+        let owners: HashMap<u32, SocketOwner> = processes.iter().map(|p| (p.pid, SocketOwner::from_process(p))).collect();
+        let mut inventory = SocketInventory::default();
+        for service in services {
+            let listening = service.is_listening();
+            // 主动发起的连接本地端口为临时端口，按对端端口猜测
+            let remote_port = service.remote_address().rsplit_once(':').and_then(|(_, port)| port.parse::<u16>().ok());
+            let guessed = guess_service_by_port(service.port())
+                .or_else(|| if listening { None } else { remote_port.and_then(guess_service_by_port) });
+            let socket = Socket {
+                protocol: service.protocol().to_string(),
+                local_ip: service.ip_address().to_string(),
+                local_port: service.port(),
+                remote_address: service.remote_address().to_string(),
+                state: service.status().to_string(),
+                listening,
+                loopback: service.is_loopback(),
+                owner: owners.get(&service.pid()).cloned(),
+                service: guessed.map(str::to_string),
+            };
+            if socket.listening {
+                if !inventory.listening.iter().any(|s| s.protocol == socket.protocol && s.local_address() == socket.local_address()) {
+                    inventory.listening.push(socket);
+                }
+            } else {
+                inventory.connections.push(socket);
+            }
+        }
+        inventory.listening.sort_by(|a, b| (a.local_port, &a.protocol, &a.local_ip).cmp(&(b.local_port, &b.protocol, &b.local_ip)));
+        inventory
+    }
+
+    /// 连接各 TCP 监听端口读取欢迎信息，识别出的服务覆盖按端口猜测的结果
+    pub fn identify_by_banner(&mut self, timeout: Duration) {
+        for socket in self.listening.iter_mut().filter(|s| s.protocol.starts_with("tcp")) {
+            let Ok(ip) = socket.local_ip.parse::<IpAddr>() else {
+                continue;
+            };
+            // 监听所有地址时经回环地址连接
+            let ip = match ip {
+                IpAddr::V4(v4) if v4.is_unspecified() => IpAddr::V4(Ipv4Addr::LOCALHOST),
+                IpAddr::V6(v6) if v6.is_unspecified() => IpAddr::V6(Ipv6Addr::LOCALHOST),
+                ip => ip,
+            };
+            if let Some(service) = probe_banner(SocketAddr::new(ip, socket.local_port), timeout).as_deref().and_then(guess_service_by_banner) {
+                socket.service = Some(service.to_string());
+            }
+        }
+    }
+
+    /// 按服务分组，服务未知时以进程名分组，均未知时为 "unknown"
+    pub fn by_service(&self) -> BTreeMap<String, Vec<&Socket>> {
+        let mut groups: BTreeMap<String, Vec<&Socket>> = BTreeMap::new();
+        for socket in self.listening.iter().chain(self.connections.iter()) {
+            let key = socket.service.clone()
+                .or_else(|| socket.owner.as_ref().map(|o| o.name.clone()))
+                .unwrap_or_else(|| "unknown".to_string());
+            groups.entry(key).or_default().push(socket);
+        }
+        groups
+    }
+
+    pub fn display(&self) -> String {
+        let mut output = String::from("Listening:");
+        for socket in &self.listening {
+            output.push_str(&format!("\n  {:<5} {:<40} {:<12} {:<24} {}",
+                socket.protocol,
+                socket.local_address().green(),
+                socket.service.as_deref().unwrap_or("-").blue(),
+                socket.owner.as_ref().map(|o| format!("{}({})", o.name, o.pid)).unwrap_or_else(|| "-".to_string()),
+                if socket.loopback { "loopback" } else { "" }));
+        }
+        output.push_str("\n\nConnections by service:");
+        for (service, sockets) in self.by_service() {
+            let connections: Vec<&&Socket> = sockets.iter().filter(|s| !s.listening).collect();
+            if connections.is_empty() {
+                continue;
+            }
+            output.push_str(&format!("\n  {} ({})", service.blue(), connections.len()));
+            for socket in connections {
+                output.push_str(&format!("\n    {:<5} {:<40} -> {:<40} {}", socket.protocol, socket.local_address(), socket.remote_address.yellow(), socket.state));
+            }
+        }
+        println!("{}\n", output);
+        output
+    }
+
+    /// 转换为 JSON 字符串
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|err| {
+            eprintln!("序列化套接字信息到 JSON 失败: {}", err);
+            "{}".to_string()
+        })
+    }
+}
+
+#[cfg(test)]
+mod unit_test_socket {
+    use super::*;
+    use std::io::Write;
+    use std::net::TcpListener;
+    use chrono::Utc;
+
+    fn process(pid: u32, exe_path: &str, user: &str) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            parent_pid: Some(1),
+            command: exe_path.to_string(),
+            exe_path: exe_path.to_string(),
+            full_command: exe_path.to_string(),
+            user: user.to_string(),
+            uid: None,
+            state: "Sleeping".to_string(),
+            start_time: Utc::now(),
+            cpu_percent: 0.0,
+            rss: 0,
+            vsz: 0,
+            threads: None,
+            open_fds: None,
+            cwd: String::new(),
+            listening: vec![],
+        }
+    }
+
+    #[test]
+    fn test_socket_01() {
+        assert_eq!(guess_service_by_port(22), Some("ssh"));
+        assert_eq!(guess_service_by_port(8080), Some("http"));
+        assert_eq!(guess_service_by_port(40000), None);
+
+        assert_eq!(guess_service_by_banner(b"SSH-2.0-OpenSSH_9.2p1 Debian-2\r\n"), Some("ssh"));
+        assert_eq!(guess_service_by_banner(b"220 ProFTPD Server ready.\r\n"), Some("ftp"));
+        assert_eq!(guess_service_by_banner(b"220 mail.example.com ESMTP Postfix\r\n"), Some("smtp"));
+        assert_eq!(guess_service_by_banner(b"* OK [CAPABILITY IMAP4rev1] Dovecot ready.\r\n"), Some("imap"));
+        assert_eq!(guess_service_by_banner(b"\x4a\x00\x00\x00\x0a8.0.36\x00\x08\x00\x00\x00"), Some("mysql"));
+        assert_eq!(guess_service_by_banner(b"\x5b\x00\x00\x00\x0a5.5.5-10.11.6-MariaDB\x00"), Some("mysql"));
+        assert_eq!(guess_service_by_banner(b"hello"), None);
+    }
+
+    #[test]
+    fn test_socket_02() {
+        let services = vec![
+            Service::new("0.0.0.0", &22, "tcp", "LISTEN", &100, "0.0.0.0:*"),
+            Service::new("::", &22, "tcp6", "LISTEN", &100, ":::*"),
+            Service::new("127.0.0.1", &631, "tcp", "LISTEN", &200, "0.0.0.0:*"),
+            Service::new("0.0.0.0", &68, "udp", "", &0, "0.0.0.0:*"),
+            Service::new("192.168.1.10", &22, "tcp", "ESTABLISHED", &300, "192.168.1.20:51234"),
+            Service::new("192.168.1.10", &40000, "tcp", "TIME_WAIT", &0, "10.0.0.1:443"),
+        ];
+        let processes = vec![process(100, "/usr/sbin/sshd", "root"), process(200, "/usr/sbin/cupsd", "root"), process(300, "/usr/sbin/sshd", "alice")];
+        let inventory = SocketInventory::from_parts(&services, &processes);
+
+        assert_eq!(inventory.listening.len(), 4);
+        assert_eq!(inventory.connections.len(), 2);
+        assert_eq!(inventory.listening[0].describe(), "sshd on 0.0.0.0:22");
+        assert_eq!(inventory.listening[1].local_address(), "[::]:22");
+        assert_eq!(inventory.listening[2].describe(), "dhcp on 0.0.0.0:68");
+        let cups = inventory.listening.iter().find(|s| s.local_port == 631).unwrap();
+        assert!(cups.loopback && cups.service.as_deref() == Some("ipp"));
+
+        let groups = inventory.by_service();
+        assert_eq!(groups["ssh"].len(), 3);
+        assert_eq!(groups["https"].len(), 1);
+        assert_eq!(groups["dhcp"].len(), 1);
+        assert!(!groups.contains_key("unknown"));
+        assert!(inventory.display().contains("sshd(100)"));
+        assert!(inventory.to_json().contains("\"loopback\":true"));
+    }
+
+    #[test]
+    fn test_socket_03() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            stream.write_all(b"SSH-2.0-OpenSSH_9.2p1\r\n").unwrap();
+        });
+        let mut inventory = SocketInventory::from_parts(&[Service::new("127.0.0.1", &port, "tcp", "LISTEN", &0, "0.0.0.0:*")], &[]);
+        assert_eq!(inventory.listening[0].service, None);
+        inventory.identify_by_banner(Duration::from_secs(2));
+        server.join().unwrap();
+        assert_eq!(inventory.listening[0].service.as_deref(), Some("ssh"));
+        assert_eq!(inventory.listening[0].describe(), format!("ssh on 127.0.0.1:{}", port));
+    }
+}
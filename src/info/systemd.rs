@@ -0,0 +1,268 @@
+//! systemd 服务单元清单和控制，通过 systemctl 命令实现
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+
+use crate::info::error::{run_command, InfoError, InfoResult};
+
+// systemctl show 查询的属性
+const UNIT_PROPERTIES: &str = "Id,Description,LoadState,ActiveState,SubState,UnitFileState,MainPID,MemoryCurrent,NRestarts";
+
+// 服务单元信息
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnitInfo {
+    pub name: String,                  // 单元名，如 sshd.service
+    pub description: String,
+    pub load_state: String,            // loaded, not-found, masked
+    pub active_state: String,          // active, inactive, failed, activating...
+    pub sub_state: String,             // running, exited, dead, auto-restart...
+    pub enabled: String,               // enabled, disabled, static, masked，无单元文件时为空
+    pub main_pid: Option<u32>,         // 未运行时为 None
+    pub memory: Option<u64>,           // 当前内存字节数，未启用内存统计时为 None
+    pub restarts: Option<u32>,         // systemd 自动重启次数，旧版本 systemd 不支持时为 None
+}
+
+impl UnitInfo {
+    pub fn is_active(&self) -> bool {
+        self.active_state == "active"
+    }
+
+    pub fn is_failed(&self) -> bool {
+        self.active_state == "failed"
+    }
+
+    pub fn header() -> String {
+        format!("{:<40} {:<10} {:<14} {:<10} {:>8} {:>10} {:>8}", "UNIT", "ACTIVE", "SUB", "ENABLED", "PID", "MEMORY", "RESTARTS")
+    }
+
+    pub fn display(&self) -> String {
+        let active = match self.active_state.as_str() {
+            "active" => self.active_state.green(),
+            "failed" => self.active_state.red(),
+            _ => self.active_state.yellow(),
+        };
+        let output = format!("{:<40} {:<10} {:<14} {:<10} {:>8} {:>10} {:>8}",
+            self.name, active, self.sub_state, self.enabled,
+            self.main_pid.map_or("-".to_string(), |pid| pid.to_string()),
+            self.memory.map_or("-".to_string(), |memory| format!("{}M", memory / 1024 / 1024)),
+            self.restarts.map_or("-".to_string(), |restarts| restarts.to_string()));
+        println!("{}", output);
+        output
+    }
+}
+
+// 单元控制操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnitAction {
+    Start,
+    Stop,
+    Restart,
+    Reload,
+    Enable,
+    Disable,
+}
+
+impl UnitAction {
+    /// 对应的 systemctl 子命令
+    pub fn command(&self) -> &'static str {
+        match self {
+            UnitAction::Start => "start",
+            UnitAction::Stop => "stop",
+            UnitAction::Restart => "restart",
+            UnitAction::Reload => "reload",
+            UnitAction::Enable => "enable",
+            UnitAction::Disable => "disable",
+        }
+    }
+}
+
+impl fmt::Display for UnitAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.command())
+    }
+}
+
+impl FromStr for UnitAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "start" => Ok(UnitAction::Start),
+            "stop" => Ok(UnitAction::Stop),
+            "restart" => Ok(UnitAction::Restart),
+            "reload" => Ok(UnitAction::Reload),
+            "enable" => Ok(UnitAction::Enable),
+            "disable" => Ok(UnitAction::Disable),
+            _ => Err(format!("unknown unit action: {}", s)),
+        }
+    }
+}
+
+/// 补全单元名后缀，"sshd" 视为 "sshd.service"
+pub fn unit_name(name: &str) -> String {
+    if name.contains('.') || name.contains('*') {
+        name.to_string()
+    } else {
+        format!("{}.service", name)
+    }
+}
+
+// systemctl list-units --output=json 的一项
+#[derive(Debug, Deserialize)]
+struct ListedUnit {
+    unit: String,
+}
+
+/// 解析 systemctl list-units --output=json 输出的单元名
+pub fn parse_list_units(json: &str) -> InfoResult<Vec<String>> {
+    let units: Vec<ListedUnit> = serde_json::from_str(json).map_err(|_| InfoError::parse("systemctl unit list", json))?;
+    Ok(units.into_iter().map(|unit| unit.unit).collect())
+}
+
+/// 解析 systemctl show 输出，多个单元以空行分隔
+pub fn parse_show(text: &str) -> Vec<UnitInfo> {
+    let mut units = Vec::new();
+    for block in text.split("\n\n") {
+        let properties: HashMap<&str, &str> = block.lines().filter_map(|line| line.split_once('=')).collect();
+        let Some(name) = properties.get("Id") else {
+            continue;
+        };
+        let get = |key: &str| properties.get(key).copied().unwrap_or_default().to_string();
+        units.push(UnitInfo {
+            name: name.to_string(),
+            description: get("Description"),
+            load_state: get("LoadState"),
+            active_state: get("ActiveState"),
+            sub_state: get("SubState"),
+            enabled: get("UnitFileState"),
+            main_pid: properties.get("MainPID").and_then(|pid| pid.parse().ok()).filter(|pid| *pid != 0),
+            // 未统计时为 "[not set]" 或 u64::MAX
+            memory: properties.get("MemoryCurrent").and_then(|memory| memory.parse().ok()).filter(|memory| *memory != u64::MAX),
+            restarts: properties.get("NRestarts").and_then(|restarts| restarts.parse().ok()),
+        });
+    }
+    units
+}
+
+// systemctl 命令
+#[derive(Debug, Clone)]
+pub struct Systemctl {
+    program: String,
+}
+
+impl Default for Systemctl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Systemctl {
+    pub fn new() -> Self {
+        Self::with_program("systemctl")
+    }
+
+    /// 指定 systemctl 路径，可用于测试替身
+    pub fn with_program(program: &str) -> Self {
+        Systemctl { program: program.to_string() }
+    }
+
+    /// 所有已加载的服务单元，pattern 如 "ssh*"
+    pub fn list_units(&self, pattern: Option<&str>) -> InfoResult<Vec<UnitInfo>> {
+        let mut args = vec!["list-units", "--type=service", "--all", "--output=json", "--no-pager"];
+        if let Some(pattern) = pattern {
+            args.push(pattern);
+        }
+        let names = parse_list_units(&run_command(&self.program, &args)?)?;
+        if names.is_empty() {
+            return Ok(Vec::new());
+        }
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+        self.show(&names)
+    }
+
+    /// 单个单元信息，单元不存在时 load_state 为 not-found
+    pub fn unit(&self, name: &str) -> InfoResult<UnitInfo> {
+        let name = unit_name(name);
+        self.show(&[&name])?.into_iter().next().ok_or_else(|| InfoError::parse("systemctl show", &name))
+    }
+
+    fn show(&self, names: &[&str]) -> InfoResult<Vec<UnitInfo>> {
+        let property = format!("--property={}", UNIT_PROPERTIES);
+        let mut args = vec!["show", property.as_str(), "--no-pager"];
+        args.extend_from_slice(names);
+        Ok(parse_show(&run_command(&self.program, &args)?))
+    }
+
+    /// 执行单元控制操作
+    pub fn control(&self, action: UnitAction, name: &str) -> InfoResult<()> {
+        run_command(&self.program, &[action.command(), &unit_name(name)]).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod unit_test_systemd {
+    use super::*;
+
+    const SHOW: &str = "Id=sshd.service\nDescription=OpenBSD Secure Shell server\nLoadState=loaded\nActiveState=active\n\
+SubState=running\nUnitFileState=enabled\nMainPID=812\nMemoryCurrent=5242880\nNRestarts=0\n\n\
+Id=nginx.service\nDescription=A high performance web server\nLoadState=loaded\nActiveState=failed\nSubState=failed\n\
+UnitFileState=enabled\nMainPID=0\nMemoryCurrent=[not set]\nNRestarts=5\n";
+
+    #[test]
+    fn test_systemd_01() {
+        let units = parse_show(SHOW);
+        assert_eq!(units.len(), 2);
+        assert_eq!(units[0].name, "sshd.service");
+        assert!(units[0].is_active());
+        assert_eq!(units[0].main_pid, Some(812));
+        assert_eq!(units[0].memory, Some(5242880));
+        assert!(units[1].is_failed());
+        assert_eq!(units[1].main_pid, None);
+        assert_eq!(units[1].memory, None);
+        assert_eq!(units[1].restarts, Some(5));
+
+        let names = parse_list_units(r#"[{"unit":"sshd.service","load":"loaded","active":"active","sub":"running","description":"OpenSSH"}]"#).unwrap();
+        assert_eq!(names, vec!["sshd.service"]);
+        assert!(parse_list_units("not json").is_err());
+
+        assert_eq!(unit_name("sshd"), "sshd.service");
+        assert_eq!(unit_name("docker.socket"), "docker.socket");
+        assert_eq!("Restart".parse::<UnitAction>(), Ok(UnitAction::Restart));
+        assert!("kill".parse::<UnitAction>().is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_systemd_02() {
+        use std::os::unix::fs::PermissionsExt;
+
+        // systemctl 替身：list-units 输出 JSON，show 输出属性，其余命令记录到日志
+        let dir = std::env::temp_dir().join(format!("minirobot-systemctl-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let script = dir.join("systemctl");
+        let log = dir.join("calls.log");
+        std::fs::write(&script, format!(r#"#!/bin/sh
+case "$1" in
+    list-units) echo '[{{"unit":"sshd.service"}},{{"unit":"nginx.service"}}]' ;;
+    show) printf '{}' ;;
+    *) echo "$@" >> {} ;;
+esac
+"#, SHOW.replace('\n', "\\n"), log.display())).unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let systemctl = Systemctl::with_program(script.to_str().unwrap());
+        let units = systemctl.list_units(None).unwrap();
+        assert_eq!(units.len(), 2);
+        assert_eq!(units[1].name, "nginx.service");
+        systemctl.control(UnitAction::Restart, "nginx").unwrap();
+        assert_eq!(std::fs::read_to_string(&log).unwrap(), "restart nginx.service\n");
+
+        assert!(Systemctl::with_program("minirobot-no-such-systemctl").list_units(None).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,332 @@
+//! 虚拟化和容器识别，以及 cgroup v1/v2 资源限制
+//!
+//! 名称与 systemd-detect-virt 一致，如 kvm、vmware、microsoft、docker、podman、lxc；
+//! 检测函数以根目录为参数（读取其下的 proc、sys 等），便于以夹具目录测试。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+// DMI 字段中的虚拟化厂商标识，按顺序匹配
+const DMI_VENDORS: [(&str, &str); 16] = [
+    ("KVM", "kvm"), ("OpenStack", "kvm"), ("KubeVirt", "kvm"), ("Amazon EC2", "amazon"), ("QEMU", "qemu"),
+    ("VMware", "vmware"), ("VMW", "vmware"), ("innotek GmbH", "oracle"), ("VirtualBox", "oracle"), ("Xen", "xen"),
+    ("Bochs", "bochs"), ("Parallels", "parallels"), ("BHYVE", "bhyve"), ("Hyper-V", "microsoft"),
+    ("Apple Virtualization", "apple"), ("Google Compute Engine", "google"),
+];
+
+// cgroup 中的容器运行时路径特征，kubepods 在前：Kubernetes 中的 docker 容器路径两者都有
+const CGROUP_RUNTIMES: [(&str, &str); 6] = [
+    ("kubepods", "kubernetes"), ("/docker/", "docker"), ("docker-", "docker"), ("libpod", "podman"),
+    ("/lxc/", "lxc"), ("containerd", "containerd"),
+];
+
+// cgroup v1 中表示不限制内存的值接近 i64::MAX，按页对齐
+const MEMORY_UNLIMITED: u64 = 1 << 62;
+
+fn read(path: &Path) -> Option<String> {
+    fs::read_to_string(path).ok().map(|s| s.trim().to_string())
+}
+
+fn read_u64(path: &Path) -> Option<u64> {
+    read(path)?.parse().ok()
+}
+
+// cpuset 列表中的 CPU 数，如 "0-3,6" 为 5
+fn count_cpu_list(list: &str) -> Option<u32> {
+    let mut count = 0;
+    for part in list.split(',').map(str::trim).filter(|part| !part.is_empty()) {
+        count += match part.split_once('-') {
+            Some((start, end)) => end.parse::<u32>().ok()?.checked_sub(start.parse::<u32>().ok()?)? + 1,
+            None => part.parse::<u32>().map(|_| 1).ok()?,
+        };
+    }
+    (count > 0).then_some(count)
+}
+
+// memory.stat 中的字段
+fn read_stat(path: &Path, key: &str) -> Option<u64> {
+    read(path)?.lines()
+        .filter_map(|line| line.split_once(' '))
+        .find(|(name, _)| *name == key)
+        .and_then(|(_, value)| value.trim().parse().ok())
+}
+
+// cgroup 资源限制，未限制时为 None
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CgroupLimits {
+    pub version: u8,                         // 1 或 2，无 cgroup 时为 0
+    pub cpu_quota: Option<f64>,              // CPU 配额折合的 CPU 数，如 1.5
+    pub cpuset: Option<u32>,                 // cpuset 中的 CPU 数
+    pub memory_limit: Option<u64>,           // 字节
+    pub memory_usage: Option<u64>,           // 字节，不含可回收的文件缓存，与 docker stats 一致
+}
+
+impl CgroupLimits {
+    /// 实际可用的 CPU 数：主机线程数、CPU 配额和 cpuset 中的最小值
+    pub fn effective_cpus(&self, host_threads: u32) -> f64 {
+        let mut cpus = host_threads as f64;
+        if let Some(quota) = self.cpu_quota {
+            cpus = cpus.min(quota);
+        }
+        if let Some(cpuset) = self.cpuset {
+            cpus = cpus.min(cpuset as f64);
+        }
+        cpus
+    }
+
+    /// 实际可用的内存：主机内存和内存限制中的较小值
+    pub fn effective_memory(&self, host_total: u64) -> u64 {
+        self.memory_limit.map_or(host_total, |limit| limit.min(host_total))
+    }
+}
+
+// 进程所在的 cgroup，(控制器列表, 路径)，v2 的控制器列表为空
+fn parse_proc_cgroup(content: &str) -> Vec<(String, String)> {
+    content.lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(3, ':');
+            let _id = parts.next()?;
+            Some((parts.next()?.to_string(), parts.next()?.to_string()))
+        })
+        .collect()
+}
+
+// cgroup 目录：有 cgroup 命名空间时进程路径为 "/"，否则在挂载点下按路径查找，找不到时取挂载点
+fn cgroup_dir(mount: &Path, path: &str) -> PathBuf {
+    let dir = mount.join(path.trim_start_matches('/'));
+    if dir.is_dir() { dir } else { mount.to_path_buf() }
+}
+
+/// 读取 root 下当前进程的 cgroup 资源限制
+pub fn read_cgroup_limits(root: &Path) -> CgroupLimits {
+    let mount = root.join("sys/fs/cgroup");
+    let cgroups = read(&root.join("proc/self/cgroup")).map(|content| parse_proc_cgroup(&content)).unwrap_or_default();
+
+    if mount.join("cgroup.controllers").exists() {
+        let path = cgroups.iter().find(|(controllers, _)| controllers.is_empty()).map_or("/", |(_, path)| path.as_str());
+        let dir = cgroup_dir(&mount, path);
+        // cpu.max 形如 "max 100000" 或 "150000 100000"
+        let cpu_quota = read(&dir.join("cpu.max")).and_then(|max| {
+            let (quota, period) = max.split_once(' ')?;
+            Some(quota.parse::<f64>().ok()? / period.parse::<f64>().ok()?)
+        });
+        let memory_limit = read_u64(&dir.join("memory.max"));
+        let memory_usage = read_u64(&dir.join("memory.current"))
+            .map(|current| current.saturating_sub(read_stat(&dir.join("memory.stat"), "inactive_file").unwrap_or(0)));
+        return CgroupLimits {
+            version: 2,
+            cpu_quota,
+            cpuset: read(&dir.join("cpuset.cpus.effective")).and_then(|list| count_cpu_list(&list)),
+            memory_limit,
+            memory_usage,
+        };
+    }
+
+    // v1：各控制器分别挂载，如 /sys/fs/cgroup/cpu,cpuacct
+    let controller_dir = |name: &str| -> Option<PathBuf> {
+        let (controllers, path) = cgroups.iter().find(|(controllers, _)| controllers.split(',').any(|c| c == name))?;
+        let mount = [mount.join(name), mount.join(controllers)].into_iter().find(|dir| dir.is_dir())?;
+        Some(cgroup_dir(&mount, path))
+    };
+    if cgroups.is_empty() || !mount.is_dir() {
+        return CgroupLimits::default();
+    }
+    let cpu_quota = controller_dir("cpu").and_then(|dir| {
+        let quota = read(&dir.join("cpu.cfs_quota_us"))?.parse::<i64>().ok().filter(|quota| *quota > 0)?;
+        Some(quota as f64 / read_u64(&dir.join("cpu.cfs_period_us"))? as f64)
+    });
+    let cpuset = controller_dir("cpuset").and_then(|dir| {
+        read(&dir.join("cpuset.effective_cpus")).or_else(|| read(&dir.join("cpuset.cpus")))
+    }).and_then(|list| count_cpu_list(&list));
+    let memory = controller_dir("memory");
+    let memory_limit = memory.as_ref()
+        .and_then(|dir| read_u64(&dir.join("memory.limit_in_bytes")))
+        .filter(|limit| *limit < MEMORY_UNLIMITED);
+    let memory_usage = memory.as_ref().and_then(|dir| {
+        let usage = read_u64(&dir.join("memory.usage_in_bytes"))?;
+        Some(usage.saturating_sub(read_stat(&dir.join("memory.stat"), "total_inactive_file").unwrap_or(0)))
+    });
+    CgroupLimits { version: 1, cpu_quota, cpuset, memory_limit, memory_usage }
+}
+
+/// 按 DMI 和 /proc/cpuinfo 识别虚拟机，cpuid 为 CPUID 0x40000000 识别的虚拟化厂商
+pub fn detect_hypervisor(root: &Path, cpuid: Option<&str>) -> Option<String> {
+    let dmi = root.join("sys/class/dmi/id");
+    let fields: Vec<String> = ["sys_vendor", "product_name", "board_vendor", "bios_vendor", "product_version"].iter()
+        .filter_map(|name| read(&dmi.join(name)))
+        .collect();
+    // Hyper-V 的 DMI 只有 "Microsoft Corporation" 和 "Virtual Machine"
+    if fields.iter().any(|f| f == "Microsoft Corporation") && fields.iter().any(|f| f == "Virtual Machine") {
+        return Some("microsoft".to_string());
+    }
+    if let Some((_, name)) = DMI_VENDORS.iter().find(|(pattern, _)| fields.iter().any(|f| f.contains(pattern))) {
+        return Some(name.to_string());
+    }
+    if let Some(name) = cpuid {
+        return Some(name.to_string());
+    }
+    if let Some(kind) = read(&root.join("sys/hypervisor/type")) {
+        return Some(kind);
+    }
+    // 其他虚拟机仍会设置 hypervisor 标志
+    let hypervisor_flag = read(&root.join("proc/cpuinfo")).is_some_and(|cpuinfo| cpuinfo.lines()
+        .filter(|line| line.starts_with("flags"))
+        .any(|line| line.split_whitespace().any(|flag| flag == "hypervisor")));
+    hypervisor_flag.then(|| "vm-other".to_string())
+}
+
+/// 识别容器运行时，顺序与 systemd-detect-virt 相近：WSL、1 号进程的 container 环境变量、运行时标记文件、cgroup 路径
+pub fn detect_container(root: &Path) -> Option<String> {
+    if read(&root.join("proc/sys/kernel/osrelease")).is_some_and(|release| release.contains("Microsoft") || release.contains("WSL")) {
+        return Some("wsl".to_string());
+    }
+    if let Ok(environ) = fs::read(root.join("proc/1/environ")) {
+        let container = environ.split(|byte| *byte == 0)
+            .find_map(|entry| entry.strip_prefix(b"container="))
+            .map(|value| String::from_utf8_lossy(value).into_owned());
+        if let Some(container) = container.filter(|c| !c.is_empty()) {
+            return Some(container);
+        }
+    }
+    if root.join("run/.containerenv").exists() {
+        return Some("podman".to_string());
+    }
+    if root.join(".dockerenv").exists() {
+        return Some("docker".to_string());
+    }
+    if let Some(container) = read(&root.join("run/systemd/container")).filter(|c| !c.is_empty()) {
+        return Some(container);
+    }
+    let cgroup = ["proc/1/cgroup", "proc/self/cgroup"].iter().filter_map(|path| read(&root.join(path))).collect::<Vec<_>>().join("\n");
+    CGROUP_RUNTIMES.iter().find(|(pattern, _)| cgroup.contains(pattern)).map(|(_, name)| name.to_string())
+}
+
+// CPUID 0x40000000 返回的虚拟化厂商签名
+#[cfg(target_arch = "x86_64")]
+fn cpuid_hypervisor() -> Option<&'static str> {
+    use std::arch::x86_64::__cpuid;
+
+    const SIGNATURES: [(&[u8; 12], &str); 7] = [
+        (b"KVMKVMKVM\0\0\0", "kvm"), (b"TCGTCGTCGTCG", "qemu"), (b"VMwareVMware", "vmware"), (b"Microsoft Hv", "microsoft"),
+        (b"XenVMMXenVMM", "xen"), (b"bhyve bhyve ", "bhyve"), (b"ACRNACRNACRN", "acrn"),
+    ];
+    // 叶 1 的 ECX 第 31 位为 hypervisor 标志
+    let leaf1 = __cpuid(1);
+    if leaf1.ecx & (1 << 31) == 0 {
+        return None;
+    }
+    let leaf = __cpuid(0x4000_0000);
+    let mut signature = [0u8; 12];
+    signature[..4].copy_from_slice(&leaf.ebx.to_le_bytes());
+    signature[4..8].copy_from_slice(&leaf.ecx.to_le_bytes());
+    signature[8..].copy_from_slice(&leaf.edx.to_le_bytes());
+    SIGNATURES.iter().find(|(expected, _)| **expected == signature).map(|(_, name)| *name)
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn cpuid_hypervisor() -> Option<&'static str> {
+    None
+}
+
+// 虚拟化环境
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Virtualization {
+    pub hypervisor: Option<String>,          // 虚拟机类型，物理机为 None；容器内为宿主机所在的虚拟机类型
+    pub container: Option<String>,           // 容器运行时，不在容器内为 None
+    pub cgroup: CgroupLimits,
+}
+
+impl Virtualization {
+    /// 检测本机虚拟化环境，目前仅支持 Linux，其他平台返回空
+    #[cfg(target_os = "linux")]
+    pub fn detect() -> Self {
+        let root = Path::new("/");
+        Virtualization {
+            hypervisor: detect_hypervisor(root, cpuid_hypervisor()),
+            container: detect_container(root),
+            cgroup: read_cgroup_limits(root),
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn detect() -> Self {
+        Virtualization { hypervisor: cpuid_hypervisor().map(str::to_string), ..Default::default() }
+    }
+
+    /// 按根目录检测，不使用 CPUID
+    pub fn detect_from(root: &Path) -> Self {
+        Virtualization {
+            hypervisor: detect_hypervisor(root, None),
+            container: detect_container(root),
+            cgroup: read_cgroup_limits(root),
+        }
+    }
+
+    pub fn is_container(&self) -> bool {
+        self.container.is_some()
+    }
+
+    pub fn is_vm(&self) -> bool {
+        self.hypervisor.is_some()
+    }
+
+    /// 简述，如 "docker on kvm"、"kvm"、"none"
+    pub fn summary(&self) -> String {
+        match (&self.container, &self.hypervisor) {
+            (Some(container), Some(hypervisor)) => format!("{} on {}", container, hypervisor),
+            (Some(container), None) => container.clone(),
+            (None, Some(hypervisor)) => hypervisor.clone(),
+            (None, None) => "none".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod unit_test_virt {
+    use super::*;
+
+    fn fixture(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/virt").join(name)
+    }
+
+    #[test]
+    fn test_virt_01() {
+        let docker = Virtualization::detect_from(&fixture("docker"));
+        assert_eq!(docker.container.as_deref(), Some("docker"));
+        assert_eq!(docker.hypervisor.as_deref(), Some("kvm"));
+        assert_eq!(docker.summary(), "docker on kvm");
+        assert_eq!(docker.cgroup, CgroupLimits {
+            version: 2,
+            cpu_quota: Some(1.5),
+            cpuset: Some(3),
+            memory_limit: Some(512 << 20),
+            memory_usage: Some(100 << 20),
+        });
+        assert_eq!(docker.cgroup.effective_cpus(8), 1.5);
+        assert_eq!(docker.cgroup.effective_memory(8 << 30), 512 << 20);
+
+        let vm = Virtualization::detect_from(&fixture("vm"));
+        assert_eq!(vm.summary(), "vm-other");
+        assert_eq!(vm.cgroup.version, 2);
+        assert_eq!(vm.cgroup.memory_limit, None);
+        assert_eq!(vm.cgroup.effective_cpus(4), 4.0);
+    }
+
+    #[test]
+    fn test_virt_02() {
+        let k8s = Virtualization::detect_from(&fixture("k8s-v1"));
+        assert_eq!(k8s.container.as_deref(), Some("kubernetes"));
+        assert_eq!(k8s.hypervisor.as_deref(), Some("microsoft"));
+        assert_eq!(k8s.cgroup.version, 1);
+        assert_eq!(k8s.cgroup.cpu_quota, Some(0.5));
+        assert_eq!(k8s.cgroup.cpuset, None);
+        assert_eq!(k8s.cgroup.memory_limit, Some(256 << 20));
+        assert_eq!(k8s.cgroup.memory_usage, Some(64 << 20));
+
+        assert_eq!(count_cpu_list("0-3,6"), Some(5));
+        assert_eq!(count_cpu_list(""), None);
+        assert_eq!(Virtualization::detect_from(&fixture("missing")), Virtualization::default());
+    }
+}
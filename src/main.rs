@@ -4,7 +4,10 @@ use std::env;
 
 use minirobot::common::config::{read_config, GlobalConfig};
 use minirobot::common::logger;
+use minirobot::database::repository::Repository;
 use minirobot::host;
+use minirobot::monitor::metrics::{self, Exporter};
+use minirobot::task::history::{default_history_dir, TaskHistory, TaskStore};
 
 include!(concat!(env!("OUT_DIR"), "/version.rs"));
 include!(concat!(env!("OUT_DIR"), "/configfile.rs"));
@@ -25,6 +28,13 @@ fn main() {
     let database_url = global_config.database_url;
     info!("Starting {} in {} mode on port {}", NAME.green(), app_env.green(), app_port.to_string().green());
     debug!("Connecting to database at {}", database_url.green());
+    let repository = match Repository::new(&database_url) {
+        Ok(repository) => Some(repository),
+        Err(e) => {
+            error!("Failed to connect to database {}: {}", database_url, e);
+            None
+        }
+    };
 
     // 使用不同日志级别和颜色打印信息
     info!("{}", "Information message".green());
@@ -37,4 +47,33 @@ fn main() {
     let host = host::Host::new();
     // 显示主机信息
     host.hostinfo().display();
+
+    // 记录主机信息
+    if let Some(repository) = &repository {
+        if let Err(e) = repository.upsert_host(host.hostinfo().hostname(), host.hostinfo().os_info()) {
+            error!("Failed to record host: {}", e);
+        }
+    }
+
+    // 提供 Prometheus 指标接口，任务指标优先读取数据库中的任务记录
+    if let Some(metrics_config) = &global_config.metrics {
+        let tasks: Box<dyn TaskStore + Send + Sync> = match repository {
+            Some(repository) => Box::new(repository),
+            None => Box::new(TaskHistory::new(&default_history_dir())),
+        };
+        let served = Exporter::new(metrics_config)
+            .and_then(|exporter| {
+                let address = metrics_config.listen.parse()?;
+                let runtime = tokio::runtime::Runtime::new()?;
+                runtime.block_on(async {
+                    let (address, server) = metrics::bind(exporter.with_tasks(tasks), address)?;
+                    info!("Serving metrics on http://{}/metrics", address);
+                    server.await;
+                    Ok(())
+                })
+            });
+        if let Err(e) = served {
+            error!("Failed to serve metrics on {}: {}", metrics_config.listen, e);
+        }
+    }
 }
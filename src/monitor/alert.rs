@@ -0,0 +1,217 @@
+//! 告警跟踪
+//!
+//! 监控项产生的事件按监控项和告警对象生成指纹，同一指纹的告警在解决前只保留一条，重复发生时累计次数和最近发生时间；
+//! 监控项再次检查时不再产生的告警自动解决。只跟踪告警和严重级别的事件，调试和信息级别的事件（如未启用告警的监控项产生的事件、恢复通知）不纳入跟踪，直接记录。
+//! 告警可以确认和静默，均可设置有效期。关联数据库后告警随每次变化保存，启动时加载未解决的告警。
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::{NaiveDateTime, Utc};
+use log::warn;
+
+use crate::database::repository::Repository;
+use crate::monitor::check::CheckStatus;
+use crate::monitor::event::{fingerprint, Event, Severity};
+
+// 当前时刻之后 duration，超出范围时为最大时刻
+fn after(duration: Duration) -> NaiveDateTime {
+    chrono::Duration::from_std(duration).ok()
+        .and_then(|duration| Utc::now().naive_utc().checked_add_signed(duration))
+        .unwrap_or(NaiveDateTime::MAX)
+}
+
+#[derive(Debug, Default)]
+pub struct AlertStore {
+    alerts: Mutex<BTreeMap<String, Event>>,  // 按指纹索引的未解决告警
+    repository: Mutex<Option<Repository>>,
+}
+
+impl AlertStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 关联数据库并加载未解决的告警，返回加载的告警数
+    pub fn attach(&self, repository: Repository) -> Result<usize, Box<dyn Error>> {
+        let alerts = repository.list_open_alerts();
+        *self.repository.lock().unwrap_or_else(|e| e.into_inner()) = Some(repository);
+        let alerts = alerts?;
+        let count = alerts.len();
+        let mut open = self.alerts.lock().unwrap_or_else(|e| e.into_inner());
+        for alert in alerts {
+            open.insert(alert.fingerprint().to_string(), alert);
+        }
+        Ok(count)
+    }
+
+    fn save(&self, event: &Event) {
+        if let Some(repository) = self.repository.lock().unwrap_or_else(|e| e.into_inner()).as_ref() {
+            if let Err(e) = repository.save_event(event) {
+                warn!("failed to save event {}: {}", event.id(), e);
+            }
+        }
+    }
+
+    /// 记录监控项一次检查产生的事件，返回需要通知的事件：新告警、未静默的重复告警、自动解决的告警和未跟踪的事件。
+    /// 状态为 unknown 时无法判断告警是否仍然存在，不自动解决
+    pub fn record(&self, check: &str, status: CheckStatus, events: Vec<Event>) -> Vec<Event> {
+        let now = Utc::now().naive_utc();
+        let mut alerts = self.alerts.lock().unwrap_or_else(|e| e.into_inner());
+        let mut notify = Vec::new();
+        let mut seen = Vec::new();
+        for mut event in events {
+            if !matches!(event.severity(), Severity::Warning | Severity::Critical) {
+                self.save(&event);
+                notify.push(event);
+                continue;
+            }
+
+            let key = fingerprint(check, event.subject());
+            match alerts.get_mut(&key) {
+                Some(alert) => {
+                    alert.expire(&now);
+                    alert.repeat(&event);
+                    self.save(alert);
+                    if !alert.is_silenced(&now) {
+                        notify.push(alert.clone());
+                    }
+                }
+                None => {
+                    event.set_fingerprint(&key);
+                    self.save(&event);
+                    notify.push(event.clone());
+                    alerts.insert(key.clone(), event);
+                }
+            }
+            seen.push(key);
+        }
+
+        if status != CheckStatus::Unknown {
+            let cleared: Vec<String> = alerts.iter()
+                .filter(|(key, alert)| **key == fingerprint(check, alert.subject()) && !seen.contains(key))
+                .map(|(key, _)| key.clone())
+                .collect();
+            for key in cleared {
+                if let Some(mut alert) = alerts.remove(&key) {
+                    alert.resolve("告警条件已消失，自动解决");
+                    self.save(&alert);
+                    notify.push(alert);
+                }
+            }
+        }
+        notify
+    }
+
+    /// 未解决的告警，按指纹排序，到期的确认和静默在此时清除
+    pub fn alerts(&self) -> Vec<Event> {
+        let now = Utc::now().naive_utc();
+        let mut alerts = self.alerts.lock().unwrap_or_else(|e| e.into_inner());
+        for alert in alerts.values_mut() {
+            if alert.expire(&now) {
+                self.save(alert);
+            }
+        }
+        alerts.values().cloned().collect()
+    }
+
+    // 按事件 ID 或指纹修改告警并保存
+    fn update<F: FnOnce(&mut Event)>(&self, id: &str, f: F) -> Option<Event> {
+        let mut alerts = self.alerts.lock().unwrap_or_else(|e| e.into_inner());
+        let alert = alerts.values_mut().find(|alert| alert.id() == id || alert.fingerprint() == id)?;
+        f(alert);
+        self.save(alert);
+        Some(alert.clone())
+    }
+
+    /// 确认告警，duration 为空时一直有效直到告警解决，告警不存在时返回 None
+    pub fn acknowledge(&self, id: &str, note: &str, duration: Option<Duration>) -> Option<Event> {
+        let until = duration.map(after);
+        let note = if note.is_empty() { "已确认" } else { note };
+        self.update(id, |alert| alert.acknowledge(note, until))
+    }
+
+    /// 静默告警，期间重复发生不再通知，告警不存在时返回 None
+    pub fn silence(&self, id: &str, duration: Duration) -> Option<Event> {
+        self.update(id, |alert| alert.silence(after(duration)))
+    }
+}
+
+#[cfg(test)]
+mod unit_test_alert {
+    use super::*;
+    use crate::monitor::event::{Priority, Status};
+
+    fn disk_alert(subject: &str) -> Event {
+        Event::new("磁盘使用率监控", &format!("{} 使用率95%", subject), Priority::Low, Severity::Warning, "本地主机监控")
+            .with_subject(subject)
+    }
+
+    #[test]
+    fn test_alert_01() {
+        let store = AlertStore::new();
+        let notify = store.record("disk_usage", CheckStatus::Warning, vec![disk_alert("/"), disk_alert("/data")]);
+        assert_eq!(notify.len(), 2);
+        let id = notify[0].id().to_string();
+
+        let notify = store.record("disk_usage", CheckStatus::Warning, vec![disk_alert("/"), disk_alert("/data")]);
+        assert_eq!(notify[0].id(), id);
+        assert_eq!(notify[0].count(), 2);
+        assert_eq!(store.alerts().len(), 2);
+
+        // 其他监控项和 unknown 状态不影响已有告警
+        store.record("open_port", CheckStatus::Ok, vec![]);
+        store.record("disk_usage", CheckStatus::Unknown, vec![]);
+        assert_eq!(store.alerts().len(), 2);
+
+        let acknowledged = store.acknowledge("disk_usage:/data", "扩容中", Some(Duration::from_secs(3600))).unwrap();
+        assert_eq!(acknowledged.status(), &Status::InProgress);
+        assert!(acknowledged.acknowledged_until().is_some());
+        store.silence(&id, Duration::from_secs(3600)).unwrap();
+        assert!(store.silence("nope", Duration::from_secs(60)).is_none());
+
+        let mut recovery = disk_alert("/data");
+        recovery.set_severity(Severity::Info);
+        let notify = store.record("disk_usage", CheckStatus::Warning, vec![disk_alert("/"), recovery]);
+        // 静默的重复告警不通知，/data 恢复通知和自动解决
+        assert_eq!(notify.len(), 2);
+        assert_eq!(notify[0].severity(), &Severity::Info);
+        assert_eq!(notify[1].status(), &Status::Resolved);
+        assert_eq!(notify[1].count(), 2);
+        let alerts = store.alerts();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].count(), 3);
+
+        // 未启用告警的监控项产生的调试事件只记录
+        let mut debug = disk_alert("/home");
+        debug.set_severity(Severity::Debug);
+        let notify = store.record("disk_usage", CheckStatus::Warning, vec![disk_alert("/"), debug]);
+        assert_eq!(notify.len(), 1);
+        assert_eq!(notify[0].fingerprint(), "");
+        assert_eq!(store.alerts().len(), 1);
+    }
+
+    #[test]
+    fn test_alert_02() {
+        let repository = Repository::new(":memory:").unwrap();
+        let store = AlertStore::new();
+        store.attach(repository.clone()).unwrap();
+        store.record("check_load", CheckStatus::Critical, vec![disk_alert("")]);
+        let id = store.record("check_load", CheckStatus::Critical, vec![disk_alert("")])[0].id().to_string();
+        store.acknowledge(&id, "", None).unwrap();
+
+        // 重新启动后继续累计同一告警
+        let restarted = AlertStore::new();
+        assert_eq!(restarted.attach(repository.clone()).unwrap(), 1);
+        let notify = restarted.record("check_load", CheckStatus::Critical, vec![disk_alert("")]);
+        assert_eq!(notify[0].id(), id);
+        assert_eq!(notify[0].count(), 3);
+        assert_eq!(notify[0].status(), &Status::InProgress);
+
+        restarted.record("check_load", CheckStatus::Ok, vec![]);
+        assert!(repository.list_open_alerts().unwrap().is_empty());
+        assert_eq!(repository.list_events(None, None).unwrap()[0].status(), &Status::Resolved);
+    }
+}
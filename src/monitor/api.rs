@@ -0,0 +1,157 @@
+//! 监控状态接口
+//!
+//! - `GET /checks`: 所有监控项的状态和最近一次结果
+//! - `GET /checks/<name>`: 单个监控项，不存在时返回 404
+//! - `GET /events`: 最近通知的事件
+//! - `GET /alerts`: 未解决的告警
+//! - `POST /alerts/<id>/ack`: 确认告警，请求体 `{"note": "...", "duration_secs": 3600}`，字段均可省略
+//! - `POST /alerts/<id>/silence`: 静默告警，请求体 `{"duration_secs": 3600}`
+
+use std::error::Error;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use serde::Deserialize;
+use warp::http::StatusCode;
+use warp::{Filter, Rejection, Reply};
+
+use crate::monitor::event::Event;
+use crate::monitor::monitor::MonitorState;
+
+// 请求体上限
+const BODY_LIMIT: u64 = 16 * 1024;
+
+#[derive(Debug, Default, Deserialize)]
+struct AcknowledgeRequest {
+    #[serde(default)]
+    note: String,
+    #[serde(default)]
+    duration_secs: Option<u64>,              // 为空时一直有效直到告警解决
+}
+
+#[derive(Debug, Deserialize)]
+struct SilenceRequest {
+    duration_secs: u64,
+}
+
+// 修改后的告警，不存在时返回 404
+fn alert_reply(id: &str, alert: Option<Event>) -> warp::reply::WithStatus<warp::reply::Json> {
+    match alert {
+        Some(alert) => warp::reply::with_status(warp::reply::json(&alert), StatusCode::OK),
+        None => warp::reply::with_status(warp::reply::json(&format!("alert {} not found", id)), StatusCode::NOT_FOUND),
+    }
+}
+
+pub fn routes(state: MonitorState) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let checks = {
+        let state = state.clone();
+        warp::path!("checks").and(warp::get()).map(move || warp::reply::json(&state.checks()))
+    };
+    let check = {
+        let state = state.clone();
+        warp::path!("checks" / String).and(warp::get()).map(move |name: String| match state.check(&name) {
+            Some(check) => warp::reply::with_status(warp::reply::json(&check), StatusCode::OK),
+            None => warp::reply::with_status(warp::reply::json(&format!("check {} not found", name)), StatusCode::NOT_FOUND),
+        })
+    };
+    let events = {
+        let state = state.clone();
+        warp::path!("events").and(warp::get()).map(move || warp::reply::json(&state.events()))
+    };
+    let alerts = {
+        let state = state.clone();
+        warp::path!("alerts").and(warp::get()).map(move || warp::reply::json(&state.alerts().alerts()))
+    };
+    let acknowledge = {
+        let state = state.clone();
+        warp::path!("alerts" / String / "ack")
+            .and(warp::post())
+            .and(warp::body::content_length_limit(BODY_LIMIT))
+            .and(warp::body::json())
+            .map(move |id: String, request: AcknowledgeRequest| {
+                let duration = request.duration_secs.map(Duration::from_secs);
+                alert_reply(&id, state.alerts().acknowledge(&id, &request.note, duration))
+            })
+    };
+    let silence = warp::path!("alerts" / String / "silence")
+        .and(warp::post())
+        .and(warp::body::content_length_limit(BODY_LIMIT))
+        .and(warp::body::json())
+        .map(move |id: String, request: SilenceRequest| {
+            alert_reply(&id, state.alerts().silence(&id, Duration::from_secs(request.duration_secs)))
+        });
+    checks.or(check).or(events).or(alerts).or(acknowledge).or(silence)
+}
+
+/// 绑定监听地址，shutdown 完成后不再接受新连接，需要在 tokio 运行时中执行
+pub fn bind(state: MonitorState, address: SocketAddr, shutdown: impl Future<Output = ()> + Send + 'static)
+    -> Result<(SocketAddr, impl Future<Output = ()>), Box<dyn Error>> {
+    Ok(warp::serve(routes(state)).try_bind_with_graceful_shutdown(address, shutdown)?)
+}
+
+#[cfg(test)]
+mod unit_test_api {
+    use super::*;
+    use crate::common::config::MonitorConfig;
+    use crate::monitor::check::CheckStatus;
+    use crate::monitor::event::{Priority, Severity};
+    use crate::monitor::monitor::Monitor;
+    use tokio::sync::watch;
+
+    #[test]
+    fn test_api_01() {
+        let config: MonitorConfig = serde_json::from_str(r#"{
+            "disk_usage": {"threshold": 100, "interval_secs": 3600, "is_active": true},
+            "open_port": {"high_risk_ports": [], "interval_secs": 3600, "is_active": true},
+            "task_status": {"default_statuses": [], "task_list": [], "interval_secs": 3600, "is_active": true}
+        }"#).unwrap();
+        assert_eq!(config.listen, "127.0.0.1:9103");
+        let monitor = Monitor::from_config(config).unwrap();
+        let state = monitor.state();
+        let filter = routes(state.clone());
+        let (sender, receiver) = watch::channel(false);
+
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let handle = tokio::spawn(monitor.run(receiver));
+            tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+
+            let response = warp::test::request().path("/checks").reply(&filter).await;
+            assert_eq!(response.status(), StatusCode::OK);
+            let checks: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+            assert_eq!(checks.as_array().unwrap().len(), 3);
+            assert_eq!(checks[0]["name"], "disk_usage");
+
+            let response = warp::test::request().path("/checks/open_port").reply(&filter).await;
+            let check: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+            assert_eq!(check["interval_secs"], 3600);
+            assert_eq!(check["timeout_secs"], 30);
+
+            let response = warp::test::request().path("/checks/nope").reply(&filter).await;
+            assert_eq!(response.status(), StatusCode::NOT_FOUND);
+            let response = warp::test::request().path("/events").reply(&filter).await;
+            assert_eq!(response.status(), StatusCode::OK);
+            let response = warp::test::request().path("/alerts").reply(&filter).await;
+            assert_eq!(response.body().as_ref(), b"[]");
+            let response = warp::test::request().method("POST").path("/alerts/nope/silence")
+                .json(&serde_json::json!({"duration_secs": 60})).reply(&filter).await;
+            assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+            let event = Event::new("check_load", "LOAD CRITICAL", Priority::High, Severity::Critical, "脚本监控");
+            let id = state.alerts().record("check_load", CheckStatus::Critical, vec![event])[0].id().to_string();
+            let response = warp::test::request().method("POST").path(&format!("/alerts/{}/ack", id))
+                .json(&serde_json::json!({"note": "investigating"})).reply(&filter).await;
+            assert_eq!(response.status(), StatusCode::OK);
+            let alert: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+            assert_eq!(alert["status"], "InProgress");
+            assert_eq!(alert["fingerprint"], "check_load");
+            let response = warp::test::request().path("/alerts").reply(&filter).await;
+            let alerts: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+            assert_eq!(alerts[0]["id"], id.as_str());
+
+            sender.send(true).unwrap();
+            handle.await.unwrap();
+        });
+    }
+}
@@ -0,0 +1,87 @@
+use crate::monitor::asset::snapshot::{ChangeCategory, ChangeKind, SnapshotDiff};
+use crate::monitor::event::*;
+
+// 需要告警的账号变更，形如 "user mallory added(uid 0 /bin/bash); alice root:AAAA added(ssh-ed25519 x@y)"
+//
+// 新增的用户、组、SSH 公钥和 sudo 规则，以及用户 uid、附加组和组成员的修改
+fn account_alerts(diff: &SnapshotDiff) -> String {
+    let mut alerts = Vec::new();
+    for change in &diff.changes {
+        let security = matches!(change.category, ChangeCategory::Account | ChangeCategory::SshKey | ChangeCategory::Sudo);
+        if !security {
+            continue;
+        }
+        match change.kind {
+            ChangeKind::Added => alerts.push(format!("{} added({})", change.item, change.to.as_deref().unwrap_or_default())),
+            ChangeKind::Modified if ["uid", "groups", "members"].iter().any(|field| change.item.ends_with(field)) => {
+                alerts.push(format!("{} {} -> {}", change.item, change.from.as_deref().unwrap_or_default(),
+                                    change.to.as_deref().unwrap_or_default()));
+            }
+            _ => {}
+        }
+    }
+    alerts.join("; ")
+}
+
+// 账号安全监控项，比对两次资产快照的差异
+pub fn check_account_changes(diff: &SnapshotDiff, is_active: &bool) -> Option<Event> {
+    let alert_contents = account_alerts(diff);
+
+    if alert_contents.is_empty() {
+        None
+    } else {
+        Some(Event::new("账号变更监控",
+            &format!("{} 账号变更: {}", diff.hostname, alert_contents),
+            Priority::High,
+            if *is_active { Severity::Warning } else { Severity::Debug },
+            "主机资产监控"
+        ))
+    }
+}
+
+#[cfg(test)]
+mod unit_test_account {
+    use super::*;
+    use crate::info::account::{AuthorizedKey, User};
+    use crate::monitor::asset::snapshot::diff;
+    use crate::monitor::asset::snapshot::unit_test_snapshot::snapshot;
+
+    fn user(name: &str, uid: u32) -> User {
+        User {
+            name: name.to_string(),
+            uid,
+            gid: uid,
+            gecos: String::new(),
+            home: format!("/home/{}", name),
+            shell: "/bin/bash".to_string(),
+            groups: vec![],
+            password: None,
+        }
+    }
+
+    #[test]
+    fn test_account_monitor_01() {
+        let mut old = snapshot("node-1", 1);
+        old.users = vec![user("root", 0), user("alice", 1000)];
+        let mut new = old.clone();
+        assert!(check_account_changes(&diff(&old, &new), &true).is_none());
+
+        new.users[1].groups = vec!["sudo".to_string()];
+        new.users.push(user("mallory", 0));
+        new.authorized_keys.push(AuthorizedKey {
+            user: "root".to_string(),
+            key_type: "ssh-ed25519".to_string(),
+            fingerprint: "SHA256:abc".to_string(),
+            comment: "mallory@evil".to_string(),
+            options: String::new(),
+        });
+        let event = check_account_changes(&diff(&old, &new), &true).unwrap();
+        assert_eq!(event.description(), "node-1 账号变更: user alice groups  -> sudo; user mallory added(uid 0 /bin/bash); \
+root SHA256:abc added(ssh-ed25519 mallory@evil)");
+        assert_eq!(event.severity(), &Severity::Warning);
+
+        // 旧快照没有账号信息时不告警
+        old.users.clear();
+        assert!(check_account_changes(&diff(&old, &new), &true).is_none());
+    }
+}
@@ -0,0 +1,677 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use chrono::{DateTime, Local, Utc};
+use colored::Colorize;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::info::account::{AuthorizedKey, Group, SudoRule, User};
+use crate::info::hardware::HardwareInfo;
+use crate::info::hostinfo::HostInfo;
+use crate::info::package::{installed_packages, Package, PackageQuery};
+
+// 磁盘设备
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiskDevice {
+    pub name: String,
+    pub total: u64,                          // 字节
+    pub model: String,
+}
+
+// 分区
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Partition {
+    pub mount_point: String,
+    pub total: u64,                          // 字节
+}
+
+// 网卡
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Nic {
+    pub name: String,
+    pub mac: String,
+    pub status: String,
+    pub ipv4: Vec<String>,
+    pub ipv6: Vec<String>,
+}
+
+// 监听端口
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ListenPort {
+    pub protocol: String,
+    pub ip_address: String,
+    pub port: u16,
+    pub pid: u32,
+}
+
+impl ListenPort {
+    fn key(&self) -> String {
+        format!("{} {}:{}", self.protocol, self.ip_address, self.port)
+    }
+}
+
+// 主机资产快照，保存 HostInfo 中用于比对的部分
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HostSnapshot {
+    pub id: String,
+    pub hostname: String,
+    pub taken_at: DateTime<Utc>,
+    pub os_type: String,
+    pub os_name: String,
+    pub os_version: String,
+    pub os_arch: String,
+    #[serde(default)]
+    pub kernel_release: String,
+    pub cpu_sockets: u32,
+    pub cpu_cores: u32,
+    pub cpu_threads: u32,
+    pub memory_total: u64,                   // 字节
+    pub swap_total: u64,                     // 字节
+    pub disks: Vec<DiskDevice>,
+    pub partitions: Vec<Partition>,
+    pub nics: Vec<Nic>,
+    pub listening: Vec<ListenPort>,
+    pub process_count: usize,
+    #[serde(default)]
+    pub packages: Vec<Package>,              // 已安装软件包，无法读取包数据库时为空
+    #[serde(default)]
+    pub hardware: HardwareInfo,
+    #[serde(default)]
+    pub fingerprint: Option<String>,         // 硬件指纹，重装系统或修改主机名后不变
+    #[serde(default)]
+    pub users: Vec<User>,                    // 本地用户，无法读取 /etc/passwd 时为空
+    #[serde(default)]
+    pub groups: Vec<Group>,
+    #[serde(default)]
+    pub authorized_keys: Vec<AuthorizedKey>,
+    #[serde(default)]
+    pub sudoers: Vec<SudoRule>,
+}
+
+impl HostSnapshot {
+    /// 采集本机当前信息和已安装软件包生成快照
+    pub fn capture() -> Self {
+        let mut snapshot = Self::from_host_info(&HostInfo::new());
+        snapshot.packages = installed_packages().unwrap_or_else(|e| {
+            warn!("failed to collect installed packages: {}", e);
+            Vec::new()
+        });
+        snapshot.hardware = HardwareInfo::new();
+        snapshot.fingerprint = snapshot.hardware.fingerprint().map(|fingerprint| fingerprint.id);
+        snapshot
+    }
+
+    pub fn from_host_info(host_info: &HostInfo) -> Self {
+        let os_info = host_info.os_info();
+        let cpu_info = host_info.cpu_info();
+        let mem_info = host_info.mem_info();
+
+        let mut disks: Vec<DiskDevice> = host_info.disk_info().block_devices().iter().map(|device| DiskDevice {
+            name: device.name.clone(),
+            total: device.size,
+            model: device.model.clone(),
+        }).collect();
+        disks.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut partitions: Vec<Partition> = host_info.disk_info().partitions().iter().map(|p| Partition {
+            mount_point: p.mount.clone(),
+            total: p.total,
+        }).collect();
+        partitions.sort_by(|a, b| a.mount_point.cmp(&b.mount_point));
+
+        let mut nics: Vec<Nic> = host_info.net_info().iter().map(|nic| {
+            let mut ipv4 = nic.ipv4().clone();
+            let mut ipv6 = nic.ipv6().clone();
+            ipv4.sort();
+            ipv6.sort();
+            Nic {
+                name: nic.name().to_string(),
+                mac: nic.mac().to_string(),
+                status: nic.status().to_string(),
+                ipv4,
+                ipv6,
+            }
+        }).collect();
+        nics.sort_by(|a, b| a.name.cmp(&b.name));
+
+        // 仅回环地址上的监听不对外暴露，不计入资产
+        let mut listening: Vec<ListenPort> = host_info.service_info().iter()
+            .filter(|s| s.is_listening() && !s.is_loopback())
+            .map(|s| ListenPort {
+                protocol: s.protocol().to_string(),
+                ip_address: s.ip_address().to_string(),
+                port: s.port(),
+                pid: s.pid(),
+            }).collect();
+        listening.sort();
+        listening.dedup_by(|a, b| a.key() == b.key());
+
+        let accounts = host_info.account_info();
+        let mut authorized_keys = accounts.authorized_keys.clone();
+        authorized_keys.sort();
+
+        HostSnapshot {
+            id: Uuid::new_v4().to_string(),
+            hostname: host_info.hostname().to_string(),
+            taken_at: Utc::now(),
+            os_type: os_info.os_type().to_string(),
+            os_name: os_info.os_name().to_string(),
+            os_version: os_info.os_version().to_string(),
+            os_arch: os_info.os_arch().to_string(),
+            kernel_release: os_info.kernel().release.clone(),
+            cpu_sockets: cpu_info.cpu_socket_count(),
+            cpu_cores: cpu_info.cpu_core_count(),
+            cpu_threads: cpu_info.cpu_thread_count(),
+            memory_total: mem_info.memory_total(),
+            swap_total: mem_info.swap_total(),
+            disks,
+            partitions,
+            nics,
+            listening,
+            process_count: host_info.process_info().len(),
+            packages: Vec::new(),
+            hardware: HardwareInfo::default(),
+            fingerprint: None,
+            users: accounts.users.clone(),
+            groups: accounts.groups.clone(),
+            authorized_keys,
+            sudoers: accounts.sudoers.clone(),
+        }
+    }
+
+    pub fn display(&self) -> String {
+        let mut output = format!("Snapshot {} of {} at {}\n", self.id.green(), self.hostname.green(),
+                                 self.taken_at.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S"));
+        if let Some(dmi) = &self.hardware.dmi {
+            output.push_str(&format!("  System: {} {} (serial {}, BIOS {})\n", dmi.sys_vendor, dmi.product_name, dmi.product_serial, dmi.bios_version));
+        }
+        output.push_str(&format!("  Fingerprint: {}\n", self.fingerprint.as_deref().unwrap_or("N/A")));
+        output.push_str(&format!("  OS: {} {} {} ({}), kernel {}\n", self.os_type, self.os_name, self.os_version, self.os_arch, self.kernel_release));
+        output.push_str(&format!("  CPU: {} socket(s) {} core(s) {} thread(s)\n", self.cpu_sockets, self.cpu_cores, self.cpu_threads));
+        output.push_str(&format!("  Memory: {} bytes, swap {} bytes\n", self.memory_total, self.swap_total));
+        for disk in &self.disks {
+            output.push_str(&format!("  Disk {}: {} bytes ({})\n", disk.name, disk.total, disk.model));
+        }
+        for partition in &self.partitions {
+            output.push_str(&format!("  Partition {}: {} bytes\n", partition.mount_point, partition.total));
+        }
+        for nic in &self.nics {
+            output.push_str(&format!("  NIC {} {} {} {}\n", nic.name, nic.status, nic.mac,
+                                     nic.ipv4.iter().chain(nic.ipv6.iter()).cloned().collect::<Vec<_>>().join(" ")));
+        }
+        for port in &self.listening {
+            output.push_str(&format!("  Listen {} (pid {})\n", port.key(), port.pid));
+        }
+        for user in self.users.iter().filter(|user| user.can_login()) {
+            output.push_str(&format!("  User {} (uid {}, {})\n", user.name, user.uid, user.shell));
+        }
+        for key in &self.authorized_keys {
+            output.push_str(&format!("  Key {} {} {}\n", key.user, key.fingerprint, key.comment));
+        }
+        output.push_str(&format!("  Sudo rules: {}\n", self.sudoers.len()));
+        output.push_str(&format!("  Processes: {}\n", self.process_count));
+        output.push_str(&format!("  Packages: {}", self.packages.len()));
+        println!("{}\n", output);
+        output
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|err| {
+            eprintln!("序列化主机快照到 JSON 失败: {}", err);
+            "{}".to_string()
+        })
+    }
+}
+
+// 变更对象类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChangeCategory {
+    Host,
+    Os,
+    Cpu,
+    Memory,
+    Disk,
+    Partition,
+    Network,
+    Port,
+    Package,
+    Hardware,
+    Account,
+    SshKey,
+    Sudo,
+}
+
+// 变更类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+// 单项变更，新增时仅有 to，删除时仅有 from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Change {
+    pub category: ChangeCategory,
+    pub kind: ChangeKind,
+    pub item: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+impl Change {
+    fn added(category: ChangeCategory, item: &str, to: &str) -> Self {
+        Change { category, kind: ChangeKind::Added, item: item.to_string(), from: None, to: Some(to.to_string()) }
+    }
+
+    fn removed(category: ChangeCategory, item: &str, from: &str) -> Self {
+        Change { category, kind: ChangeKind::Removed, item: item.to_string(), from: Some(from.to_string()), to: None }
+    }
+
+    fn modified(category: ChangeCategory, item: &str, from: &str, to: &str) -> Self {
+        Change { category, kind: ChangeKind::Modified, item: item.to_string(), from: Some(from.to_string()), to: Some(to.to_string()) }
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let category = format!("{:?}", self.category);
+        match self.kind {
+            ChangeKind::Added => write!(f, "{:<10} + {} {}", category, self.item, self.to.as_deref().unwrap_or_default()),
+            ChangeKind::Removed => write!(f, "{:<10} - {} {}", category, self.item, self.from.as_deref().unwrap_or_default()),
+            ChangeKind::Modified => write!(f, "{:<10} ~ {}: {} -> {}", category, self.item,
+                                           self.from.as_deref().unwrap_or_default(), self.to.as_deref().unwrap_or_default()),
+        }
+    }
+}
+
+// 两个快照之间的差异
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotDiff {
+    pub hostname: String,
+    pub from_id: String,
+    pub to_id: String,
+    pub from_time: DateTime<Utc>,
+    pub to_time: DateTime<Utc>,
+    pub changes: Vec<Change>,
+}
+
+impl SnapshotDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub fn display(&self) -> String {
+        let mut output = format!("{} {} -> {} ({} changes)", self.hostname.green(),
+                                 self.from_time.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S"),
+                                 self.to_time.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S"),
+                                 self.changes.len());
+        for change in &self.changes {
+            let line = match change.kind {
+                ChangeKind::Added => change.to_string().green(),
+                ChangeKind::Removed => change.to_string().red(),
+                ChangeKind::Modified => change.to_string().yellow(),
+            };
+            output.push_str(&format!("\n  {}", line));
+        }
+        println!("{}\n", output);
+        output
+    }
+}
+
+fn push_modified(changes: &mut Vec<Change>, category: ChangeCategory, item: &str, from: &str, to: &str) {
+    if from != to {
+        changes.push(Change::modified(category, item, from, to));
+    }
+}
+
+// 按键比对两组对象，新增、删除各记一项，共有的交给 compare 比对属性
+fn diff_keyed<T, K, C>(changes: &mut Vec<Change>, category: ChangeCategory, old: &[T], new: &[T],
+                       key: K, describe: fn(&T) -> String, mut compare: C)
+where
+    K: Fn(&T) -> String,
+    C: FnMut(&mut Vec<Change>, &str, &T, &T),
+{
+    let old_map: BTreeMap<String, &T> = old.iter().map(|item| (key(item), item)).collect();
+    let new_map: BTreeMap<String, &T> = new.iter().map(|item| (key(item), item)).collect();
+    for (name, item) in &old_map {
+        match new_map.get(name) {
+            Some(new_item) => compare(changes, name, item, new_item),
+            None => changes.push(Change::removed(category, name, &describe(item))),
+        }
+    }
+    for (name, item) in &new_map {
+        if !old_map.contains_key(name) {
+            changes.push(Change::added(category, name, &describe(item)));
+        }
+    }
+}
+
+fn diff_addresses(changes: &mut Vec<Change>, nic: &str, family: &str, old: &[String], new: &[String]) {
+    // 早期快照记录的地址不带前缀长度，此时只比对地址
+    let bare = old.iter().chain(new.iter()).any(|ip| !ip.contains('/'));
+    let address = |ip: &String| if bare { ip.split('/').next().unwrap_or(ip).to_string() } else { ip.clone() };
+    let old: BTreeSet<String> = old.iter().map(address).collect();
+    let new: BTreeSet<String> = new.iter().map(address).collect();
+    let item = format!("{} {}", nic, family);
+    for ip in old.difference(&new) {
+        changes.push(Change::removed(ChangeCategory::Network, &item, ip));
+    }
+    for ip in new.difference(&old) {
+        changes.push(Change::added(ChangeCategory::Network, &item, ip));
+    }
+}
+
+// 按 "包名:架构" 汇总版本，同名包可同时安装多个版本（如 rpm 的 kernel）
+fn package_versions(packages: &[Package]) -> Vec<(String, String)> {
+    let mut versions: BTreeMap<String, Vec<&str>> = BTreeMap::new();
+    for package in packages {
+        let key = if package.arch.is_empty() { package.name.clone() } else { format!("{}:{}", package.name, package.arch) };
+        versions.entry(key).or_default().push(&package.version);
+    }
+    versions.into_iter().map(|(key, versions)| (key, versions.join(", "))).collect()
+}
+
+// 主机上满足查询条件的软件包
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HostPackage {
+    pub hostname: String,
+    pub snapshot_id: String,
+    pub taken_at: DateTime<Utc>,
+    pub package: Package,
+}
+
+/// 在快照中查找满足条件的软件包
+pub fn find_packages(snapshot: &HostSnapshot, query: &PackageQuery) -> Vec<HostPackage> {
+    snapshot.packages.iter()
+        .filter(|package| query.matches(package))
+        .map(|package| HostPackage {
+            hostname: snapshot.hostname.clone(),
+            snapshot_id: snapshot.id.clone(),
+            taken_at: snapshot.taken_at,
+            package: package.clone(),
+        })
+        .collect()
+}
+
+/// 比对两个快照，返回 old 到 new 的变更
+pub fn diff(old: &HostSnapshot, new: &HostSnapshot) -> SnapshotDiff {
+    use ChangeCategory::*;
+
+    let mut changes = Vec::new();
+    push_modified(&mut changes, Host, "hostname", &old.hostname, &new.hostname);
+    push_modified(&mut changes, Os, "os_type", &old.os_type, &new.os_type);
+    push_modified(&mut changes, Os, "os_name", &old.os_name, &new.os_name);
+    push_modified(&mut changes, Os, "os_version", &old.os_version, &new.os_version);
+    push_modified(&mut changes, Os, "os_arch", &old.os_arch, &new.os_arch);
+    if !old.kernel_release.is_empty() && !new.kernel_release.is_empty() {
+        push_modified(&mut changes, Os, "kernel_release", &old.kernel_release, &new.kernel_release);
+    }
+    push_modified(&mut changes, Cpu, "sockets", &old.cpu_sockets.to_string(), &new.cpu_sockets.to_string());
+    push_modified(&mut changes, Cpu, "cores", &old.cpu_cores.to_string(), &new.cpu_cores.to_string());
+    push_modified(&mut changes, Cpu, "threads", &old.cpu_threads.to_string(), &new.cpu_threads.to_string());
+    push_modified(&mut changes, Memory, "memory_total", &old.memory_total.to_string(), &new.memory_total.to_string());
+    push_modified(&mut changes, Memory, "swap_total", &old.swap_total.to_string(), &new.swap_total.to_string());
+
+    diff_keyed(&mut changes, Disk, &old.disks, &new.disks, |d| d.name.clone(),
+               |d| format!("{} ({})", d.total, d.model),
+               |changes, name, a, b| {
+                   push_modified(changes, Disk, &format!("{} total", name), &a.total.to_string(), &b.total.to_string());
+                   push_modified(changes, Disk, &format!("{} model", name), &a.model, &b.model);
+               });
+    diff_keyed(&mut changes, Partition, &old.partitions, &new.partitions, |p| p.mount_point.clone(),
+               |p| p.total.to_string(),
+               |changes, name, a, b| push_modified(changes, Partition, &format!("{} total", name), &a.total.to_string(), &b.total.to_string()));
+    diff_keyed(&mut changes, Network, &old.nics, &new.nics, |n| n.name.clone(),
+               |n| n.ipv4.iter().chain(n.ipv6.iter()).cloned().collect::<Vec<_>>().join(" "),
+               |changes, name, a, b| {
+                   push_modified(changes, Network, &format!("{} mac", name), &a.mac, &b.mac);
+                   push_modified(changes, Network, &format!("{} status", name), &a.status, &b.status);
+                   diff_addresses(changes, name, "ipv4", &a.ipv4, &b.ipv4);
+                   diff_addresses(changes, name, "ipv6", &a.ipv6, &b.ipv6);
+               });
+    diff_keyed(&mut changes, Port, &old.listening, &new.listening, ListenPort::key,
+               |p| format!("pid {}", p.pid),
+               |_, _, _, _| {});
+    push_modified(&mut changes, Hardware, "fingerprint", old.fingerprint.as_deref().unwrap_or_default(),
+                  new.fingerprint.as_deref().unwrap_or_default());
+    if let (Some(a), Some(b)) = (&old.hardware.dmi, &new.hardware.dmi) {
+        push_modified(&mut changes, Hardware, "bios_version", &a.bios_version, &b.bios_version);
+    }
+    diff_keyed(&mut changes, Hardware, &old.hardware.pci, &new.hardware.pci,
+               |d| format!("pci {} {:04x}:{:04x}", d.slot, d.vendor_id, d.device_id),
+               |d| format!("{} {}", d.vendor, d.device).trim().to_string(),
+               |_, _, _, _| {});
+    diff_keyed(&mut changes, Hardware, &old.hardware.usb, &new.hardware.usb,
+               |d| format!("usb {} {:04x}:{:04x}", d.port, d.vendor_id, d.product_id),
+               |d| format!("{} {}", d.manufacturer, d.product).trim().to_string(),
+               |_, _, _, _| {});
+    // 任一快照未能读取包数据库时不比对软件包
+    if !old.packages.is_empty() && !new.packages.is_empty() {
+        diff_keyed(&mut changes, Package, &package_versions(&old.packages), &package_versions(&new.packages),
+                   |(name, _)| name.clone(),
+                   |(_, versions)| versions.clone(),
+                   |changes, name, a, b| push_modified(changes, Package, name, &a.1, &b.1));
+    }
+
+    // 旧快照没有账号信息时不比对账号、公钥和 sudo 规则
+    if !old.users.is_empty() && !new.users.is_empty() {
+        diff_keyed(&mut changes, Account, &old.users, &new.users, |u| format!("user {}", u.name),
+                   |u| format!("uid {} {}", u.uid, u.shell),
+                   |changes, name, a, b| {
+                       push_modified(changes, Account, &format!("{} uid", name), &a.uid.to_string(), &b.uid.to_string());
+                       push_modified(changes, Account, &format!("{} shell", name), &a.shell, &b.shell);
+                       push_modified(changes, Account, &format!("{} groups", name), &a.groups.join(","), &b.groups.join(","));
+                       if let (Some(a), Some(b)) = (&a.password, &b.password) {
+                           push_modified(changes, Account, &format!("{} password", name), &a.status, &b.status);
+                           push_modified(changes, Account, &format!("{} password_changed", name),
+                                         &a.last_change.map(|d| d.to_string()).unwrap_or_default(),
+                                         &b.last_change.map(|d| d.to_string()).unwrap_or_default());
+                       }
+                   });
+        diff_keyed(&mut changes, Account, &old.groups, &new.groups, |g| format!("group {}", g.name),
+                   |g| format!("gid {} {}", g.gid, g.members.join(",")),
+                   |changes, name, a, b| push_modified(changes, Account, &format!("{} members", name), &a.members.join(","), &b.members.join(",")));
+        diff_keyed(&mut changes, SshKey, &old.authorized_keys, &new.authorized_keys, |k| format!("{} {}", k.user, k.fingerprint),
+                   |k| format!("{} {}", k.key_type, k.comment).trim().to_string(),
+                   |changes, name, a, b| push_modified(changes, SshKey, &format!("{} options", name), &a.options, &b.options));
+        diff_keyed(&mut changes, Sudo, &old.sudoers, &new.sudoers, |r| r.rule.clone(),
+                   |r| r.file.clone(),
+                   |_, _, _, _| {});
+    }
+
+    SnapshotDiff {
+        hostname: new.hostname.clone(),
+        from_id: old.id.clone(),
+        to_id: new.id.clone(),
+        from_time: old.taken_at,
+        to_time: new.taken_at,
+        changes,
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod unit_test_snapshot {
+    use super::*;
+    use chrono::Duration as ChronoDuration;
+    use crate::info::package::PackageManager;
+
+    pub(crate) fn snapshot(hostname: &str, hours_ago: i64) -> HostSnapshot {
+        HostSnapshot {
+            id: Uuid::new_v4().to_string(),
+            hostname: hostname.to_string(),
+            taken_at: Utc::now() - ChronoDuration::hours(hours_ago),
+            os_type: "Linux".to_string(),
+            os_name: "Ubuntu".to_string(),
+            os_version: "22.04".to_string(),
+            os_arch: "x86_64".to_string(),
+            kernel_release: "5.15.0-105-generic".to_string(),
+            cpu_sockets: 1,
+            cpu_cores: 4,
+            cpu_threads: 8,
+            memory_total: 8 << 30,
+            swap_total: 2 << 30,
+            disks: vec![DiskDevice { name: "sda".to_string(), total: 100 << 30, model: "VBOX".to_string() }],
+            partitions: vec![Partition { mount_point: "/".to_string(), total: 100 << 20 }],
+            nics: vec![Nic {
+                name: "eth0".to_string(),
+                mac: "00:11:22:33:44:55".to_string(),
+                status: "UP".to_string(),
+                ipv4: vec!["10.0.0.2/24".to_string()],
+                ipv6: vec![],
+            }],
+            listening: vec![ListenPort { protocol: "tcp".to_string(), ip_address: "0.0.0.0".to_string(), port: 22, pid: 100 }],
+            process_count: 120,
+            packages: vec![],
+            hardware: HardwareInfo::default(),
+            fingerprint: None,
+            users: vec![],
+            groups: vec![],
+            authorized_keys: vec![],
+            sudoers: vec![],
+        }
+    }
+
+    pub(crate) fn package(name: &str, version: &str) -> Package {
+        Package {
+            name: name.to_string(),
+            version: version.to_string(),
+            arch: "amd64".to_string(),
+            source: if name.contains("ssl") { "openssl" } else { name }.to_string(),
+            manager: PackageManager::Dpkg,
+        }
+    }
+
+    #[test]
+    fn test_snapshot_01() {
+        let old = snapshot("node-1", 2);
+        let mut new = snapshot("node-1", 0);
+        new.process_count = 130;
+        new.listening[0].pid = 200;
+        assert!(diff(&old, &new).is_empty(), "进程数和监听进程号变化不计入差异");
+
+        new.os_version = "24.04".to_string();
+        new.memory_total = 16 << 30;
+        new.disks.push(DiskDevice { name: "sdb".to_string(), total: 1 << 40, model: "DATA".to_string() });
+        new.nics[0].ipv4 = vec!["10.0.0.3".to_string()];
+        new.listening.push(ListenPort { protocol: "tcp".to_string(), ip_address: "0.0.0.0".to_string(), port: 8080, pid: 300 });
+        let result = diff(&old, &new);
+        let changes = &result.changes;
+        assert_eq!(changes.len(), 6);
+        assert!(changes.contains(&Change::modified(ChangeCategory::Os, "os_version", "22.04", "24.04")));
+        assert!(changes.contains(&Change::modified(ChangeCategory::Memory, "memory_total", "8589934592", "17179869184")));
+        assert!(changes.contains(&Change::added(ChangeCategory::Disk, "sdb", "1099511627776 (DATA)")));
+        assert!(changes.contains(&Change::removed(ChangeCategory::Network, "eth0 ipv4", "10.0.0.2")));
+        assert!(changes.contains(&Change::added(ChangeCategory::Network, "eth0 ipv4", "10.0.0.3")));
+        assert!(changes.contains(&Change::added(ChangeCategory::Port, "tcp 0.0.0.0:8080", "pid 300")));
+        assert_eq!(result.from_id, old.id);
+        assert!(result.display().contains("6 changes"));
+
+        // 前缀长度变化计入差异，早期不带前缀的地址只比对地址
+        new = old.clone();
+        new.nics[0].ipv4 = vec!["10.0.0.2/16".to_string()];
+        assert_eq!(diff(&old, &new).changes.len(), 2);
+        new.nics[0].ipv4 = vec!["10.0.0.2".to_string()];
+        assert!(diff(&old, &new).is_empty());
+    }
+
+    #[test]
+    fn test_snapshot_02() {
+        let old = snapshot("node-1", 1);
+        let mut new = old.clone();
+        new.partitions.clear();
+        new.nics.push(Nic {
+            name: "docker0".to_string(),
+            mac: "02:42:00:00:00:01".to_string(),
+            status: "DOWN".to_string(),
+            ipv4: vec!["172.17.0.1".to_string()],
+            ipv6: vec![],
+        });
+        new.nics[0].status = "DOWN".to_string();
+        let changes = diff(&old, &new).changes;
+        assert_eq!(changes, vec![
+            Change::removed(ChangeCategory::Partition, "/", "104857600"),
+            Change::modified(ChangeCategory::Network, "eth0 status", "UP", "DOWN"),
+            Change::added(ChangeCategory::Network, "docker0", "172.17.0.1"),
+        ]);
+        assert_eq!(changes[1].to_string(), "Network    ~ eth0 status: UP -> DOWN");
+    }
+
+    #[test]
+    fn test_snapshot_03() {
+        let snapshot = HostSnapshot::capture();
+        assert!(!snapshot.hostname.is_empty());
+        let json = snapshot.to_json();
+        let parsed: HostSnapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, snapshot);
+    }
+
+    #[test]
+    fn test_snapshot_04() {
+        let mut old = snapshot("node-1", 1);
+        let mut new = old.clone();
+        new.packages = vec![package("libssl3", "3.0.11-1~deb12u2"), package("openssl", "3.0.11-1~deb12u2")];
+        assert!(diff(&old, &new).is_empty(), "未能读取包数据库的快照不比对软件包");
+
+        old.packages = vec![package("libssl3", "3.0.9-1"), package("telnet", "0.17+2.4-2")];
+        let changes = diff(&old, &new).changes;
+        assert_eq!(changes, vec![
+            Change::modified(ChangeCategory::Package, "libssl3:amd64", "3.0.9-1", "3.0.11-1~deb12u2"),
+            Change::removed(ChangeCategory::Package, "telnet:amd64", "0.17+2.4-2"),
+            Change::added(ChangeCategory::Package, "openssl:amd64", "3.0.11-1~deb12u2"),
+        ]);
+
+        let query: PackageQuery = "openssl<3.0.11-1".parse().unwrap();
+        let found = find_packages(&new, &query);
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].hostname, "node-1");
+        assert_eq!(found[0].package.name, "libssl3");
+    }
+
+    #[test]
+    fn test_snapshot_05() {
+        use crate::info::hardware::PciDevice;
+
+        let old = snapshot("node-1", 1);
+        let mut new = old.clone();
+        new.fingerprint = Some("ab12".to_string());
+        new.hardware.pci.push(PciDevice {
+            slot: "0000:01:00.0".to_string(),
+            vendor_id: 0x10de,
+            device_id: 0x1eb8,
+            class: 0x030200,
+            class_name: "Display controller".to_string(),
+            vendor: "NVIDIA Corporation".to_string(),
+            device: String::new(),
+            driver: "nvidia".to_string(),
+        });
+        assert_eq!(diff(&old, &new).changes, vec![
+            Change::modified(ChangeCategory::Hardware, "fingerprint", "", "ab12"),
+            Change::added(ChangeCategory::Hardware, "pci 0000:01:00.0 10de:1eb8", "NVIDIA Corporation"),
+        ]);
+    }
+
+    #[test]
+    fn test_snapshot_06() {
+        use crate::info::account::{AccountInfo, PasswordAge};
+
+        let accounts = AccountInfo::collect_from(&std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/accounts/rootfs")).unwrap();
+        let mut old = snapshot("node-1", 1);
+        old.users = accounts.users;
+        old.authorized_keys = accounts.authorized_keys;
+        old.sudoers = accounts.sudoers;
+        let mut new = old.clone();
+        assert!(diff(&old, &new).is_empty());
+
+        new.users[3].password = Some(PasswordAge { last_change: chrono::NaiveDate::from_ymd_opt(2024, 3, 31), ..old.users[3].password.clone().unwrap() });
+        new.authorized_keys.remove(0);
+        new.sudoers.pop();
+        assert_eq!(diff(&old, &new).changes, vec![
+            Change::modified(ChangeCategory::Account, "user alice password_changed", "2024-01-01", "2024-03-31"),
+            Change::removed(ChangeCategory::SshKey, "root SHA256:rjLbv2a2u/er+9ut5xHyEcojGDhVCbx9PDuusV8e/5I", "ssh-rsa deploy@ci"),
+            Change::removed(ChangeCategory::Sudo, "deploy ALL=(root) NOPASSWD: RESTART", "/etc/sudoers.d/deploy"),
+        ]);
+    }
+}
@@ -0,0 +1,304 @@
+//! 监控项接口
+//!
+//! 每个监控项实现 `Check`，由监控循环按各自的间隔调用 `run`，结果包括状态、说明、性能数据和需要记录的事件。
+//! 状态与 Nagios 插件的退出码一致：0 OK、1 WARNING、2 CRITICAL、3 UNKNOWN。
+
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::common::config::MonitorConfig;
+use crate::monitor::event::{Event, Severity};
+use crate::monitor::script::ScriptCheck;
+use crate::monitor::system::disk_usage::DiskUsageCheck;
+use crate::monitor::system::open_port::OpenPortCheck;
+use crate::monitor::system::resource_usage::ResourceUsageCheck;
+use crate::monitor::system::systemd_unit::SystemdUnitCheck;
+use crate::monitor::task::status::TaskStatusCheck;
+
+// 检查状态，按严重程度排序
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    #[default]
+    Ok,
+    Warning,
+    Critical,
+    Unknown,
+}
+
+impl CheckStatus {
+    /// 按 Nagios 插件退出码转换，其他退出码和被信号终止视为 UNKNOWN
+    pub fn from_exit_code(code: Option<i32>) -> Self {
+        match code {
+            Some(0) => CheckStatus::Ok,
+            Some(1) => CheckStatus::Warning,
+            Some(2) => CheckStatus::Critical,
+            _ => CheckStatus::Unknown,
+        }
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CheckStatus::Ok => 0,
+            CheckStatus::Warning => 1,
+            CheckStatus::Critical => 2,
+            CheckStatus::Unknown => 3,
+        }
+    }
+}
+
+impl fmt::Display for CheckStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            CheckStatus::Ok => "OK",
+            CheckStatus::Warning => "WARNING",
+            CheckStatus::Critical => "CRITICAL",
+            CheckStatus::Unknown => "UNKNOWN",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+// 性能数据，格式同 Nagios perfdata：'label'=value[UOM];[warn];[crit];[min];[max]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PerfData {
+    pub label: String,
+    pub value: f64,
+    pub unit: String,                        // 如 %、s、B，可为空
+    pub warn: Option<String>,                // 阈值范围原样保留，如 "10:"、"@5:10"
+    pub crit: Option<String>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+impl PerfData {
+    pub fn new(label: &str, value: f64, unit: &str) -> Self {
+        PerfData {
+            label: label.to_string(),
+            value,
+            unit: unit.to_string(),
+            warn: None,
+            crit: None,
+            min: None,
+            max: None,
+        }
+    }
+
+    pub fn with_thresholds(mut self, warn: Option<f64>, crit: Option<f64>) -> Self {
+        self.warn = warn.map(|warn| warn.to_string());
+        self.crit = crit.map(|crit| crit.to_string());
+        self
+    }
+
+    pub fn with_range(mut self, min: f64, max: f64) -> Self {
+        self.min = Some(min);
+        self.max = Some(max);
+        self
+    }
+}
+
+impl fmt::Display for PerfData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = if self.label.contains([' ', '=', '\'']) {
+            format!("'{}'", self.label.replace('\'', "''"))
+        } else {
+            self.label.clone()
+        };
+        let optional = |value: &Option<String>| value.clone().unwrap_or_default();
+        let number = |value: &Option<f64>| value.map(|value| value.to_string()).unwrap_or_default();
+        let text = format!("{}={}{};{};{};{};{}", label, self.value, self.unit, optional(&self.warn), optional(&self.crit),
+                           number(&self.min), number(&self.max));
+        write!(f, "{}", text.trim_end_matches(';'))
+    }
+}
+
+// 一次检查的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckResult {
+    pub status: CheckStatus,
+    pub message: String,
+    pub metrics: Vec<PerfData>,
+    #[serde(skip)]
+    pub events: Vec<Event>,                  // 需要记录的事件
+}
+
+impl CheckResult {
+    pub fn new(status: CheckStatus, message: &str) -> Self {
+        CheckResult {
+            status,
+            message: message.to_string(),
+            metrics: vec![],
+            events: vec![],
+        }
+    }
+
+    pub fn with_metrics(mut self, metrics: Vec<PerfData>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    pub fn with_events(mut self, events: Vec<Event>) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// 由内置监控项产生的事件得到结果：没有事件为 OK，否则按事件严重等级，调试和信息级别的事件只记录不告警
+    pub fn from_event(event: Option<Event>) -> Self {
+        match event {
+            Some(event) => {
+                let status = match event.severity() {
+                    Severity::Critical => CheckStatus::Critical,
+                    Severity::Warning => CheckStatus::Warning,
+                    Severity::Info | Severity::Debug => CheckStatus::Ok,
+                };
+                CheckResult {
+                    status,
+                    message: event.description().to_string(),
+                    metrics: vec![],
+                    events: vec![event],
+                }
+            }
+            None => CheckResult::new(CheckStatus::Ok, "OK"),
+        }
+    }
+}
+
+// 监控项
+pub trait Check: Send + Sync {
+    /// 监控项名称，在监控中唯一
+    fn name(&self) -> &str;
+
+    /// 检查间隔
+    fn interval(&self) -> Duration;
+
+    /// 超过该时长未完成视为超时
+    fn timeout(&self) -> Duration;
+
+    /// 执行一次检查
+    fn run(&self) -> CheckResult;
+}
+
+// 已注册的监控项
+#[derive(Default)]
+pub struct CheckRegistry {
+    checks: Vec<Arc<dyn Check>>,
+}
+
+impl fmt::Debug for CheckRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.checks.iter().map(|check| check.name())).finish()
+    }
+}
+
+impl CheckRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 注册内置监控项和配置中的脚本监控项，未配置的可选监控项不注册
+    pub fn from_config(config: &MonitorConfig) -> Result<Self, Box<dyn Error>> {
+        let mut registry = CheckRegistry::new();
+        registry.register(Box::new(DiskUsageCheck::new(config.disk_usage.clone())))?;
+        registry.register(Box::new(OpenPortCheck::new(config.open_port.clone())))?;
+        if let Some(config) = &config.resource_usage {
+            registry.register(Box::new(ResourceUsageCheck::new(config.clone())))?;
+        }
+        if let Some(config) = &config.systemd_units {
+            registry.register(Box::new(SystemdUnitCheck::new(config.clone())))?;
+        }
+        registry.register(Box::new(TaskStatusCheck::new(config.task_status.clone())))?;
+        for script in &config.scripts {
+            registry.register(Box::new(ScriptCheck::new(script.clone())?))?;
+        }
+        Ok(registry)
+    }
+
+    /// 注册监控项，名称重复或间隔为 0 时返回错误
+    pub fn register(&mut self, check: Box<dyn Check>) -> Result<(), Box<dyn Error>> {
+        if self.get(check.name()).is_some() {
+            return Err(format!("check {} is already registered", check.name()).into());
+        }
+        if check.interval().is_zero() {
+            return Err(format!("check {} has zero interval", check.name()).into());
+        }
+        self.checks.push(Arc::from(check));
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Arc<dyn Check>> {
+        self.checks.iter().find(|check| check.name() == name)
+    }
+
+    pub fn checks(&self) -> &[Arc<dyn Check>] {
+        &self.checks
+    }
+}
+
+#[cfg(test)]
+mod unit_test_check {
+    use super::*;
+    use crate::monitor::event::Priority;
+
+    struct Fixed(&'static str, u64);
+
+    impl Check for Fixed {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn interval(&self) -> Duration {
+            Duration::from_secs(self.1)
+        }
+
+        fn timeout(&self) -> Duration {
+            Duration::from_secs(1)
+        }
+
+        fn run(&self) -> CheckResult {
+            CheckResult::new(CheckStatus::Ok, "fixed")
+        }
+    }
+
+    #[test]
+    fn test_check_01() {
+        assert_eq!(CheckStatus::from_exit_code(Some(2)), CheckStatus::Critical);
+        assert_eq!(CheckStatus::from_exit_code(Some(7)), CheckStatus::Unknown);
+        assert_eq!(CheckStatus::from_exit_code(None), CheckStatus::Unknown);
+        assert!(CheckStatus::Critical > CheckStatus::Warning);
+
+        let perf = PerfData::new("/data usage", 91.0, "%").with_thresholds(Some(90.0), None).with_range(0.0, 100.0);
+        assert_eq!(perf.to_string(), "'/data usage'=91%;90;;0;100");
+        assert_eq!(PerfData::new("load", 0.5, "").to_string(), "load=0.5");
+
+        let event = Event::new("磁盘使用率监控", "/-91%", Priority::Low, Severity::Debug, "本地主机监控");
+        let result = CheckResult::from_event(Some(event));
+        assert_eq!(result.status, CheckStatus::Ok);
+        assert_eq!(result.events.len(), 1);
+        assert_eq!(CheckResult::from_event(None).status, CheckStatus::Ok);
+    }
+
+    #[test]
+    fn test_check_02() {
+        let mut registry = CheckRegistry::new();
+        registry.register(Box::new(Fixed("a", 10))).unwrap();
+        assert!(registry.register(Box::new(Fixed("a", 20))).is_err());
+        assert!(registry.register(Box::new(Fixed("b", 0))).is_err());
+        assert_eq!(registry.checks().len(), 1);
+        assert_eq!(registry.get("a").unwrap().run().message, "fixed");
+
+        let config: MonitorConfig = serde_json::from_str(r#"{
+            "disk_usage": {"threshold": 90, "is_active": true},
+            "open_port": {"high_risk_ports": [23], "is_active": true},
+            "task_status": {"default_statuses": [], "task_list": [], "is_active": true},
+            "scripts": [{"name": "check_true", "command": "true", "interval_secs": 10, "is_active": true}]
+        }"#).unwrap();
+        let registry = CheckRegistry::from_config(&config).unwrap();
+        let names: Vec<&str> = registry.checks().iter().map(|check| check.name()).collect();
+        assert_eq!(names, vec!["disk_usage", "open_port", "task_status", "check_true"]);
+        assert_eq!(registry.get("check_true").unwrap().interval(), Duration::from_secs(10));
+    }
+}
@@ -0,0 +1,114 @@
+use std::fmt;
+use std::net::Ipv4Addr;
+
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+
+// 发现设备的纳管状态
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeviceStatus {
+    #[default]
+    Candidate,                               // 待确认的候选资产
+    Accepted,                                // 已纳入环境管理
+    Ignored,                                 // 忽略，不再提示
+}
+
+impl fmt::Display for DeviceStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            DeviceStatus::Candidate => "candidate",
+            DeviceStatus::Accepted => "accepted",
+            DeviceStatus::Ignored => "ignored",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+impl std::str::FromStr for DeviceStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "candidate" => Ok(DeviceStatus::Candidate),
+            "accepted" => Ok(DeviceStatus::Accepted),
+            "ignored" => Ok(DeviceStatus::Ignored),
+            _ => Err(format!("unknown device status '{}', expect candidate, accepted or ignored", s)),
+        }
+    }
+}
+
+// 开放端口
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OpenPort {
+    pub port: u16,
+    pub service: Option<String>,             // 按欢迎信息或端口猜测的服务
+    pub banner: Option<String>,              // 服务端主动发送的欢迎信息
+}
+
+// 网络发现得到的设备
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Device {
+    pub ip: Ipv4Addr,
+    pub mac: Option<String>,                 // ARP 应答中的 MAC 地址
+    pub methods: Vec<String>,                // 发现方式：arp、icmp、tcp
+    pub open_ports: Vec<OpenPort>,
+    pub status: DeviceStatus,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+}
+
+impl Device {
+    pub fn new(ip: Ipv4Addr) -> Self {
+        let now = Utc::now();
+        Device {
+            ip,
+            mac: None,
+            methods: vec![],
+            open_ports: vec![],
+            status: DeviceStatus::Candidate,
+            first_seen: now,
+            last_seen: now,
+        }
+    }
+
+    /// 记录发现方式，重复的忽略
+    pub fn add_method(&mut self, method: &str) {
+        if !self.methods.iter().any(|m| m == method) {
+            self.methods.push(method.to_string());
+        }
+    }
+
+    /// 合并同一地址上一次的记录：保留首次发现时刻和纳管状态，本次没有 ARP 应答时沿用旧 MAC
+    pub fn merge_previous(&mut self, previous: &Device) {
+        self.first_seen = previous.first_seen;
+        self.status = previous.status;
+        if self.mac.is_none() {
+            self.mac = previous.mac.clone();
+        }
+    }
+
+    pub fn ports_summary(&self) -> String {
+        self.open_ports.iter()
+            .map(|p| match &p.service {
+                Some(service) => format!("{}/{}", p.port, service),
+                None => p.port.to_string(),
+            })
+            .collect::<Vec<String>>()
+            .join(",")
+    }
+}
+
+/// 以表格打印设备列表
+pub fn display_devices(devices: &[Device]) {
+    println!("{:<16} {:<18} {:<10} {:<12} {:<20} PORTS", "IP", "MAC", "STATUS", "METHODS", "LAST SEEN");
+    for device in devices {
+        println!("{:<16} {:<18} {:<10} {:<12} {:<20} {}", device.ip.to_string(),
+                 device.mac.as_deref().unwrap_or("-"), device.status.to_string(), device.methods.join(","),
+                 device.last_seen.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S").to_string(),
+                 device.ports_summary());
+        for port in device.open_ports.iter().filter(|p| p.banner.is_some()) {
+            println!("{:<16} {:>5}: {}", "", port.port, port.banner.as_deref().unwrap_or_default());
+        }
+    }
+}
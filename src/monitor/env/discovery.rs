@@ -0,0 +1,771 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::error::Error;
+use std::io::Read;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpStream, UdpSocket};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use pnet::datalink::{self, Channel, NetworkInterface};
+use pnet::ipnetwork::{IpNetwork, Ipv4Network};
+use pnet::packet::arp::{ArpHardwareTypes, ArpOperations, ArpPacket, MutableArpPacket};
+use pnet::packet::ethernet::{EtherTypes, EthernetPacket, MutableEthernetPacket};
+use pnet::packet::icmp::echo_reply::EchoReplyPacket;
+use pnet::packet::icmp::echo_request::MutableEchoRequestPacket;
+use pnet::packet::icmp::{self, IcmpPacket, IcmpTypes};
+use pnet::packet::ip::IpNextHeaderProtocols;
+use pnet::packet::tcp::{self, MutableTcpPacket, TcpFlags, TcpPacket};
+use pnet::packet::{MutablePacket, Packet};
+use pnet::transport::{self, TransportChannelType, TransportProtocol};
+use pnet::util::MacAddr;
+use rand::Rng;
+
+use crate::info::socket::{guess_service_by_banner, guess_service_by_port};
+use crate::monitor::env::device::{Device, OpenPort};
+
+// 默认扫描的端口
+pub const DEFAULT_PORTS: [u16; 15] = [21, 22, 23, 25, 53, 80, 110, 143, 443, 445, 3306, 3389, 5432, 6379, 8080];
+// 默认发包速率（个/秒）
+pub const DEFAULT_RATE: u32 = 100;
+// 单次发现最多扫描的地址数，超出时需要缩小网段
+pub const DEFAULT_MAX_HOSTS: usize = 4096;
+
+// 接收线程轮询间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+// TCP 连接扫描并发数
+const SCAN_WORKERS: usize = 32;
+
+/// 解析端口列表，如 "22,80,8000-8010"
+pub fn parse_ports(spec: &str) -> Result<Vec<u16>, Box<dyn Error>> {
+    let mut ports = BTreeSet::new();
+    for part in spec.split(',').map(str::trim).filter(|part| !part.is_empty()) {
+        let (start, end) = match part.split_once('-') {
+            Some((start, end)) => (start.trim().parse::<u16>()?, end.trim().parse::<u16>()?),
+            None => {
+                let port = part.parse::<u16>()?;
+                (port, port)
+            }
+        };
+        if start == 0 || start > end {
+            return Err(format!("invalid port range '{}'", part).into());
+        }
+        ports.extend(start..=end);
+    }
+    if ports.is_empty() {
+        return Err(format!("no port in '{}'", spec).into());
+    }
+    Ok(ports.into_iter().collect())
+}
+
+/// 解析 CIDR，地址统一为网络号，不带前缀时视为 /32
+pub fn parse_network(cidr: &str) -> Result<Ipv4Network, Box<dyn Error>> {
+    let network: Ipv4Network = cidr.trim().parse().map_err(|e| format!("invalid CIDR '{}': {}", cidr, e))?;
+    Ok(Ipv4Network::new(network.network(), network.prefix())?)
+}
+
+// 网段包含的地址数，/0 时超出 u32
+fn network_size(network: &Ipv4Network) -> u64 {
+    1u64 << (32 - u32::from(network.prefix()))
+}
+
+// 地址数不超过 max_hosts 的网段才展开
+fn bounded_hosts(network: &Ipv4Network, max_hosts: usize) -> Result<Vec<Ipv4Addr>, Box<dyn Error>> {
+    if network_size(network) > max_hosts as u64 + 2 {
+        return Err(format!("{} has too many addresses, at most {} allowed; narrow the targets or allow-list", network, max_hosts).into());
+    }
+    Ok(host_addresses(network))
+}
+
+/// 网段内可用的主机地址，/31 和 /32 以外不含网络号和广播地址
+pub fn host_addresses(network: &Ipv4Network) -> Vec<Ipv4Addr> {
+    let start = u32::from(network.network()) as u64;
+    let size = network_size(network);
+    let range = if network.prefix() >= 31 { start..start + size } else { start + 1..start + size - 1 };
+    range.map(|address| Ipv4Addr::from(address as u32)).collect()
+}
+
+// 允许扫描的网段，发现过程中发出的每个探测包都要先经过检查
+#[derive(Debug, Clone, PartialEq)]
+pub struct AllowList {
+    networks: Vec<Ipv4Network>,
+}
+
+impl AllowList {
+    /// 允许列表不能为空，避免误扫整个网络
+    pub fn parse(cidrs: &[String]) -> Result<Self, Box<dyn Error>> {
+        let networks = cidrs.iter().map(|cidr| parse_network(cidr)).collect::<Result<Vec<_>, _>>()?;
+        if networks.is_empty() {
+            return Err("allow-list is empty, specify the CIDRs allowed to scan".into());
+        }
+        Ok(AllowList { networks })
+    }
+
+    pub fn networks(&self) -> &[Ipv4Network] {
+        &self.networks
+    }
+
+    pub fn contains(&self, ip: Ipv4Addr) -> bool {
+        self.networks.iter().any(|network| network.contains(ip))
+    }
+
+    /// 整个网段都在某个允许的网段内
+    pub fn covers(&self, network: &Ipv4Network) -> bool {
+        self.networks.iter().any(|allowed| {
+            allowed.prefix() <= network.prefix() && allowed.contains(network.network())
+        })
+    }
+}
+
+// 令牌间隔固定的发包限速器，多个线程共用
+#[derive(Debug)]
+pub struct RateLimiter {
+    interval: Duration,
+    next: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    pub fn new(per_second: u32) -> Self {
+        RateLimiter {
+            interval: Duration::from_secs(1) / per_second.max(1),
+            next: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// 等待到下一个发包时刻
+    pub fn acquire(&self) {
+        let wait = {
+            let mut next = self.next.lock().unwrap_or_else(|e| e.into_inner());
+            let now = Instant::now();
+            let start = (*next).max(now);
+            *next = start + self.interval;
+            start - now
+        };
+        if !wait.is_zero() {
+            thread::sleep(wait);
+        }
+    }
+}
+
+// 待扫描的网段，attached 为直连该网段的本机网卡及其地址，只有直连网段才能做 ARP 扫描
+#[derive(Debug, Clone)]
+pub struct ScanNetwork {
+    pub network: Ipv4Network,
+    pub hosts: Vec<Ipv4Addr>,
+    pub attached: Option<(NetworkInterface, Ipv4Addr)>,
+}
+
+// 网络发现参数
+#[derive(Debug, Clone)]
+pub struct DiscoveryOptions {
+    pub allow: AllowList,
+    pub targets: Vec<Ipv4Network>,           // 为空时扫描本机直连的网段
+    pub interface: Option<String>,           // 只使用指定网卡
+    pub ports: Vec<u16>,                     // 为空时不扫描端口
+    pub rate: u32,                           // 发包速率（个/秒）
+    pub timeout: Duration,                   // 等待应答或建立连接的超时
+    pub arp: bool,
+    pub icmp: bool,
+    pub syn: bool,                           // 使用 SYN 半连接扫描代替 TCP 连接扫描
+    pub max_hosts: usize,
+}
+
+impl DiscoveryOptions {
+    pub fn new(allow: AllowList) -> Self {
+        DiscoveryOptions {
+            allow,
+            targets: vec![],
+            interface: None,
+            ports: DEFAULT_PORTS.to_vec(),
+            rate: DEFAULT_RATE,
+            timeout: Duration::from_secs(1),
+            arp: true,
+            icmp: true,
+            syn: false,
+            max_hosts: DEFAULT_MAX_HOSTS,
+        }
+    }
+}
+
+/// 根据参数和本机网卡确定扫描范围
+///
+/// 显式指定的目标必须完整落在允许列表内，否则报错；直连网段只保留允许列表内的地址，并排除本机地址。
+pub fn plan_networks(options: &DiscoveryOptions, interfaces: &[NetworkInterface]) -> Result<Vec<ScanNetwork>, Box<dyn Error>> {
+    if let Some(name) = &options.interface {
+        if !interfaces.iter().any(|interface| &interface.name == name) {
+            return Err(format!("interface {} not found", name).into());
+        }
+    }
+    let attached: Vec<(&NetworkInterface, Ipv4Network)> = interfaces.iter()
+        .filter(|interface| options.interface.as_ref().is_none_or(|name| &interface.name == name))
+        .filter(|interface| interface.is_up() && !interface.is_loopback())
+        .flat_map(|interface| interface.ips.iter().filter_map(move |ip| match ip {
+            IpNetwork::V4(network) if network.prefix() < 32 => Some((interface, *network)),
+            _ => None,
+        }))
+        .collect();
+
+    let mut networks = Vec::new();
+    if options.targets.is_empty() {
+        for (interface, network) in &attached {
+            let subnet = Ipv4Network::new(network.network(), network.prefix())?;
+            // CIDR 之间只有包含或不相交两种关系
+            let allowed: Vec<Ipv4Network> = if options.allow.covers(&subnet) {
+                vec![subnet]
+            } else {
+                options.allow.networks().iter()
+                    .filter(|allowed| allowed.prefix() >= subnet.prefix() && subnet.contains(allowed.network()))
+                    .copied()
+                    .collect()
+            };
+            let mut hosts = BTreeSet::new();
+            for allowed in &allowed {
+                hosts.extend(bounded_hosts(allowed, options.max_hosts)?);
+            }
+            hosts.remove(&network.ip());
+            let hosts: Vec<Ipv4Addr> = hosts.into_iter().collect();
+            if hosts.is_empty() {
+                log::info!("skip {} on {}: not in the allow-list", subnet, interface.name);
+                continue;
+            }
+            networks.push(ScanNetwork { network: subnet, hosts, attached: Some(((*interface).clone(), network.ip())) });
+        }
+        if networks.is_empty() {
+            return Err("no attached subnet is inside the allow-list".into());
+        }
+    } else {
+        for target in &options.targets {
+            if !options.allow.covers(target) {
+                return Err(format!("target {} is outside the allow-list", target).into());
+            }
+            let attached = attached.iter()
+                .find(|(_, network)| network.prefix() <= target.prefix() && network.contains(target.network()))
+                .map(|(interface, network)| ((*interface).clone(), network.ip()));
+            networks.push(ScanNetwork { network: *target, hosts: bounded_hosts(target, options.max_hosts)?, attached });
+        }
+    }
+
+    let total: usize = networks.iter().map(|network| network.hosts.len()).sum();
+    if total > options.max_hosts {
+        return Err(format!("{} addresses to scan, at most {} allowed; narrow the targets or allow-list", total, options.max_hosts).into());
+    }
+    Ok(networks)
+}
+
+// 离开作用域时通知接收线程退出，发送过程出错时接收线程也不会一直运行
+struct StopGuard<'a>(&'a AtomicBool);
+
+impl Drop for StopGuard<'_> {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+// 接收线程持续接收应答，同时在当前线程发送探测包，发送完毕后再等待 timeout
+fn exchange<T: Send>(timeout: Duration, mut receive: impl FnMut() -> Option<T> + Send, send: impl FnOnce()) -> Vec<T> {
+    let stop = AtomicBool::new(false);
+    thread::scope(|scope| {
+        let receiver = scope.spawn(|| {
+            let mut replies = Vec::new();
+            while !stop.load(Ordering::Relaxed) {
+                if let Some(reply) = receive() {
+                    replies.push(reply);
+                }
+            }
+            replies
+        });
+        let guard = StopGuard(&stop);
+        send();
+        thread::sleep(timeout);
+        drop(guard);
+        receiver.join().unwrap_or_default()
+    })
+}
+
+/// 构造 ARP 请求帧
+pub fn build_arp_request(source_mac: MacAddr, source_ip: Ipv4Addr, target_ip: Ipv4Addr) -> Vec<u8> {
+    let mut frame = vec![0u8; 42];
+    let mut ethernet = MutableEthernetPacket::new(&mut frame).unwrap();
+    ethernet.set_destination(MacAddr::broadcast());
+    ethernet.set_source(source_mac);
+    ethernet.set_ethertype(EtherTypes::Arp);
+
+    let mut arp = MutableArpPacket::new(ethernet.payload_mut()).unwrap();
+    arp.set_hardware_type(ArpHardwareTypes::Ethernet);
+    arp.set_protocol_type(EtherTypes::Ipv4);
+    arp.set_hw_addr_len(6);
+    arp.set_proto_addr_len(4);
+    arp.set_operation(ArpOperations::Request);
+    arp.set_sender_hw_addr(source_mac);
+    arp.set_sender_proto_addr(source_ip);
+    arp.set_target_hw_addr(MacAddr::zero());
+    arp.set_target_proto_addr(target_ip);
+    frame
+}
+
+/// 解析 ARP 应答帧，返回应答方的地址
+pub fn parse_arp_reply(frame: &[u8]) -> Option<(Ipv4Addr, MacAddr)> {
+    let ethernet = EthernetPacket::new(frame)?;
+    if ethernet.get_ethertype() != EtherTypes::Arp {
+        return None;
+    }
+    let arp = ArpPacket::new(ethernet.payload())?;
+    if arp.get_operation() != ArpOperations::Reply {
+        return None;
+    }
+    Some((arp.get_sender_proto_addr(), arp.get_sender_hw_addr()))
+}
+
+/// 在 interface 上对 hosts 做 ARP 扫描，需要原始套接字权限
+pub fn arp_sweep(interface: &NetworkInterface, source_ip: Ipv4Addr, hosts: &[Ipv4Addr], allow: &AllowList,
+                 limiter: &RateLimiter, timeout: Duration) -> Result<BTreeMap<Ipv4Addr, MacAddr>, Box<dyn Error>> {
+    let source_mac = interface.mac.filter(|mac| !mac.is_zero())
+        .ok_or_else(|| format!("interface {} has no MAC address", interface.name))?;
+    let config = datalink::Config { read_timeout: Some(POLL_INTERVAL), ..Default::default() };
+    let (mut tx, mut rx) = match datalink::channel(interface, config)? {
+        Channel::Ethernet(tx, rx) => (tx, rx),
+        _ => return Err(format!("unsupported channel type of interface {}", interface.name).into()),
+    };
+    let wanted: HashSet<Ipv4Addr> = hosts.iter().copied().collect();
+
+    let replies = exchange(timeout, || {
+        rx.next().ok().and_then(parse_arp_reply).filter(|(ip, _)| wanted.contains(ip))
+    }, || {
+        for host in hosts.iter().filter(|host| allow.contains(**host)) {
+            limiter.acquire();
+            let frame = build_arp_request(source_mac, source_ip, *host);
+            if let Some(Err(e)) = tx.send_to(&frame, None) {
+                log::debug!("send ARP request to {} failed: {}", host, e);
+            }
+        }
+    });
+    Ok(replies.into_iter().collect())
+}
+
+/// 构造 ICMP 回显请求
+pub fn build_echo_request(identifier: u16, sequence: u16) -> Vec<u8> {
+    let payload = b"minirobot";
+    let mut buffer = vec![0u8; MutableEchoRequestPacket::minimum_packet_size() + payload.len()];
+    let mut packet = MutableEchoRequestPacket::new(&mut buffer).unwrap();
+    packet.set_icmp_type(IcmpTypes::EchoRequest);
+    packet.set_identifier(identifier);
+    packet.set_sequence_number(sequence);
+    packet.set_payload(payload);
+    let checksum = icmp::checksum(&IcmpPacket::new(packet.packet()).unwrap());
+    packet.set_checksum(checksum);
+    buffer
+}
+
+fn is_echo_reply(packet: &IcmpPacket, identifier: u16) -> bool {
+    packet.get_icmp_type() == IcmpTypes::EchoReply
+        && EchoReplyPacket::new(packet.packet()).is_some_and(|reply| reply.get_identifier() == identifier)
+}
+
+/// ICMP 回显扫描，返回有应答的地址，需要原始套接字权限
+pub fn icmp_sweep(hosts: &[Ipv4Addr], allow: &AllowList, limiter: &RateLimiter, timeout: Duration) -> Result<BTreeSet<Ipv4Addr>, Box<dyn Error>> {
+    let protocol = TransportChannelType::Layer4(TransportProtocol::Ipv4(IpNextHeaderProtocols::Icmp));
+    let (mut tx, mut rx) = transport::transport_channel(4096, protocol)?;
+    let identifier = std::process::id() as u16;
+    let wanted: HashSet<Ipv4Addr> = hosts.iter().copied().collect();
+
+    let replies = exchange(timeout, || {
+        match transport::icmp_packet_iter(&mut rx).next_with_timeout(POLL_INTERVAL) {
+            Ok(Some((packet, IpAddr::V4(address)))) if wanted.contains(&address) && is_echo_reply(&packet, identifier) => Some(address),
+            _ => None,
+        }
+    }, || {
+        for (sequence, host) in hosts.iter().filter(|host| allow.contains(**host)).enumerate() {
+            limiter.acquire();
+            let buffer = build_echo_request(identifier, sequence as u16);
+            if let Err(e) = tx.send_to(IcmpPacket::new(&buffer).unwrap(), IpAddr::V4(*host)) {
+                log::debug!("send ICMP echo request to {} failed: {}", host, e);
+            }
+        }
+    });
+    Ok(replies.into_iter().collect())
+}
+
+// 发往 destination 时内核选用的本机地址
+fn local_address(destination: Ipv4Addr) -> Option<Ipv4Addr> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).ok()?;
+    socket.connect((destination, 9)).ok()?;
+    match socket.local_addr().ok()?.ip() {
+        IpAddr::V4(address) => Some(address),
+        IpAddr::V6(_) => None,
+    }
+}
+
+/// 构造 TCP SYN 报文（不含 IP 头）
+pub fn build_syn(source: Ipv4Addr, destination: Ipv4Addr, source_port: u16, destination_port: u16, sequence: u32) -> Vec<u8> {
+    let mut buffer = vec![0u8; 20];
+    let mut packet = MutableTcpPacket::new(&mut buffer).unwrap();
+    packet.set_source(source_port);
+    packet.set_destination(destination_port);
+    packet.set_sequence(sequence);
+    packet.set_data_offset(5);
+    packet.set_flags(TcpFlags::SYN);
+    packet.set_window(1024);
+    let checksum = tcp::ipv4_checksum(&packet.to_immutable(), &source, &destination);
+    packet.set_checksum(checksum);
+    buffer
+}
+
+/// SYN 半连接扫描，返回应答 SYN+ACK 的地址和端口，需要原始套接字权限
+///
+/// 本机没有对应的连接，内核收到 SYN+ACK 后会自动回复 RST 关闭半连接。
+pub fn syn_scan(hosts: &[Ipv4Addr], ports: &[u16], allow: &AllowList, limiter: &RateLimiter,
+                timeout: Duration) -> Result<BTreeSet<(Ipv4Addr, u16)>, Box<dyn Error>> {
+    let protocol = TransportChannelType::Layer4(TransportProtocol::Ipv4(IpNextHeaderProtocols::Tcp));
+    let (mut tx, mut rx) = transport::transport_channel(4096, protocol)?;
+    let mut rng = rand::thread_rng();
+    let source_port: u16 = rng.gen_range(40000..60000);
+    let sequence: u32 = rng.gen();
+    let wanted: HashSet<Ipv4Addr> = hosts.iter().copied().collect();
+    let wanted_ports: HashSet<u16> = ports.iter().copied().collect();
+
+    let replies = exchange(timeout, || {
+        match transport::tcp_packet_iter(&mut rx).next_with_timeout(POLL_INTERVAL) {
+            Ok(Some((packet, IpAddr::V4(address)))) if wanted.contains(&address)
+                && packet.get_destination() == source_port
+                && wanted_ports.contains(&packet.get_source())
+                && packet.get_flags() & (TcpFlags::SYN | TcpFlags::ACK) == TcpFlags::SYN | TcpFlags::ACK => {
+                Some((address, packet.get_source()))
+            }
+            _ => None,
+        }
+    }, || {
+        for host in hosts.iter().filter(|host| allow.contains(**host)) {
+            let Some(source) = local_address(*host) else {
+                log::debug!("no route to {}", host);
+                continue;
+            };
+            for port in ports {
+                limiter.acquire();
+                let buffer = build_syn(source, *host, source_port, *port, sequence);
+                if let Err(e) = tx.send_to(TcpPacket::new(&buffer).unwrap(), IpAddr::V4(*host)) {
+                    log::debug!("send SYN to {}:{} failed: {}", host, port, e);
+                }
+            }
+        }
+    });
+    Ok(replies.into_iter().collect())
+}
+
+// 欢迎信息中的不可打印字符替换为 '.'
+fn banner_text(banner: &[u8]) -> String {
+    String::from_utf8_lossy(banner)
+        .trim()
+        .chars()
+        .map(|c| if c.is_control() { '.' } else { c })
+        .collect()
+}
+
+/// 连接端口并读取欢迎信息，端口未开放时返回 None
+pub fn probe_port(address: SocketAddr, timeout: Duration) -> Option<OpenPort> {
+    let mut stream = TcpStream::connect_timeout(&address, timeout).ok()?;
+    let mut buffer = [0u8; 256];
+    let banner = match stream.set_read_timeout(Some(timeout)).and_then(|_| stream.read(&mut buffer)) {
+        Ok(n) if n > 0 => Some(&buffer[..n]),
+        _ => None,
+    };
+    Some(OpenPort {
+        port: address.port(),
+        service: banner.and_then(guess_service_by_banner)
+            .or_else(|| guess_service_by_port(address.port()))
+            .map(str::to_string),
+        banner: banner.map(banner_text),
+    })
+}
+
+// 由 SCAN_WORKERS 个线程并发处理 items
+fn parallel<T: Sync, R: Send>(items: &[T], probe: impl Fn(&T) -> Option<R> + Sync) -> Vec<R> {
+    let index = AtomicUsize::new(0);
+    let results = Mutex::new(Vec::new());
+    thread::scope(|scope| {
+        for _ in 0..SCAN_WORKERS.min(items.len()) {
+            scope.spawn(|| {
+                while let Some(item) = items.get(index.fetch_add(1, Ordering::Relaxed)) {
+                    if let Some(result) = probe(item) {
+                        results.lock().unwrap_or_else(|e| e.into_inner()).push(result);
+                    }
+                }
+            });
+        }
+    });
+    results.into_inner().unwrap_or_else(|e| e.into_inner())
+}
+
+/// 扫描端口，SYN 扫描只对开放的端口建立连接读取欢迎信息
+pub fn port_scan(hosts: &[Ipv4Addr], options: &DiscoveryOptions, limiter: &RateLimiter) -> Result<Vec<(Ipv4Addr, OpenPort)>, Box<dyn Error>> {
+    let allowed: Vec<Ipv4Addr> = hosts.iter().copied().filter(|host| options.allow.contains(*host)).collect();
+    if options.syn {
+        let open: Vec<(Ipv4Addr, u16)> = syn_scan(&allowed, &options.ports, &options.allow, limiter, options.timeout)?
+            .into_iter().collect();
+        Ok(parallel(&open, |(host, port)| {
+            let open_port = probe_port(SocketAddr::from((*host, *port)), options.timeout).unwrap_or_else(|| OpenPort {
+                port: *port,
+                service: guess_service_by_port(*port).map(str::to_string),
+                banner: None,
+            });
+            Some((*host, open_port))
+        }))
+    } else {
+        let jobs: Vec<(Ipv4Addr, u16)> = allowed.iter()
+            .flat_map(|host| options.ports.iter().map(move |port| (*host, *port)))
+            .collect();
+        Ok(parallel(&jobs, |(host, port)| {
+            limiter.acquire();
+            probe_port(SocketAddr::from((*host, *port)), options.timeout).map(|open_port| (*host, open_port))
+        }))
+    }
+}
+
+/// 发现网络中的设备
+///
+/// 先用 ARP 和 ICMP 探测存活主机，再扫描存活主机的端口；存活探测都不可用（如没有原始套接字权限）时扫描全部地址。
+pub fn discover(options: &DiscoveryOptions) -> Result<Vec<Device>, Box<dyn Error>> {
+    let networks = plan_networks(options, &datalink::interfaces())?;
+    let limiter = RateLimiter::new(options.rate);
+    let mut devices: BTreeMap<Ipv4Addr, Device> = BTreeMap::new();
+    let mut probed = false;
+
+    if options.arp {
+        for network in &networks {
+            let Some((interface, source)) = &network.attached else { continue };
+            match arp_sweep(interface, *source, &network.hosts, &options.allow, &limiter, options.timeout) {
+                Ok(replies) => {
+                    probed = true;
+                    for (ip, mac) in replies {
+                        let device = devices.entry(ip).or_insert_with(|| Device::new(ip));
+                        device.mac = Some(mac.to_string());
+                        device.add_method("arp");
+                    }
+                }
+                Err(e) => log::warn!("ARP sweep of {} on {} failed: {}", network.network, interface.name, e),
+            }
+        }
+    }
+
+    let hosts: Vec<Ipv4Addr> = networks.iter().flat_map(|network| network.hosts.iter().copied()).collect();
+    if options.icmp {
+        match icmp_sweep(&hosts, &options.allow, &limiter, options.timeout) {
+            Ok(replies) => {
+                probed = true;
+                for ip in replies {
+                    devices.entry(ip).or_insert_with(|| Device::new(ip)).add_method("icmp");
+                }
+            }
+            Err(e) => log::warn!("ICMP sweep failed: {}", e),
+        }
+    }
+
+    if !options.ports.is_empty() {
+        let scan_hosts: Vec<Ipv4Addr> = if probed { devices.keys().copied().collect() } else { hosts };
+        for (ip, open_port) in port_scan(&scan_hosts, options, &limiter)? {
+            let device = devices.entry(ip).or_insert_with(|| Device::new(ip));
+            device.add_method("tcp");
+            device.open_ports.push(open_port);
+        }
+    }
+
+    let mut devices: Vec<Device> = devices.into_values().collect();
+    for device in devices.iter_mut() {
+        device.open_ports.sort_by_key(|p| p.port);
+    }
+    Ok(devices)
+}
+
+#[cfg(test)]
+mod unit_test_discovery {
+    use super::*;
+    use std::io::Write;
+    use std::net::TcpListener;
+
+    fn interface(name: &str, flags: u32, ips: &[&str]) -> NetworkInterface {
+        NetworkInterface {
+            name: name.to_string(),
+            description: String::new(),
+            index: 1,
+            mac: Some(MacAddr::new(0x52, 0x54, 0, 0x12, 0x34, 0x56)),
+            ips: ips.iter().map(|ip| ip.parse().unwrap()).collect(),
+            flags,
+        }
+    }
+
+    fn allow(cidrs: &[&str]) -> AllowList {
+        AllowList::parse(&cidrs.iter().map(|cidr| cidr.to_string()).collect::<Vec<String>>()).unwrap()
+    }
+
+    #[test]
+    fn test_discovery_01() {
+        assert_eq!(parse_ports("22, 80,8000-8002,22").unwrap(), vec![22, 80, 8000, 8001, 8002]);
+        assert!(parse_ports("0").is_err());
+        assert!(parse_ports("90-80").is_err());
+        assert!(parse_ports("http").is_err());
+
+        assert!(AllowList::parse(&[]).is_err());
+        assert!(AllowList::parse(&["10.0.0.0/33".to_string()]).is_err());
+        let allow = allow(&["192.168.1.10/24", "10.0.0.5"]);
+        assert_eq!(allow.networks()[0].to_string(), "192.168.1.0/24");
+        assert!(allow.contains("192.168.1.200".parse().unwrap()));
+        assert!(allow.contains("10.0.0.5".parse().unwrap()));
+        assert!(!allow.contains("10.0.0.6".parse().unwrap()));
+        assert!(allow.covers(&parse_network("192.168.1.128/25").unwrap()));
+        assert!(!allow.covers(&parse_network("192.168.0.0/16").unwrap()));
+
+        assert_eq!(host_addresses(&parse_network("10.0.0.0/30").unwrap()).len(), 2);
+        assert_eq!(host_addresses(&parse_network("10.0.0.0/31").unwrap()).len(), 2);
+        assert_eq!(host_addresses(&parse_network("10.0.0.7").unwrap()), vec![Ipv4Addr::new(10, 0, 0, 7)]);
+    }
+
+    #[test]
+    fn test_discovery_02() {
+        let up = libc::IFF_UP as u32;
+        let interfaces = vec![
+            interface("lo", up | libc::IFF_LOOPBACK as u32, &["127.0.0.1/8"]),
+            interface("eth0", up, &["192.168.1.10/24", "fe80::1/64"]),
+            interface("eth1", 0, &["172.16.0.2/24"]),
+            interface("eth2", up, &["10.8.0.2/16"]),
+        ];
+
+        // 直连网段只扫描允许列表内的部分，排除本机地址
+        let mut options = DiscoveryOptions::new(allow(&["192.168.1.0/25", "10.8.3.0/30"]));
+        let networks = plan_networks(&options, &interfaces).unwrap();
+        assert_eq!(networks.len(), 2);
+        assert_eq!(networks[0].network.to_string(), "192.168.1.0/24");
+        assert_eq!(networks[0].hosts.len(), 125);
+        assert!(!networks[0].hosts.contains(&Ipv4Addr::new(192, 168, 1, 10)));
+        assert_eq!(networks[0].attached.as_ref().unwrap().1, Ipv4Addr::new(192, 168, 1, 10));
+        assert_eq!(networks[1].hosts, vec![Ipv4Addr::new(10, 8, 3, 1), Ipv4Addr::new(10, 8, 3, 2)]);
+
+        options.interface = Some("eth2".to_string());
+        assert_eq!(plan_networks(&options, &interfaces).unwrap().len(), 1);
+        options.interface = Some("wlan0".to_string());
+        assert!(plan_networks(&options, &interfaces).is_err());
+        options.interface = None;
+
+        // 显式目标必须在允许列表内
+        options.targets = vec![parse_network("192.168.1.64/26").unwrap()];
+        let networks = plan_networks(&options, &interfaces).unwrap();
+        assert_eq!(networks[0].hosts.len(), 62);
+        assert_eq!(networks[0].attached.as_ref().unwrap().0.name, "eth0");
+        options.targets = vec![parse_network("192.168.1.128/26").unwrap()];
+        assert!(plan_networks(&options, &interfaces).unwrap_err().to_string().contains("outside the allow-list"));
+
+        // 地址数超出上限
+        let mut options = DiscoveryOptions::new(allow(&["10.0.0.0/8"]));
+        options.targets = vec![parse_network("10.1.0.0/16").unwrap()];
+        assert!(plan_networks(&options, &interfaces).is_err());
+        options.max_hosts = 65536;
+        assert_eq!(plan_networks(&options, &interfaces).unwrap()[0].hosts.len(), 65534);
+
+        // 没有直连网段在允许列表内
+        let options = DiscoveryOptions::new(allow(&["203.0.113.0/24"]));
+        assert!(plan_networks(&options, &interfaces).is_err());
+    }
+
+    #[test]
+    fn test_discovery_03() {
+        let limiter = RateLimiter::new(50);
+        let start = Instant::now();
+        for _ in 0..6 {
+            limiter.acquire();
+        }
+        assert!(start.elapsed() >= Duration::from_millis(100));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_discovery_04() {
+        let mac = MacAddr::new(0x52, 0x54, 0, 0x12, 0x34, 0x56);
+        let mut frame = build_arp_request(mac, Ipv4Addr::new(192, 168, 1, 10), Ipv4Addr::new(192, 168, 1, 1));
+        assert_eq!(frame.len(), 42);
+        assert_eq!(&frame[..6], &[0xff; 6]);
+        assert_eq!(parse_arp_reply(&frame), None);
+        // 改为应答方发出的 ARP 应答
+        let peer = MacAddr::new(0, 0x11, 0x22, 0x33, 0x44, 0x55);
+        {
+            let mut ethernet = MutableEthernetPacket::new(&mut frame).unwrap();
+            let mut arp = MutableArpPacket::new(ethernet.payload_mut()).unwrap();
+            arp.set_operation(ArpOperations::Reply);
+            arp.set_sender_hw_addr(peer);
+            arp.set_sender_proto_addr(Ipv4Addr::new(192, 168, 1, 1));
+        }
+        assert_eq!(parse_arp_reply(&frame), Some((Ipv4Addr::new(192, 168, 1, 1), peer)));
+
+        let buffer = build_echo_request(0x1234, 7);
+        let packet = IcmpPacket::new(&buffer).unwrap();
+        assert_eq!(packet.get_icmp_type(), IcmpTypes::EchoRequest);
+        assert_eq!(packet.get_checksum(), icmp::checksum(&packet));
+        assert!(!is_echo_reply(&packet, 0x1234));
+
+        let source = Ipv4Addr::new(10, 0, 0, 1);
+        let destination = Ipv4Addr::new(10, 0, 0, 2);
+        let buffer = build_syn(source, destination, 40001, 22, 1000);
+        let packet = TcpPacket::new(&buffer).unwrap();
+        assert_eq!((packet.get_source(), packet.get_destination()), (40001, 22));
+        assert_eq!(packet.get_flags(), TcpFlags::SYN);
+        assert_eq!(packet.get_checksum(), tcp::ipv4_checksum(&packet, &source, &destination));
+
+        assert_eq!(banner_text(b"SSH-2.0-OpenSSH_9.6\r\n"), "SSH-2.0-OpenSSH_9.6");
+        assert_eq!(banner_text(b"\x0a5.7.44\x00abc"), "5.7.44.abc");
+    }
+
+    // 回环地址上的 TCP 连接扫描和欢迎信息读取
+    #[test]
+    fn test_discovery_05() {
+        let ssh = TcpListener::bind("127.0.0.1:0").unwrap();
+        let silent = TcpListener::bind("127.0.0.1:0").unwrap();
+        let closed = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let ssh_port = ssh.local_addr().unwrap().port();
+        let silent_port = silent.local_addr().unwrap().port();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = ssh.accept().unwrap();
+            stream.write_all(b"SSH-2.0-minirobot\r\n").unwrap();
+        });
+
+        let mut options = DiscoveryOptions::new(allow(&["127.0.0.0/8"]));
+        options.targets = vec![parse_network("127.0.0.1").unwrap()];
+        options.ports = vec![ssh_port, silent_port, closed];
+        options.arp = false;
+        options.icmp = false;
+        options.timeout = Duration::from_millis(300);
+        let devices = discover(&options).unwrap();
+        server.join().unwrap();
+
+        assert_eq!(devices.len(), 1);
+        assert_eq!(devices[0].ip, Ipv4Addr::LOCALHOST);
+        assert_eq!(devices[0].methods, vec!["tcp"]);
+        let mut ports = devices[0].open_ports.clone();
+        ports.sort_by_key(|p| p.port != ssh_port);
+        assert_eq!(ports.len(), 2);
+        assert_eq!(ports[0].service.as_deref(), Some("ssh"));
+        assert_eq!(ports[0].banner.as_deref(), Some("SSH-2.0-minirobot"));
+        assert_eq!(ports[1].port, silent_port);
+        assert_eq!(ports[1].banner, None);
+
+        // 允许列表外的目标拒绝扫描
+        options.allow = allow(&["10.0.0.0/8"]);
+        assert!(discover(&options).is_err());
+    }
+
+    // 原始套接字扫描回环地址，没有权限时跳过
+    #[test]
+    fn test_discovery_06() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let allow = allow(&["127.0.0.1/32"]);
+        let limiter = RateLimiter::new(1000);
+        let hosts = [Ipv4Addr::LOCALHOST];
+
+        let Ok(alive) = icmp_sweep(&hosts, &allow, &limiter, Duration::from_millis(300)) else { return };
+        assert!(alive.contains(&Ipv4Addr::LOCALHOST));
+
+        let open = syn_scan(&hosts, &[port, 1], &allow, &limiter, Duration::from_millis(300)).unwrap();
+        assert_eq!(open.into_iter().collect::<Vec<_>>(), vec![(Ipv4Addr::LOCALHOST, port)]);
+
+        // 允许列表外的地址不发包
+        let other = AllowList::parse(&["10.0.0.0/8".to_string()]).unwrap();
+        assert!(icmp_sweep(&hosts, &other, &limiter, Duration::from_millis(200)).unwrap().is_empty());
+    }
+}
@@ -1,3 +1,4 @@
 pub mod actor;
 pub mod device;
+pub mod discovery;
 pub mod node;
\ No newline at end of file
@@ -1,8 +1,31 @@
-use chrono::Utc;
+use chrono::{NaiveDateTime, Utc};
 use serde::{Serialize, Deserialize};
 use uuid::Uuid;
 
-#[derive(Debug, Serialize, Deserialize)]
+/// 解析事件中的时间，格式与 `NaiveDateTime` 的显示格式相同
+pub fn parse_time(text: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S%.f").ok()
+}
+
+/// 告警指纹，同一监控项同一对象的告警指纹相同
+pub fn fingerprint(check: &str, subject: &str) -> String {
+    if subject.is_empty() {
+        check.to_string()
+    } else {
+        format!("{}:{}", check, subject)
+    }
+}
+
+fn default_count() -> u64 {
+    1
+}
+
+// 时间已过
+fn passed(until: &Option<String>, now: &NaiveDateTime) -> bool {
+    until.as_deref().and_then(parse_time).is_some_and(|until| until <= *now)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Event {
     id: String,                 // 事件ID
     name: String,               // 事件名称
@@ -13,10 +36,26 @@ pub struct Event {
     category: String,           // 事件类别
     status: Status,             // 事件状态
     progress: String,           // 事件进展描述
+    #[serde(default)]
+    subject: String,            // 告警对象，如分区挂载点，为空时表示监控项本身
+    #[serde(default)]
+    fingerprint: String,        // 告警指纹，未纳入告警跟踪的事件为空
+    #[serde(default = "default_count")]
+    count: u64,                 // 重复发生次数
+    #[serde(default)]
+    first_seen: String,         // 首次发生时间
+    #[serde(default)]
+    last_seen: String,          // 最近发生时间
+    #[serde(default)]
+    resolved_at: Option<String>,         // 解决时间
+    #[serde(default)]
+    acknowledged_until: Option<String>,  // 确认有效期，到期后恢复为未处理
+    #[serde(default)]
+    silenced_until: Option<String>,      // 静默有效期，期间不再通知
 }
 
 // 定义优先级枚举
-#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Priority {
     Low,     // 低优先级
     Medium,  // 中等优先级
@@ -24,7 +63,7 @@ pub enum Priority {
 }
 
 // 定义严重等级枚举
-#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Severity {
     Debug,     // 调试级别
     Info,      // 信息级别
@@ -33,7 +72,7 @@ pub enum Severity {
 }
 
 // 定义事件状态枚举
-#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Status {
     Pending,     // 未处理
     InProgress,  // 处理中
@@ -44,19 +83,34 @@ pub enum Status {
 impl Event {
     // 创建一个新的事件
     pub fn new(name: &str, description: &str, priority: Priority, severity: Severity, category: &str) -> Self {
+        let timestamp = Utc::now().naive_utc().to_string();
         Self {
             id: Uuid::new_v4().to_string(),
             name: name.to_string(),
-            timestamp: Utc::now().naive_utc().to_string(),
+            timestamp: timestamp.clone(),
             description: description.to_string(),
             priority,
             severity,
             category: category.to_string(),
             status: Status::Pending,
             progress: String::new(),
+            subject: String::new(),
+            fingerprint: String::new(),
+            count: 1,
+            first_seen: timestamp.clone(),
+            last_seen: timestamp,
+            resolved_at: None,
+            acknowledged_until: None,
+            silenced_until: None,
         }
     }
 
+    // 设置告警对象
+    pub fn with_subject(mut self, subject: &str) -> Self {
+        self.subject = subject.to_string();
+        self
+    }
+
     // 获取事件ID
     pub fn id(&self) -> &str {
         &self.id
@@ -102,6 +156,56 @@ impl Event {
         &self.progress
     }
 
+    // 获取告警对象
+    pub fn subject(&self) -> &str {
+        &self.subject
+    }
+
+    // 获取告警指纹
+    pub fn fingerprint(&self) -> &str {
+        &self.fingerprint
+    }
+
+    // 获取重复发生次数
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    // 获取首次发生时间
+    pub fn first_seen(&self) -> &str {
+        &self.first_seen
+    }
+
+    // 获取最近发生时间
+    pub fn last_seen(&self) -> &str {
+        &self.last_seen
+    }
+
+    // 获取解决时间
+    pub fn resolved_at(&self) -> Option<&str> {
+        self.resolved_at.as_deref()
+    }
+
+    // 获取确认有效期
+    pub fn acknowledged_until(&self) -> Option<&str> {
+        self.acknowledged_until.as_deref()
+    }
+
+    // 获取静默有效期
+    pub fn silenced_until(&self) -> Option<&str> {
+        self.silenced_until.as_deref()
+    }
+
+    // 未处理或处理中的事件
+    pub fn is_open(&self) -> bool {
+        matches!(self.status, Status::Pending | Status::InProgress)
+    }
+
+    // 是否处于静默期
+    pub fn is_silenced(&self, now: &NaiveDateTime) -> bool {
+        self.silenced_until.is_some() && !passed(&self.silenced_until, now)
+    }
+
     // 设置事件名称
     pub fn set_name(&mut self, name: &str) {
         self.name = name.to_string();
@@ -136,6 +240,59 @@ impl Event {
     pub fn set_progress(&mut self, progress: &str) {
         self.progress.push_str(&format!("\n{} 更新进展:\n{}", Utc::now().naive_utc(), progress));
     }
+
+    // 设置告警指纹，纳入告警跟踪
+    pub fn set_fingerprint(&mut self, fingerprint: &str) {
+        self.fingerprint = fingerprint.to_string();
+    }
+
+    // 同一告警再次发生，更新次数、最近发生时间和告警内容
+    pub fn repeat(&mut self, event: &Event) {
+        self.count += 1;
+        self.last_seen = event.timestamp.clone();
+        self.description = event.description.clone();
+        self.priority = event.priority.clone();
+        self.severity = event.severity.clone();
+    }
+
+    // 解决告警，清除确认和静默
+    pub fn resolve(&mut self, progress: &str) {
+        self.status = Status::Resolved;
+        self.resolved_at = Some(Utc::now().naive_utc().to_string());
+        self.acknowledged_until = None;
+        self.silenced_until = None;
+        self.set_progress(progress);
+    }
+
+    // 确认告警，until 为空时一直有效直到解决
+    pub fn acknowledge(&mut self, progress: &str, until: Option<NaiveDateTime>) {
+        self.status = Status::InProgress;
+        self.acknowledged_until = until.map(|until| until.to_string());
+        self.set_progress(progress);
+    }
+
+    // 静默告警至 until
+    pub fn silence(&mut self, until: NaiveDateTime) {
+        self.silenced_until = Some(until.to_string());
+        self.set_progress(&format!("静默至 {}", until));
+    }
+
+    // 清除到期的确认和静默，确认到期后恢复为未处理，有变化时返回 true
+    pub fn expire(&mut self, now: &NaiveDateTime) -> bool {
+        let mut changed = false;
+        if passed(&self.acknowledged_until, now) {
+            self.acknowledged_until = None;
+            if self.status == Status::InProgress {
+                self.status = Status::Pending;
+            }
+            changed = true;
+        }
+        if passed(&self.silenced_until, now) {
+            self.silenced_until = None;
+            changed = true;
+        }
+        changed
+    }
 }
 
 #[cfg(test)]
@@ -162,4 +319,38 @@ mod unit_test_events {
         // 打印修改后的事件信息
         println!("Updated Event: {:?}", event);
     }
+
+    #[test]
+    fn test_events_02() {
+        let mut event = Event::new("磁盘使用率监控", "/ 使用率91%", Priority::Low, Severity::Warning, "本地主机监控")
+            .with_subject("/");
+        assert_eq!(fingerprint("disk_usage", event.subject()), "disk_usage:/");
+        assert_eq!(fingerprint("check_load", ""), "check_load");
+        assert_eq!(event.first_seen(), event.timestamp());
+
+        let again = Event::new("磁盘使用率监控", "/ 使用率95%", Priority::Medium, Severity::Critical, "本地主机监控");
+        event.repeat(&again);
+        assert_eq!(event.count(), 2);
+        assert_eq!(event.last_seen(), again.timestamp());
+        assert_eq!(event.severity(), &Severity::Critical);
+
+        let now = Utc::now().naive_utc();
+        event.acknowledge("处理中", Some(now + chrono::Duration::minutes(10)));
+        event.silence(now + chrono::Duration::minutes(5));
+        assert_eq!(event.status(), &Status::InProgress);
+        assert!(event.is_silenced(&now));
+        assert!(!event.expire(&now));
+        assert!(event.expire(&(now + chrono::Duration::minutes(10))));
+        assert_eq!(event.status(), &Status::Pending);
+        assert!(!event.is_silenced(&(now + chrono::Duration::minutes(10))));
+
+        event.resolve("自动解决");
+        assert!(!event.is_open());
+        assert!(event.resolved_at().is_some());
+        assert!(parse_time(event.resolved_at().unwrap()).is_some());
+
+        let old: Event = serde_json::from_str(r#"{"id": "1", "name": "n", "timestamp": "2024-06-01 00:00:00", "description": "",
+            "priority": "Low", "severity": "Info", "category": "", "status": "Pending", "progress": ""}"#).unwrap();
+        assert_eq!(old.count(), 1);
+    }
 }
@@ -0,0 +1,644 @@
+//! Prometheus/OpenMetrics 指标导出
+//!
+//! 每次抓取时由各采集器读取 CPU、内存、分区、网卡、进程、监听端口和任务记录，生成 gauge、counter 和 histogram，
+//! 按请求的 Accept 头以 Prometheus 文本格式或 OpenMetrics 格式输出。
+
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::thread;
+use std::time::Instant;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use log::warn;
+use warp::http::{header, Response, StatusCode};
+use warp::{Filter, Rejection, Reply};
+
+use crate::common::config::{MetricsAuthConfig, MetricsConfig};
+use crate::common::ds::TaskStatus;
+use crate::common::format::label_value;
+use crate::info::disk::DiskInfo;
+use crate::info::memory::MemInfo;
+use crate::info::process::gather_process_info;
+use crate::info::report::joined;
+use crate::info::sampler::{self, Counters};
+use crate::info::socket::SocketInventory;
+use crate::task::history::{TaskFilter, TaskStore};
+
+pub const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+pub const OPENMETRICS_CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+// 任务耗时直方图的桶上界，秒
+const TASK_DURATION_BUCKETS: [f64; 9] = [1.0, 5.0, 15.0, 30.0, 60.0, 300.0, 900.0, 1800.0, 3600.0];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricType {
+    Gauge,
+    Counter,                                 // 样本名带 _total 后缀
+    Histogram,
+}
+
+impl fmt::Display for MetricType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            MetricType::Gauge => "gauge",
+            MetricType::Counter => "counter",
+            MetricType::Histogram => "histogram",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+// 一个样本，名称为指标名加后缀
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricSample {
+    pub suffix: &'static str,
+    pub labels: Vec<(String, String)>,
+    pub value: f64,
+}
+
+// 同名指标的一组样本
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricFamily {
+    pub name: String,                        // 不含 _total 等后缀
+    pub help: String,
+    pub metric_type: MetricType,
+    pub samples: Vec<MetricSample>,
+}
+
+fn owned_labels(labels: &[(&str, &str)]) -> Vec<(String, String)> {
+    labels.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+}
+
+impl MetricFamily {
+    pub fn new(name: &str, help: &str, metric_type: MetricType) -> Self {
+        MetricFamily {
+            name: name.to_string(),
+            help: help.to_string(),
+            metric_type,
+            samples: vec![],
+        }
+    }
+
+    pub fn gauge(name: &str, help: &str) -> Self {
+        Self::new(name, help, MetricType::Gauge)
+    }
+
+    pub fn counter(name: &str, help: &str) -> Self {
+        Self::new(name, help, MetricType::Counter)
+    }
+
+    pub fn histogram(name: &str, help: &str) -> Self {
+        Self::new(name, help, MetricType::Histogram)
+    }
+
+    /// 添加 gauge 或 counter 样本
+    pub fn add(&mut self, labels: &[(&str, &str)], value: f64) {
+        let suffix = if self.metric_type == MetricType::Counter { "_total" } else { "" };
+        self.samples.push(MetricSample { suffix, labels: owned_labels(labels), value });
+    }
+
+    /// 添加一组观测值的直方图样本：各桶累计计数、+Inf 桶、总和与个数
+    pub fn observe(&mut self, labels: &[(&str, &str)], buckets: &[f64], values: &[f64]) {
+        for bound in buckets.iter().copied().chain([f64::INFINITY]) {
+            let mut bucket_labels = owned_labels(labels);
+            bucket_labels.push(("le".to_string(), number(bound)));
+            let count = values.iter().filter(|value| **value <= bound).count();
+            self.samples.push(MetricSample { suffix: "_bucket", labels: bucket_labels, value: count as f64 });
+        }
+        self.samples.push(MetricSample { suffix: "_sum", labels: owned_labels(labels), value: values.iter().sum() });
+        self.samples.push(MetricSample { suffix: "_count", labels: owned_labels(labels), value: values.len() as f64 });
+    }
+}
+
+// 样本值，无穷大写作 +Inf
+fn number(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "+Inf".to_string() } else { "-Inf".to_string() }
+    } else {
+        value.to_string()
+    }
+}
+
+// 输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Prometheus,                              // text/plain; version=0.0.4
+    OpenMetrics,                             // application/openmetrics-text; version=1.0.0
+}
+
+impl Encoding {
+    /// 按 Accept 头选择格式，接受 OpenMetrics 时优先使用
+    pub fn negotiate(accept: Option<&str>) -> Self {
+        match accept {
+            Some(accept) if accept.contains("application/openmetrics-text") => Encoding::OpenMetrics,
+            _ => Encoding::Prometheus,
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Encoding::Prometheus => PROMETHEUS_CONTENT_TYPE,
+            Encoding::OpenMetrics => OPENMETRICS_CONTENT_TYPE,
+        }
+    }
+}
+
+/// 编码指标
+///
+/// Prometheus 格式中 counter 的 HELP、TYPE 行使用带 _total 的名称，OpenMetrics 使用不带后缀的名称并以 `# EOF` 结尾。
+pub fn encode(families: &[MetricFamily], encoding: Encoding) -> String {
+    let mut output = String::new();
+    for family in families {
+        let name = match (encoding, family.metric_type) {
+            (Encoding::Prometheus, MetricType::Counter) => format!("{}_total", family.name),
+            _ => family.name.clone(),
+        };
+        output.push_str(&format!("# HELP {} {}\n", name, family.help.replace('\\', "\\\\").replace('\n', "\\n")));
+        output.push_str(&format!("# TYPE {} {}\n", name, family.metric_type));
+        for sample in &family.samples {
+            let labels = sample.labels.iter()
+                .map(|(name, value)| format!("{}=\"{}\"", name, label_value(value)))
+                .collect::<Vec<String>>()
+                .join(",");
+            if labels.is_empty() {
+                output.push_str(&format!("{}{} {}\n", family.name, sample.suffix, number(sample.value)));
+            } else {
+                output.push_str(&format!("{}{}{{{}}} {}\n", family.name, sample.suffix, labels, number(sample.value)));
+            }
+        }
+    }
+    if encoding == Encoding::OpenMetrics {
+        output.push_str("# EOF\n");
+    }
+    output
+}
+
+// 指标采集器
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Collector {
+    Cpu,
+    Memory,
+    Disk,
+    Network,
+    Process,
+    Socket,
+    Task,
+}
+
+impl Collector {
+    pub const ALL: [Collector; 7] = [Collector::Cpu, Collector::Memory, Collector::Disk, Collector::Network,
+        Collector::Process, Collector::Socket, Collector::Task];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Collector::Cpu => "cpu",
+            Collector::Memory => "memory",
+            Collector::Disk => "disk",
+            Collector::Network => "network",
+            Collector::Process => "process",
+            Collector::Socket => "socket",
+            Collector::Task => "task",
+        }
+    }
+}
+
+impl FromStr for Collector {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Collector::ALL.iter()
+            .find(|collector| collector.name() == s.to_lowercase())
+            .copied()
+            .ok_or_else(|| format!("unknown collector '{}', expect cpu, memory, disk, network, process, socket or task", s))
+    }
+}
+
+impl fmt::Display for Collector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+// 每秒 jiffies 数，用于把 /proc/stat 的累计时间换算为秒
+fn clock_ticks() -> f64 {
+    #[cfg(unix)]
+    let ticks = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+
+    #[cfg(not(unix))]
+    let ticks = 100;
+
+    if ticks > 0 { ticks as f64 } else { 100.0 }
+}
+
+fn collect_cpu(counters: &Counters) -> Vec<MetricFamily> {
+    let hz = clock_ticks();
+    let mut seconds = MetricFamily::counter("minirobot_cpu_seconds", "CPU time of all cores in seconds");
+    seconds.add(&[("mode", "busy")], counters.cpu_total.busy as f64 / hz);
+    seconds.add(&[("mode", "idle")], counters.cpu_total.idle as f64 / hz);
+    let mut core_seconds = MetricFamily::counter("minirobot_cpu_core_seconds", "CPU time of each core in seconds");
+    for (index, core) in counters.cpu_cores.iter().enumerate() {
+        let cpu = index.to_string();
+        core_seconds.add(&[("cpu", &cpu), ("mode", "busy")], core.busy as f64 / hz);
+        core_seconds.add(&[("cpu", &cpu), ("mode", "idle")], core.idle as f64 / hz);
+    }
+    let mut cores = MetricFamily::gauge("minirobot_cpu_cores", "Number of logical CPUs");
+    cores.add(&[], counters.cpu_cores.len() as f64);
+
+    let mut families = vec![seconds, core_seconds, cores];
+    for (name, value) in [("1", counters.load.one), ("5", counters.load.five), ("15", counters.load.fifteen)] {
+        let mut load = MetricFamily::gauge(&format!("minirobot_load{}", name), &format!("{}-minute load average", name));
+        load.add(&[], value);
+        families.push(load);
+    }
+    families
+}
+
+fn collect_memory() -> Result<Vec<MetricFamily>, String> {
+    let mem = MemInfo::collect().map_err(|e| e.to_string())?;
+    let gauges = [
+        ("minirobot_memory_total_bytes", "Total memory in bytes", mem.memory_total()),
+        ("minirobot_memory_used_bytes", "Used memory in bytes", mem.memory_used()),
+        ("minirobot_memory_free_bytes", "Free memory in bytes", mem.memory_free()),
+        ("minirobot_swap_total_bytes", "Total swap in bytes", mem.swap_total()),
+        ("minirobot_swap_used_bytes", "Used swap in bytes", mem.swap_used()),
+        ("minirobot_swap_free_bytes", "Free swap in bytes", mem.swap_free()),
+    ];
+    Ok(gauges.iter().map(|(name, help, value)| {
+        let mut family = MetricFamily::gauge(name, help);
+        family.add(&[], *value as f64);
+        family
+    }).collect())
+}
+
+fn collect_disk(counters: Option<&Counters>) -> Result<Vec<MetricFamily>, String> {
+    let disk = DiskInfo::collect().map_err(|e| e.to_string())?;
+    let mut size = MetricFamily::gauge("minirobot_filesystem_size_bytes", "Filesystem size in bytes");
+    let mut used = MetricFamily::gauge("minirobot_filesystem_used_bytes", "Filesystem used space in bytes");
+    let mut free = MetricFamily::gauge("minirobot_filesystem_free_bytes", "Filesystem space available to unprivileged users in bytes");
+    let mut used_ratio = MetricFamily::gauge("minirobot_filesystem_used_ratio", "Filesystem usage ratio, same as df");
+    let mut inodes = MetricFamily::gauge("minirobot_filesystem_inodes", "Filesystem total inodes");
+    let mut inodes_free = MetricFamily::gauge("minirobot_filesystem_inodes_free", "Filesystem free inodes");
+    for partition in disk.partitions() {
+        let labels = [("mount", partition.mount.as_str()), ("device", partition.device.as_str()), ("fstype", partition.fs_type.as_str())];
+        size.add(&labels, partition.total as f64);
+        used.add(&labels, partition.used as f64);
+        free.add(&labels, partition.free as f64);
+        used_ratio.add(&labels, partition.use_percent() as f64 / 100.0);
+        if let Some(partition_inodes) = &partition.inodes {
+            inodes.add(&labels, partition_inodes.total as f64);
+            inodes_free.add(&labels, partition_inodes.free as f64);
+        }
+    }
+    let mut families = vec![size, used, free, used_ratio, inodes, inodes_free];
+
+    // 磁盘读写计数只在 Linux 上可用
+    if let Some(counters) = counters {
+        let mut reads = MetricFamily::counter("minirobot_disk_reads_completed", "Completed disk reads");
+        let mut read_bytes = MetricFamily::counter("minirobot_disk_read_bytes", "Bytes read from disk");
+        let mut writes = MetricFamily::counter("minirobot_disk_writes_completed", "Completed disk writes");
+        let mut written_bytes = MetricFamily::counter("minirobot_disk_written_bytes", "Bytes written to disk");
+        for disk in &counters.disks {
+            let labels = [("device", disk.name.as_str())];
+            reads.add(&labels, disk.reads as f64);
+            read_bytes.add(&labels, disk.read_bytes as f64);
+            writes.add(&labels, disk.writes as f64);
+            written_bytes.add(&labels, disk.write_bytes as f64);
+        }
+        families.extend([reads, read_bytes, writes, written_bytes]);
+    }
+    Ok(families)
+}
+
+fn collect_network(counters: &Counters) -> Vec<MetricFamily> {
+    let mut families: Vec<MetricFamily> = [
+        ("minirobot_network_receive_bytes", "Bytes received by the interface"),
+        ("minirobot_network_receive_packets", "Packets received by the interface"),
+        ("minirobot_network_receive_errors", "Receive errors of the interface"),
+        ("minirobot_network_receive_drops", "Received packets dropped by the interface"),
+        ("minirobot_network_transmit_bytes", "Bytes transmitted by the interface"),
+        ("minirobot_network_transmit_packets", "Packets transmitted by the interface"),
+        ("minirobot_network_transmit_errors", "Transmit errors of the interface"),
+        ("minirobot_network_transmit_drops", "Transmitted packets dropped by the interface"),
+    ].iter().map(|(name, help)| MetricFamily::counter(name, help)).collect();
+    for nic in &counters.nics {
+        let values = [nic.rx_bytes, nic.rx_packets, nic.rx_errors, nic.rx_drops, nic.tx_bytes, nic.tx_packets, nic.tx_errors, nic.tx_drops];
+        for (family, value) in families.iter_mut().zip(values) {
+            family.add(&[("device", nic.name.as_str())], value as f64);
+        }
+    }
+    families
+}
+
+fn collect_process() -> Vec<MetricFamily> {
+    let processes = gather_process_info();
+    let mut states: BTreeMap<String, usize> = BTreeMap::new();
+    for process in &processes {
+        *states.entry(process.state.to_lowercase()).or_default() += 1;
+    }
+    let mut count = MetricFamily::gauge("minirobot_processes", "Number of processes by state");
+    for (state, total) in &states {
+        count.add(&[("state", state)], *total as f64);
+    }
+    let mut threads = MetricFamily::gauge("minirobot_process_threads", "Number of threads of all processes");
+    threads.add(&[], processes.iter().filter_map(|process| process.threads).sum::<u32>() as f64);
+    vec![count, threads]
+}
+
+fn collect_socket() -> Vec<MetricFamily> {
+    let inventory = SocketInventory::collect();
+    let mut protocols: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut info = MetricFamily::gauge("minirobot_listening_port_info", "Listening socket with its owner process and service");
+    for socket in &inventory.listening {
+        *protocols.entry(socket.protocol.as_str()).or_default() += 1;
+        let port = socket.local_port.to_string();
+        info.add(&[
+            ("protocol", &socket.protocol),
+            ("address", &socket.local_ip),
+            ("port", &port),
+            ("service", socket.service.as_deref().unwrap_or("")),
+            ("process", socket.owner.as_ref().map(|owner| owner.name.as_str()).unwrap_or("")),
+        ], 1.0);
+    }
+    let mut count = MetricFamily::gauge("minirobot_listening_ports", "Number of listening sockets by protocol");
+    for (protocol, total) in &protocols {
+        count.add(&[("protocol", protocol)], *total as f64);
+    }
+    vec![count, info]
+}
+
+// 任务状态标签，与命令行参数一致
+fn status_label(status: &TaskStatus) -> String {
+    format!("{:?}", status).to_lowercase()
+}
+
+fn collect_task(store: &dyn TaskStore) -> Result<Vec<MetricFamily>, String> {
+    let records = store.list(&TaskFilter::default()).map_err(|e| e.to_string())?;
+    let mut count = MetricFamily::gauge("minirobot_tasks", "Number of recorded tasks by status");
+    for status in TaskStatus::ALL {
+        let total = records.iter().filter(|record| record.status == status).count();
+        count.add(&[("status", &status_label(&status))], total as f64);
+    }
+
+    let durations: Vec<f64> = records.iter().filter_map(|record| record.cost_ms).map(|ms| ms as f64 / 1000.0).collect();
+    let mut duration = MetricFamily::histogram("minirobot_task_duration_seconds", "Duration of recorded tasks in seconds");
+    duration.observe(&[], &TASK_DURATION_BUCKETS, &durations);
+
+    // 记录按创建时刻排序，同名任务取最后一次
+    let mut last: BTreeMap<&str, (&TaskStatus, f64)> = BTreeMap::new();
+    for record in &records {
+        if let Some(cost_ms) = record.cost_ms {
+            last.insert(&record.name, (&record.status, cost_ms as f64 / 1000.0));
+        }
+    }
+    let mut last_duration = MetricFamily::gauge("minirobot_task_last_duration_seconds", "Duration of the latest run of each task in seconds");
+    for (name, (status, seconds)) in &last {
+        last_duration.add(&[("task", name), ("status", &status_label(status))], *seconds);
+    }
+    Ok(vec![count, duration, last_duration])
+}
+
+// 常量时间比较，避免按响应时间猜测口令
+fn constant_time_eq(left: &[u8], right: &[u8]) -> bool {
+    left.len() == right.len() && left.iter().zip(right).fold(0u8, |acc, (l, r)| acc | (l ^ r)) == 0
+}
+
+/// 校验 Authorization 头，未配置认证时总是通过
+pub fn authorized(auth: Option<&MetricsAuthConfig>, authorization: Option<&str>) -> bool {
+    let Some(auth) = auth else {
+        return true;
+    };
+    let Some((scheme, credentials)) = authorization.and_then(|header| header.trim().split_once(' ')) else {
+        return false;
+    };
+    match auth {
+        MetricsAuthConfig::Basic { username, password } => {
+            let Ok(decoded) = STANDARD.decode(credentials.trim()) else {
+                return false;
+            };
+            scheme.eq_ignore_ascii_case("basic") && constant_time_eq(&decoded, format!("{}:{}", username, password).as_bytes())
+        }
+        MetricsAuthConfig::Bearer { token } => {
+            scheme.eq_ignore_ascii_case("bearer") && constant_time_eq(credentials.trim().as_bytes(), token.as_bytes())
+        }
+    }
+}
+
+// 指标导出器
+pub struct Exporter {
+    collectors: Vec<Collector>,
+    auth: Option<MetricsAuthConfig>,
+    tasks: Option<Box<dyn TaskStore + Send + Sync>>,
+}
+
+impl Exporter {
+    /// 按配置创建导出器，采集器名无效时返回错误
+    pub fn new(config: &MetricsConfig) -> Result<Self, Box<dyn Error>> {
+        let mut collectors = if config.collectors.is_empty() {
+            Collector::ALL.to_vec()
+        } else {
+            config.collectors.iter().map(|name| name.parse::<Collector>()).collect::<Result<Vec<_>, _>>()?
+        };
+        collectors.sort();
+        collectors.dedup();
+        Ok(Exporter {
+            collectors,
+            auth: config.auth.clone(),
+            tasks: None,
+        })
+    }
+
+    /// 任务指标读取的任务记录存储，未设置时不输出任务指标
+    pub fn with_tasks(mut self, store: Box<dyn TaskStore + Send + Sync>) -> Self {
+        self.tasks = Some(store);
+        self
+    }
+
+    pub fn collectors(&self) -> &[Collector] {
+        &self.collectors
+    }
+
+    fn collect(&self, collector: Collector, counters: &Result<Counters, String>) -> Result<Vec<MetricFamily>, String> {
+        match collector {
+            Collector::Cpu => counters.as_ref().map(collect_cpu).map_err(|e| e.clone()),
+            Collector::Memory => collect_memory(),
+            Collector::Disk => collect_disk(counters.as_ref().ok()),
+            Collector::Network => counters.as_ref().map(collect_network).map_err(|e| e.clone()),
+            Collector::Process => Ok(collect_process()),
+            Collector::Socket => Ok(collect_socket()),
+            Collector::Task => match &self.tasks {
+                Some(store) => collect_task(store.as_ref()),
+                None => Err("no task store".to_string()),
+            },
+        }
+    }
+
+    /// 并行运行启用的采集器，另外输出各采集器是否成功及耗时
+    pub fn gather(&self) -> Vec<MetricFamily> {
+        let counters = sampler::read_counters().map_err(|e| e.to_string());
+        let results: Vec<(Collector, Result<Vec<MetricFamily>, String>, f64)> = thread::scope(|scope| {
+            let handles: Vec<_> = self.collectors.iter().map(|collector| {
+                let counters = &counters;
+                (*collector, scope.spawn(move || {
+                    let start = Instant::now();
+                    let result = self.collect(*collector, counters);
+                    (result, start.elapsed().as_secs_f64())
+                }))
+            }).collect();
+            handles.into_iter().map(|(collector, handle)| {
+                let (result, elapsed) = joined(handle);
+                (collector, result, elapsed)
+            }).collect()
+        });
+
+        let mut success = MetricFamily::gauge("minirobot_scrape_collector_success", "Whether the collector succeeded");
+        let mut duration = MetricFamily::gauge("minirobot_scrape_collector_duration_seconds", "Time the collector took");
+        let mut families = Vec::new();
+        for (collector, result, elapsed) in results {
+            let succeeded = match result {
+                Ok(collected) => {
+                    families.extend(collected);
+                    true
+                }
+                Err(e) => {
+                    warn!("Metrics collector {} failed: {}", collector, e);
+                    false
+                }
+            };
+            success.add(&[("collector", collector.name())], succeeded as u8 as f64);
+            duration.add(&[("collector", collector.name())], elapsed);
+        }
+        families.push(success);
+        families.push(duration);
+        families
+    }
+
+    // 处理一次抓取请求
+    async fn respond(self: Arc<Self>, authorization: Option<String>, accept: Option<String>) -> Response<String> {
+        if !authorized(self.auth.as_ref(), authorization.as_deref()) {
+            let challenge = match self.auth {
+                Some(MetricsAuthConfig::Bearer { .. }) => "Bearer realm=\"minirobot\"",
+                _ => "Basic realm=\"minirobot\"",
+            };
+            return Response::builder()
+                .status(StatusCode::UNAUTHORIZED)
+                .header(header::WWW_AUTHENTICATE, challenge)
+                .body("unauthorized\n".to_string())
+                .unwrap_or_default();
+        }
+        let encoding = Encoding::negotiate(accept.as_deref());
+        let exporter = self.clone();
+        match tokio::task::spawn_blocking(move || exporter.gather()).await {
+            Ok(families) => Response::builder()
+                .header(header::CONTENT_TYPE, encoding.content_type())
+                .body(encode(&families, encoding))
+                .unwrap_or_default(),
+            Err(e) => Response::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .body(format!("failed to gather metrics: {}\n", e))
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// GET /metrics 路由
+pub fn routes(exporter: Arc<Exporter>) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    warp::path("metrics")
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(warp::header::optional::<String>("authorization"))
+        .and(warp::header::optional::<String>("accept"))
+        .and_then(move |authorization: Option<String>, accept: Option<String>| {
+            let exporter = exporter.clone();
+            async move { Ok::<_, Infallible>(exporter.respond(authorization, accept).await) }
+        })
+}
+
+/// 绑定监听地址，返回实际地址和服务，需要在 tokio 运行时中执行
+pub fn bind(exporter: Exporter, address: SocketAddr) -> Result<(SocketAddr, impl Future<Output = ()>), Box<dyn Error>> {
+    Ok(warp::serve(routes(Arc::new(exporter))).try_bind_ephemeral(address)?)
+}
+
+#[cfg(test)]
+mod unit_test_metrics {
+    use super::*;
+    use crate::task::history::TaskHistory;
+
+    #[test]
+    fn test_metrics_01() {
+        let mut counter = MetricFamily::counter("minirobot_network_receive_bytes", "Bytes received");
+        counter.add(&[("device", "eth\"0")], 1024.0);
+        let mut histogram = MetricFamily::histogram("minirobot_task_duration_seconds", "Duration");
+        histogram.observe(&[], &[1.0, 5.0], &[0.5, 3.0, 10.0]);
+        let families = vec![counter, histogram];
+
+        let prometheus = encode(&families, Encoding::Prometheus);
+        assert!(prometheus.starts_with("# HELP minirobot_network_receive_bytes_total Bytes received\n# TYPE minirobot_network_receive_bytes_total counter\n"));
+        assert!(prometheus.contains("\nminirobot_network_receive_bytes_total{device=\"eth\\\"0\"} 1024\n"));
+        assert!(prometheus.contains("\nminirobot_task_duration_seconds_bucket{le=\"1\"} 1\nminirobot_task_duration_seconds_bucket{le=\"5\"} 2\n\
+            minirobot_task_duration_seconds_bucket{le=\"+Inf\"} 3\nminirobot_task_duration_seconds_sum 13.5\nminirobot_task_duration_seconds_count 3\n"));
+        assert!(!prometheus.contains("# EOF"));
+
+        let openmetrics = encode(&families, Encoding::OpenMetrics);
+        assert!(openmetrics.starts_with("# HELP minirobot_network_receive_bytes Bytes received\n# TYPE minirobot_network_receive_bytes counter\n"));
+        assert!(openmetrics.ends_with("\n# EOF\n"));
+        assert_eq!(Encoding::negotiate(Some("application/openmetrics-text;version=1.0.0,text/plain;q=0.5")), Encoding::OpenMetrics);
+        assert_eq!(Encoding::negotiate(None), Encoding::Prometheus);
+    }
+
+    #[test]
+    fn test_metrics_02() {
+        assert!(authorized(None, None));
+        let basic = MetricsAuthConfig::Basic { username: "prom".to_string(), password: "secret".to_string() };
+        assert!(authorized(Some(&basic), Some(&format!("Basic {}", STANDARD.encode("prom:secret")))));
+        assert!(!authorized(Some(&basic), Some(&format!("Basic {}", STANDARD.encode("prom:wrong")))));
+        assert!(!authorized(Some(&basic), Some("Bearer secret")));
+        assert!(!authorized(Some(&basic), None));
+        let bearer = MetricsAuthConfig::Bearer { token: "t0ken".to_string() };
+        assert!(authorized(Some(&bearer), Some("bearer t0ken")));
+        assert!(!authorized(Some(&bearer), Some("Bearer t0ke")));
+
+        let config: MetricsConfig = serde_json::from_str(r#"{"collectors": ["task", "cpu", "cpu"], "auth": {"type": "bearer", "token": "t"}}"#).unwrap();
+        assert_eq!(config.listen, "127.0.0.1:9102");
+        assert_eq!(Exporter::new(&config).unwrap().collectors(), &[Collector::Cpu, Collector::Task]);
+        let config: MetricsConfig = serde_json::from_str(r#"{"collectors": ["gpu"]}"#).unwrap();
+        assert!(Exporter::new(&config).is_err());
+    }
+
+    #[test]
+    fn test_metrics_03() {
+        let dir = std::env::temp_dir().join(format!("minirobot_metrics_{}", std::process::id()));
+        let config: MetricsConfig = serde_json::from_str(r#"{"collectors": ["memory", "task"], "auth": {"type": "bearer", "token": "t0ken"}}"#).unwrap();
+        let exporter = Exporter::new(&config).unwrap().with_tasks(Box::new(TaskHistory::new(dir.to_str().unwrap())));
+        let filter = routes(Arc::new(exporter));
+
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let response = warp::test::request().path("/metrics").reply(&filter).await;
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+            assert!(response.headers()[header::WWW_AUTHENTICATE].to_str().unwrap().starts_with("Bearer"));
+
+            let response = warp::test::request().path("/metrics")
+                .header("authorization", "Bearer t0ken")
+                .header("accept", "application/openmetrics-text; version=1.0.0")
+                .reply(&filter).await;
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(response.headers()[header::CONTENT_TYPE], OPENMETRICS_CONTENT_TYPE);
+            let body = String::from_utf8(response.body().to_vec()).unwrap();
+            assert!(body.contains("\nminirobot_memory_total_bytes "));
+            assert!(body.contains("\nminirobot_tasks{status=\"finished\"} 0\n"));
+            assert!(body.contains("\nminirobot_task_duration_seconds_count 0\n"));
+            assert!(body.contains("\nminirobot_scrape_collector_success{collector=\"task\"} 1\n"));
+            assert!(!body.contains("minirobot_cpu_seconds"));
+            assert!(body.ends_with("# EOF\n"));
+        });
+    }
+}
@@ -1,7 +1,13 @@
+pub mod alert;
+pub mod api;
 pub mod event;
 pub mod desktop_inform;
 pub mod monitor;
+pub mod rule;
+pub mod script;
 pub mod asset;
+pub mod check;
 pub mod env;
+pub mod metrics;
 pub mod system;
 pub mod task;
\ No newline at end of file
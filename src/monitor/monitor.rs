@@ -1,48 +1,344 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use serde::Serialize;
+use tokio::sync::{oneshot, watch};
+use tokio::task::JoinSet;
+
 use crate::common::config::{read_config, MonitorConfig};
+use crate::common::ds::RingBuffer;
+use crate::database::repository::Repository;
+use crate::monitor::alert::AlertStore;
+use crate::monitor::check::{Check, CheckRegistry, CheckResult, CheckStatus, PerfData};
 use crate::monitor::event::Event;
-use crate::monitor::system::disk_usage::check_disk_usage;
-use crate::monitor::system::open_port::check_open_port;
-use crate::monitor::task::status::check_task_status;
 
 include!(concat!(env!("OUT_DIR"), "/version.rs"));
 
+// 调度循环检查到期监控项的间隔
+const TICK: Duration = Duration::from_secs(1);
+// 状态接口保留的最近事件数
+const EVENT_HISTORY: usize = 1000;
+
+// 一次检查的记录
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckRun {
+    pub status: CheckStatus,                 // 超时或检查过程 panic 时为 unknown
+    pub message: String,
+    pub metrics: Vec<PerfData>,
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: u64,
+}
+
+// 监控项的当前状态
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckState {
+    pub name: String,
+    pub interval_secs: u64,
+    pub timeout_secs: u64,
+    pub running: bool,                       // 超时的检查在实际结束前保持为 true，期间不再启动
+    pub runs: u64,
+    pub next_run: DateTime<Utc>,
+    pub last: Option<CheckRun>,              // 最近一次结果
+}
+
+// 监控循环的共享状态，供状态接口读取
+#[derive(Debug, Clone)]
+pub struct MonitorState {
+    checks: Arc<Mutex<BTreeMap<String, CheckState>>>,
+    events: Arc<Mutex<RingBuffer<Event>>>,
+    alerts: Arc<AlertStore>,
+}
+
+impl MonitorState {
+    fn new() -> Self {
+        MonitorState {
+            checks: Arc::new(Mutex::new(BTreeMap::new())),
+            events: Arc::new(Mutex::new(RingBuffer::new(EVENT_HISTORY))),
+            alerts: Arc::new(AlertStore::new()),
+        }
+    }
+
+    /// 所有监控项的状态，按名称排序
+    pub fn checks(&self) -> Vec<CheckState> {
+        self.checks.lock().unwrap_or_else(|e| e.into_inner()).values().cloned().collect()
+    }
+
+    pub fn check(&self, name: &str) -> Option<CheckState> {
+        self.checks.lock().unwrap_or_else(|e| e.into_inner()).get(name).cloned()
+    }
+
+    /// 告警跟踪，包括未解决的告警及其确认和静默
+    pub fn alerts(&self) -> &AlertStore {
+        &self.alerts
+    }
+
+    /// 最近通知的事件，按时间顺序，静默的告警重复发生时不在其中
+    pub fn events(&self) -> Vec<Event> {
+        self.events.lock().unwrap_or_else(|e| e.into_inner()).iter().cloned().collect()
+    }
+
+    fn update<F: FnOnce(&mut CheckState)>(&self, name: &str, f: F) {
+        if let Some(state) = self.checks.lock().unwrap_or_else(|e| e.into_inner()).get_mut(name) {
+            f(state);
+        }
+    }
+
+    fn push_event(&self, event: Event) {
+        self.events.lock().unwrap_or_else(|e| e.into_inner()).push(event);
+    }
+}
+
+// 检查线程结束（包括 panic）时清除运行标记
+struct RunningGuard {
+    state: MonitorState,
+    name: String,
+}
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        self.state.update(&self.name, |state| state.running = false);
+    }
+}
+
+/// 等待 SIGTERM 或 Ctrl-C
+pub async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = terminate.recv() => {}
+                    _ = tokio::signal::ctrl_c() => {}
+                }
+            }
+            Err(e) => {
+                warn!("failed to listen for SIGTERM: {}", e);
+                let _ = tokio::signal::ctrl_c().await;
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
+
 #[derive(Debug)]
 pub struct Monitor {
     config: MonitorConfig,
+    checks: CheckRegistry,
     events: Vec<Event>,
-    // 监控策略
+    state: MonitorState,
 }
+
 impl Monitor {
-    pub fn new(config_json: &str) -> Self {
-        Monitor {
-            config: read_config::<MonitorConfig>(config_json),
+    pub fn new(config_json: &str) -> Result<Self, Box<dyn Error>> {
+        Self::from_config(read_config::<MonitorConfig>(config_json))
+    }
+
+    /// 按配置注册内置监控项和脚本监控项，脚本配置无效或名称重复时返回错误
+    pub fn from_config(config: MonitorConfig) -> Result<Self, Box<dyn Error>> {
+        Ok(Monitor {
+            checks: CheckRegistry::from_config(&config)?,
+            config,
             events: Vec::new(),
+            state: MonitorState::new(),
+        })
+    }
+
+    // 注册其他监控项，需要在 run 之前调用
+    pub fn register(&mut self, check: Box<dyn Check>) -> Result<(), Box<dyn Error>> {
+        self.checks.register(check)
+    }
+
+    pub fn checks(&self) -> &CheckRegistry {
+        &self.checks
+    }
+
+    // 关联数据库，加载未解决的告警，此后产生的事件同时写入数据库
+    pub fn attach_repository(&mut self, repository: Repository) {
+        match self.state.alerts.attach(repository) {
+            Ok(count) => info!("Loaded {} open alerts", count),
+            Err(e) => warn!("failed to load open alerts: {}", e),
         }
     }
 
-    // 监控并产生事件
-    pub fn start(&mut self) {
+    pub fn config(&self) -> &MonitorConfig {
+        &self.config
+    }
+
+    pub fn events(&self) -> &Vec<Event> {
+        &self.events
+    }
 
-        // 磁盘监控
-        let mut event = check_disk_usage(&self.config.disk_usage.threshold,
-                                                                      &self.config.disk_usage.is_active);
-        if event.is_some() {
-            self.events.push(event.unwrap());
+    // 监控循环的状态，循环运行期间持续更新
+    pub fn state(&self) -> MonitorState {
+        self.state.clone()
+    }
+
+    // 依次执行所有监控项一次并记录事件，返回各监控项的结果
+    pub fn start(&mut self) -> Vec<(String, CheckResult)> {
+        let mut results = Vec::new();
+        for check in self.checks.checks() {
+            let mut result = check.run();
+            let events = std::mem::take(&mut result.events);
+            self.events.extend(self.state.alerts.record(check.name(), result.status, events));
+            results.push((check.name().to_string(), result));
+        }
+        results
+    }
+
+    // 按各监控项的间隔循环检查，直到 shutdown 变为 true 或发送端关闭，等待进行中的检查结束或超时后返回
+    pub async fn run(self, mut shutdown: watch::Receiver<bool>) {
+        let checks = self.checks.checks();
+        {
+            let now = Utc::now();
+            let mut states = self.state.checks.lock().unwrap_or_else(|e| e.into_inner());
+            for check in checks {
+                states.insert(check.name().to_string(), CheckState {
+                    name: check.name().to_string(),
+                    interval_secs: check.interval().as_secs(),
+                    timeout_secs: check.timeout().as_secs(),
+                    running: false,
+                    runs: 0,
+                    next_run: now,
+                    last: None,
+                });
+            }
         }
+        info!("{} monitor started with {} checks", NAME, checks.len());
 
-        // 开放端口监控
-        event = check_open_port(&self.config.open_port.high_risk_ports, &self.config.open_port.is_active);
-        if event.is_some() {
-            self.events.push(event.unwrap());
+        let mut running = JoinSet::new();
+        let mut ticker = tokio::time::interval(TICK);
+        while !*shutdown.borrow() {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                changed = shutdown.changed() => {
+                    if changed.is_err() {
+                        break;
+                    }
+                    continue;
+                }
+            }
+            while running.try_join_next().is_some() {}
+
+            let now = Utc::now();
+            for check in checks {
+                let mut due = false;
+                self.state.update(check.name(), |state| {
+                    if state.next_run > now {
+                        return;
+                    }
+                    state.next_run = now + check.interval();
+                    if state.running {
+                        warn!("check {} is still running, skipped", check.name());
+                        return;
+                    }
+                    state.running = true;
+                    due = true;
+                });
+                if due {
+                    running.spawn(self.execute(check.clone()));
+                }
+            }
         }
 
-        // 任务状态监控
-        event = check_task_status(&self.config.task_status.default_statuses,
-                                  &self.config.task_status.task_list,
-                                  &self.config.task_status.is_active);
-        if event.is_some() {
-            self.events.push(event.unwrap());
+        info!("{} monitor stopping, waiting for {} running checks", NAME, running.len());
+        while running.join_next().await.is_some() {}
+        info!("{} monitor stopped", NAME);
+    }
+
+    // 在独立线程中执行检查，超时后不再等待，线程结束前该监控项不会再次启动
+    fn execute(&self, check: Arc<dyn Check>) -> impl std::future::Future<Output = ()> + Send + 'static {
+        let state = self.state.clone();
+        async move {
+            let name = check.name().to_string();
+            let timeout = check.timeout();
+            let started_at = Utc::now();
+            let start = Instant::now();
+            let (sender, receiver) = oneshot::channel();
+            let guard = RunningGuard { state: state.clone(), name: name.clone() };
+            thread::spawn(move || {
+                let _guard = guard;
+                let _ = sender.send(check.run());
+            });
+
+            let result = match tokio::time::timeout(timeout, receiver).await {
+                Ok(Ok(result)) => result,
+                Ok(Err(_)) => CheckResult::new(CheckStatus::Unknown, "check panicked"),
+                Err(_) => CheckResult::new(CheckStatus::Unknown, &format!("check did not finish in {}s", timeout.as_secs())),
+            };
+            if result.status == CheckStatus::Unknown {
+                warn!("check {}: {}", name, result.message);
+            }
+            for event in state.alerts.record(&name, result.status, result.events) {
+                state.push_event(event);
+            }
+            let run = CheckRun {
+                status: result.status,
+                message: result.message,
+                metrics: result.metrics,
+                started_at,
+                duration_ms: start.elapsed().as_millis() as u64,
+            };
+            state.update(&name, |state| {
+                state.runs += 1;
+                state.last = Some(run);
+            });
         }
+    }
+}
+
+#[cfg(test)]
+mod unit_test_monitor {
+    use super::*;
+
+    #[test]
+    fn test_monitor_01() {
+        // 阈值 0 时任何分区都告警；资源采样 3 秒超过 1 秒超时
+        let config: MonitorConfig = serde_json::from_str(r#"{
+            "disk_usage": {"threshold": 0, "interval_secs": 1, "is_active": true},
+            "open_port": {"high_risk_ports": [], "interval_secs": 3600, "is_active": true},
+            "task_status": {"default_statuses": [], "task_list": [], "is_active": true},
+            "resource_usage": {"cpu_threshold": 100, "memory_threshold": 100, "window_secs": 3, "timeout_secs": 1, "is_active": true}
+        }"#).unwrap();
+        let monitor = Monitor::from_config(config).unwrap();
+        let state = monitor.state();
+        let (sender, receiver) = watch::channel(false);
+
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let handle = tokio::spawn(monitor.run(receiver));
+            tokio::time::sleep(Duration::from_millis(2500)).await;
+
+            let resource = state.check("resource_usage").unwrap();
+            assert_eq!(resource.last.as_ref().unwrap().status, CheckStatus::Unknown);
+            assert!(resource.last.as_ref().unwrap().message.contains("did not finish"));
+            assert!(resource.running);
+            assert_eq!(resource.runs, 1);
+
+            let open_port = state.check("open_port").unwrap();
+            assert_eq!(open_port.runs, 1);
+            assert_eq!(open_port.last.as_ref().unwrap().status, CheckStatus::Ok);
+            assert!(open_port.next_run > Utc::now() + chrono::Duration::seconds(3000));
+
+            sender.send(true).unwrap();
+            tokio::time::timeout(Duration::from_secs(5), handle).await.unwrap().unwrap();
+        });
 
+        let names: Vec<String> = state.checks().into_iter().map(|check| check.name).collect();
+        assert_eq!(names, vec!["disk_usage", "open_port", "resource_usage", "task_status"]);
+        let disk = state.check("disk_usage").unwrap();
+        assert!(disk.runs >= 2);
+        let last = disk.last.unwrap();
+        assert_eq!(last.status == CheckStatus::Warning, !state.events().is_empty());
+        assert!(last.metrics.iter().all(|metric| metric.unit == "%" && metric.warn.as_deref() == Some("0")));
     }
 }
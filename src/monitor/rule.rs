@@ -0,0 +1,223 @@
+//! 阈值规则
+//!
+//! 数值达到告警或严重阈值并持续 `for_secs` 后才进入相应状态，降级和恢复立即生效；
+//! 已告警的对象需低于阈值减去回差才降级，避免数值在阈值附近波动时反复告警和恢复。
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::common::config::ThresholdConfig;
+use crate::monitor::check::CheckStatus;
+
+/// 通配符匹配，`*` 匹配任意个字符，`?` 匹配一个字符
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = star {
+            // 回溯，让上一个 * 多匹配一个字符
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+// 阈值规则
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ThresholdRule {
+    pub warning: Option<f64>,
+    pub critical: Option<f64>,
+    pub duration: Duration,
+    pub hysteresis: f64,
+}
+
+impl ThresholdRule {
+    pub fn new(config: &ThresholdConfig) -> Self {
+        ThresholdRule::default().merge(config)
+    }
+
+    /// 以 config 中已配置的字段覆盖当前规则
+    pub fn merge(&self, config: &ThresholdConfig) -> Self {
+        ThresholdRule {
+            warning: config.warning.or(self.warning),
+            critical: config.critical.or(self.critical),
+            duration: config.for_secs.map(Duration::from_secs).unwrap_or(self.duration),
+            hysteresis: config.hysteresis.unwrap_or(self.hysteresis),
+        }
+    }
+
+    pub fn threshold(&self, status: CheckStatus) -> Option<f64> {
+        match status {
+            CheckStatus::Warning => self.warning,
+            CheckStatus::Critical => self.critical,
+            _ => None,
+        }
+    }
+
+    /// 数值对应的状态，current 为当前状态，已处于的级别按回差判断
+    pub fn level(&self, value: f64, current: CheckStatus) -> CheckStatus {
+        let reached = |level: CheckStatus| match self.threshold(level) {
+            Some(threshold) if current >= level && current != CheckStatus::Unknown => value > threshold - self.hysteresis,
+            Some(threshold) => value >= threshold,
+            None => false,
+        };
+        if reached(CheckStatus::Critical) {
+            CheckStatus::Critical
+        } else if reached(CheckStatus::Warning) {
+            CheckStatus::Warning
+        } else {
+            CheckStatus::Ok
+        }
+    }
+}
+
+// 一个对象的一次判断结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct Evaluation {
+    pub subject: String,
+    pub value: f64,
+    pub status: CheckStatus,
+    pub previous: CheckStatus,
+    pub pending: Option<CheckStatus>,        // 已达到但未持续足够时长的级别
+}
+
+impl Evaluation {
+    /// 由告警状态恢复正常
+    pub fn recovered(&self) -> bool {
+        self.status == CheckStatus::Ok && self.previous != CheckStatus::Ok
+    }
+}
+
+#[derive(Debug, Default)]
+struct SubjectState {
+    status: CheckStatus,
+    warning_since: Option<Instant>,
+    critical_since: Option<Instant>,
+}
+
+// 各对象的告警状态，在多次检查之间保留
+#[derive(Debug, Default)]
+pub struct RuleState {
+    subjects: Mutex<HashMap<String, SubjectState>>,
+}
+
+impl RuleState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 按规则判断 subject 在 now 时刻的数值，并更新其状态
+    pub fn evaluate(&self, rule: &ThresholdRule, subject: &str, value: f64, now: Instant) -> Evaluation {
+        let mut subjects = self.subjects.lock().unwrap_or_else(|e| e.into_inner());
+        let state = subjects.entry(subject.to_string()).or_default();
+        let previous = state.status;
+        let level = rule.level(value, previous);
+
+        let since = |since: &mut Option<Instant>, reached: bool| {
+            if reached {
+                Some(*since.get_or_insert(now))
+            } else {
+                *since = None;
+                None
+            }
+        };
+        let sustained = |since: Option<Instant>| since.is_some_and(|since| now.duration_since(since) >= rule.duration);
+        let warning_since = since(&mut state.warning_since, level >= CheckStatus::Warning);
+        let critical_since = since(&mut state.critical_since, level == CheckStatus::Critical);
+
+        let confirmed = if sustained(critical_since) {
+            CheckStatus::Critical
+        } else if sustained(warning_since) {
+            CheckStatus::Warning
+        } else {
+            CheckStatus::Ok
+        };
+        // 已确认的级别在数值未降到其下之前保持
+        let status = confirmed.max(previous.min(level));
+        state.status = status;
+        Evaluation {
+            subject: subject.to_string(),
+            value,
+            status,
+            previous,
+            pending: (level > status).then_some(level),
+        }
+    }
+
+    /// 清除不再存在的对象，返回其中处于告警状态的对象
+    pub fn retain(&self, subjects: &[String]) -> Vec<String> {
+        let mut states = self.subjects.lock().unwrap_or_else(|e| e.into_inner());
+        let removed: Vec<String> = states.keys().filter(|subject| !subjects.contains(subject)).cloned().collect();
+        removed.into_iter()
+            .filter(|subject| states.remove(subject).is_some_and(|state| state.status != CheckStatus::Ok))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod unit_test_rule {
+    use super::*;
+
+    #[test]
+    fn test_rule_01() {
+        assert!(glob_match("/data*", "/data"));
+        assert!(glob_match("/data*", "/data/backup"));
+        assert!(glob_match("/dev/sd?1", "/dev/sda1"));
+        assert!(glob_match("*/log*", "/var/log"));
+        assert!(!glob_match("/data*", "/home/data"));
+        assert!(!glob_match("/dev/sd?1", "/dev/sda12"));
+
+        let config = ThresholdConfig { warning: Some(80.0), critical: Some(90.0), for_secs: None, hysteresis: Some(5.0) };
+        let rule = ThresholdRule::new(&config);
+        assert_eq!(rule.level(85.0, CheckStatus::Ok), CheckStatus::Warning);
+        assert_eq!(rule.level(78.0, CheckStatus::Warning), CheckStatus::Warning);
+        assert_eq!(rule.level(75.0, CheckStatus::Warning), CheckStatus::Ok);
+        assert_eq!(rule.level(88.0, CheckStatus::Critical), CheckStatus::Critical);
+        assert_eq!(rule.level(88.0, CheckStatus::Ok), CheckStatus::Warning);
+
+        let merged = rule.merge(&ThresholdConfig { critical: Some(95.0), for_secs: Some(60), ..Default::default() });
+        assert_eq!(merged.warning, Some(80.0));
+        assert_eq!(merged.critical, Some(95.0));
+        assert_eq!(merged.duration, Duration::from_secs(60));
+    }
+
+    #[test]
+    fn test_rule_02() {
+        let rule = ThresholdRule { warning: Some(80.0), critical: Some(90.0), duration: Duration::from_secs(300), hysteresis: 2.0 };
+        let state = RuleState::new();
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+
+        let evaluation = state.evaluate(&rule, "/", 95.0, at(0));
+        assert_eq!(evaluation.status, CheckStatus::Ok);
+        assert_eq!(evaluation.pending, Some(CheckStatus::Critical));
+        assert_eq!(state.evaluate(&rule, "/", 85.0, at(200)).status, CheckStatus::Ok);
+        let evaluation = state.evaluate(&rule, "/", 92.0, at(300));
+        assert_eq!(evaluation.status, CheckStatus::Warning);
+        assert_eq!(evaluation.pending, Some(CheckStatus::Critical));
+        assert_eq!(state.evaluate(&rule, "/", 92.0, at(600)).status, CheckStatus::Critical);
+        assert_eq!(state.evaluate(&rule, "/", 89.0, at(660)).status, CheckStatus::Critical);
+        assert_eq!(state.evaluate(&rule, "/", 85.0, at(720)).status, CheckStatus::Warning);
+
+        let evaluation = state.evaluate(&rule, "/", 70.0, at(780));
+        assert!(evaluation.recovered());
+        assert!(!state.evaluate(&rule, "/", 70.0, at(840)).recovered());
+
+        state.evaluate(&rule, "/data", 99.0, at(0));
+        state.evaluate(&rule, "/data", 99.0, at(900));
+        assert_eq!(state.retain(&["/".to_string()]), vec!["/data".to_string()]);
+    }
+}
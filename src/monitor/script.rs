@@ -0,0 +1,214 @@
+//! 脚本监控项
+//!
+//! 以 `actors::shell::Shell` 执行配置中的脚本，按 Nagios 插件约定解析：退出码 0/1/2/3 对应 OK/WARNING/CRITICAL/UNKNOWN，
+//! 标准输出第一行为 "说明 | 性能数据"，之后的行为详细说明，其中 '|' 之后的内容同样是性能数据。
+
+use std::time::Duration;
+
+use crate::actors::shell::Shell;
+use crate::common::config::ScriptMonitorConfig;
+use crate::monitor::check::{Check, CheckResult, CheckStatus, PerfData};
+use crate::monitor::event::*;
+
+// 脚本超时后终止并回收需要的时间，监控的超时比脚本超时多出该时长，由脚本自身的超时先生效
+const KILL_GRACE: Duration = Duration::from_secs(5);
+
+// 拆分性能数据，单引号内的空格属于标签，'' 表示引号本身
+fn perfdata_tokens(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut token = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' if quoted && chars.peek() == Some(&'\'') => {
+                token.push('\'');
+                chars.next();
+            }
+            '\'' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !token.is_empty() {
+                    tokens.push(std::mem::take(&mut token));
+                }
+            }
+            c => token.push(c),
+        }
+    }
+    if !token.is_empty() {
+        tokens.push(token);
+    }
+    tokens
+}
+
+// 解析一项性能数据，值无法解析（包括 "U"）时忽略
+fn parse_perfdata(token: &str) -> Option<PerfData> {
+    let (label, data) = token.rsplit_once('=')?;
+    let mut fields = data.split(';');
+    let value_text = fields.next()?;
+    let split = value_text.find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-' || c == '+' || c == 'e' || c == 'E'))
+        .unwrap_or(value_text.len());
+    let value = value_text[..split].parse::<f64>().ok()?;
+    let text = |field: Option<&str>| field.filter(|field| !field.is_empty()).map(|field| field.to_string());
+    let number = |field: Option<&str>| field.and_then(|field| field.parse::<f64>().ok());
+    Some(PerfData {
+        label: label.to_string(),
+        value,
+        unit: value_text[split..].to_string(),
+        warn: text(fields.next()),
+        crit: text(fields.next()),
+        min: number(fields.next()),
+        max: number(fields.next()),
+    })
+}
+
+/// 解析插件输出，返回说明（第一行和详细说明）和性能数据
+pub fn parse_plugin_output(output: &str) -> (String, Vec<PerfData>) {
+    let mut lines = output.lines();
+    let (summary, mut perfdata) = match lines.next() {
+        Some(line) => match line.split_once('|') {
+            Some((summary, perfdata)) => (summary.trim().to_string(), perfdata.to_string()),
+            None => (line.trim().to_string(), String::new()),
+        },
+        None => (String::new(), String::new()),
+    };
+    let mut message = vec![summary];
+    let mut in_perfdata = false;
+    for line in lines {
+        if in_perfdata {
+            perfdata.push(' ');
+            perfdata.push_str(line);
+        } else if let Some((text, more)) = line.split_once('|') {
+            message.push(text.to_string());
+            perfdata.push(' ');
+            perfdata.push_str(more);
+            in_perfdata = true;
+        } else {
+            message.push(line.to_string());
+        }
+    }
+    let message = message.join("\n").trim_end().to_string();
+    (message, perfdata_tokens(&perfdata).iter().filter_map(|token| parse_perfdata(token)).collect())
+}
+
+// 脚本监控项
+#[derive(Debug)]
+pub struct ScriptCheck {
+    config: ScriptMonitorConfig,
+}
+
+impl ScriptCheck {
+    pub fn new(config: ScriptMonitorConfig) -> Result<Self, String> {
+        if config.name.trim().is_empty() {
+            return Err("script check without name".to_string());
+        }
+        if config.command.trim().is_empty() {
+            return Err(format!("script check {} without command", config.name));
+        }
+        Ok(ScriptCheck { config })
+    }
+}
+
+impl Check for ScriptCheck {
+    fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(self.config.interval_secs)
+    }
+
+    fn timeout(&self) -> Duration {
+        Duration::from_secs(self.config.timeout_secs) + KILL_GRACE
+    }
+
+    fn run(&self) -> CheckResult {
+        let args: Vec<&str> = self.config.args.iter().map(|arg| arg.as_str()).collect();
+        let mut shell = Shell::new(&0, &self.config.command, Some(&args), &self.config.timeout_secs, None);
+        shell.execute();
+
+        let (status, message, metrics) = match shell.stdout() {
+            Some(stdout) => {
+                let (message, metrics) = parse_plugin_output(stdout);
+                (CheckStatus::from_exit_code(*shell.status()), message, metrics)
+            }
+            // 超时或无法执行时没有输出，原因在错误输出中
+            None => (CheckStatus::Unknown, shell.stderr().clone().unwrap_or_default().trim().to_string(), vec![]),
+        };
+        let message = if message.is_empty() { status.to_string() } else { message };
+        let mut result = CheckResult::new(status, &message).with_metrics(metrics);
+        if status != CheckStatus::Ok {
+            let priority = if status == CheckStatus::Critical { Priority::High } else { Priority::Medium };
+            let severity = match (self.config.is_active, status) {
+                (false, _) => Severity::Debug,
+                (true, CheckStatus::Critical) => Severity::Critical,
+                (true, _) => Severity::Warning,
+            };
+            result.events.push(Event::new(&self.config.name, &format!("{}: {}", status, message), priority, severity, "脚本监控"));
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod unit_test_script {
+    use super::*;
+
+    fn script(name: &str, command: &str, args: &[&str]) -> ScriptCheck {
+        ScriptCheck::new(ScriptMonitorConfig {
+            name: name.to_string(),
+            command: command.to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            interval_secs: 60,
+            timeout_secs: 5,
+            is_active: true,
+        }).unwrap()
+    }
+
+    #[test]
+    fn test_script_01() {
+        let (message, metrics) = parse_plugin_output("DISK OK - free space: / 3326 MB (56%); | '/ free'=3326MB;4000;2000;0;5000 load=U\n\
+            / 15272 MB (77%);\n/boot 68 MB (69%); | /boot=68MB;88;93;0;98\n/home=69357MB;253404;253409;0;253414\n");
+        assert_eq!(message, "DISK OK - free space: / 3326 MB (56%);\n/ 15272 MB (77%);\n/boot 68 MB (69%);");
+        assert_eq!(metrics.len(), 3);
+        assert_eq!(metrics[0].label, "/ free");
+        assert_eq!(metrics[0].value, 3326.0);
+        assert_eq!(metrics[0].unit, "MB");
+        assert_eq!(metrics[0].warn.as_deref(), Some("4000"));
+        assert_eq!(metrics[0].max, Some(5000.0));
+        assert_eq!(metrics[2].label, "/home");
+
+        let (message, metrics) = parse_plugin_output("PING OK\n");
+        assert_eq!(message, "PING OK");
+        assert!(metrics.is_empty());
+        assert_eq!(parse_perfdata("'it''s'=1.5s;;@10:20").unwrap().crit.as_deref(), Some("@10:20"));
+        assert_eq!(perfdata_tokens("'it''s'=1"), vec!["it's=1"]);
+        assert!(ScriptCheck::new(ScriptMonitorConfig { command: " ".to_string(), ..script("x", "true", &[]).config }).is_err());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_script_02() {
+        let check = script("nagios", "sh", &["-c", "echo 'LOAD WARNING - load 5.1 | load1=5.1;4;8;0'; exit 1"]);
+        let result = check.run();
+        assert_eq!(result.status, CheckStatus::Warning);
+        assert_eq!(result.message, "LOAD WARNING - load 5.1");
+        assert_eq!(result.metrics[0].to_string(), "load1=5.1;4;8;0");
+        let event = &result.events[0];
+        assert_eq!(event.name(), "nagios");
+        assert_eq!(event.severity(), &Severity::Warning);
+
+        let result = script("ok", "sh", &["-c", "echo fine"]).run();
+        assert_eq!(result.status, CheckStatus::Ok);
+        assert!(result.events.is_empty());
+        assert_eq!(script("crit", "sh", &["-c", "exit 2"]).run().status, CheckStatus::Critical);
+        assert_eq!(script("odd", "sh", &["-c", "exit 9"]).run().status, CheckStatus::Unknown);
+        assert_eq!(script("missing", "/nonexistent/check", &[]).run().status, CheckStatus::Unknown);
+
+        let mut hung = script("hung", "sleep", &["30"]);
+        hung.config.timeout_secs = 1;
+        assert!(hung.timeout() > Duration::from_secs(1));
+        let result = hung.run();
+        assert_eq!(result.status, CheckStatus::Unknown);
+        assert_eq!(result.message, "Command timed out");
+    }
+}
@@ -1,37 +1,205 @@
-use crate::info::disk::DiskInfo;
+use std::time::{Duration, Instant};
+
+use crate::common::config::DiskUsageMonitorConfig;
+use crate::info::disk::{DiskInfo, Partition};
+use crate::monitor::check::{Check, CheckResult, CheckStatus, PerfData};
 use crate::monitor::event::*;
+use crate::monitor::rule::{glob_match, Evaluation, RuleState, ThresholdRule};
+
+// 磁盘使用率监控项，各分区按阈值规则分别告警和恢复，性能数据为各分区使用率
+#[derive(Debug)]
+pub struct DiskUsageCheck {
+    config: DiskUsageMonitorConfig,
+    rule: ThresholdRule,
+    state: RuleState,
+}
 
-// 磁盘使用情况监控项
-pub fn check_disk_usage(threshold_max: &u8, is_active: &bool) -> Option<Event> {
-    let mut alert_contens = String::new();
-
-    let disk_info = DiskInfo::new();
-    let partition_info = disk_info.partition_info();
-    for p in partition_info {
-        let name = p.get("name").unwrap();
-        let percentage = p.get("use%").unwrap().trim_end_matches("%").parse::<u8>().expect("failed to get partition used percentage");
-        if percentage >= *threshold_max {
-            alert_contens.push_str(&format!("{}-{}%", name, percentage));
+impl DiskUsageCheck {
+    pub fn new(config: DiskUsageMonitorConfig) -> Self {
+        let mut rule = ThresholdRule::new(&config.rule);
+        if rule.warning.is_none() {
+            rule.warning = config.threshold.map(|threshold| threshold as f64);
         }
+        DiskUsageCheck { config, rule, state: RuleState::new() }
+    }
+
+    // 挂载点或设备名匹配任一模式
+    fn matches(patterns: &[String], partition: &Partition) -> bool {
+        patterns.iter().any(|pattern| glob_match(pattern, &partition.mount) || glob_match(pattern, &partition.device))
+    }
+
+    fn selected(&self, partition: &Partition) -> bool {
+        (self.config.include.is_empty() || Self::matches(&self.config.include, partition))
+            && !Self::matches(&self.config.exclude, partition)
+    }
+
+    // 分区适用的规则，使用第一条匹配的覆盖规则
+    fn rule(&self, partition: &Partition) -> ThresholdRule {
+        self.config.overrides.iter()
+            .find(|rule| Self::matches(std::slice::from_ref(&rule.pattern), partition))
+            .map(|rule| self.rule.merge(&rule.rule))
+            .unwrap_or_else(|| self.rule.clone())
+    }
+
+    // 以挂载点为告警对象，同一分区的告警合并跟踪；未启用告警时告警降为调试级别，恢复通知不变
+    fn event(&self, mount: &str, description: &str, priority: Priority, severity: Severity) -> Event {
+        let severity = match severity {
+            Severity::Warning | Severity::Critical if !self.config.is_active => Severity::Debug,
+            severity => severity,
+        };
+        Event::new("磁盘使用率监控", description, priority, severity, "本地主机监控").with_subject(mount)
     }
-    
-    if alert_contens.is_empty() {
-        None
-    } else {
-        if *is_active {
-            Some(Event::new("磁盘使用率监控",
-                &format!("使用率达到告警阈值{}%: {}", threshold_max, alert_contens),
+
+    // 告警中的分区每次检查都产生事件，恢复正常时产生一次恢复事件
+    fn evaluation_event(&self, evaluation: &Evaluation, rule: &ThresholdRule) -> Option<Event> {
+        let threshold = |status| rule.threshold(status).unwrap_or_default();
+        match evaluation.status {
+            CheckStatus::Critical => Some(self.event(
+                &evaluation.subject,
+                &format!("{} 使用率{}%，达到严重阈值{}%", evaluation.subject, evaluation.value, threshold(CheckStatus::Critical)),
+                Priority::Medium,
+                Severity::Critical,
+            )),
+            CheckStatus::Warning => Some(self.event(
+                &evaluation.subject,
+                &format!("{} 使用率{}%，达到告警阈值{}%", evaluation.subject, evaluation.value, threshold(CheckStatus::Warning)),
                 Priority::Low,
                 Severity::Warning,
-                "本地主机监控"
-            ))
-        } else {
-            Some(Event::new("磁盘使用率监控",
-                &format!("使用率达到告警阈值{}%: {}", threshold_max, alert_contens),
+            )),
+            _ if evaluation.recovered() => Some(self.event(
+                &evaluation.subject,
+                &format!("{} 使用率恢复至{}%", evaluation.subject, evaluation.value),
                 Priority::Low,
-                Severity::Debug,
-                "本地主机监控"
-            ))
+                Severity::Info,
+            )),
+            _ => None,
+        }
+    }
+
+    fn evaluate(&self, partitions: &[Partition], now: Instant) -> CheckResult {
+        let mut status = CheckStatus::Ok;
+        let mut alerts = Vec::new();
+        let mut recoveries = Vec::new();
+        let mut metrics = Vec::new();
+        let mut subjects = Vec::new();
+        for partition in partitions.iter().filter(|partition| self.selected(partition)) {
+            let rule = self.rule(partition);
+            let evaluation = self.state.evaluate(&rule, &partition.mount, partition.use_percent() as f64, now);
+            status = status.max(evaluation.status);
+            if let Some(event) = self.evaluation_event(&evaluation, &rule) {
+                if evaluation.status == CheckStatus::Ok {
+                    recoveries.push(event);
+                } else {
+                    alerts.push(event);
+                }
+            }
+            metrics.push(PerfData::new(&partition.mount, evaluation.value, "%")
+                .with_thresholds(rule.warning, rule.critical)
+                .with_range(0.0, 100.0));
+            subjects.push(partition.mount.clone());
+        }
+        // 告警中的分区卸载或被排除后视为恢复
+        for mount in self.state.retain(&subjects) {
+            recoveries.push(self.event(&mount, &format!("{} 已不再监控", mount), Priority::Low, Severity::Info));
         }
+
+        let message = match (alerts.is_empty(), recoveries.is_empty()) {
+            (false, _) => &alerts,
+            (true, false) => &recoveries,
+            (true, true) => return CheckResult::new(status, "OK").with_metrics(metrics),
+        }.iter().map(|event| event.description()).collect::<Vec<&str>>().join("; ");
+        alerts.append(&mut recoveries);
+        CheckResult::new(status, &message).with_metrics(metrics).with_events(alerts)
+    }
+}
+
+impl Check for DiskUsageCheck {
+    fn name(&self) -> &str {
+        "disk_usage"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(self.config.interval_secs)
+    }
+
+    fn timeout(&self) -> Duration {
+        Duration::from_secs(self.config.timeout_secs)
+    }
+
+    fn run(&self) -> CheckResult {
+        let disk_info = DiskInfo::new();
+        self.evaluate(disk_info.partitions(), Instant::now())
+    }
+}
+
+#[cfg(test)]
+mod unit_test_disk_usage {
+    use super::*;
+
+    fn partition(mount: &str, used: u64, free: u64) -> Partition {
+        Partition {
+            mount: mount.to_string(),
+            device: format!("/dev/{}", mount.trim_start_matches('/').replace('/', "_")),
+            fs_type: "ext4".to_string(),
+            total: used + free,
+            used,
+            free,
+            inodes: None,
+        }
+    }
+
+    fn disk_check(config: &str) -> DiskUsageCheck {
+        DiskUsageCheck::new(serde_json::from_str(config).unwrap())
+    }
+
+    #[test]
+    fn test_disk_usage_01() {
+        let check = disk_check(r#"{"threshold": 90, "is_active": true}"#);
+        let partitions = vec![partition("/", 91, 9), partition("/boot", 10, 90), partition("/data", 95, 5)];
+        let result = check.evaluate(&partitions, Instant::now());
+        assert_eq!(result.status, CheckStatus::Warning);
+        assert_eq!(result.message, "/ 使用率91%，达到告警阈值90%; /data 使用率95%，达到告警阈值90%");
+        assert_eq!(result.events.len(), 2);
+        assert_eq!(result.events[1].subject(), "/data");
+        assert_eq!(result.metrics[1].to_string(), "/boot=10%;90;;0;100");
+
+        let check = disk_check(r#"{"warning": 80, "critical": 90, "is_active": false,
+            "include": ["/*"], "exclude": ["/boot"], "overrides": [{"pattern": "/dev/data", "critical": 98}]}"#);
+        let result = check.evaluate(&partitions, Instant::now());
+        assert_eq!(result.status, CheckStatus::Critical);
+        assert_eq!(result.metrics.len(), 2);
+        assert_eq!(result.metrics[1].to_string(), "/data=95%;80;98;0;100");
+        assert_eq!(result.events[0].severity(), &Severity::Debug);
+        assert!(result.events[1].description().contains("告警阈值80%"));
+        let result = check.evaluate(&[partition("/", 50, 50)], Instant::now());
+        assert_eq!(result.events.len(), 2);
+        assert!(result.events.iter().all(|event| event.severity() == &Severity::Info));
+    }
+
+    #[test]
+    fn test_disk_usage_02() {
+        let check = disk_check(r#"{"warning": 80, "critical": 90, "for_secs": 600, "hysteresis": 5, "is_active": true}"#);
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+
+        let result = check.evaluate(&[partition("/", 85, 15)], at(0));
+        assert_eq!(result.status, CheckStatus::Ok);
+        assert!(result.events.is_empty());
+        let result = check.evaluate(&[partition("/", 85, 15)], at(600));
+        assert_eq!(result.status, CheckStatus::Warning);
+        assert_eq!(result.events[0].severity(), &Severity::Warning);
+        assert_eq!(check.evaluate(&[partition("/", 77, 23)], at(660)).status, CheckStatus::Warning);
+
+        let result = check.evaluate(&[partition("/", 70, 30)], at(720));
+        assert_eq!(result.status, CheckStatus::Ok);
+        assert_eq!(result.message, "/ 使用率恢复至70%");
+        assert_eq!(result.events[0].severity(), &Severity::Info);
+        assert!(check.evaluate(&[partition("/", 70, 30)], at(780)).events.is_empty());
+
+        check.evaluate(&[partition("/", 85, 15)], at(800));
+        check.evaluate(&[partition("/", 85, 15)], at(1400));
+        let result = check.evaluate(&[], at(1460));
+        assert_eq!(result.message, "/ 已不再监控");
+        assert!(check.run().metrics.iter().all(|metric| metric.max == Some(100.0)));
     }
 }
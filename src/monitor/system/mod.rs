@@ -1,2 +1,4 @@
 pub mod disk_usage;
 pub mod open_port;
+pub mod resource_usage;
+pub mod systemd_unit;
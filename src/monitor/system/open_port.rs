@@ -1,20 +1,33 @@
-use crate::info::service;
+use std::time::Duration;
+
+use crate::common::config::OpenPortMonitorConfig;
+use crate::info::socket::{Socket, SocketInventory};
+use crate::monitor::check::{Check, CheckResult, PerfData};
 use crate::monitor::event::*;
 
-pub fn check_open_port(high_risk_ports: &Vec<u16>, is_active: &bool) -> Option<Event> {
-    let mut alert_contents = String::new();
+// 非回环地址上监听高危端口的套接字
+fn exposed_sockets<'a>(listening: &'a [Socket], high_risk_ports: &[u16]) -> Vec<&'a Socket> {
+    listening.iter()
+        .filter(|s| !s.loopback && high_risk_ports.contains(&s.local_port))
+        .collect()
+}
 
-    let services = service::get_services();
-    for s in services {
-        if high_risk_ports.contains(&s.port()) {
-            alert_contents.push_str(&format!("Port{} OPEN", s.port()));
-        }
-    }
+// 暴露的高危端口，描述如 "sshd on 0.0.0.0:22 OPEN"
+pub fn exposed_ports(listening: &[Socket], high_risk_ports: &[u16]) -> String {
+    exposed_sockets(listening, high_risk_ports).iter()
+        .map(|s| format!("{} OPEN", s.describe()))
+        .collect::<Vec<String>>()
+        .join("; ")
+}
+
+// 暴露的高危端口产生事件
+fn exposed_event(listening: &[Socket], high_risk_ports: &[u16], is_active: bool) -> Option<Event> {
+    let alert_contents = exposed_ports(listening, high_risk_ports);
 
     if alert_contents.is_empty() {
         None
     } else {
-        if *is_active {
+        if is_active {
             Some(Event::new("本地开放端口监控",
                 &alert_contents,
                 Priority::High,
@@ -32,3 +45,58 @@ pub fn check_open_port(high_risk_ports: &Vec<u16>, is_active: &bool) -> Option<E
     }
 
 }
+
+// 开放端口监控项，性能数据为监听端口数和暴露的高危端口数
+#[derive(Debug)]
+pub struct OpenPortCheck {
+    config: OpenPortMonitorConfig,
+}
+
+impl OpenPortCheck {
+    pub fn new(config: OpenPortMonitorConfig) -> Self {
+        OpenPortCheck { config }
+    }
+}
+
+impl Check for OpenPortCheck {
+    fn name(&self) -> &str {
+        "open_port"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(self.config.interval_secs)
+    }
+
+    fn timeout(&self) -> Duration {
+        Duration::from_secs(self.config.timeout_secs)
+    }
+
+    fn run(&self) -> CheckResult {
+        let inventory = SocketInventory::collect();
+        let exposed = exposed_sockets(&inventory.listening, &self.config.high_risk_ports).len();
+        let metrics = vec![
+            PerfData::new("listening", inventory.listening.len() as f64, ""),
+            PerfData::new("exposed", exposed as f64, "").with_thresholds(Some(0.0), None),
+        ];
+        CheckResult::from_event(exposed_event(&inventory.listening, &self.config.high_risk_ports, self.config.is_active))
+            .with_metrics(metrics)
+    }
+}
+
+#[cfg(test)]
+mod unit_test_open_port {
+    use super::*;
+    use crate::info::service::Service;
+
+    #[test]
+    fn test_open_port_01() {
+        let services = vec![
+            Service::new("0.0.0.0", &22, "tcp", "LISTEN", &0, "0.0.0.0:*"),
+            Service::new("127.0.0.1", &3306, "tcp", "LISTEN", &0, "0.0.0.0:*"),
+            Service::new("0.0.0.0", &8080, "tcp", "LISTEN", &0, "0.0.0.0:*"),
+        ];
+        let inventory = SocketInventory::from_parts(&services, &[]);
+        assert_eq!(exposed_ports(&inventory.listening, &[22, 3306]), "ssh on 0.0.0.0:22 OPEN");
+        assert_eq!(exposed_ports(&inventory.listening, &[23]), "");
+    }
+}
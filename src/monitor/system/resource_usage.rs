@@ -0,0 +1,99 @@
+use std::time::Duration;
+
+use crate::common::config::ResourceUsageMonitorConfig;
+use crate::info::sampler::{measure, Sample};
+use crate::monitor::check::{Check, CheckResult, CheckStatus, PerfData};
+use crate::monitor::event::*;
+
+// 超过阈值的资源，形如 "CPU-95.0% 内存-91.2%"
+fn over_threshold(sample: &Sample, cpu_max: u8, memory_max: u8) -> String {
+    let mut alerts = Vec::new();
+    if sample.cpu_percent >= cpu_max as f64 {
+        alerts.push(format!("CPU-{:.1}%", sample.cpu_percent));
+    }
+    if sample.memory.used_percent >= memory_max as f64 {
+        alerts.push(format!("内存-{:.1}%", sample.memory.used_percent));
+    }
+    alerts.join(" ")
+}
+
+// 超过阈值的资源产生事件
+fn usage_event(sample: &Sample, cpu_max: u8, memory_max: u8, is_active: bool) -> Option<Event> {
+    let alert_contents = over_threshold(sample, cpu_max, memory_max);
+
+    if alert_contents.is_empty() {
+        None
+    } else {
+        Some(Event::new("资源使用率监控",
+            &format!("使用率达到告警阈值: {}", alert_contents),
+            Priority::Low,
+            if is_active { Severity::Warning } else { Severity::Debug },
+            "本地主机监控"
+        ))
+    }
+}
+
+// 资源使用率监控项，性能数据为采样区间内的 CPU 和内存使用率
+#[derive(Debug)]
+pub struct ResourceUsageCheck {
+    config: ResourceUsageMonitorConfig,
+}
+
+impl ResourceUsageCheck {
+    pub fn new(config: ResourceUsageMonitorConfig) -> Self {
+        ResourceUsageCheck { config }
+    }
+}
+
+impl Check for ResourceUsageCheck {
+    fn name(&self) -> &str {
+        "resource_usage"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(self.config.interval_secs)
+    }
+
+    fn timeout(&self) -> Duration {
+        Duration::from_secs(self.config.timeout_secs)
+    }
+
+    fn run(&self) -> CheckResult {
+        let sample = match measure(Duration::from_secs(self.config.window_secs)) {
+            Ok(sample) => sample,
+            Err(e) => return CheckResult::new(CheckStatus::Unknown, &format!("failed to sample resource usage: {}", e)),
+        };
+        // 使用率保留一位小数，与告警说明一致
+        let round = |percent: f64| (percent * 10.0).round() / 10.0;
+        let metrics = vec![
+            PerfData::new("cpu", round(sample.cpu_percent), "%").with_thresholds(Some(self.config.cpu_threshold as f64), None),
+            PerfData::new("memory", round(sample.memory.used_percent), "%").with_thresholds(Some(self.config.memory_threshold as f64), None),
+        ];
+        CheckResult::from_event(usage_event(&sample, self.config.cpu_threshold, self.config.memory_threshold, self.config.is_active))
+            .with_metrics(metrics)
+    }
+}
+
+#[cfg(test)]
+mod unit_test_resource_usage {
+    use super::*;
+    use chrono::Utc;
+    use crate::info::sampler::{LoadAverage, MemoryPressure};
+
+    #[test]
+    fn test_resource_usage_01() {
+        let sample = Sample {
+            timestamp: Utc::now(),
+            interval_secs: 1.0,
+            cpu_percent: 95.0,
+            cpu_core_percent: vec![90.0, 100.0],
+            load: LoadAverage::default(),
+            memory: MemoryPressure { used_percent: 50.0, ..Default::default() },
+            disks: vec![],
+            nics: vec![],
+        };
+        assert_eq!(over_threshold(&sample, 90, 90), "CPU-95.0%");
+        assert_eq!(over_threshold(&sample, 96, 50), "内存-50.0%");
+        assert_eq!(over_threshold(&sample, 100, 100), "");
+    }
+}
@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::common::config::SystemdUnitMonitorConfig;
+use crate::info::error::InfoResult;
+use crate::info::systemd::{Systemctl, UnitInfo};
+use crate::monitor::check::{Check, CheckResult, CheckStatus, PerfData};
+use crate::monitor::event::*;
+
+// 失败或频繁重启的单元，形如 "nginx.service-failed redis.service-flapping(3次重启)"
+//
+// last_restarts 记录上次检查时各单元的重启次数，两次检查之间重启次数增加达到 flap_restarts 视为频繁重启
+fn unit_alerts(units: &[UnitInfo], last_restarts: &mut HashMap<String, u32>, flap_restarts: u32) -> String {
+    let mut alerts = Vec::new();
+    for unit in units {
+        if unit.is_failed() {
+            alerts.push(format!("{}-failed", unit.name));
+        } else if unit.load_state == "not-found" {
+            alerts.push(format!("{}-not-found", unit.name));
+        }
+        if let Some(restarts) = unit.restarts {
+            if let Some(last) = last_restarts.insert(unit.name.clone(), restarts) {
+                let increase = restarts.saturating_sub(last);
+                if flap_restarts > 0 && increase >= flap_restarts {
+                    alerts.push(format!("{}-flapping({}次重启)", unit.name, increase));
+                }
+            }
+        }
+    }
+    alerts.join(" ")
+}
+
+// 查询指定的单元，units 为空时查询所有服务单元
+fn query_units(units: &[String]) -> InfoResult<Vec<UnitInfo>> {
+    let systemctl = Systemctl::new();
+    if units.is_empty() {
+        systemctl.list_units(None)
+    } else {
+        units.iter().map(|name| systemctl.unit(name)).collect()
+    }
+}
+
+// 失败或频繁重启的单元产生事件
+fn unit_event(units: &[UnitInfo], flap_restarts: u32, last_restarts: &mut HashMap<String, u32>, is_active: bool) -> Option<Event> {
+    let alert_contents = unit_alerts(units, last_restarts, flap_restarts);
+
+    if alert_contents.is_empty() {
+        None
+    } else {
+        Some(Event::new("服务单元监控",
+            &format!("服务单元异常: {}", alert_contents),
+            Priority::High,
+            if is_active { Severity::Warning } else { Severity::Debug },
+            "本地主机监控"
+        ))
+    }
+}
+
+// 服务单元监控项，记录上次检查时各单元的重启次数，性能数据为单元数和失败单元数
+#[derive(Debug)]
+pub struct SystemdUnitCheck {
+    config: SystemdUnitMonitorConfig,
+    last_restarts: Mutex<HashMap<String, u32>>,
+}
+
+impl SystemdUnitCheck {
+    pub fn new(config: SystemdUnitMonitorConfig) -> Self {
+        SystemdUnitCheck {
+            config,
+            last_restarts: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl Check for SystemdUnitCheck {
+    fn name(&self) -> &str {
+        "systemd_units"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(self.config.interval_secs)
+    }
+
+    fn timeout(&self) -> Duration {
+        Duration::from_secs(self.config.timeout_secs)
+    }
+
+    fn run(&self) -> CheckResult {
+        let units = match query_units(&self.config.units) {
+            Ok(units) => units,
+            Err(e) => return CheckResult::new(CheckStatus::Unknown, &format!("failed to query systemd units: {}", e)),
+        };
+        let metrics = vec![
+            PerfData::new("units", units.len() as f64, ""),
+            PerfData::new("failed", units.iter().filter(|unit| unit.is_failed()).count() as f64, "").with_thresholds(Some(0.0), None),
+        ];
+        let mut last_restarts = self.last_restarts.lock().unwrap_or_else(|e| e.into_inner());
+        CheckResult::from_event(unit_event(&units, self.config.flap_restarts, &mut last_restarts, self.config.is_active))
+            .with_metrics(metrics)
+    }
+}
+
+#[cfg(test)]
+mod unit_test_systemd_unit {
+    use super::*;
+
+    fn unit(name: &str, active_state: &str, restarts: u32) -> UnitInfo {
+        UnitInfo {
+            name: name.to_string(),
+            load_state: "loaded".to_string(),
+            active_state: active_state.to_string(),
+            restarts: Some(restarts),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_systemd_unit_01() {
+        let mut last_restarts = HashMap::new();
+        let units = vec![unit("sshd.service", "active", 0), unit("nginx.service", "failed", 5), unit("redis.service", "activating", 1)];
+        assert_eq!(unit_alerts(&units, &mut last_restarts, 3), "nginx.service-failed");
+
+        let units = vec![unit("sshd.service", "active", 0), unit("nginx.service", "active", 5), unit("redis.service", "active", 4)];
+        assert_eq!(unit_alerts(&units, &mut last_restarts, 3), "redis.service-flapping(3次重启)");
+        assert_eq!(unit_alerts(&units, &mut last_restarts, 3), "");
+    }
+}
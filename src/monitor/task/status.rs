@@ -1,6 +1,9 @@
+use std::time::Duration;
+
+use crate::monitor::check::{Check, CheckResult};
 use crate::monitor::event::*;
 use crate::common::ds::TaskStatus;
-use crate::common::config::TaskListMonitorConfig;
+use crate::common::config::{TaskListMonitorConfig, TaskStatusMonitorConfig};
 
 pub fn check_task_status(default_statuses: &Vec<TaskStatus>, 
                          task_status_list: &Vec<TaskListMonitorConfig>,
@@ -26,4 +29,34 @@ pub fn check_task_status(default_statuses: &Vec<TaskStatus>,
             ))
         }
     }
-}
\ No newline at end of file
+}
+
+// 任务状态监控项
+#[derive(Debug)]
+pub struct TaskStatusCheck {
+    config: TaskStatusMonitorConfig,
+}
+
+impl TaskStatusCheck {
+    pub fn new(config: TaskStatusMonitorConfig) -> Self {
+        TaskStatusCheck { config }
+    }
+}
+
+impl Check for TaskStatusCheck {
+    fn name(&self) -> &str {
+        "task_status"
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(self.config.interval_secs)
+    }
+
+    fn timeout(&self) -> Duration {
+        Duration::from_secs(self.config.timeout_secs)
+    }
+
+    fn run(&self) -> CheckResult {
+        CheckResult::from_event(check_task_status(&self.config.default_statuses, &self.config.task_list, &self.config.is_active))
+    }
+}
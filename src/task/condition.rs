@@ -0,0 +1,290 @@
+use std::collections::HashMap;
+use std::error::Error;
+
+use regex::Regex;
+
+// 条件表达式求值时使用的变量表（主机事实、前序作业输出、循环变量等）
+pub type Vars = HashMap<String, String>;
+
+// 比较运算符
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Operator {
+    Eq,        // ==
+    Ne,        // !=
+    Gt,        // >
+    Ge,        // >=
+    Lt,        // <
+    Le,        // <=
+    Contains,  // contains
+    Matches,   // =~ 正则匹配
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Word(String),                      // 变量名或字面量
+    Str(String),                       // 引号字符串，总是字面量
+    Op(Operator),
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+}
+
+/// 对条件表达式求值
+///
+/// 语法示例:
+/// - `os_type == Linux`
+/// - `prev.status != 0 and prev.stdout contains "error"`
+/// - `(item =~ "^eth" or item == lo) && !prev.failed`
+///
+/// 数字之间按数值比较，其余按字符串比较，`==`、`!=` 不区分 ASCII 大小写；未定义的变量参与比较时结果为假。
+/// 单独出现的变量按真值判断：未定义、空、`0`、`false` 为假，其余为真。
+pub fn evaluate(expr: &str, vars: &Vars) -> Result<bool, Box<dyn Error>> {
+    let tokens = tokenize(expr)?;
+    if tokens.is_empty() {
+        return Err(format!("empty condition: '{}'", expr).into());
+    }
+    let mut parser = Parser { tokens: &tokens, pos: 0, vars };
+    let value = parser.parse_or()?;
+    if parser.pos != tokens.len() {
+        return Err(format!("unexpected token {:?} in condition '{}'", tokens[parser.pos], expr).into());
+    }
+    Ok(value)
+}
+
+/// 将模板中的 `{{ name }}` 替换为变量值，未定义的变量保持原样
+pub fn render(template: &str, vars: &Vars) -> String {
+    let re = Regex::new(r"\{\{\s*([\w.\-]+)\s*\}\}").expect("Failed to compile regex");
+    re.replace_all(template, |caps: &regex::Captures| {
+        match vars.get(&caps[1]) {
+            Some(value) => value.clone(),
+            None => caps[0].to_string(),
+        }
+    }).to_string()
+}
+
+fn tokenize(expr: &str) -> Result<Vec<Token>, Box<dyn Error>> {
+    let mut tokens = Vec::new();
+    let chars: Vec<char> = expr.chars().collect();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            ' ' | '\t' | '\n' => i += 1,
+            '(' => { tokens.push(Token::LParen); i += 1; }
+            ')' => { tokens.push(Token::RParen); i += 1; }
+            '"' | '\'' => {
+                let quote = c;
+                let mut word = String::new();
+                i += 1;
+                while i < chars.len() && chars[i] != quote {
+                    word.push(chars[i]);
+                    i += 1;
+                }
+                if i >= chars.len() {
+                    return Err(format!("unterminated string in condition '{}'", expr).into());
+                }
+                i += 1;
+                tokens.push(Token::Str(word));
+            }
+            '=' | '!' | '>' | '<' | '&' | '|' => {
+                let next = chars.get(i + 1).copied();
+                let (token, len) = match (c, next) {
+                    ('=', Some('=')) => (Token::Op(Operator::Eq), 2),
+                    ('=', Some('~')) => (Token::Op(Operator::Matches), 2),
+                    ('!', Some('=')) => (Token::Op(Operator::Ne), 2),
+                    ('>', Some('=')) => (Token::Op(Operator::Ge), 2),
+                    ('<', Some('=')) => (Token::Op(Operator::Le), 2),
+                    ('&', Some('&')) => (Token::And, 2),
+                    ('|', Some('|')) => (Token::Or, 2),
+                    ('>', _) => (Token::Op(Operator::Gt), 1),
+                    ('<', _) => (Token::Op(Operator::Lt), 1),
+                    ('!', _) => (Token::Not, 1),
+                    _ => return Err(format!("invalid operator '{}' in condition '{}'", c, expr).into()),
+                };
+                tokens.push(token);
+                i += len;
+            }
+            _ => {
+                let mut word = String::new();
+                while i < chars.len() && !" \t\n()\"'=!<>&|".contains(chars[i]) {
+                    word.push(chars[i]);
+                    i += 1;
+                }
+                tokens.push(match word.as_str() {
+                    "and" => Token::And,
+                    "or" => Token::Or,
+                    "not" => Token::Not,
+                    "contains" => Token::Op(Operator::Contains),
+                    _ => Token::Word(word),
+                });
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+    vars: &'a Vars,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn parse_or(&mut self) -> Result<bool, Box<dyn Error>> {
+        let mut value = self.parse_and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            let rhs = self.parse_and()?;
+            value = value || rhs;
+        }
+        Ok(value)
+    }
+
+    fn parse_and(&mut self) -> Result<bool, Box<dyn Error>> {
+        let mut value = self.parse_unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.pos += 1;
+            let rhs = self.parse_unary()?;
+            value = value && rhs;
+        }
+        Ok(value)
+    }
+
+    fn parse_unary(&mut self) -> Result<bool, Box<dyn Error>> {
+        match self.peek() {
+            Some(Token::Not) => {
+                self.pos += 1;
+                Ok(!self.parse_unary()?)
+            }
+            Some(Token::LParen) => {
+                self.pos += 1;
+                let value = self.parse_or()?;
+                match self.next() {
+                    Some(Token::RParen) => Ok(value),
+                    _ => Err("missing ')' in condition".into()),
+                }
+            }
+            _ => self.parse_comparison(),
+        }
+    }
+
+    fn parse_comparison(&mut self) -> Result<bool, Box<dyn Error>> {
+        let lhs = match self.next() {
+            Some(Token::Word(word)) => word,
+            other => return Err(format!("expected variable, found {:?}", other).into()),
+        };
+        let op = match self.peek() {
+            Some(Token::Op(op)) => op.clone(),
+            _ => return Ok(self.vars.get(&lhs).is_some_and(|value| is_truthy(value))),
+        };
+        self.pos += 1;
+        // 右值若是已定义变量则取变量值，否则按字面量处理
+        let rhs = match self.next() {
+            Some(Token::Word(word)) => self.vars.get(&word).cloned().unwrap_or(word),
+            Some(Token::Str(value)) => value,
+            other => return Err(format!("expected value after {:?}, found {:?}", op, other).into()),
+        };
+        match self.vars.get(&lhs) {
+            Some(value) => compare(value, &op, &rhs),
+            None => Ok(false),
+        }
+    }
+}
+
+fn is_truthy(value: &str) -> bool {
+    !(value.is_empty() || value == "0" || value.eq_ignore_ascii_case("false"))
+}
+
+fn compare(lhs: &str, op: &Operator, rhs: &str) -> Result<bool, Box<dyn Error>> {
+    if let (Ok(l), Ok(r)) = (lhs.trim().parse::<f64>(), rhs.trim().parse::<f64>()) {
+        let result = match op {
+            Operator::Eq => l == r,
+            Operator::Ne => l != r,
+            Operator::Gt => l > r,
+            Operator::Ge => l >= r,
+            Operator::Lt => l < r,
+            Operator::Le => l <= r,
+            Operator::Contains => lhs.contains(rhs),
+            Operator::Matches => Regex::new(rhs)?.is_match(lhs),
+        };
+        return Ok(result);
+    }
+
+    let result = match op {
+        // 如 os_type 为 Linux，写作 linux 也能匹配
+        Operator::Eq => lhs.eq_ignore_ascii_case(rhs),
+        Operator::Ne => !lhs.eq_ignore_ascii_case(rhs),
+        Operator::Gt => lhs > rhs,
+        Operator::Ge => lhs >= rhs,
+        Operator::Lt => lhs < rhs,
+        Operator::Le => lhs <= rhs,
+        Operator::Contains => lhs.contains(rhs),
+        Operator::Matches => Regex::new(rhs)?.is_match(lhs),
+    };
+    Ok(result)
+}
+
+#[cfg(test)]
+mod unit_test_condition {
+    use super::*;
+
+    fn vars() -> Vars {
+        HashMap::from([
+            ("os_type".to_string(), "Linux".to_string()),
+            ("prev.status".to_string(), "2".to_string()),
+            ("prev.stdout".to_string(), "disk error on sda".to_string()),
+            ("item".to_string(), "eth0".to_string()),
+        ])
+    }
+
+    #[test]
+    fn test_condition_01() {
+        let vars = vars();
+        assert!(evaluate("os_type == Linux", &vars).unwrap());
+        assert!(!evaluate("os_type == 'Darwin'", &vars).unwrap());
+        assert!(evaluate("os_type == linux", &vars).unwrap());
+        assert!(!evaluate("os_type != LINUX", &vars).unwrap());
+        assert!(evaluate("prev.status != 0", &vars).unwrap());
+        assert!(evaluate("prev.status >= 2 and prev.status < 10", &vars).unwrap());
+    }
+
+    #[test]
+    fn test_condition_02() {
+        let vars = vars();
+        assert!(evaluate("prev.stdout contains \"error\"", &vars).unwrap());
+        assert!(evaluate("item =~ '^eth[0-9]+$' || item == lo", &vars).unwrap());
+        assert!(evaluate("!(os_type == Darwin) && not missing", &vars).unwrap());
+        assert!(!evaluate("missing", &vars).unwrap());
+        assert!(!evaluate("missing != 0", &vars).unwrap());
+    }
+
+    #[test]
+    fn test_condition_03() {
+        let vars = vars();
+        assert!(evaluate("", &vars).is_err());
+        assert!(evaluate("os_type ==", &vars).is_err());
+        assert!(evaluate("(os_type == Linux", &vars).is_err());
+        assert!(evaluate("os_type == 'Linux", &vars).is_err());
+    }
+
+    #[test]
+    fn test_condition_04() {
+        let vars = vars();
+        assert_eq!(render("ethtool {{ item }}", &vars), "ethtool eth0");
+        assert_eq!(render("{{os_type}}-{{ unknown }}", &vars), "Linux-{{ unknown }}");
+    }
+}
@@ -0,0 +1,38 @@
+use crate::info;
+use crate::task::condition::Vars;
+
+/// 采集用于任务条件判断的主机事实
+///
+/// 只采集开销较小的信息（不遍历进程和端口），变量名与 `HostInfo` JSON 字段保持一致。
+pub fn host_facts() -> Vars {
+    let mut facts = Vars::new();
+
+    facts.insert("hostname".to_string(), info::hostname::hostname());
+
+    let os_info = info::os::OSInfo::new();
+    facts.insert("os_type".to_string(), os_info.os_type().to_string());
+    facts.insert("os_name".to_string(), os_info.os_name().to_string());
+    facts.insert("os_version".to_string(), os_info.os_version().to_string());
+    facts.insert("os_arch".to_string(), os_info.os_arch().to_string());
+
+    let cpu_info = info::cpu::CpuInfo::new();
+    facts.insert("cpu_socket_count".to_string(), cpu_info.cpu_socket_count().to_string());
+    facts.insert("cpu_core_count".to_string(), cpu_info.cpu_core_count().to_string());
+    facts.insert("cpu_thread_count".to_string(), cpu_info.cpu_thread_count().to_string());
+
+    facts
+}
+
+#[cfg(test)]
+mod unit_test_facts {
+    use super::*;
+
+    #[test]
+    fn test_facts_01() {
+        let facts = host_facts();
+        for key in ["hostname", "os_type", "os_name", "os_version", "os_arch", "cpu_thread_count"] {
+            assert!(facts.contains_key(key), "failed since fact {} missing", key);
+        }
+        assert!(!facts["os_type"].is_empty(), "failed since os type fact empty");
+    }
+}
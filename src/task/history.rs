@@ -0,0 +1,453 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use chrono::{DateTime, Datelike, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+
+use crate::common::ds::{self, TaskStatus};
+use crate::task::job::Job;
+use crate::task::task::Task;
+
+// 单次命令执行记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AttemptRecord {
+    pub id: u32,                             // 命令序列号
+    pub cmd: String,                         // 命令
+    pub args: Vec<String>,                   // 命令参数
+    #[serde(default)]
+    pub check_str: Option<String>,           // 校验字符串
+    pub status: Option<i32>,                 // 返回码
+    pub stdout: Option<String>,              // 标准输出
+    pub stderr: Option<String>,              // 错误输出
+    pub result: Option<ds::Result>,          // 执行结果
+    pub cost_ms: Option<u64>,                // 执行耗时毫秒
+}
+
+// 作业执行记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JobRecord {
+    pub name: String,                        // 作业名
+    pub status: TaskStatus,                  // 作业状态
+    pub result: Option<ds::Result>,          // 作业结果
+    pub message: String,                     // 跳过或失败原因
+    pub cost_ms: Option<u64>,                // 作业耗时毫秒
+    pub attempts: Vec<AttemptRecord>,        // 命令执行记录
+}
+
+// 任务执行记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskRecord {
+    pub id: String,                          // 任务ID
+    pub name: String,                        // 任务名
+    pub description: String,                 // 任务描述
+    pub status: TaskStatus,                  // 任务状态
+    pub result: Option<ds::Result>,          // 任务结果
+    pub created_at: DateTime<Utc>,           // 创建时刻
+    pub started_at: Option<DateTime<Utc>>,   // 开始时刻
+    pub finished_at: Option<DateTime<Utc>>,  // 结束时刻
+    pub cost_ms: Option<u64>,                // 任务耗时毫秒
+    pub log_file: String,                    // 任务日志文件
+    pub jobs: Vec<JobRecord>,                // 作业记录
+}
+
+fn millis(cost: &Option<Duration>) -> Option<u64> {
+    cost.map(|cost| cost.as_millis() as u64)
+}
+
+impl From<&Job> for JobRecord {
+    fn from(job: &Job) -> Self {
+        JobRecord {
+            name: job.name().to_string(),
+            status: job.status().clone(),
+            result: job.result().clone(),
+            message: job.message().to_string(),
+            cost_ms: millis(job.cost()),
+            attempts: job.attempts().iter().map(|shell| AttemptRecord {
+                id: *shell.id(),
+                cmd: shell.cmd().to_string(),
+                args: shell.args().clone().unwrap_or_default(),
+                check_str: shell.check_str().clone(),
+                status: *shell.status(),
+                stdout: shell.stdout().clone(),
+                stderr: shell.stderr().clone(),
+                result: shell.result().clone(),
+                cost_ms: millis(shell.cost()),
+            }).collect(),
+        }
+    }
+}
+
+impl From<&Task> for TaskRecord {
+    fn from(task: &Task) -> Self {
+        TaskRecord {
+            id: task.id().to_string(),
+            name: task.name().to_string(),
+            description: task.description().to_string(),
+            status: task.status().clone(),
+            result: task.result().clone(),
+            created_at: *task.created_at(),
+            started_at: *task.started_at(),
+            finished_at: *task.finished_at(),
+            cost_ms: millis(task.cost()),
+            log_file: task.log_file().to_string(),
+            jobs: task.jobs().iter().map(JobRecord::from).collect(),
+        }
+    }
+}
+
+fn format_cost(cost_ms: &Option<u64>) -> String {
+    cost_ms.map_or("-".to_string(), |ms| format!("{:.3}s", ms as f64 / 1000.0))
+}
+
+fn format_result(result: &Option<ds::Result>) -> String {
+    result.as_ref().map_or("-".to_string(), |r| format!("{:?}", r))
+}
+
+fn format_time(time: &Option<DateTime<Utc>>) -> String {
+    time.map_or("-".to_string(), |t| t.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S").to_string())
+}
+
+impl TaskRecord {
+    /// 显示任务详情（作业、耗时、结果）
+    pub fn display(&self) -> String {
+        let mut output = format!("Task {} ({})\n  Description: {}\n  Status: {:?}\n  Result: {}\n  Created: {}\n  Started: {}\n  Finished: {}\n  Cost: {}\n  Log: {}\n\nJobs:",
+            self.name.green().bold(),
+            self.id,
+            self.description,
+            self.status,
+            format_result(&self.result).yellow(),
+            format_time(&Some(self.created_at)),
+            format_time(&self.started_at),
+            format_time(&self.finished_at),
+            format_cost(&self.cost_ms).blue(),
+            self.log_file,
+        );
+        for job in &self.jobs {
+            output.push_str(&format!("\n  {:<24} {:<12} {:<8} {:>10} {}",
+                job.name, format!("{:?}", job.status), format_result(&job.result), format_cost(&job.cost_ms), job.message));
+            for attempt in &job.attempts {
+                output.push_str(&format!("\n    #{:<3} {} {} => {} ({})",
+                    attempt.id, attempt.cmd, attempt.args.join(" "),
+                    attempt.status.map_or("-".to_string(), |s| s.to_string()), format_cost(&attempt.cost_ms)));
+            }
+        }
+        println!("{}\n", output);
+        output
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|err| {
+            eprintln!("序列化 Task 记录到 JSON 失败: {}", err);
+            "{}".to_string()
+        })
+    }
+}
+
+/// 以表格形式显示任务列表
+pub fn display_table(records: &[TaskRecord]) -> String {
+    let mut output = format!("{:<36}  {:<24}  {:<12}  {:<8}  {:<19}  {:>10}",
+        "ID", "NAME", "STATUS", "RESULT", "CREATED", "COST");
+    for record in records {
+        output.push_str(&format!("\n{:<36}  {:<24}  {:<12}  {:<8}  {:<19}  {:>10}",
+            record.id, record.name, format!("{:?}", record.status), format_result(&record.result),
+            format_time(&Some(record.created_at)), format_cost(&record.cost_ms)));
+    }
+    println!("{}", output);
+    output
+}
+
+// 任务列表过滤条件，所有条件同时满足才保留
+#[derive(Debug, Clone, Default)]
+pub struct TaskFilter {
+    pub status: Option<TaskStatus>,          // 任务状态
+    pub result: Option<ds::Result>,          // 任务结果
+    pub name: Option<String>,                // 任务名关键字
+    pub since: Option<DateTime<Utc>>,        // 创建时刻下限（含）
+    pub until: Option<DateTime<Utc>>,        // 创建时刻上限（不含）
+}
+
+impl TaskFilter {
+    pub fn matches(&self, record: &TaskRecord) -> bool {
+        self.status.as_ref().is_none_or(|status| &record.status == status)
+            && self.result.as_ref().is_none_or(|result| record.result.as_ref() == Some(result))
+            && self.name.as_ref().is_none_or(|name| record.name.contains(name.as_str()))
+            && self.since.is_none_or(|since| record.created_at >= since)
+            && self.until.is_none_or(|until| record.created_at < until)
+    }
+}
+
+/// 解析命令行时间参数，支持 RFC3339、`YYYY-MM-DD HH:MM:SS` 和 `YYYY-MM-DD`（本地时区）
+pub fn parse_time(s: &str) -> Result<DateTime<Utc>, Box<dyn Error>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(s) {
+        return Ok(time.with_timezone(&Utc));
+    }
+    let naive = match NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S") {
+        Ok(naive) => naive,
+        Err(_) => NaiveDate::parse_from_str(s, "%Y-%m-%d")?
+            .and_hms_opt(0, 0, 0)
+            .ok_or("invalid time")?,
+    };
+    match Local.from_local_datetime(&naive).earliest() {
+        Some(time) => Ok(time.with_timezone(&Utc)),
+        None => Err(format!("invalid local time: {}", s).into()),
+    }
+}
+
+// 统计周期
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Period {
+    All,
+    Day,
+    Week,
+    Month,
+}
+
+impl std::str::FromStr for Period {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "all" => Ok(Period::All),
+            "day" => Ok(Period::Day),
+            "week" => Ok(Period::Week),
+            "month" => Ok(Period::Month),
+            _ => Err(format!("invalid period: {}", s)),
+        }
+    }
+}
+
+impl Period {
+    fn key(&self, time: &DateTime<Utc>) -> String {
+        let time = time.with_timezone(&Local);
+        match self {
+            Period::All => "all".to_string(),
+            Period::Day => time.format("%Y-%m-%d").to_string(),
+            Period::Week => format!("{}-W{:02}", time.iso_week().year(), time.iso_week().week()),
+            Period::Month => time.format("%Y-%m").to_string(),
+        }
+    }
+}
+
+// 按任务名和周期统计的通过率、平均耗时
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskStats {
+    pub name: String,                        // 任务名
+    pub period: String,                      // 统计周期
+    pub total: usize,                        // 有结果的任务数
+    pub passed: usize,                       // 成功数
+    pub failed: usize,                       // 失败数（含错误）
+    pub pass_rate: f64,                      // 通过率 0~1
+    pub fail_rate: f64,                      // 失败率 0~1
+    pub avg_cost_ms: Option<f64>,            // 平均耗时毫秒
+}
+
+/// 统计各任务在各周期内的通过率和平均耗时，未出结果的任务不计入
+pub fn compute_stats(records: &[TaskRecord], period: Period) -> Vec<TaskStats> {
+    let mut groups: BTreeMap<(String, String), Vec<&TaskRecord>> = BTreeMap::new();
+    for record in records.iter().filter(|r| r.result.is_some()) {
+        groups.entry((record.name.clone(), period.key(&record.created_at))).or_default().push(record);
+    }
+
+    groups.into_iter().map(|((name, period), records)| {
+        let total = records.len();
+        let passed = records.iter().filter(|r| r.result == Some(ds::Result::Success)).count();
+        let costs: Vec<u64> = records.iter().filter_map(|r| r.cost_ms).collect();
+        TaskStats {
+            name,
+            period,
+            total,
+            passed,
+            failed: total - passed,
+            pass_rate: passed as f64 / total as f64,
+            fail_rate: (total - passed) as f64 / total as f64,
+            avg_cost_ms: if costs.is_empty() { None } else { Some(costs.iter().sum::<u64>() as f64 / costs.len() as f64) },
+        }
+    }).collect()
+}
+
+/// 以表格形式显示统计结果
+pub fn display_stats(stats: &[TaskStats]) -> String {
+    let mut output = format!("{:<24}  {:<10}  {:>6}  {:>6}  {:>6}  {:>8}  {:>10}",
+        "NAME", "PERIOD", "TOTAL", "PASS", "FAIL", "PASS%", "AVG COST");
+    for s in stats {
+        output.push_str(&format!("\n{:<24}  {:<10}  {:>6}  {:>6}  {:>6}  {:>7.1}%  {:>10}",
+            s.name, s.period, s.total, s.passed, s.failed, s.pass_rate * 100.0,
+            format_cost(&s.avg_cost_ms.map(|ms| ms.round() as u64))));
+    }
+    println!("{}", output);
+    output
+}
+
+/// 读取日志文件最后若干行
+pub fn tail_log(log_file: &str, lines: usize) -> Result<Vec<String>, Box<dyn Error>> {
+    let content = fs::read_to_string(log_file)?;
+    let all: Vec<&str> = content.lines().collect();
+    let start = all.len().saturating_sub(lines);
+    Ok(all[start..].iter().map(|line| line.to_string()).collect())
+}
+
+/// 默认任务历史目录
+pub fn default_history_dir() -> String {
+    #[cfg(target_os = "linux")]
+    let dir = "/var/lib/minirobot/task".to_string();
+
+    #[cfg(not(target_os = "linux"))]
+    let dir = "task_history".to_string();
+
+    dir
+}
+
+// 任务记录存储，文件目录和数据库各有一种实现
+pub trait TaskStore {
+    fn save(&self, record: &TaskRecord) -> Result<(), Box<dyn Error>>;
+
+    /// 按任务ID或唯一的ID前缀读取记录
+    fn load(&self, id: &str) -> Result<TaskRecord, Box<dyn Error>>;
+
+    /// 按创建时刻排序列出满足过滤条件的记录
+    fn list(&self, filter: &TaskFilter) -> Result<Vec<TaskRecord>, Box<dyn Error>>;
+}
+
+// 任务历史，每个任务记录保存为目录下的 <id>.json
+#[derive(Debug)]
+pub struct TaskHistory {
+    dir: PathBuf,
+}
+
+impl TaskHistory {
+    pub fn new(dir: &str) -> Self {
+        Self {
+            dir: PathBuf::from(dir),
+        }
+    }
+
+    pub fn dir(&self) -> &PathBuf {
+        &self.dir
+    }
+
+    fn load_all(&self) -> Result<Vec<TaskRecord>, Box<dyn Error>> {
+        let mut records = Vec::new();
+        if !self.dir.exists() {
+            return Ok(records);
+        }
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                let json_content = fs::read_to_string(&path)?;
+                records.push(serde_json::from_str(&json_content)?);
+            }
+        }
+        Ok(records)
+    }
+}
+
+impl TaskStore for TaskHistory {
+    fn save(&self, record: &TaskRecord) -> Result<(), Box<dyn Error>> {
+        fs::create_dir_all(&self.dir)?;
+        let json_content = serde_json::to_string_pretty(record)?;
+        fs::write(self.dir.join(format!("{}.json", record.id)), json_content)?;
+        Ok(())
+    }
+
+    fn load(&self, id: &str) -> Result<TaskRecord, Box<dyn Error>> {
+        let matched: Vec<TaskRecord> = self.load_all()?.into_iter().filter(|r| r.id.starts_with(id)).collect();
+        match matched.len() {
+            0 => Err(format!("task {} not found in {}", id, self.dir.display()).into()),
+            1 => Ok(matched.into_iter().next().unwrap()),
+            n => Err(format!("task id prefix {} is ambiguous ({} matches)", id, n).into()),
+        }
+    }
+
+    fn list(&self, filter: &TaskFilter) -> Result<Vec<TaskRecord>, Box<dyn Error>> {
+        let mut records: Vec<TaskRecord> = self.load_all()?.into_iter().filter(|r| filter.matches(r)).collect();
+        records.sort_by_key(|r| r.created_at);
+        Ok(records)
+    }
+}
+
+#[cfg(test)]
+mod unit_test_history {
+    use super::*;
+    use chrono::Duration as ChronoDuration;
+
+    fn record(name: &str, result: Option<ds::Result>, cost_ms: u64, days_ago: i64) -> TaskRecord {
+        let created_at = Utc::now() - ChronoDuration::days(days_ago);
+        TaskRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: name.to_string(),
+            description: String::new(),
+            status: if result.is_some() { TaskStatus::Finished } else { TaskStatus::Running },
+            result,
+            created_at,
+            started_at: Some(created_at),
+            finished_at: None,
+            cost_ms: Some(cost_ms),
+            log_file: String::new(),
+            jobs: vec![],
+        }
+    }
+
+    #[test]
+    fn test_history_01() {
+        let dir = std::env::temp_dir().join(format!("minirobot-history-{}", uuid::Uuid::new_v4()));
+        let history = TaskHistory::new(dir.to_str().unwrap());
+        assert!(history.list(&TaskFilter::default()).unwrap().is_empty());
+
+        let old = record("backup", Some(ds::Result::Success), 1000, 3);
+        let new = record("backup", Some(ds::Result::Failed), 3000, 0);
+        let running = record("health-check", None, 10, 0);
+        for r in [&old, &new, &running] {
+            history.save(r).unwrap();
+        }
+
+        assert_eq!(history.load(&new.id).unwrap(), new);
+        assert_eq!(history.load(&old.id[..8]).unwrap().id, old.id);
+        assert!(history.load("not-exists").is_err());
+
+        let all = history.list(&TaskFilter::default()).unwrap();
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[0].id, old.id);
+
+        let filter = TaskFilter { name: Some("back".to_string()), ..Default::default() };
+        assert_eq!(history.list(&filter).unwrap().len(), 2);
+        let filter = TaskFilter { status: Some(TaskStatus::Running), ..Default::default() };
+        assert_eq!(history.list(&filter).unwrap()[0].id, running.id);
+        let filter = TaskFilter { since: Some(Utc::now() - ChronoDuration::days(1)), result: Some(ds::Result::Failed), ..Default::default() };
+        assert_eq!(history.list(&filter).unwrap()[0].id, new.id);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_history_02() {
+        let records = vec![
+            record("backup", Some(ds::Result::Success), 1000, 0),
+            record("backup", Some(ds::Result::Failed), 3000, 0),
+            record("backup", Some(ds::Result::Error), 2000, 40),
+            record("backup", None, 5000, 0),
+        ];
+        let stats = compute_stats(&records, Period::All);
+        assert_eq!(stats.len(), 1);
+        assert_eq!((stats[0].total, stats[0].passed, stats[0].failed), (3, 1, 2));
+        assert_eq!(stats[0].avg_cost_ms, Some(2000.0));
+
+        let stats = compute_stats(&records, Period::Month);
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats.iter().map(|s| s.total).sum::<usize>(), 3);
+        assert!(display_stats(&stats).contains("backup"));
+    }
+
+    #[test]
+    fn test_history_03() {
+        assert!(parse_time("2024-05-01T08:00:00Z").is_ok());
+        assert!(parse_time("2024-05-01 08:00:00").is_ok());
+        assert_eq!(parse_time("2024-05-01").unwrap().with_timezone(&Local).format("%H:%M").to_string(), "00:00");
+        assert!(parse_time("yesterday").is_err());
+        assert_eq!("week".parse::<Period>(), Ok(Period::Week));
+        assert_eq!("finished".parse::<TaskStatus>(), Ok(TaskStatus::Finished));
+    }
+}
@@ -1,12 +1,353 @@
-pub struct Job {
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::actors::shell::Shell;
+use crate::common::ds::{Result, TaskStatus};
+use crate::info;
+use crate::info::systemd::{unit_name, UnitAction};
+use crate::task::condition::{self, Vars};
+
+// 循环数据源
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ForEach {
+    Items(Vec<String>),                // 指定列表
+    Nics,                              // 所有活动网卡名
+    Partitions,                        // 所有分区挂载点
+}
+
+impl ForEach {
+    /// 展开为循环变量列表
+    pub fn items(&self) -> Vec<String> {
+        match self {
+            ForEach::Items(items) => items.clone(),
+            ForEach::Nics => {
+                let (_, active_nics) = info::network::get_nics();
+                active_nics.iter().map(|nic| nic.name().to_string()).collect()
+            }
+            ForEach::Partitions => {
+                let disk_info = info::disk::DiskInfo::new();
+                disk_info.partitions().iter().map(|p| p.mount.clone()).collect()
+            }
+        }
+    }
+}
+
+// 轮询：重复执行直到条件满足或超过截止时间
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Until {
+    pub condition: String,             // 结束条件
+    #[serde(default = "default_interval_sec")]
+    pub interval_sec: u64,             // 轮询间隔秒
+    pub deadline_sec: u64,             // 截止秒（从本轮开始计时）
+}
+
+// 服务单元操作，以 systemctl 执行
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnitJob {
+    pub name: String,                  // 单元名，支持 {{ var }} 模板，无后缀时视为 .service
+    pub action: UnitAction,            // start, stop, restart, reload, enable, disable
+}
+
+fn default_interval_sec() -> u64 {
+    5
+}
 
+fn default_timeout_sec() -> u64 {
+    60
+}
+
+// 作业定义（任务文件中的一项）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobDefinition {
+    pub name: String,                  // 作业名
+    #[serde(default)]
+    pub cmd: String,                   // 待执行命令，支持 {{ var }} 模板，指定 unit 时可省略
+    #[serde(default)]
+    pub args: Vec<String>,             // 命令参数，支持 {{ var }} 模板
+    #[serde(default = "default_timeout_sec")]
+    pub timeout_sec: u64,              // 单次执行超时秒
+    #[serde(default)]
+    pub check_str: Option<String>,     // 校验字符串
+    #[serde(default)]
+    pub when: Option<String>,          // 执行条件，不满足则跳过
+    #[serde(default)]
+    pub for_each: Option<ForEach>,     // 循环，循环变量为 item / item_index
+    #[serde(default)]
+    pub until: Option<Until>,          // 轮询
+    #[serde(default)]
+    pub unit: Option<UnitJob>,         // 服务单元操作，指定时忽略 cmd 和 args
+}
+
+// 作业
+#[derive(Debug, Clone)]
+pub struct Job {
+    definition: JobDefinition,         // 作业定义
+    status: TaskStatus,                // 作业状态
+    attempts: Vec<Shell>,              // 每次执行的命令（循环、轮询会产生多次执行）
+    message: String,                   // 跳过或失败原因
+    result: Option<Result>,            // 作业结果状态
+    start: Option<Instant>,            // 作业开始时间
+    end: Option<Instant>,              // 作业结束时间
+    cost: Option<Duration>,            // 作业执行时间
 }
 
 impl Job {
-    pub fn new() -> Self {
+    pub fn new(definition: JobDefinition) -> Self {
         Self {
+            definition,
+            status: TaskStatus::Created,
+            attempts: Vec::new(),
+            message: String::new(),
+            result: None,
+            start: None,
+            end: None,
+            cost: None,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.definition.name
+    }
+
+    pub fn definition(&self) -> &JobDefinition {
+        &self.definition
+    }
+
+    pub fn status(&self) -> &TaskStatus {
+        &self.status
+    }
+
+    pub fn attempts(&self) -> &Vec<Shell> {
+        &self.attempts
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn result(&self) -> &Option<Result> {
+        &self.result
+    }
+
+    pub fn start(&self) -> &Option<Instant> {
+        &self.start
+    }
+
+    pub fn end(&self) -> &Option<Instant> {
+        &self.end
+    }
+
+    pub fn cost(&self) -> &Option<Duration> {
+        &self.cost
+    }
+
+    /// 最后一次执行的命令
+    pub fn last_attempt(&self) -> Option<&Shell> {
+        self.attempts.last()
+    }
+
+    /// 作业输出变量: status / stdout / stderr / result / state
+    pub fn outputs(&self) -> Vars {
+        let mut outputs = Vars::new();
+        outputs.insert("state".to_string(), format!("{:?}", self.status));
+        outputs.insert("result".to_string(), self.result.as_ref().map_or(String::new(), |r| format!("{:?}", r)));
+        if let Some(shell) = self.last_attempt() {
+            outputs.insert("status".to_string(), shell.status().map_or(String::new(), |s| s.to_string()));
+            outputs.insert("stdout".to_string(), shell.stdout().clone().unwrap_or_default());
+            outputs.insert("stderr".to_string(), shell.stderr().clone().unwrap_or_default());
+        }
+        outputs
+    }
+
+    /// 执行作业
+    ///
+    /// 先判断 `when`，再按 `for_each` 展开，每一轮按 `until` 轮询或只执行一次。
+    /// 任一轮失败则作业失败，但不会中断其余轮次。
+    pub fn execute(&mut self, vars: &Vars) {
+        self.start = Some(Instant::now());
+        self.status = TaskStatus::Running;
+        self.attempts.clear();
+        self.message.clear();
+
+        self.result = match &self.definition.when {
+            Some(when) => match condition::evaluate(when, vars) {
+                Ok(true) => Some(self.run_loop(vars)),
+                Ok(false) => {
+                    self.status = TaskStatus::Skipped;
+                    self.message = format!("condition not met: {}", when);
+                    None
+                }
+                Err(e) => {
+                    self.status = TaskStatus::Unavailable;
+                    self.message = format!("invalid condition '{}': {}", when, e);
+                    Some(Result::Error)
+                }
+            },
+            None => Some(self.run_loop(vars)),
+        };
+
+        if self.status == TaskStatus::Running {
+            self.status = TaskStatus::Finished;
+        }
+        self.end = Some(Instant::now());
+        self.cost = Some(self.end.unwrap() - self.start.unwrap());
+    }
+
+    fn run_loop(&mut self, vars: &Vars) -> Result {
+        let items = match &self.definition.for_each {
+            Some(for_each) => for_each.items().into_iter().map(Some).collect(),
+            None => vec![None],
+        };
+
+        let mut result = Result::Success;
+        for (index, item) in items.into_iter().enumerate() {
+            let mut local_vars = vars.clone();
+            if let Some(item) = item {
+                local_vars.insert("item".to_string(), item);
+                local_vars.insert("item_index".to_string(), index.to_string());
+            }
+
+            let round = match self.definition.until.clone() {
+                Some(until) => self.run_until(&until, &mut local_vars),
+                None => self.run_once(&local_vars),
+            };
+            result = match (&result, round) {
+                (Result::Error, _) | (_, Result::Error) => Result::Error,
+                (Result::Failed, _) | (_, Result::Failed) => Result::Failed,
+                _ => Result::Success,
+            };
+        }
+        result
+    }
+
+    fn run_once(&mut self, vars: &Vars) -> Result {
+        let (cmd, args) = match &self.definition.unit {
+            Some(unit) => ("systemctl".to_string(), vec![unit.action.command().to_string(), unit_name(&condition::render(&unit.name, vars))]),
+            None => (condition::render(&self.definition.cmd, vars),
+                     self.definition.args.iter().map(|arg| condition::render(arg, vars)).collect()),
+        };
+        if cmd.trim().is_empty() {
+            self.status = TaskStatus::Unavailable;
+            self.message = "job has neither cmd nor unit".to_string();
+            return Result::Error;
+        }
+        let args: Vec<&str> = args.iter().map(|arg| arg.as_str()).collect();
+        let id = self.attempts.len() as u32;
+
+        let mut shell = Shell::new(&id, &cmd, Some(&args), &self.definition.timeout_sec, self.definition.check_str.as_deref());
+        shell.execute();
+        let result = shell.result().clone().unwrap_or(Result::Error);
+        self.attempts.push(shell);
+        result
+    }
 
+    fn run_until(&mut self, until: &Until, vars: &mut Vars) -> Result {
+        let deadline = Instant::now() + Duration::from_secs(until.deadline_sec);
+        loop {
+            // 命令无法执行时继续轮询没有意义，直接返回
+            if self.run_once(vars) == Result::Error {
+                return Result::Error;
+            }
+            for (key, value) in self.outputs() {
+                vars.insert(key, value);
+            }
+            match condition::evaluate(&until.condition, vars) {
+                Ok(true) => return Result::Success,
+                Ok(false) => {}
+                Err(e) => {
+                    self.status = TaskStatus::Unavailable;
+                    self.message = format!("invalid until condition '{}': {}", until.condition, e);
+                    return Result::Error;
+                }
+            }
+            if Instant::now() + Duration::from_secs(until.interval_sec) > deadline {
+                self.status = TaskStatus::Timeout;
+                self.message = format!("until '{}' not met within {}s", until.condition, until.deadline_sec);
+                return Result::Failed;
+            }
+            thread::sleep(Duration::from_secs(until.interval_sec));
         }
     }
-    
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod unit_test_job {
+    use super::*;
+
+    fn definition(json: &str) -> JobDefinition {
+        serde_json::from_str(json).expect("failed to parse job definition")
+    }
+
+    #[test]
+    fn test_job_01() {
+        let def = definition(r#"{"name": "loop", "cmd": "echo", "for_each": {"items": ["a", "b"]}, "when": "os_type == linux"}"#);
+        assert_eq!(def.for_each, Some(ForEach::Items(vec!["a".to_string(), "b".to_string()])));
+        assert_eq!(def.timeout_sec, 60);
+
+        let def = definition(r#"{"name": "nics", "cmd": "ip", "for_each": "nics", "until": {"condition": "status == 0", "deadline_sec": 10}}"#);
+        assert_eq!(def.for_each, Some(ForEach::Nics));
+        assert_eq!(def.until.unwrap().interval_sec, 5);
+
+        let def = definition(r#"{"name": "restart", "unit": {"name": "nginx", "action": "restart"}}"#);
+        assert_eq!(def.cmd, "");
+        assert_eq!(def.unit, Some(UnitJob { name: "nginx".to_string(), action: UnitAction::Restart }));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_job_02() {
+        let mut job = Job::new(definition(r#"{"name": "skip", "cmd": "echo", "when": "os_type == Darwin"}"#));
+        job.execute(&Vars::from([("os_type".to_string(), "Linux".to_string())]));
+        assert_eq!(job.status(), &TaskStatus::Skipped);
+        assert!(job.result().is_none());
+        assert!(job.attempts().is_empty());
+
+        let mut job = Job::new(definition(r#"{"name": "bad", "cmd": "echo", "when": "os_type =="}"#));
+        job.execute(&Vars::new());
+        assert_eq!(job.status(), &TaskStatus::Unavailable);
+        assert_eq!(job.result(), &Some(Result::Error));
+
+        let mut job = Job::new(definition(r#"{"name": "empty"}"#));
+        job.execute(&Vars::new());
+        assert_eq!(job.status(), &TaskStatus::Unavailable);
+        assert_eq!(job.message(), "job has neither cmd nor unit");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_job_03() {
+        let mut job = Job::new(definition(r#"{"name": "loop", "cmd": "echo", "args": ["{{ item_index }}:{{ item }}"], "for_each": {"items": ["a", "b"]}}"#));
+        job.execute(&Vars::new());
+        assert_eq!(job.status(), &TaskStatus::Finished);
+        assert_eq!(job.result(), &Some(Result::Success));
+        assert_eq!(job.attempts().len(), 2);
+        assert!(job.attempts()[0].stdout().as_ref().is_some_and(|out| out.contains("0:a")));
+        assert!(job.outputs()["stdout"].contains("1:b"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_job_04() {
+        let mut job = Job::new(definition(r#"{"name": "poll", "cmd": "echo", "args": ["ready"], "until": {"condition": "stdout contains ready", "interval_sec": 1, "deadline_sec": 5}}"#));
+        job.execute(&Vars::new());
+        assert_eq!(job.result(), &Some(Result::Success));
+        assert_eq!(job.attempts().len(), 1);
+
+        let mut job = Job::new(definition(r#"{"name": "poll", "cmd": "false", "until": {"condition": "status == 0", "interval_sec": 1, "deadline_sec": 1}}"#));
+        job.execute(&Vars::new());
+        assert_eq!(job.status(), &TaskStatus::Timeout);
+        assert_eq!(job.result(), &Some(Result::Failed));
+        assert!(!job.attempts().is_empty());
+
+        let mut job = Job::new(definition(r#"{"name": "typo", "cmd": "/nonexistent/systemctl", "until": {"condition": "status == 0", "interval_sec": 1, "deadline_sec": 30}}"#));
+        let start = Instant::now();
+        job.execute(&Vars::new());
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert_eq!(job.result(), &Some(Result::Error));
+        assert_ne!(job.status(), &TaskStatus::Timeout);
+        assert_eq!(job.attempts().len(), 1);
+    }
+}
@@ -1,2 +1,6 @@
+pub mod condition;
+pub mod facts;
+pub mod history;
 pub mod job;
+pub mod report;
 pub mod task;
\ No newline at end of file
@@ -0,0 +1,370 @@
+use std::error::Error;
+use std::fs;
+use std::str::FromStr;
+
+use chrono::Local;
+
+use crate::common::ds::{self, TaskStatus};
+use crate::info;
+use crate::task::history::{AttemptRecord, JobRecord, TaskRecord};
+
+// 报告格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Junit,                             // JUnit XML，供 CI 解析
+    Html,                              // HTML 概要
+}
+
+impl FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "junit" | "xml" => Ok(ReportFormat::Junit),
+            "html" => Ok(ReportFormat::Html),
+            _ => Err(format!("invalid report format: {}", s)),
+        }
+    }
+}
+
+/// 生成指定格式的报告
+pub fn render(records: &[TaskRecord], format: ReportFormat) -> String {
+    match format {
+        ReportFormat::Junit => to_junit_xml(records),
+        ReportFormat::Html => to_html(records),
+    }
+}
+
+/// 生成报告并写入文件
+pub fn write_report(records: &[TaskRecord], format: ReportFormat, path: &str) -> Result<(), Box<dyn Error>> {
+    fs::write(path, render(records, format))?;
+    Ok(())
+}
+
+// 作业在报告中的结论
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Outcome {
+    Passed,
+    Failed(String),
+    Error(String),
+    Skipped(String),
+}
+
+fn outcome(job: &JobRecord) -> Outcome {
+    if job.status == TaskStatus::Skipped {
+        return Outcome::Skipped(job.message.clone());
+    }
+    match &job.result {
+        Some(ds::Result::Success) => Outcome::Passed,
+        Some(ds::Result::Failed) => Outcome::Failed(failure_message(job)),
+        Some(ds::Result::Error) => Outcome::Error(failure_message(job)),
+        None => Outcome::Error(format!("job not finished: {:?}", job.status)),
+    }
+}
+
+// 失败原因：优先使用作业记录的原因，其次根据最后一次失败命令的返回码和校验字符串推断
+fn failure_message(job: &JobRecord) -> String {
+    if !job.message.is_empty() {
+        return job.message.clone();
+    }
+    let attempt = job.attempts.iter().rev()
+        .find(|a| a.result != Some(ds::Result::Success))
+        .or(job.attempts.last());
+    match attempt {
+        Some(attempt) => attempt_failure(attempt),
+        None => "no command executed".to_string(),
+    }
+}
+
+fn attempt_failure(attempt: &AttemptRecord) -> String {
+    let command = format!("{} {}", attempt.cmd, attempt.args.join(" ")).trim().to_string();
+    match (attempt.status, &attempt.check_str) {
+        (Some(0), Some(check_str)) => format!("`{}` output does not contain '{}'", command, check_str),
+        (Some(-1), _) => format!("`{}` timed out", command),
+        (Some(status), _) => format!("`{}` exited with status {}", command, status),
+        (None, _) => format!("`{}` terminated without exit status", command),
+    }
+}
+
+fn seconds(cost_ms: &Option<u64>) -> String {
+    format!("{:.3}", cost_ms.unwrap_or(0) as f64 / 1000.0)
+}
+
+fn joined_output(job: &JobRecord, stdout: bool) -> String {
+    job.attempts.iter().filter_map(|a| {
+        let output = if stdout { a.stdout.as_deref() } else { a.stderr.as_deref() };
+        output.filter(|o| !o.is_empty()).map(|o| {
+            if job.attempts.len() > 1 {
+                format!("--- attempt #{} ---\n{}", a.id, o)
+            } else {
+                o.to_string()
+            }
+        })
+    }).collect::<Vec<String>>().join("\n")
+}
+
+/// XML/HTML 转义
+pub fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // XML 1.0 不允许的控制字符直接丢弃
+            c if (c as u32) < 0x20 && c != '\n' && c != '\r' && c != '\t' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// 每个任务的用例统计: (总数, 失败数, 错误数, 跳过数)
+fn counts(record: &TaskRecord) -> (usize, usize, usize, usize) {
+    let mut counts = (record.jobs.len(), 0, 0, 0);
+    for job in &record.jobs {
+        match outcome(job) {
+            Outcome::Failed(_) => counts.1 += 1,
+            Outcome::Error(_) => counts.2 += 1,
+            Outcome::Skipped(_) => counts.3 += 1,
+            Outcome::Passed => {}
+        }
+    }
+    counts
+}
+
+/// 生成 JUnit XML 报告：每个任务对应一个 testsuite，每个作业对应一个 testcase
+pub fn to_junit_xml(records: &[TaskRecord]) -> String {
+    let hostname = escape(&info::hostname::hostname());
+    let mut totals = (0, 0, 0, 0);
+    let mut total_ms = 0u64;
+    let mut suites = String::new();
+
+    for record in records {
+        let (tests, failures, errors, skipped) = counts(record);
+        totals = (totals.0 + tests, totals.1 + failures, totals.2 + errors, totals.3 + skipped);
+        total_ms += record.cost_ms.unwrap_or(0);
+
+        let timestamp = record.started_at.unwrap_or(record.created_at).format("%Y-%m-%dT%H:%M:%S");
+        suites.push_str(&format!(
+            "  <testsuite name=\"{}\" id=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" skipped=\"{}\" time=\"{}\" timestamp=\"{}\" hostname=\"{}\">\n",
+            escape(&record.name), escape(&record.id), tests, failures, errors, skipped, seconds(&record.cost_ms), timestamp, hostname));
+        suites.push_str("    <properties>\n");
+        suites.push_str(&format!("      <property name=\"task.id\" value=\"{}\"/>\n", escape(&record.id)));
+        suites.push_str(&format!("      <property name=\"task.description\" value=\"{}\"/>\n", escape(&record.description)));
+        suites.push_str(&format!("      <property name=\"task.status\" value=\"{:?}\"/>\n", record.status));
+        suites.push_str(&format!("      <property name=\"task.log_file\" value=\"{}\"/>\n", escape(&record.log_file)));
+        suites.push_str("    </properties>\n");
+
+        for job in &record.jobs {
+            suites.push_str(&format!("    <testcase name=\"{}\" classname=\"{}\" time=\"{}\">\n",
+                escape(&job.name), escape(&record.name), seconds(&job.cost_ms)));
+            match outcome(job) {
+                Outcome::Passed => {}
+                Outcome::Failed(message) => suites.push_str(&format!(
+                    "      <failure message=\"{}\" type=\"{:?}\">{}</failure>\n", escape(&message), job.status, escape(&message))),
+                Outcome::Error(message) => suites.push_str(&format!(
+                    "      <error message=\"{}\" type=\"{:?}\">{}</error>\n", escape(&message), job.status, escape(&message))),
+                Outcome::Skipped(message) => suites.push_str(&format!(
+                    "      <skipped message=\"{}\"/>\n", escape(&message))),
+            }
+            let stdout = joined_output(job, true);
+            if !stdout.is_empty() {
+                suites.push_str(&format!("      <system-out>{}</system-out>\n", escape(&stdout)));
+            }
+            let stderr = joined_output(job, false);
+            if !stderr.is_empty() {
+                suites.push_str(&format!("      <system-err>{}</system-err>\n", escape(&stderr)));
+            }
+            suites.push_str("    </testcase>\n");
+        }
+        suites.push_str("  </testsuite>\n");
+    }
+
+    format!("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites name=\"minirobot\" tests=\"{}\" failures=\"{}\" errors=\"{}\" skipped=\"{}\" time=\"{}\">\n{}</testsuites>\n",
+        totals.0, totals.1, totals.2, totals.3, seconds(&Some(total_ms)), suites)
+}
+
+const HTML_STYLE: &str = "body{font-family:sans-serif;margin:2em;color:#222}\
+table{border-collapse:collapse;margin:1em 0}th,td{border:1px solid #ccc;padding:4px 8px;text-align:left}\
+th{background:#f0f0f0}.passed{color:#2a7d2a}.failed,.error{color:#c62828}.skipped{color:#888}\
+pre{background:#f7f7f7;padding:8px;max-height:20em;overflow:auto}";
+
+/// 生成 HTML 概要报告：总体统计、任务列表和各任务作业详情
+pub fn to_html(records: &[TaskRecord]) -> String {
+    let mut totals = (0, 0, 0, 0);
+    let passed_tasks = records.iter().filter(|r| r.result == Some(ds::Result::Success)).count();
+    for record in records {
+        let (tests, failures, errors, skipped) = counts(record);
+        totals = (totals.0 + tests, totals.1 + failures, totals.2 + errors, totals.3 + skipped);
+    }
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>minirobot task report</title>\n");
+    html.push_str(&format!("<style>{}</style>\n</head>\n<body>\n", HTML_STYLE));
+    html.push_str(&format!("<h1>Task report</h1>\n<p>Host {} &middot; generated {}</p>\n",
+        escape(&info::hostname::hostname()), Local::now().format("%Y-%m-%d %H:%M:%S")));
+    html.push_str(&format!("<p>Tasks: {} ({} passed, pass rate {:.1}%) &middot; Jobs: {} ({} failed, {} error, {} skipped)</p>\n",
+        records.len(), passed_tasks,
+        if records.is_empty() { 0.0 } else { passed_tasks as f64 * 100.0 / records.len() as f64 },
+        totals.0, totals.1, totals.2, totals.3));
+
+    html.push_str("<table>\n<tr><th>Task</th><th>ID</th><th>Status</th><th>Result</th><th>Started</th><th>Cost (s)</th><th>Jobs</th></tr>\n");
+    for record in records {
+        let class = result_class(&record.result);
+        html.push_str(&format!("<tr><td><a href=\"#{}\">{}</a></td><td>{}</td><td>{:?}</td><td class=\"{}\">{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            escape(&record.id), escape(&record.name), escape(&record.id), record.status, class,
+            record.result.as_ref().map_or("-".to_string(), |r| format!("{:?}", r)),
+            record.started_at.map_or("-".to_string(), |t| t.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S").to_string()),
+            seconds(&record.cost_ms), record.jobs.len()));
+    }
+    html.push_str("</table>\n");
+
+    for record in records {
+        html.push_str(&format!("<h2 id=\"{}\">{}</h2>\n<p>{}</p>\n", escape(&record.id), escape(&record.name), escape(&record.description)));
+        html.push_str("<table>\n<tr><th>Job</th><th>Outcome</th><th>Cost (s)</th><th>Attempts</th><th>Message</th></tr>\n");
+        for job in &record.jobs {
+            let (class, message) = match outcome(job) {
+                Outcome::Passed => ("passed", String::new()),
+                Outcome::Failed(m) => ("failed", m),
+                Outcome::Error(m) => ("error", m),
+                Outcome::Skipped(m) => ("skipped", m),
+            };
+            html.push_str(&format!("<tr><td>{}</td><td class=\"{}\">{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                escape(&job.name), class, class, seconds(&job.cost_ms), job.attempts.len(), escape(&message)));
+        }
+        html.push_str("</table>\n");
+        for job in &record.jobs {
+            let stdout = joined_output(job, true);
+            let stderr = joined_output(job, false);
+            if stdout.is_empty() && stderr.is_empty() {
+                continue;
+            }
+            html.push_str(&format!("<details><summary>{} output</summary>\n", escape(&job.name)));
+            if !stdout.is_empty() {
+                html.push_str(&format!("<h4>stdout</h4><pre>{}</pre>\n", escape(&stdout)));
+            }
+            if !stderr.is_empty() {
+                html.push_str(&format!("<h4>stderr</h4><pre>{}</pre>\n", escape(&stderr)));
+            }
+            html.push_str("</details>\n");
+        }
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+fn result_class(result: &Option<ds::Result>) -> &'static str {
+    match result {
+        Some(ds::Result::Success) => "passed",
+        Some(ds::Result::Failed) => "failed",
+        Some(ds::Result::Error) => "error",
+        None => "skipped",
+    }
+}
+
+#[cfg(test)]
+mod unit_test_report {
+    use super::*;
+    use chrono::Utc;
+
+    fn attempt(status: i32, result: ds::Result, check_str: Option<&str>, stdout: &str, stderr: &str) -> AttemptRecord {
+        AttemptRecord {
+            id: 0,
+            cmd: "check".to_string(),
+            args: vec!["--all".to_string()],
+            check_str: check_str.map(|s| s.to_string()),
+            status: Some(status),
+            stdout: Some(stdout.to_string()),
+            stderr: Some(stderr.to_string()),
+            result: Some(result),
+            cost_ms: Some(5),
+        }
+    }
+
+    fn job(name: &str, status: TaskStatus, result: Option<ds::Result>, message: &str, attempts: Vec<AttemptRecord>) -> JobRecord {
+        JobRecord {
+            name: name.to_string(),
+            status,
+            result,
+            message: message.to_string(),
+            cost_ms: Some(1500),
+            attempts,
+        }
+    }
+
+    fn record() -> TaskRecord {
+        TaskRecord {
+            id: "0f1e2d3c".to_string(),
+            name: "health <check>".to_string(),
+            description: "nightly & weekly".to_string(),
+            status: TaskStatus::Finished,
+            result: Some(ds::Result::Failed),
+            created_at: Utc::now(),
+            started_at: Some(Utc::now()),
+            finished_at: Some(Utc::now()),
+            cost_ms: Some(4200),
+            log_file: "/tmp/task.log".to_string(),
+            jobs: vec![
+                job("ok", TaskStatus::Finished, Some(ds::Result::Success), "", vec![attempt(0, ds::Result::Success, None, "all good", "")]),
+                job("grep", TaskStatus::Finished, Some(ds::Result::Failed), "", vec![attempt(0, ds::Result::Failed, Some("READY"), "booting", "warn: slow")]),
+                job("exit", TaskStatus::Finished, Some(ds::Result::Failed), "", vec![attempt(2, ds::Result::Failed, None, "", "")]),
+                job("bad", TaskStatus::Unavailable, Some(ds::Result::Error), "invalid condition 'x =='", vec![]),
+                job("later", TaskStatus::Skipped, None, "condition not met: os_type == Darwin", vec![]),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_report_01() {
+        let xml = to_junit_xml(&[record()]);
+        println!("{}", xml);
+        assert!(xml.starts_with("<?xml"));
+        assert!(xml.contains("<testsuites name=\"minirobot\" tests=\"5\" failures=\"2\" errors=\"1\" skipped=\"1\" time=\"4.200\">"));
+        assert!(xml.contains("<testsuite name=\"health &lt;check&gt;\""));
+        assert!(xml.contains("<testcase name=\"ok\" classname=\"health &lt;check&gt;\" time=\"1.500\">"));
+        assert!(xml.contains("output does not contain &apos;READY&apos;"));
+        assert!(xml.contains("exited with status 2"));
+        assert!(xml.contains("<error message=\"invalid condition &apos;x ==&apos;\""));
+        assert!(xml.contains("<skipped message=\"condition not met: os_type == Darwin\"/>"));
+        assert!(xml.contains("<system-out>all good</system-out>"));
+        assert!(xml.contains("<system-err>warn: slow</system-err>"));
+        assert!(xml.contains("value=\"nightly &amp; weekly\""));
+    }
+
+    #[test]
+    fn test_report_02() {
+        let mut passed = record();
+        passed.id = "aa11".to_string();
+        passed.result = Some(ds::Result::Success);
+        passed.jobs.truncate(1);
+        let records = vec![record(), passed];
+
+        let xml = to_junit_xml(&records);
+        assert_eq!(xml.matches("<testsuite ").count(), 2);
+        assert!(xml.contains("tests=\"6\""));
+
+        let html = render(&records, ReportFormat::Html);
+        assert!(html.contains("<!DOCTYPE html>"));
+        assert!(html.contains("Tasks: 2 (1 passed, pass rate 50.0%)"));
+        assert!(html.contains("health &lt;check&gt;"));
+        assert!(html.contains("<td class=\"failed\">failed</td>"));
+        assert!(!html.contains("<check>"));
+    }
+
+    #[test]
+    fn test_report_03() {
+        assert_eq!("junit".parse::<ReportFormat>(), Ok(ReportFormat::Junit));
+        assert_eq!("HTML".parse::<ReportFormat>(), Ok(ReportFormat::Html));
+        assert!("pdf".parse::<ReportFormat>().is_err());
+        assert_eq!(escape("a\u{1}<b>"), "a&lt;b&gt;");
+
+        let path = std::env::temp_dir().join(format!("minirobot-report-{}.xml", uuid::Uuid::new_v4()));
+        write_report(&[record()], ReportFormat::Junit, path.to_str().unwrap()).unwrap();
+        assert!(fs::read_to_string(&path).unwrap().contains("<testsuites"));
+        fs::remove_file(&path).ok();
+    }
+}
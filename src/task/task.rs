@@ -1,8 +1,37 @@
+use std::error::Error;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
 use std::time::{Instant, Duration};
 
+use chrono::{DateTime, Utc};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use serde_json::from_reader;
 use uuid::Uuid;
 
 use crate::common::ds::{Result, TaskStatus};
+use crate::task::condition::Vars;
+use crate::task::facts::host_facts;
+use crate::task::job::{Job, JobDefinition};
+
+// 任务定义（任务文件内容）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskDefinition {
+    pub name: String,                  // 任务名
+    #[serde(default)]
+    pub description: String,           // 任务描述
+    #[serde(default)]
+    pub log_file: Option<String>,      // 任务执行日志文件
+    pub jobs: Vec<JobDefinition>,      // 作业序列
+}
+
+/// 从 JSON 文件读取任务定义
+pub fn load_task_definition(task_file: &str) -> std::result::Result<TaskDefinition, Box<dyn Error>> {
+    let file = File::open(task_file)?;
+    let definition = from_reader(file)?;
+    Ok(definition)
+}
 
 #[derive(Debug, Clone)]
 // 定义任务结构体
@@ -14,16 +43,18 @@ pub struct Task {
     create: Instant,                   // 任务创建时间
     // actor: Actor,                      // 任务执行器
     log_file: String,                  // 任务执行日志文件
-    // jobs: Option<Vec<Job>>,            // 执行子任务序列
+    jobs: Vec<Job>,                    // 执行子任务序列
     start: Option<Instant>,            // 任务开始时间
     end: Option<Instant>,              // 任务结束时间
     cost: Option<Duration>,            // 任务执行时间
     result: Option<Result>,            // 任务结果状态
+    created_at: DateTime<Utc>,         // 任务创建时刻（墙钟时间）
+    started_at: Option<DateTime<Utc>>, // 任务开始时刻（墙钟时间）
+    finished_at: Option<DateTime<Utc>>,// 任务结束时刻（墙钟时间）
 }
 
 impl Task {
-    // pub fn new(name: &str, description: &str, jobs: Option<Vec<Job>>, log_file: Option<&str>) -> Task {
-    pub fn new(name: &str, description: &str, log_file: Option<&str>) -> Task {
+    pub fn new(name: &str, description: &str, jobs: Option<Vec<Job>>, log_file: Option<&str>) -> Task {
 
         let id = Uuid::new_v4().to_string();
 
@@ -35,12 +66,12 @@ impl Task {
 
         #[cfg(target_os = "macos")]
         let default_log_file = format!("");
-        
+
         Task {
             id: id,
             name: name.to_string(),
             description: description.to_string(),
-            // jobs: jobs,
+            jobs: jobs.unwrap_or_default(),
             status: TaskStatus::Created,
             create: Instant::now(),
             log_file: log_file.unwrap_or(&default_log_file).to_string(),
@@ -48,6 +79,227 @@ impl Task {
             start: None,
             end: None,
             cost: None,
+            created_at: Utc::now(),
+            started_at: None,
+            finished_at: None,
+        }
+    }
+
+    /// 由任务定义创建任务
+    pub fn from_definition(definition: &TaskDefinition) -> Task {
+        let jobs = definition.jobs.iter().cloned().map(Job::new).collect();
+        Task::new(&definition.name, &definition.description, Some(jobs), definition.log_file.as_deref())
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    pub fn status(&self) -> &TaskStatus {
+        &self.status
+    }
+
+    pub fn create(&self) -> &Instant {
+        &self.create
+    }
+
+    pub fn log_file(&self) -> &str {
+        &self.log_file
+    }
+
+    pub fn jobs(&self) -> &Vec<Job> {
+        &self.jobs
+    }
+
+    pub fn start(&self) -> &Option<Instant> {
+        &self.start
+    }
+
+    pub fn end(&self) -> &Option<Instant> {
+        &self.end
+    }
+
+    pub fn cost(&self) -> &Option<Duration> {
+        &self.cost
+    }
+
+    pub fn result(&self) -> &Option<Result> {
+        &self.result
+    }
+
+    pub fn created_at(&self) -> &DateTime<Utc> {
+        &self.created_at
+    }
+
+    pub fn started_at(&self) -> &Option<DateTime<Utc>> {
+        &self.started_at
+    }
+
+    pub fn finished_at(&self) -> &Option<DateTime<Utc>> {
+        &self.finished_at
+    }
+
+    /// 采集主机事实后执行任务
+    pub fn run(&mut self) {
+        self.run_with_facts(host_facts());
+    }
+
+    /// 使用给定的主机事实执行任务
+    ///
+    /// 作业依次执行，失败不会中断后续作业，以便后续作业通过 `when` 处理失败。
+    /// 作业条件中可引用:
+    /// - 主机事实，如 `os_type`、`hostname`
+    /// - 上一个实际执行的作业输出 `prev.status`、`prev.stdout`、`prev.stderr`、`prev.result`
+    /// - 任一前序作业输出 `jobs.<name>.status` 等，以及 `jobs.<name>.state`（如 `Skipped`）
+    pub fn run_with_facts(&mut self, facts: Vars) {
+        self.start = Some(Instant::now());
+        self.started_at = Some(Utc::now());
+        self.status = TaskStatus::Running;
+        self.log(&format!("task {} ({}) started", self.name, self.id));
+
+        let mut vars = facts;
+        vars.insert("task.id".to_string(), self.id.clone());
+        vars.insert("task.name".to_string(), self.name.clone());
+
+        let mut result = Result::Success;
+        for job in self.jobs.iter_mut() {
+            job.execute(&vars);
+            write_job_log(&self.log_file, job);
+
+            let outputs = job.outputs();
+            for (key, value) in &outputs {
+                vars.insert(format!("jobs.{}.{}", job.name(), key), value.clone());
+            }
+            if job.status() != &TaskStatus::Skipped {
+                vars.retain(|key, _| !key.starts_with("prev."));
+                for (key, value) in outputs {
+                    vars.insert(format!("prev.{}", key), value);
+                }
+            }
+
+            result = match (&result, job.result()) {
+                (Result::Error, _) | (_, Some(Result::Error)) => Result::Error,
+                (Result::Failed, _) | (_, Some(Result::Failed)) => Result::Failed,
+                _ => Result::Success,
+            };
         }
+
+        self.result = Some(result);
+        self.status = TaskStatus::Finished;
+        self.end = Some(Instant::now());
+        self.finished_at = Some(Utc::now());
+        self.cost = Some(self.end.unwrap() - self.start.unwrap());
+        self.log(&format!("task {} finished: {:?} in {:?}", self.name, self.result.as_ref().unwrap(), self.cost.unwrap()));
+    }
+
+    // 追加任务日志，日志文件不可写时只告警不中断任务
+    fn log(&self, message: &str) {
+        append_log(&self.log_file, &[message.to_string()]);
+    }
+}
+
+fn write_job_log(log_file: &str, job: &Job) {
+    let mut lines = vec![format!("job {} {:?} {} {}", job.name(), job.status(),
+        job.result().as_ref().map_or("-".to_string(), |r| format!("{:?}", r)), job.message())];
+    for shell in job.attempts() {
+        lines.push(format!("  $ {} {} => {} ({:?})", shell.cmd(), shell.args().clone().unwrap_or_default().join(" "),
+            shell.status().map_or("-".to_string(), |s| s.to_string()), shell.cost().unwrap_or_default()));
+        for line in shell.stdout().as_deref().unwrap_or_default().lines() {
+            lines.push(format!("  | {}", line));
+        }
+        for line in shell.stderr().as_deref().unwrap_or_default().lines() {
+            lines.push(format!("  ! {}", line));
+        }
+    }
+    append_log(log_file, &lines);
+}
+
+fn append_log(log_file: &str, lines: &[String]) {
+    if log_file.is_empty() {
+        return;
+    }
+    let write = || -> std::io::Result<()> {
+        if let Some(parent) = Path::new(log_file).parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(log_file)?;
+        let timestamp = Utc::now().naive_utc();
+        for line in lines {
+            writeln!(file, "[{}] {}", timestamp, line)?;
+        }
+        Ok(())
+    };
+    if let Err(e) = write() {
+        warn!("failed to write task log {}: {}", log_file, e);
+    }
+}
+
+#[cfg(test)]
+mod unit_test_task {
+    use super::*;
+
+    fn task(json: &str, log_file: &Path) -> Task {
+        let mut definition: TaskDefinition = serde_json::from_str(json).expect("failed to parse task definition");
+        definition.log_file = Some(log_file.display().to_string());
+        Task::from_definition(&definition)
+    }
+
+    fn temp_log_file() -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("minirobot-task-{}.log", Uuid::new_v4()))
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_task_01() {
+        let log_file = temp_log_file();
+        let mut task = task(r#"{
+            "name": "health-check",
+            "jobs": [
+                {"name": "probe", "cmd": "false"},
+                {"name": "linux-only", "cmd": "echo", "args": ["{{ os_type }}"], "when": "os_type == Linux"},
+                {"name": "darwin-only", "cmd": "echo", "when": "os_type == Darwin"},
+                {"name": "on-failure", "cmd": "echo", "args": ["probe {{ jobs.probe.status }}"], "when": "jobs.probe.status != 0 and prev.stdout contains Linux"}
+            ]
+        }"#, &log_file);
+        task.run_with_facts(Vars::from([("os_type".to_string(), "Linux".to_string())]));
+
+        assert_eq!(task.status(), &TaskStatus::Finished);
+        assert_eq!(task.result(), &Some(Result::Failed));
+        let jobs = task.jobs();
+        assert_eq!(jobs[0].result(), &Some(Result::Failed));
+        assert_eq!(jobs[1].result(), &Some(Result::Success));
+        assert_eq!(jobs[2].status(), &TaskStatus::Skipped);
+        assert_eq!(jobs[3].result(), &Some(Result::Success));
+        assert!(jobs[3].outputs()["stdout"].contains("probe 1"));
+
+        let log = fs::read_to_string(&log_file).expect("failed to read task log");
+        assert!(log.contains("job probe Finished Failed") && log.contains("  | probe 1"));
+        fs::remove_file(&log_file).ok();
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_task_02() {
+        let log_file = temp_log_file();
+        let mut task = task(r#"{
+            "name": "all-skipped",
+            "description": "nothing to do",
+            "jobs": [{"name": "never", "cmd": "echo", "when": "prev.status != 0"}]
+        }"#, &log_file);
+        task.run_with_facts(Vars::new());
+        assert_eq!(task.description(), "nothing to do");
+        assert_eq!(task.jobs()[0].status(), &TaskStatus::Skipped);
+        assert_eq!(task.result(), &Some(Result::Success));
+        assert!(task.cost().is_some());
+        assert!(task.started_at().is_some_and(|t| t >= *task.created_at()));
+        fs::remove_file(&log_file).ok();
     }
 }